@@ -0,0 +1,23 @@
+use rusty_machine::linalg::Matrix;
+use rusty_machine::learning::dbscan::DBSCAN;
+use rusty_machine::learning::UnSupModel;
+
+use rand::{Rng, thread_rng};
+
+use test::{Bencher, black_box};
+
+// Compare against a build with `--features rayon` (and `RAYON_NUM_THREADS=8`)
+// to measure the speedup from the parallel neighbourhood queries.
+#[bench]
+fn dbscan_train_100k(b: &mut Bencher) {
+    const SAMPLES: usize = 100000;
+
+    let mut rng = thread_rng();
+    let data = (0..2 * SAMPLES).map(|_| rng.gen_range(0f64, 100f64)).collect::<Vec<f64>>();
+    let inputs = Matrix::new(SAMPLES, 2, data);
+
+    b.iter(|| {
+        let mut model = black_box(DBSCAN::new(0.5, 5));
+        let _ = black_box(model.train(&inputs).unwrap());
+    });
+}
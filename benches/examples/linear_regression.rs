@@ -0,0 +1,44 @@
+use rusty_machine::linalg::{Matrix, Vector};
+use rusty_machine::learning::lin_reg::{LinRegressor, MultiLinRegressor};
+use rusty_machine::learning::SupModel;
+
+use rand::{Rng, thread_rng};
+
+use test::{Bencher, black_box};
+
+const SAMPLES: usize = 1000;
+const FEATURES: usize = 20;
+const OUTPUTS: usize = 50;
+
+fn random_matrix(rows: usize, cols: usize) -> Matrix<f64> {
+    let mut rng = thread_rng();
+    let data = (0..rows * cols).map(|_| rng.gen_range(-1f64, 1f64)).collect::<Vec<f64>>();
+    Matrix::new(rows, cols, data)
+}
+
+// The multi-output model factorizes the inputs once for all outputs.
+#[bench]
+fn lin_reg_multi_output_train(b: &mut Bencher) {
+    let inputs = random_matrix(SAMPLES, FEATURES);
+    let targets = random_matrix(SAMPLES, OUTPUTS);
+
+    b.iter(|| {
+        let mut model = black_box(MultiLinRegressor::default());
+        let _ = black_box(model.train(&inputs, &targets).unwrap());
+    });
+}
+
+#[bench]
+fn lin_reg_separate_outputs_train(b: &mut Bencher) {
+    let inputs = random_matrix(SAMPLES, FEATURES);
+    let targets = (0..OUTPUTS)
+        .map(|_| Vector::new(random_matrix(SAMPLES, 1).into_vec()))
+        .collect::<Vec<Vector<f64>>>();
+
+    b.iter(|| {
+        for t in &targets {
+            let mut model = black_box(LinRegressor::default());
+            let _ = black_box(model.train(&inputs, t).unwrap());
+        }
+    });
+}
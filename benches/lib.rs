@@ -6,7 +6,9 @@ extern crate rand;
 
 mod examples {
     mod cross_validation;
+    mod dbscan;
     mod k_means;
+    mod linear_regression;
     mod nnet;
     mod svm;
 }
@@ -0,0 +1,545 @@
+//! Probability calibration
+//!
+//! This module contains the `CalibratedClassifier` which maps the scores of
+//! a binary classifier to calibrated probabilities of the positive class.
+//! Scores such as SVM margins rank the inputs well but are not probabilities,
+//! and squashing them through a sigmoid gives badly calibrated outputs.
+//!
+//! The calibration is fitted on out-of-fold scores computed with
+//! `cross_validation::cross_val_predict`, so the calibrator never sees scores
+//! of samples the scoring model was trained on. The scoring model used for
+//! predictions is then trained on all of the data.
+//!
+//! Two calibration methods are available:
+//!
+//! - `Sigmoid` fits Platt scaling, a one-dimensional logistic regression
+//! `p = 1 / (1 + exp(-(a * score + b)))`.
+//! - `Isotonic` fits a non-decreasing function of the score using the
+//! pool-adjacent-violators algorithm. It is more flexible but needs more
+//! data, and may output probabilities of exactly zero or one.
+//!
+//! A target is treated as the positive class if it is greater than zero, so
+//! both `0`/`1` and `-1`/`1` targets are supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::analysis::calibration::{CalibratedClassifier, CalibrationMethod};
+//! use rusty_machine::learning::svm::SVM;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(8, 1, vec![-4.0, -3.0, -2.0, -1.0, 1.0, 2.0, 3.0, 4.0]);
+//! let targets = Vector::new(vec![-1.0, -1.0, -1.0, 1.0, -1.0, 1.0, 1.0, 1.0]);
+//!
+//! let mut model = CalibratedClassifier::new(SVM::default, CalibrationMethod::Sigmoid, 2);
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! // The calibrated probabilities of the positive class.
+//! let probs = model.predict(&Matrix::new(2, 1, vec![-5.0, 5.0])).unwrap();
+//! assert_eq!(probs.size(), 2);
+//! ```
+
+use analysis::cross_validation::cross_val_predict;
+use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
+use learning::logistic_reg::{BaseLogisticRegressor, LogisticRegressor};
+use learning::optim::OptimAlgorithm;
+use learning::svm::SVM;
+use learning::toolkit::activ_fn::{ActivationFunc, Sigmoid};
+use learning::toolkit::kernel::Kernel;
+use linalg::{BaseMatrix, Matrix, Vector};
+
+use std::fmt;
+use std::iter;
+
+/// The maximum number of Newton iterations used to fit Platt scaling.
+const SIGMOID_MAX_ITERS: usize = 100;
+
+/// A binary classifier which scores its inputs.
+pub trait BinaryScorer: SupModel<Matrix<f64>, Vector<f64>> {
+    /// Score the inputs, with higher scores indicating the positive class.
+    ///
+    /// The model must be trained.
+    fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>>;
+}
+
+impl<K: Kernel> BinaryScorer for SVM<K> {
+    fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        SVM::decision_function(self, inputs)
+    }
+}
+
+impl<A> BinaryScorer for LogisticRegressor<A>
+    where A: OptimAlgorithm<BaseLogisticRegressor>
+{
+    fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        LogisticRegressor::decision_function(self, inputs)
+    }
+}
+
+/// Adapts a scorer so that `predict` returns its scores.
+struct Scores<M>(M);
+
+impl<M: BinaryScorer> SupModel<Matrix<f64>, Vector<f64>> for Scores<M> {
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        self.0.decision_function(inputs)
+    }
+
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        self.0.train(inputs, targets)
+    }
+}
+
+/// The method used to map scores to probabilities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CalibrationMethod {
+    /// Platt scaling with a one-dimensional logistic regression.
+    Sigmoid,
+    /// Isotonic regression.
+    Isotonic,
+}
+
+/// A fitted map from scores to probabilities.
+#[derive(Debug)]
+enum Calibrator {
+    /// The logistic function `1 / (1 + exp(-(a * score + b)))`.
+    Sigmoid {
+        a: f64,
+        b: f64,
+    },
+    /// A non-decreasing function which linearly interpolates the
+    /// probabilities fitted at the distinct training scores.
+    Isotonic {
+        scores: Vec<f64>,
+        probs: Vec<f64>,
+    },
+}
+
+impl Calibrator {
+    /// Fit Platt scaling to the scores.
+    ///
+    /// Uses Newton's method with a backtracking line search on
+    /// Platt's smoothed targets, which keep the fit finite when the
+    /// classes are separable.
+    fn sigmoid(scores: &[f64], positive: &[bool]) -> Calibrator {
+        let n_pos = positive.iter().filter(|&&p| p).count() as f64;
+        let n_neg = positive.len() as f64 - n_pos;
+        let hi = (n_pos + 1f64) / (n_pos + 2f64);
+        let lo = 1f64 / (n_neg + 2f64);
+        let targets = positive.iter().map(|&p| if p { hi } else { lo }).collect::<Vec<f64>>();
+
+        let cost = |a: f64, b: f64| {
+            scores.iter()
+                .zip(targets.iter())
+                .map(|(s, t)| {
+                    let z = a * s + b;
+                    if z >= 0f64 {
+                        (-z).exp().ln_1p() + (1f64 - t) * z
+                    } else {
+                        z.exp().ln_1p() - t * z
+                    }
+                })
+                .sum::<f64>()
+        };
+
+        let mut a = 0f64;
+        let mut b = ((n_pos + 1f64) / (n_neg + 1f64)).ln();
+        let mut f = cost(a, b);
+
+        'newton: for _ in 0..SIGMOID_MAX_ITERS {
+            let (mut g_a, mut g_b) = (0f64, 0f64);
+            // A tiny ridge keeps the Hessian invertible.
+            let (mut h_aa, mut h_ab, mut h_bb) = (1e-12, 0f64, 1e-12);
+            for (s, t) in scores.iter().zip(targets.iter()) {
+                let p = Sigmoid::func(a * s + b);
+                let w = p * (1f64 - p);
+                g_a += (p - t) * s;
+                g_b += p - t;
+                h_aa += w * s * s;
+                h_ab += w * s;
+                h_bb += w;
+            }
+
+            if g_a.abs() < 1e-5 && g_b.abs() < 1e-5 {
+                break;
+            }
+
+            let det = h_aa * h_bb - h_ab * h_ab;
+            let d_a = -(h_bb * g_a - h_ab * g_b) / det;
+            let d_b = -(h_aa * g_b - h_ab * g_a) / det;
+            let slope = g_a * d_a + g_b * d_b;
+
+            let mut step = 1f64;
+            loop {
+                let (new_a, new_b) = (a + step * d_a, b + step * d_b);
+                let new_f = cost(new_a, new_b);
+                if new_f < f + 1e-4 * step * slope {
+                    a = new_a;
+                    b = new_b;
+                    f = new_f;
+                    break;
+                }
+
+                step /= 2f64;
+                if step < 1e-10 {
+                    break 'newton;
+                }
+            }
+        }
+
+        Calibrator::Sigmoid { a: a, b: b }
+    }
+
+    /// Fit an isotonic regression of the labels on the scores.
+    ///
+    /// Samples with equal scores are pooled before fitting.
+    fn isotonic(scores: &[f64], positive: &[bool]) -> Calibrator {
+        let mut pairs = scores.iter()
+            .cloned()
+            .zip(positive.iter().map(|&p| if p { 1f64 } else { 0f64 }))
+            .collect::<Vec<(f64, f64)>>();
+        pairs.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+
+        let mut distinct: Vec<f64> = Vec::new();
+        let mut sums: Vec<f64> = Vec::new();
+        let mut weights: Vec<f64> = Vec::new();
+        for (s, y) in pairs {
+            if distinct.last() == Some(&s) {
+                let last = sums.len() - 1;
+                sums[last] += y;
+                weights[last] += 1f64;
+            } else {
+                distinct.push(s);
+                sums.push(y);
+                weights.push(1f64);
+            }
+        }
+
+        let means = sums.iter().zip(weights.iter()).map(|(s, w)| s / w).collect::<Vec<f64>>();
+
+        Calibrator::Isotonic {
+            scores: distinct,
+            probs: pool_adjacent_violators(&means, &weights),
+        }
+    }
+
+    /// The calibrated probability of a finite score.
+    fn prob(&self, score: f64) -> f64 {
+        match *self {
+            Calibrator::Sigmoid { a, b } => Sigmoid::func(a * score + b),
+            Calibrator::Isotonic { ref scores, ref probs } => {
+                match scores.binary_search_by(|s| s.partial_cmp(&score).unwrap()) {
+                    Ok(i) => probs[i],
+                    Err(0) => probs[0],
+                    Err(i) if i == scores.len() => probs[i - 1],
+                    Err(i) => {
+                        let t = (score - scores[i - 1]) / (scores[i] - scores[i - 1]);
+                        probs[i - 1] + t * (probs[i] - probs[i - 1])
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Compute the non-decreasing sequence closest to `values` in weighted least squares.
+///
+/// Adjacent values which violate the ordering are pooled into
+/// blocks taking their weighted mean.
+fn pool_adjacent_violators(values: &[f64], weights: &[f64]) -> Vec<f64> {
+    // The mean, total weight and length of each block.
+    let mut blocks: Vec<(f64, f64, usize)> = Vec::with_capacity(values.len());
+
+    for (&v, &w) in values.iter().zip(weights.iter()) {
+        blocks.push((v, w, 1));
+
+        while blocks.len() > 1 && blocks[blocks.len() - 2].0 > blocks[blocks.len() - 1].0 {
+            let (v2, w2, n2) = blocks.pop().unwrap();
+            let last = blocks.last_mut().unwrap();
+            last.0 = (last.0 * last.1 + v2 * w2) / (last.1 + w2);
+            last.1 += w2;
+            last.2 += n2;
+        }
+    }
+
+    blocks.iter().flat_map(|&(v, _, n)| iter::repeat(v).take(n)).collect()
+}
+
+/// Check that a model produced finite scores.
+fn check_scores(scores: &Vector<f64>) -> LearningResult<()> {
+    if scores.iter().all(|s| s.is_finite()) {
+        Ok(())
+    } else {
+        Err(Error::new(ErrorKind::InvalidState, "The model produced non-finite scores."))
+    }
+}
+
+/// A binary classifier with calibrated probabilities.
+///
+/// Wraps a `BinaryScorer` created by `model_factory`. Training fits
+/// the calibration on out-of-fold scores and then trains the scorer
+/// on all of the data.
+pub struct CalibratedClassifier<M, F>
+    where F: Fn() -> M
+{
+    model_factory: F,
+    method: CalibrationMethod,
+    folds: usize,
+    model: Option<M>,
+    calibrator: Option<Calibrator>,
+}
+
+impl<M, F> fmt::Debug for CalibratedClassifier<M, F>
+    where M: fmt::Debug,
+          F: Fn() -> M
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CalibratedClassifier")
+            .field("method", &self.method)
+            .field("folds", &self.folds)
+            .field("model", &self.model)
+            .field("calibrator", &self.calibrator)
+            .finish()
+    }
+}
+
+impl<M, F> CalibratedClassifier<M, F>
+    where M: BinaryScorer,
+          F: Fn() -> M
+{
+    /// Constructs an untrained calibrated classifier.
+    ///
+    /// Requires a function creating untrained scoring models, the
+    /// calibration method and the number of cross-validation folds
+    /// used to compute the scores the calibration is fitted on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::analysis::calibration::{CalibratedClassifier, CalibrationMethod};
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    ///
+    /// let model = CalibratedClassifier::new(LogisticRegressor::default,
+    ///                                       CalibrationMethod::Isotonic,
+    ///                                       5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `folds` is less than two.
+    pub fn new(model_factory: F,
+               method: CalibrationMethod,
+               folds: usize)
+               -> CalibratedClassifier<M, F> {
+        assert!(folds > 1, "The number of folds must be at least 2.");
+
+        CalibratedClassifier {
+            model_factory: model_factory,
+            method: method,
+            folds: folds,
+            model: None,
+            calibrator: None,
+        }
+    }
+
+    /// Get the calibration method.
+    pub fn method(&self) -> CalibrationMethod {
+        self.method
+    }
+
+    /// Get the number of cross-validation folds.
+    pub fn folds(&self) -> usize {
+        self.folds
+    }
+
+    /// Get the scoring model trained on all of the data.
+    pub fn model(&self) -> Option<&M> {
+        self.model.as_ref()
+    }
+
+    /// Predict the calibrated probability of the positive class for the inputs.
+    ///
+    /// The model must be trained.
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(ref model), &Some(ref calibrator)) = (&self.model, &self.calibrator) {
+            let scores = model.decision_function(inputs)?;
+            check_scores(&scores)?;
+            Ok(scores.apply(&|s| calibrator.prob(s)))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+impl<M, F> SupModel<Matrix<f64>, Vector<f64>> for CalibratedClassifier<M, F>
+    where M: BinaryScorer,
+          F: Fn() -> M
+{
+    /// Train the scoring model and its calibration.
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    /// - There are fewer inputs than folds.
+    /// - The scoring model fails to train or produces non-finite scores.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+        if inputs.rows() < self.folds {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be at least as many inputs as folds."));
+        }
+
+        let scores = {
+            let model_factory = &self.model_factory;
+            cross_val_predict(|| Scores(model_factory()), inputs, targets, self.folds)?
+        };
+        check_scores(&scores)?;
+
+        let positive = targets.iter().map(|&t| t > 0f64).collect::<Vec<bool>>();
+        let calibrator = match self.method {
+            CalibrationMethod::Sigmoid => Calibrator::sigmoid(scores.data(), &positive),
+            CalibrationMethod::Isotonic => Calibrator::isotonic(scores.data(), &positive),
+        };
+
+        let mut model = (self.model_factory)();
+        model.train(inputs, targets)?;
+
+        self.model = Some(model);
+        self.calibrator = Some(calibrator);
+        Ok(())
+    }
+
+    /// Predict the calibrated probability of the positive class for the inputs.
+    ///
+    /// The model must be trained.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        self.predict_proba(inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calibrator, CalibratedClassifier, CalibrationMethod, pool_adjacent_violators};
+    use learning::SupModel;
+    use learning::svm::SVM;
+    use learning::toolkit::activ_fn::{ActivationFunc, Sigmoid};
+    use linalg::{Matrix, Vector};
+    use rand::{Rng, SeedableRng, StdRng};
+
+    /// Points on a grid over `[-3, 3]` with labels drawn from the
+    /// posterior `P(y = 1 | x) = sigmoid(2x)`, using `-1` for the
+    /// negative class.
+    fn posterior_data(n: usize, seed: usize) -> (Matrix<f64>, Vector<f64>) {
+        let mut rng = StdRng::from_seed(&[seed]);
+        let xs = (0..n).map(|i| -3.0 + 6.0 * i as f64 / (n - 1) as f64).collect::<Vec<f64>>();
+        let ys = xs.iter()
+            .map(|&x| if rng.gen::<f64>() < Sigmoid::func(2.0 * x) { 1.0 } else { -1.0 })
+            .collect::<Vec<f64>>();
+
+        (Matrix::new(n, 1, xs), Vector::new(ys))
+    }
+
+    fn log_loss(probs: &Vector<f64>, targets: &Vector<f64>) -> f64 {
+        let eps = 1e-15;
+        probs.iter()
+            .zip(targets.iter())
+            .map(|(&p, &t)| if t > 0.0 { -p.max(eps).ln() } else { -(1.0 - p).max(eps).ln() })
+            .sum::<f64>() / probs.size() as f64
+    }
+
+    #[test]
+    fn test_pool_adjacent_violators() {
+        let fitted = pool_adjacent_violators(&[1.0, 3.0, 2.0, 4.0, 3.0, 5.0], &[1.0; 6]);
+        assert_eq!(fitted, vec![1.0, 2.5, 2.5, 3.5, 3.5, 5.0]);
+
+        let weighted = pool_adjacent_violators(&[2.0, 0.0], &[3.0, 1.0]);
+        assert_eq!(weighted, vec![1.5, 1.5]);
+    }
+
+    #[test]
+    fn test_isotonic_interpolates() {
+        let calibrator = Calibrator::isotonic(&[1.0, 0.0, 0.0], &[true, false, true]);
+
+        assert_eq!(calibrator.prob(-1.0), 0.5);
+        assert_eq!(calibrator.prob(0.0), 0.5);
+        assert_eq!(calibrator.prob(0.5), 0.75);
+        assert_eq!(calibrator.prob(2.0), 1.0);
+    }
+
+    #[test]
+    fn test_sigmoid_recovers_parameters() {
+        let mut rng = StdRng::from_seed(&[3]);
+        let scores = (0..2000).map(|i| -3.0 + 6.0 * i as f64 / 1999.0).collect::<Vec<f64>>();
+        let positive = scores.iter()
+            .map(|&s| rng.gen::<f64>() < Sigmoid::func(2.0 * s - 1.0))
+            .collect::<Vec<bool>>();
+
+        match Calibrator::sigmoid(&scores, &positive) {
+            Calibrator::Sigmoid { a, b } => {
+                assert!((a - 2.0).abs() < 0.3);
+                assert!((b + 1.0).abs() < 0.3);
+            }
+            _ => panic!("Expected a sigmoid calibrator."),
+        }
+    }
+
+    #[test]
+    fn test_calibrated_svm_log_loss() {
+        let (inputs, targets) = posterior_data(200, 1);
+        let (test_inputs, test_targets) = posterior_data(200, 2);
+
+        let svm = || {
+            let mut svm = SVM::default();
+            svm.optim_iters = 1000;
+            svm
+        };
+        let mut model = CalibratedClassifier::new(svm, CalibrationMethod::Sigmoid, 3);
+        model.train(&inputs, &targets).unwrap();
+
+        let margins = model.model().unwrap().decision_function(&test_inputs).unwrap();
+        let raw_loss = log_loss(&margins.apply(&Sigmoid::func), &test_targets);
+        let calibrated_loss = log_loss(&model.predict(&test_inputs).unwrap(), &test_targets);
+
+        assert!(calibrated_loss < 0.9 * raw_loss);
+    }
+
+    #[test]
+    fn test_isotonic_probabilities_are_monotone() {
+        let (inputs, targets) = posterior_data(100, 4);
+
+        let mut model = CalibratedClassifier::new(SVM::default, CalibrationMethod::Isotonic, 2);
+        model.train(&inputs, &targets).unwrap();
+
+        let margins = model.model().unwrap().decision_function(&inputs).unwrap().into_vec();
+        let probs = model.predict_proba(&inputs).unwrap().into_vec();
+
+        assert!(probs.iter().all(|&p| p >= 0.0 && p <= 1.0));
+        for i in 0..margins.len() {
+            for j in 0..margins.len() {
+                if margins[i] < margins[j] {
+                    assert!(probs[i] <= probs[j]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_training() {
+        let mut model = CalibratedClassifier::new(SVM::default, CalibrationMethod::Sigmoid, 3);
+        let inputs = Matrix::new(2, 1, vec![0.0, 1.0]);
+
+        assert!(model.predict(&inputs).is_err());
+        assert!(model.train(&inputs, &Vector::new(vec![-1.0, 1.0])).is_err());
+        assert!(model.train(&inputs, &Vector::new(vec![-1.0, 1.0, 1.0])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_single_fold() {
+        let _ = CalibratedClassifier::new(SVM::default, CalibrationMethod::Sigmoid, 1);
+    }
+}
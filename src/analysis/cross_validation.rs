@@ -1,11 +1,13 @@
 //! Module for performing cross-validation of models.
 
 use std::cmp;
+use std::collections::BTreeMap;
 use std::iter::Chain;
 use std::slice::Iter;
-use linalg::{BaseMatrix, Matrix};
+use linalg::{BaseMatrix, Matrix, Vector};
 use learning::{LearningResult, SupModel};
-use learning::toolkit::rand_utils::in_place_fisher_yates;
+use learning::toolkit::rand_utils::{in_place_fisher_yates, in_place_fisher_yates_with_rng};
+use rand::{Rng, SeedableRng, StdRng, thread_rng};
 
 /// Randomly splits the inputs into k 'folds'. For each fold a model
 /// is trained using all inputs except for that fold, and tested on the
@@ -75,6 +77,217 @@ pub fn k_fold_validate<M, S>(model: &mut M,
     Ok(costs)
 }
 
+/// Runs k-fold cross validation on a model with vector targets.
+///
+/// The inputs are randomly split into k folds. For each fold a fresh
+/// model is created using `model_factory`, trained on all inputs except
+/// for that fold and tested on the data in the fold. Returns the scores
+/// for each fold.
+///
+/// # Arguments
+/// * `model_factory` - Creates an untrained model for each fold.
+/// * `inputs` - All input samples.
+/// * `targets` - All targets.
+/// * `k` - Number of folds to use.
+/// * `scorer` - Used to compare the outputs for each fold to the targets, called as
+/// `scorer(&outputs, &targets)`. Higher scores are better.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::cross_validation::cross_val_score;
+/// use rusty_machine::learning::lin_reg::LinRegressor;
+/// use rusty_machine::linalg::{Matrix, Vector};
+///
+/// let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// let targets = Vector::new(vec![2.1, 3.9, 6.2, 7.8, 10.1, 12.0]);
+///
+/// let scores = cross_val_score(LinRegressor::default,
+///                              &inputs,
+///                              &targets,
+///                              3,
+///                              // Score each fold by the negative mean squared error.
+///                              |outputs, targets| {
+///                                  let sq_err = outputs.iter()
+///                                      .zip(targets.iter())
+///                                      .map(|(o, t)| (o - t) * (o - t))
+///                                      .sum::<f64>();
+///                                  -sq_err / outputs.size() as f64
+///                              }).unwrap();
+///
+/// assert_eq!(scores.len(), 3);
+/// let mean_score = scores.iter().sum::<f64>() / scores.len() as f64;
+/// ```
+///
+/// # Panics
+///
+/// - The number of inputs and targets differ.
+/// - `k` is less than two or greater than the number of samples.
+pub fn cross_val_score<M, F, S>(model_factory: F,
+                                inputs: &Matrix<f64>,
+                                targets: &Vector<f64>,
+                                k: usize,
+                                scorer: S) -> LearningResult<Vec<f64>>
+    where F: Fn() -> M,
+          S: Fn(&Vector<f64>, &Vector<f64>) -> f64,
+          M: SupModel<Matrix<f64>, Vector<f64>>,
+{
+    assert_eq!(inputs.rows(), targets.size());
+    let shuffled_indices = create_shuffled_indices(inputs.rows());
+    let folds = Folds::new(&shuffled_indices, k);
+
+    let mut scores: Vec<f64> = Vec::with_capacity(k);
+
+    for p in folds {
+        let train_idx = p.train_indices_iter.map(|x| *x).collect::<Vec<usize>>();
+        let test_idx = p.test_indices_iter.map(|x| *x).collect::<Vec<usize>>();
+
+        let train_inputs = inputs.select_rows(&train_idx);
+        let train_targets = targets.select(&train_idx);
+        let test_inputs = inputs.select_rows(&test_idx);
+        let test_targets = targets.select(&test_idx);
+
+        let mut model = model_factory();
+        model.train(&train_inputs, &train_targets)?;
+        let outputs = model.predict(&test_inputs)?;
+        scores.push(scorer(&outputs, &test_targets));
+    }
+
+    Ok(scores)
+}
+
+/// Predicts the target of every sample using a model which was not trained on it.
+///
+/// The inputs are randomly split into k folds. For each fold a fresh
+/// model is created using `model_factory`, trained on all inputs except
+/// for that fold and used to predict the outputs of the fold. Returns
+/// the out-of-fold outputs in the order of the inputs.
+///
+/// # Arguments
+/// * `model_factory` - Creates an untrained model for each fold.
+/// * `inputs` - All input samples.
+/// * `targets` - All targets.
+/// * `k` - Number of folds to use.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::cross_validation::cross_val_predict;
+/// use rusty_machine::learning::lin_reg::LinRegressor;
+/// use rusty_machine::linalg::{Matrix, Vector};
+///
+/// let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// let targets = Vector::new(vec![2.1, 3.9, 6.2, 7.8, 10.1, 12.0]);
+///
+/// let outputs = cross_val_predict(LinRegressor::default, &inputs, &targets, 3).unwrap();
+/// assert_eq!(outputs.size(), 6);
+/// ```
+///
+/// # Panics
+///
+/// - The number of inputs and targets differ.
+/// - `k` is less than two or greater than the number of samples.
+pub fn cross_val_predict<M, F>(model_factory: F,
+                               inputs: &Matrix<f64>,
+                               targets: &Vector<f64>,
+                               k: usize) -> LearningResult<Vector<f64>>
+    where F: Fn() -> M,
+          M: SupModel<Matrix<f64>, Vector<f64>>,
+{
+    assert_eq!(inputs.rows(), targets.size());
+    let shuffled_indices = create_shuffled_indices(inputs.rows());
+    let folds = Folds::new(&shuffled_indices, k);
+
+    let mut outputs = vec![0f64; inputs.rows()];
+
+    for p in folds {
+        let train_idx = p.train_indices_iter.map(|x| *x).collect::<Vec<usize>>();
+        let test_idx = p.test_indices_iter.map(|x| *x).collect::<Vec<usize>>();
+
+        let mut model = model_factory();
+        model.train(&inputs.select_rows(&train_idx), &targets.select(&train_idx))?;
+        let fold_outputs = model.predict(&inputs.select_rows(&test_idx))?;
+
+        for (&i, &o) in test_idx.iter().zip(fold_outputs.iter()) {
+            outputs[i] = o;
+        }
+    }
+
+    Ok(Vector::new(outputs))
+}
+
+/// Splits classification data into training and test sets with the
+/// class proportions of the full set.
+///
+/// The samples of each class are shuffled and `round(test_fraction * n_c)`
+/// of the `n_c` samples of the class are put in the test set. The rest
+/// are put in the training set. Both sets keep the order of the inputs.
+///
+/// Returns the training inputs and targets followed by the test
+/// inputs and targets.
+///
+/// # Arguments
+/// * `inputs` - All input samples.
+/// * `targets` - The class of each sample.
+/// * `test_fraction` - The fraction of each class to put in the test set.
+/// * `seed` - Seeds the shuffle so the split is reproducible.
+///
+/// # Examples
+/// ```
+/// use rusty_machine::analysis::cross_validation::stratified_train_test_split;
+/// use rusty_machine::linalg::{BaseMatrix, Matrix, Vector};
+///
+/// let inputs = Matrix::new(8, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+/// let targets = Vector::new(vec![0, 0, 0, 0, 0, 0, 1, 1]);
+///
+/// let (train_inputs, train_targets, test_inputs, test_targets) =
+///     stratified_train_test_split(&inputs, &targets, 0.5, Some(7));
+///
+/// // Each set has three samples of class 0 and one of class 1.
+/// assert_eq!(train_inputs.rows(), 4);
+/// assert_eq!(test_targets.iter().filter(|&&t| t == 1).count(), 1);
+/// ```
+///
+/// # Panics
+///
+/// - The number of inputs and targets differ.
+/// - `test_fraction` is not strictly between zero and one.
+pub fn stratified_train_test_split(inputs: &Matrix<f64>,
+                                   targets: &Vector<usize>,
+                                   test_fraction: f64,
+                                   seed: Option<usize>)
+                                   -> (Matrix<f64>, Vector<usize>, Matrix<f64>, Vector<usize>) {
+    assert_eq!(inputs.rows(), targets.size());
+    assert!(test_fraction > 0f64 && test_fraction < 1f64,
+            "The test fraction must be between 0 and 1.");
+
+    let mut classes: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, t) in targets.iter().enumerate() {
+        classes.entry(*t).or_insert_with(Vec::new).push(i);
+    }
+
+    let seed = match seed {
+        Some(s) => s,
+        None => thread_rng().gen(),
+    };
+    let mut rng = StdRng::from_seed(&[seed]);
+
+    let mut train_idx = Vec::with_capacity(targets.size());
+    let mut test_idx = Vec::with_capacity(targets.size());
+    for (_, mut members) in classes {
+        in_place_fisher_yates_with_rng(&mut members, &mut rng);
+
+        let n_test = (members.len() as f64 * test_fraction).round() as usize;
+        test_idx.extend_from_slice(&members[..n_test]);
+        train_idx.extend_from_slice(&members[n_test..]);
+    }
+    train_idx.sort();
+    test_idx.sort();
+
+    (inputs.select_rows(&train_idx),
+     targets.select(&train_idx),
+     inputs.select_rows(&test_idx),
+     targets.select(&test_idx))
+}
+
 /// A permutation of 0..n.
 struct ShuffledIndices(Vec<usize>);
 
@@ -199,7 +412,89 @@ impl<'a> Iterator for Folds<'a> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ShuffledIndices, Folds};
+    use super::{ShuffledIndices, Folds, cross_val_score, cross_val_predict,
+                stratified_train_test_split};
+    use linalg::{BaseMatrix, Matrix, Vector};
+    use learning::{LearningResult, SupModel};
+
+    // Predicts the mean of the training targets.
+    struct MeanModel(f64);
+
+    impl SupModel<Matrix<f64>, Vector<f64>> for MeanModel {
+        fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+            Ok(Vector::zeros(inputs.rows()) + self.0)
+        }
+
+        fn train(&mut self, _: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+            self.0 = targets.sum() / targets.size() as f64;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cross_val_score() {
+        let inputs = Matrix::zeros(6, 1);
+        let targets = Vector::new(vec![2.0; 6]);
+
+        let scores = cross_val_score(|| MeanModel(0.0), &inputs, &targets, 3, |outputs, targets| {
+            if outputs == targets { 1.0 } else { 0.0 }
+        }).unwrap();
+
+        assert_eq!(scores, vec![1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_cross_val_predict() {
+        let inputs = Matrix::zeros(6, 1);
+        let targets = Vector::new(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        // Leaving one sample out, the mean of the rest is (15 - t) / 5.
+        let outputs = cross_val_predict(|| MeanModel(0.0), &inputs, &targets, 6).unwrap();
+        let expected = targets.apply(&|t| (15.0 - t) / 5.0);
+
+        for (o, e) in outputs.iter().zip(expected.iter()) {
+            assert!((o - e).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_stratified_train_test_split() {
+        // The input of each sample is its index.
+        let inputs = Matrix::new(100, 1, (0..100).map(|i| i as f64).collect::<Vec<f64>>());
+        let classes = (0..100).map(|i| if i < 80 { 0 } else { 1 }).collect::<Vec<usize>>();
+        let targets = Vector::new(classes);
+
+        let (train_inputs, train_targets, test_inputs, test_targets) =
+            stratified_train_test_split(&inputs, &targets, 0.25, Some(3));
+
+        assert_eq!(train_inputs.rows(), 75);
+        assert_eq!(test_inputs.rows(), 25);
+        assert_eq!(train_targets.iter().filter(|&&t| t == 1).count(), 15);
+        assert_eq!(test_targets.iter().filter(|&&t| t == 1).count(), 5);
+
+        // Every sample is in exactly one set with its own target.
+        let mut seen = train_inputs.data().iter()
+            .chain(test_inputs.data().iter())
+            .map(|&x| x as usize)
+            .collect::<Vec<usize>>();
+        for (x, t) in train_inputs.data().iter().zip(train_targets.iter()) {
+            assert_eq!(targets[*x as usize], *t);
+        }
+        seen.sort();
+        assert_eq!(seen, (0..100).collect::<Vec<usize>>());
+
+        // The split is reproducible.
+        let (_, _, again, _) = stratified_train_test_split(&inputs, &targets, 0.25, Some(3));
+        assert_eq!(again, test_inputs);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_stratified_split_invalid_fraction() {
+        let inputs = Matrix::zeros(4, 1);
+        let targets = Vector::new(vec![0, 0, 1, 1]);
+        let _ = stratified_train_test_split(&inputs, &targets, 1.0, None);
+    }
 
     // k % n == 0
     #[test]
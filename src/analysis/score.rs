@@ -2,10 +2,15 @@
 //! how close predictions and truth are. All functions in this
 //! module obey the convention that higher is better.
 
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::f64;
+
 use libnum::{Zero, One};
 
-use linalg::{BaseMatrix, Matrix};
+use linalg::{BaseMatrix, Matrix, Vector};
 use learning::toolkit::cost_fn::{CostFunc, MeanSqError};
+use learning::toolkit::distance::{pairwise_distances, DistanceMetric};
 
 // ************************************
 // Classification Scores
@@ -51,6 +56,48 @@ pub fn row_accuracy(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
              targets.row_iter().map(|r| r.raw_slice()))
 }
 
+/// Returns the weighted fraction of outputs which match their target.
+///
+/// This is the total weight of the correct outputs divided by the total weight.
+///
+/// # Arguments
+///
+/// * `outputs` - Iterator of output (predicted) labels.
+/// * `targets` - Iterator of expected (actual) labels.
+/// * `weights` - The weight of each sample.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::weighted_accuracy;
+/// let outputs = [1, 1, 0, 0];
+/// let targets = [1, 0, 0, 1];
+/// let weights = [3.0, 1.0, 3.0, 1.0];
+///
+/// assert_eq!(weighted_accuracy(outputs.iter(), targets.iter(), &weights), 0.75);
+/// ```
+///
+/// # Panics
+///
+/// - outputs, targets and weights have different lengths
+pub fn weighted_accuracy<I1, I2, T>(outputs: I1, targets: I2, weights: &[f64]) -> f64
+    where T: PartialEq,
+          I1: ExactSizeIterator + Iterator<Item=T>,
+          I2: ExactSizeIterator + Iterator<Item=T>
+{
+    assert!(outputs.len() == targets.len(), "outputs and targets must have the same length");
+    assert!(outputs.len() == weights.len(), "outputs and weights must have the same length");
+
+    let total = weights.iter().sum::<f64>();
+    let correct = outputs
+        .zip(targets)
+        .zip(weights.iter())
+        .filter(|&((ref x, ref y), _)| x == y)
+        .map(|(_, w)| w)
+        .sum::<f64>();
+    correct / total
+}
+
 /// Returns the precision score for 2 class classification.
 ///
 /// Precision is calculated with true-positive / (true-positive + false-positive),
@@ -197,6 +244,132 @@ pub fn f1<'a, I, T>(outputs: I, targets: I) -> f64
     2.0f64 * tpos / (2.0f64 * tpos + fneg + fpos)
 }
 
+/// Accumulates classification scores over batches of predictions.
+///
+/// The accumulator keeps running counts of each pair of target and
+/// output labels, so the accuracy and the per-class precision, recall
+/// and f1 scores of all predictions seen so far are available at any
+/// time without storing the predictions.
+///
+/// Scores computed before any predictions are seen, or for a class
+/// which has not been predicted or seen as a target, may be `NaN`,
+/// matching the one-shot functions.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::MetricAccumulator;
+/// use rusty_machine::linalg::Vector;
+///
+/// let mut acc = MetricAccumulator::new();
+///
+/// acc.update(&Vector::new(vec![1, 1, 0]), &Vector::new(vec![1, 0, 0]));
+/// acc.update(&Vector::new(vec![0, 1, 1]), &Vector::new(vec![1, 1, 1]));
+///
+/// assert_eq!(acc.total(), 6);
+/// assert_eq!(acc.accuracy(), 4.0 / 6.0);
+/// assert_eq!(acc.recall(1), 0.75);
+/// ```
+#[derive(Debug, Default)]
+pub struct MetricAccumulator {
+    correct: usize,
+    total: usize,
+    n_classes: usize,
+    /// The number of samples with each (target, output) pair of labels.
+    counts: HashMap<(usize, usize), usize>,
+}
+
+impl MetricAccumulator {
+    /// Construct an accumulator which has seen no predictions.
+    pub fn new() -> MetricAccumulator {
+        MetricAccumulator::default()
+    }
+
+    /// Add a batch of outputs and their targets to the counts.
+    ///
+    /// # Panics
+    ///
+    /// - outputs and targets have different length
+    pub fn update(&mut self, outputs: &Vector<usize>, targets: &Vector<usize>) {
+        assert!(outputs.size() == targets.size(),
+                "outputs and targets must have the same length");
+
+        for (&o, &t) in outputs.iter().zip(targets.iter()) {
+            if o == t {
+                self.correct += 1;
+            }
+            *self.counts.entry((t, o)).or_insert(0) += 1;
+            self.n_classes = self.n_classes.max(o.max(t) + 1);
+        }
+        self.total += outputs.size();
+    }
+
+    /// The number of predictions seen.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The fraction of the predictions seen which match their target.
+    pub fn accuracy(&self) -> f64 {
+        self.correct as f64 / self.total as f64
+    }
+
+    /// The precision of the predictions of `class`.
+    ///
+    /// This is the fraction of the outputs equal to
+    /// `class` whose target is also `class`.
+    pub fn precision(&self, class: usize) -> f64 {
+        let (tp, fp, _) = self.class_counts(class);
+        tp / (tp + fp)
+    }
+
+    /// The recall of the predictions of `class`.
+    ///
+    /// This is the fraction of the targets equal to
+    /// `class` whose output is also `class`.
+    pub fn recall(&self, class: usize) -> f64 {
+        let (tp, _, fn_) = self.class_counts(class);
+        tp / (tp + fn_)
+    }
+
+    /// The f1 score of the predictions of `class`.
+    pub fn f1(&self, class: usize) -> f64 {
+        let (tp, fp, fn_) = self.class_counts(class);
+        2f64 * tp / (2f64 * tp + fp + fn_)
+    }
+
+    /// The confusion matrix of the predictions seen.
+    ///
+    /// The entry `C_ij` is the number of samples with target label
+    /// `i` and output label `j`. The matrix has a row and column for
+    /// each label up to the largest seen.
+    pub fn confusion_matrix(&self) -> Matrix<usize> {
+        let n = self.n_classes;
+        let mut data = vec![0; n * n];
+        for (&(t, o), &count) in &self.counts {
+            data[t * n + o] = count;
+        }
+
+        Matrix::new(n, n, data)
+    }
+
+    /// The true positive, false positive and false negative counts of a class.
+    fn class_counts(&self, class: usize) -> (f64, f64, f64) {
+        let (mut tp, mut fp, mut fn_) = (0usize, 0usize, 0usize);
+        for (&(t, o), &count) in &self.counts {
+            if t == class && o == class {
+                tp += count;
+            } else if o == class {
+                fp += count;
+            } else if t == class {
+                fn_ += count;
+            }
+        }
+
+        (tp as f64, fp as f64, fn_ as f64)
+    }
+}
+
 // ************************************
 // Regression Scores
 // ************************************
@@ -211,10 +384,184 @@ pub fn neg_mean_squared_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f
     -2f64 * MeanSqError::cost(outputs, targets)
 }
 
+/// Returns the additive inverse of the median absolute error of the
+/// outputs. So higher is better, and the returned value is never positive.
+///
+/// The absolute differences of all elements are used, so the outputs of
+/// multi-output models are treated as one flattened set of residuals.
+/// For an even number of elements the median is the mean of the two
+/// middle values.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::neg_median_absolute_error;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let outputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 100.0]);
+/// let targets = Matrix::new(4, 1, vec![1.5, 2.0, 2.0, 0.0]);
+///
+/// // The residuals are 0.5, 0, 1 and 100.
+/// assert_eq!(neg_median_absolute_error(&outputs, &targets), -0.75);
+/// ```
+///
+/// # Panics
+///
+/// - outputs and targets have different dimensions
+/// - outputs and targets are empty
+pub fn neg_median_absolute_error(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64
+{
+    assert!(outputs.rows() == targets.rows() && outputs.cols() == targets.cols(),
+            "outputs and targets must have the same dimensions");
+    assert!(outputs.rows() * outputs.cols() > 0, "outputs and targets must not be empty");
+
+    let mut residuals = outputs.data()
+        .iter()
+        .zip(targets.data().iter())
+        .map(|(o, t)| (o - t).abs())
+        .collect::<Vec<f64>>();
+    residuals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let n = residuals.len();
+    let median = if n % 2 == 1 {
+        residuals[n / 2]
+    } else {
+        (residuals[n / 2 - 1] + residuals[n / 2]) / 2f64
+    };
+
+    -median
+}
+
+// ************************************
+// Clustering Scores
+// ************************************
+
+/// Returns the mean silhouette coefficient of a clustering.
+///
+/// For each point the silhouette is `(b - a) / max(a, b)` where `a` is the
+/// mean distance to the other points in its cluster and `b` is the mean
+/// distance to the points in the nearest other cluster. Points in a
+/// cluster of their own, or for which `a` and `b` are both zero, have a
+/// silhouette of zero. See
+/// [Silhouette](https://en.wikipedia.org/wiki/Silhouette_(clustering)) for details.
+///
+/// # Arguments
+///
+/// * `inputs` - Matrix with a data point in each row.
+/// * `labels` - The cluster label of each data point.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::analysis::score::silhouette_score;
+/// use rusty_machine::linalg::Matrix;
+///
+/// let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 10.0, 11.0]);
+/// let labels = [0, 0, 1, 1];
+///
+/// assert!(silhouette_score(&inputs, &labels) > 0.8);
+/// ```
+///
+/// # Panics
+///
+/// - inputs and labels have different length
+/// - labels contains fewer than two distinct clusters
+pub fn silhouette_score(inputs: &Matrix<f64>, labels: &[usize]) -> f64 {
+    assert!(inputs.rows() == labels.len(), "inputs and labels must have the same length");
+
+    let n_clusters = labels.iter().max().map_or(0, |m| m + 1);
+    let mut sizes = vec![0usize; n_clusters];
+    for &l in labels {
+        sizes[l] += 1;
+    }
+    assert!(sizes.iter().filter(|&&s| s > 0).count() > 1,
+            "labels must contain at least two clusters");
+
+    let dists = pairwise_distances(inputs, inputs, DistanceMetric::Euclidean);
+
+    let mut total = 0f64;
+    for (i, row) in dists.row_iter().enumerate() {
+        let mut dist_sums = vec![0f64; n_clusters];
+        for (d, &l) in row.raw_slice().iter().zip(labels.iter()) {
+            dist_sums[l] += *d;
+        }
+
+        let own = labels[i];
+        if sizes[own] == 1 {
+            continue;
+        }
+
+        let a = dist_sums[own] / (sizes[own] - 1) as f64;
+        let b = (0..n_clusters)
+            .filter(|&c| c != own && sizes[c] > 0)
+            .map(|c| dist_sums[c] / sizes[c] as f64)
+            .fold(f64::INFINITY, |x, y| x.min(y));
+
+        // Coincident clusters give zero rather than NaN.
+        let scale = a.max(b);
+        if scale > 0f64 {
+            total += (b - a) / scale;
+        }
+    }
+
+    total / inputs.rows() as f64
+}
+
 #[cfg(test)]
 mod tests {
-    use linalg::Matrix;
-    use super::{accuracy, precision, recall, f1, neg_mean_squared_error};
+    use linalg::{BaseMatrix, Matrix, Vector};
+    use analysis::confusion_matrix::confusion_matrix;
+    use super::{accuracy, weighted_accuracy, precision, recall, f1, neg_mean_squared_error,
+                neg_median_absolute_error, silhouette_score, MetricAccumulator};
+
+    #[test]
+    fn test_metric_accumulator_matches_scores() {
+        let outputs = [1, 1, 1, 0, 0, 0, 1, 0];
+        let targets = [1, 1, 0, 0, 1, 1, 1, 0];
+
+        let mut acc = MetricAccumulator::new();
+        for (o, t) in outputs.chunks(3).zip(targets.chunks(3)) {
+            acc.update(&Vector::new(o.to_vec()), &Vector::new(t.to_vec()));
+        }
+
+        assert_eq!(acc.total(), 8);
+        assert_eq!(acc.accuracy(), accuracy(outputs.iter(), targets.iter()));
+        assert_eq!(acc.precision(1), precision(outputs.iter(), targets.iter()));
+        assert_eq!(acc.recall(1), recall(outputs.iter(), targets.iter()));
+        assert_eq!(acc.f1(1), f1(outputs.iter(), targets.iter()));
+    }
+
+    #[test]
+    fn test_metric_accumulator_confusion_matrix() {
+        let outputs = vec![0, 0, 2, 2, 0, 2];
+        let targets = vec![2, 0, 2, 2, 0, 1];
+
+        let mut acc = MetricAccumulator::new();
+        acc.update(&Vector::new(outputs[..2].to_vec()), &Vector::new(targets[..2].to_vec()));
+        acc.update(&Vector::new(outputs[2..].to_vec()), &Vector::new(targets[2..].to_vec()));
+
+        assert_eq!(acc.confusion_matrix(), confusion_matrix(&outputs, &targets, None));
+
+        // Class 1 is never predicted.
+        assert_eq!(acc.recall(1), 0.0);
+        assert!(acc.precision(1).is_nan());
+        assert_eq!(acc.precision(2), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn test_metric_accumulator_empty() {
+        let acc = MetricAccumulator::new();
+        assert_eq!(acc.total(), 0);
+        assert!(acc.accuracy().is_nan());
+        assert_eq!(acc.confusion_matrix().rows(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_metric_accumulator_wrong_length() {
+        let mut acc = MetricAccumulator::new();
+        acc.update(&Vector::new(vec![0, 1]), &Vector::new(vec![0]));
+    }
 
     #[test]
     fn test_accuracy() {
@@ -227,6 +574,28 @@ mod tests {
         assert_eq!(accuracy(outputs.iter(), targets.iter()), 5.0f64 / 6.0f64);
     }
 
+    #[test]
+    fn test_weighted_accuracy() {
+        let outputs = [1, 2, 3, 4];
+        let targets = [1, 2, 0, 0];
+
+        // Uniform weights give the accuracy.
+        let uniform = [2.0; 4];
+        assert_eq!(weighted_accuracy(outputs.iter(), targets.iter(), &uniform),
+                   accuracy(outputs.iter(), targets.iter()));
+
+        let weights = [1.0, 0.0, 0.5, 2.5];
+        assert_eq!(weighted_accuracy(outputs.iter(), targets.iter(), &weights), 0.25);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_weighted_accuracy_wrong_weights() {
+        let outputs = [1, 2, 3];
+        let targets = [1, 2, 3];
+        weighted_accuracy(outputs.iter(), targets.iter(), &[1.0, 1.0]);
+    }
+
     #[test]
     fn test_precision() {
         let outputs = [1, 1, 1, 0, 0, 0];
@@ -354,4 +723,57 @@ mod tests {
             ]);
         assert_eq!(neg_mean_squared_error(&outputs, &targets), -3f64);
     }
+
+    #[test]
+    fn test_neg_median_absolute_error_odd() {
+        let outputs = Matrix::new(3, 1, vec![1f64, 2f64, 3f64]);
+        let targets = Matrix::new(3, 1, vec![2f64, 4f64, 3f64]);
+        assert_eq!(neg_median_absolute_error(&outputs, &targets), -1f64);
+    }
+
+    #[test]
+    fn test_neg_median_absolute_error_2d() {
+        let outputs = Matrix::new(2, 2, vec![
+            1f64, 2f64,
+            3f64, 4f64
+            ]);
+        let targets = Matrix::new(2, 2, vec![
+            1.5f64, 2f64,
+            6f64,   -96f64
+            ]);
+        // The residuals 0.5, 0, 3 and 100 have a median of 1.75.
+        assert_eq!(neg_median_absolute_error(&outputs, &targets), -1.75f64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_neg_median_absolute_error_mismatched() {
+        let outputs = Matrix::new(2, 1, vec![1f64, 2f64]);
+        let targets = Matrix::new(1, 2, vec![1f64, 2f64]);
+        neg_median_absolute_error(&outputs, &targets);
+    }
+
+    #[test]
+    fn test_silhouette_score() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 2.0, 5.0]);
+        let labels = [0, 0, 1];
+
+        // a = 2, b = 5 for the first point, a = 2, b = 3 for the second
+        // and the singleton scores zero.
+        let expected = (3.0 / 5.0 + 1.0 / 3.0) / 3.0;
+        assert!((silhouette_score(&inputs, &labels) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_silhouette_score_coincident_points() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(silhouette_score(&inputs, &[0, 0, 1, 1]), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_silhouette_score_single_cluster() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 2.0, 5.0]);
+        silhouette_score(&inputs, &[0, 0, 0]);
+    }
 }
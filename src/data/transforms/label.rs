@@ -0,0 +1,161 @@
+//! The Label Encoder
+//!
+//! This module contains the `LabelEncoder` which maps categorical
+//! labels to the contiguous class codes `0..n_classes` used by the
+//! classifiers.
+//!
+//! The codes are assigned in the sorted order of the distinct labels
+//! seen during fitting. Because the labels and codes have different
+//! types the encoder does not implement the `Transformer` trait and
+//! instead provides its own `transform` and `inverse_transform` functions.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::data::transforms::LabelEncoder;
+//!
+//! let labels = vec!["cat", "dog", "cat", "bird"];
+//! let encoder = LabelEncoder::fit(&labels).unwrap();
+//!
+//! // The labels are sorted so "bird" is 0, "cat" is 1 and "dog" is 2.
+//! let codes = encoder.transform(&labels).unwrap();
+//! assert_eq!(codes.into_vec(), vec![1, 2, 1, 0]);
+//!
+//! let decoded = encoder.inverse_transform(&[2, 0]).unwrap();
+//! assert_eq!(decoded, vec!["dog", "bird"]);
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use linalg::Vector;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The Label Encoder
+///
+/// Maps each distinct label to its position in the sorted
+/// list of labels seen during fitting.
+#[derive(Debug)]
+pub struct LabelEncoder<L: Hash + Eq + Ord + Clone> {
+    /// The distinct labels in sorted order.
+    classes: Vec<L>,
+    /// The code of each label.
+    codes: HashMap<L, usize>,
+}
+
+impl<L: Hash + Eq + Ord + Clone> LabelEncoder<L> {
+    /// Fit a `LabelEncoder` to the given labels.
+    ///
+    /// # Failures
+    ///
+    /// - The labels are empty.
+    pub fn fit(labels: &[L]) -> LearningResult<LabelEncoder<L>> {
+        if labels.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Cannot fit a label encoder to empty labels."));
+        }
+
+        let mut classes = labels.to_vec();
+        classes.sort();
+        classes.dedup();
+
+        let codes = classes.iter()
+            .cloned()
+            .enumerate()
+            .map(|(code, label)| (label, code))
+            .collect();
+
+        Ok(LabelEncoder {
+            classes: classes,
+            codes: codes,
+        })
+    }
+
+    /// The distinct labels in the order of their codes.
+    pub fn classes(&self) -> &[L] {
+        &self.classes
+    }
+
+    /// The number of distinct labels.
+    pub fn n_classes(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Map the labels to their codes.
+    ///
+    /// # Failures
+    ///
+    /// - A label was not seen during fitting.
+    pub fn transform(&self, labels: &[L]) -> LearningResult<Vector<usize>> {
+        let mut codes = Vec::with_capacity(labels.len());
+        for label in labels {
+            match self.codes.get(label) {
+                Some(&code) => codes.push(code),
+                None => {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "Label was not seen during fitting."))
+                }
+            }
+        }
+
+        Ok(Vector::new(codes))
+    }
+
+    /// Map the codes back to their labels.
+    ///
+    /// # Failures
+    ///
+    /// - A code is not less than the number of classes.
+    pub fn inverse_transform(&self, codes: &[usize]) -> LearningResult<Vec<L>> {
+        codes.iter()
+            .map(|&code| {
+                self.classes.get(code).cloned().ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidData,
+                               "Code is not less than the number of classes.")
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LabelEncoder;
+
+    #[test]
+    fn sorted_codes_test() {
+        let labels = vec![30u32, 10, 20, 10, 30];
+        let encoder = LabelEncoder::fit(&labels).unwrap();
+
+        assert_eq!(encoder.classes(), &[10, 20, 30]);
+        assert_eq!(encoder.n_classes(), 3);
+        assert_eq!(encoder.transform(&labels).unwrap().into_vec(), vec![2, 0, 1, 0, 2]);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let labels = vec!["setosa".to_string(),
+                          "virginica".to_string(),
+                          "versicolor".to_string(),
+                          "setosa".to_string()];
+        let encoder = LabelEncoder::fit(&labels).unwrap();
+
+        let codes = encoder.transform(&labels).unwrap();
+        assert_eq!(encoder.inverse_transform(codes.data()).unwrap(), labels);
+    }
+
+    #[test]
+    fn unseen_label_test() {
+        let encoder = LabelEncoder::fit(&["a", "b"]).unwrap();
+
+        assert!(encoder.transform(&["a", "c"]).is_err());
+        assert!(encoder.inverse_transform(&[0, 2]).is_err());
+    }
+
+    #[test]
+    fn empty_labels_test() {
+        let labels: Vec<i32> = vec![];
+        assert!(LabelEncoder::fit(&labels).is_err());
+    }
+}
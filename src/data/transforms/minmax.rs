@@ -7,6 +7,9 @@
 //! This is commonly used to transform the data to have a minimum of
 //! `0` and a maximum of `1`.
 //!
+//! Constant columns have no range to scale. They are shifted so
+//! that the constant value is mapped to the specified minimum.
+//!
 //! # Examples
 //!
 //! ```
@@ -111,6 +114,11 @@ impl<T: Float> TransformFitter<Matrix<T>, MinMaxScaler<T>> for MinMaxFitter<T> {
         // holds `b`.
         let scales = input_min_max.iter()
             .map(|&(x, y)| {
+                // Constant columns are only shifted.
+                if x == y {
+                    return Ok(T::one());
+                }
+
                 let s = (self.scaled_max - self.scaled_min) / (y - x);
                 if s.is_finite() {
                     Ok(s)
@@ -123,7 +131,11 @@ impl<T: Float> TransformFitter<Matrix<T>, MinMaxScaler<T>> for MinMaxFitter<T> {
 
         let consts = input_min_max.iter()
             .zip(scales.iter())
-            .map(|(&(_, x), &s)| self.scaled_max - x * s)
+            .map(|(&(x, y), &s)| if x == y {
+                self.scaled_min - x
+            } else {
+                self.scaled_max - y * s
+            })
             .collect::<Vec<_>>();
         
         Ok(MinMaxScaler {
@@ -249,8 +261,17 @@ mod tests {
     fn constant_feature_test() {
         let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 1.0, 3.0]);
 
-        let res = MinMaxFitter::new(0.0, 1.0).fit(&inputs);
-        assert!(res.is_err());
+        let mut scaler = MinMaxFitter::new(-1.0, 1.0).fit(&inputs).unwrap();
+        let transformed = scaler.transform(inputs.clone()).unwrap();
+
+        // The constant column is mapped to the minimum.
+        assert_eq!(transformed[[0, 0]], -1.0);
+        assert_eq!(transformed[[1, 0]], -1.0);
+        assert!((transformed[[0, 1]] + 1.0).abs() < 1e-10);
+        assert!((transformed[[1, 1]] - 1.0).abs() < 1e-10);
+
+        let original = scaler.inv_transform(transformed).unwrap();
+        assert!((inputs - original).data().iter().all(|x| x.abs() < 1e-10));
     }
 
     #[test]
@@ -25,6 +25,7 @@
 //! let transformed = scaler.transform(data).expect("Failed to transformer data");
 //! ```
 
+pub mod label;
 pub mod minmax;
 pub mod normalize;
 pub mod standardize;
@@ -32,6 +33,7 @@ pub mod shuffle;
 
 use learning::LearningResult;
 
+pub use self::label::LabelEncoder;
 pub use self::minmax::MinMaxFitter;
 pub use self::normalize::Normalizer;
 pub use self::shuffle::Shuffler;
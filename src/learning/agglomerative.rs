@@ -0,0 +1,452 @@
+//! Agglomerative Hierarchical Clustering
+//!
+//! Provides an implementation of bottom-up hierarchical clustering.
+//!
+//! Training starts with every point in its own cluster and repeatedly
+//! merges the two closest clusters until a single cluster remains. The
+//! distance between clusters is determined by the `Linkage`. The sequence
+//! of merges forms a dendrogram which is available through `merges`.
+//!
+//! The dendrogram is cut at `n_clusters` to give the training clusters,
+//! and `cut` can be used to choose a different number of clusters after
+//! training. The `predict` function assigns each point to the cluster
+//! of its nearest training point.
+//!
+//! The implementation stores the full pairwise distance matrix,
+//! requiring `O(n²)` memory, and searches it for the closest pair at
+//! each merge.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::agglomerative::{Agglomerative, Linkage};
+//! use rusty_machine::learning::UnSupModel;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(6, 2, vec![1.0, 2.0,
+//!                                     1.1, 2.2,
+//!                                     0.9, 1.9,
+//!                                     -2.0, 3.0,
+//!                                     -2.2, 3.1,
+//!                                     -2.1, 2.9]);
+//!
+//! let mut model = Agglomerative::new(2, Linkage::Average);
+//! model.train(&inputs).unwrap();
+//!
+//! let clusters = model.clusters().unwrap();
+//! assert_eq!(clusters[0], clusters[1]);
+//! assert!(clusters[0] != clusters[3]);
+//!
+//! // Five merges join the six points into a single cluster.
+//! let merges = model.merges().unwrap();
+//! assert_eq!(merges.len(), 5);
+//! ```
+
+use learning::{LearningResult, UnSupModel};
+use learning::error::{Error, ErrorKind};
+use learning::toolkit::distance::{pairwise_distances, DistanceMetric};
+
+use linalg::{Matrix, BaseMatrix, Vector};
+
+use std::f64;
+
+/// The distance between clusters used to choose merges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Linkage {
+    /// The distance between the closest pair of points.
+    Single,
+    /// The distance between the furthest pair of points.
+    Complete,
+    /// The mean distance between all pairs of points.
+    Average,
+    /// Merge the pair of clusters giving the smallest increase in
+    /// the total within-cluster variance.
+    ///
+    /// Requires the Euclidean distance.
+    Ward,
+}
+
+/// A merge of two clusters in the dendrogram.
+///
+/// The training points are the clusters `0` to `n - 1`, and the
+/// cluster created by the `i`th merge has the index `n + i`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Merge {
+    /// The smaller index of the merged clusters.
+    pub left: usize,
+    /// The larger index of the merged clusters.
+    pub right: usize,
+    /// The linkage distance between the merged clusters.
+    pub distance: f64,
+    /// The number of points in the new cluster.
+    pub size: usize,
+}
+
+/// Agglomerative Clustering Model
+///
+/// Implements hierarchical clustering via the `UnSupModel` trait.
+#[derive(Debug)]
+pub struct Agglomerative {
+    n_clusters: usize,
+    linkage: Linkage,
+    metric: DistanceMetric,
+    merges: Option<Vec<Merge>>,
+    clusters: Option<Vector<usize>>,
+    train_data: Option<Matrix<f64>>,
+}
+
+impl Agglomerative {
+    /// Constructs an untrained agglomerative clustering model.
+    ///
+    /// Requires the number of clusters to cut the dendrogram at
+    /// and the linkage to use. The Euclidean distance is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::agglomerative::{Agglomerative, Linkage};
+    ///
+    /// let model = Agglomerative::new(3, Linkage::Ward);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `n_clusters` is zero.
+    pub fn new(n_clusters: usize, linkage: Linkage) -> Agglomerative {
+        assert!(n_clusters > 0, "The number of clusters must be greater than 0.");
+
+        Agglomerative {
+            n_clusters: n_clusters,
+            linkage: linkage,
+            metric: DistanceMetric::Euclidean,
+            merges: None,
+            clusters: None,
+            train_data: None,
+        }
+    }
+
+    /// Get the number of clusters the dendrogram is cut at.
+    pub fn n_clusters(&self) -> usize {
+        self.n_clusters
+    }
+
+    /// Get the linkage.
+    pub fn linkage(&self) -> Linkage {
+        self.linkage
+    }
+
+    /// Get the distance metric.
+    pub fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    /// Set the distance metric between points.
+    ///
+    /// `Linkage::Ward` only supports the Euclidean distance and the
+    /// asymmetric `DistanceMetric::KullbackLeibler` is not supported.
+    pub fn set_metric(&mut self, metric: DistanceMetric) {
+        self.metric = metric;
+    }
+
+    /// Get the merges making up the dendrogram, in the order they were made.
+    ///
+    /// Returns `None` if the model has not been trained.
+    pub fn merges(&self) -> Option<&[Merge]> {
+        self.merges.as_ref().map(|m| &m[..])
+    }
+
+    /// Get the clusters of the training data.
+    ///
+    /// Returns `None` if the model has not been trained.
+    pub fn clusters(&self) -> Option<&Vector<usize>> {
+        self.clusters.as_ref()
+    }
+
+    /// Cut the dendrogram to give `n_clusters` clusters of the training data.
+    ///
+    /// Clusters are numbered in order of their lowest training point.
+    ///
+    /// # Failures
+    ///
+    /// - The model has not been trained.
+    /// - `n_clusters` is zero or more than the number of training points.
+    pub fn cut(&self, n_clusters: usize) -> LearningResult<Vector<usize>> {
+        if let Some(ref merges) = self.merges {
+            let n = merges.len() + 1;
+            if n_clusters == 0 || n_clusters > n {
+                return Err(Error::new(ErrorKind::InvalidParameters,
+                                      "The number of clusters must be between 1 and the \
+                                       number of training points."));
+            }
+
+            // The cluster each dendrogram node was merged into.
+            let mut parent = (0..2 * n - 1).collect::<Vec<usize>>();
+            for (i, m) in merges[..n - n_clusters].iter().enumerate() {
+                parent[m.left] = n + i;
+                parent[m.right] = n + i;
+            }
+
+            let mut labels = vec![None; 2 * n - 1];
+            let mut next = 0;
+            let clusters = (0..n)
+                .map(|i| {
+                    let mut root = i;
+                    while parent[root] != root {
+                        root = parent[root];
+                    }
+
+                    *labels[root].get_or_insert_with(|| {
+                        next += 1;
+                        next - 1
+                    })
+                })
+                .collect::<Vec<usize>>();
+
+            Ok(Vector::new(clusters))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// The distance between a newly merged cluster and another cluster.
+    ///
+    /// `d_ik` and `d_jk` are the distances from the merged clusters `i`
+    /// and `j` to `k`, and `d_ij` the distance between `i` and `j`.
+    fn update_distance(&self,
+                       d_ik: f64,
+                       d_jk: f64,
+                       d_ij: f64,
+                       n_i: f64,
+                       n_j: f64,
+                       n_k: f64)
+                       -> f64 {
+        match self.linkage {
+            Linkage::Single => d_ik.min(d_jk),
+            Linkage::Complete => d_ik.max(d_jk),
+            Linkage::Average => (n_i * d_ik + n_j * d_jk) / (n_i + n_j),
+            Linkage::Ward => {
+                let sq = ((n_i + n_k) * d_ik * d_ik + (n_j + n_k) * d_jk * d_jk -
+                          n_k * d_ij * d_ij) / (n_i + n_j + n_k);
+                sq.max(0f64).sqrt()
+            }
+        }
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Vector<usize>> for Agglomerative {
+    /// Train the model using input data.
+    ///
+    /// Builds the full dendrogram and cuts it at `n_clusters`.
+    ///
+    /// # Failures
+    ///
+    /// - There are fewer input rows than `n_clusters`.
+    /// - `Linkage::Ward` is used with a non-Euclidean metric.
+    /// - The metric is `DistanceMetric::KullbackLeibler`.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        let n = inputs.rows();
+        if n < self.n_clusters {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of input rows must be at least the number \
+                                   of clusters."));
+        }
+
+        if self.linkage == Linkage::Ward && self.metric != DistanceMetric::Euclidean {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Ward linkage requires the Euclidean distance."));
+        }
+
+        if self.metric == DistanceMetric::KullbackLeibler {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Agglomerative clustering requires a symmetric distance."));
+        }
+
+        let mut dists = pairwise_distances(inputs, inputs, self.metric).into_vec();
+
+        // The dendrogram index and size of the cluster in each slot.
+        let mut ids = (0..n).collect::<Vec<usize>>();
+        let mut sizes = vec![1usize; n];
+        let mut active = vec![true; n];
+
+        let mut merges = Vec::with_capacity(n.saturating_sub(1));
+        for step in 0..n.saturating_sub(1) {
+            let mut closest = (0, 0, f64::INFINITY);
+            for i in 0..n {
+                if !active[i] {
+                    continue;
+                }
+                for j in i + 1..n {
+                    if active[j] && dists[i * n + j] < closest.2 {
+                        closest = (i, j, dists[i * n + j]);
+                    }
+                }
+            }
+
+            // Merge the cluster in slot `j` into slot `i`.
+            let (i, j, d_ij) = closest;
+            let (n_i, n_j) = (sizes[i] as f64, sizes[j] as f64);
+            for k in 0..n {
+                if active[k] && k != i && k != j {
+                    let d = self.update_distance(dists[i * n + k],
+                                                 dists[j * n + k],
+                                                 d_ij,
+                                                 n_i,
+                                                 n_j,
+                                                 sizes[k] as f64);
+                    dists[i * n + k] = d;
+                    dists[k * n + i] = d;
+                }
+            }
+
+            let (left, right) = if ids[i] < ids[j] { (ids[i], ids[j]) } else { (ids[j], ids[i]) };
+            merges.push(Merge {
+                left: left,
+                right: right,
+                distance: d_ij,
+                size: sizes[i] + sizes[j],
+            });
+
+            ids[i] = n + step;
+            sizes[i] += sizes[j];
+            active[j] = false;
+        }
+
+        self.merges = Some(merges);
+        self.clusters = Some(self.cut(self.n_clusters)?);
+        self.train_data = Some(inputs.clone());
+        Ok(())
+    }
+
+    /// Predict clusters for new points.
+    ///
+    /// Each point is assigned to the cluster of its nearest training point.
+    ///
+    /// The model must be trained.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        if let (&Some(ref train_data), &Some(ref clusters)) = (&self.train_data, &self.clusters) {
+            if inputs.cols() != train_data.cols() {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let dists = pairwise_distances(inputs, train_data, self.metric);
+            let predictions = dists.row_iter()
+                .map(|row| {
+                    let (nearest, _) = row.raw_slice()
+                        .iter()
+                        .enumerate()
+                        .fold((0, f64::INFINITY),
+                              |(a, d_a), (b, &d_b)| if d_b < d_a { (b, d_b) } else { (a, d_a) });
+                    clusters[nearest]
+                })
+                .collect::<Vec<usize>>();
+
+            Ok(Vector::new(predictions))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Agglomerative, Linkage};
+    use learning::UnSupModel;
+    use learning::toolkit::distance::DistanceMetric;
+    use linalg::Matrix;
+
+    #[test]
+    fn test_single_linkage_chains() {
+        // Evenly spaced points chain together under single linkage.
+        let inputs = Matrix::new(5, 1, vec![0.0, 1.0, 2.0, 3.0, 10.0]);
+
+        let mut model = Agglomerative::new(2, Linkage::Single);
+        model.train(&inputs).unwrap();
+
+        assert_eq!(model.clusters().unwrap().data(), &vec![0, 0, 0, 0, 1]);
+
+        let merges = model.merges().unwrap();
+        assert_eq!(merges.len(), 4);
+        assert_eq!((merges[0].left, merges[0].right, merges[0].size), (0, 1, 2));
+        assert_eq!((merges[1].left, merges[1].right, merges[1].size), (2, 5, 3));
+        assert_eq!(merges[3].distance, 7.0);
+        assert_eq!(merges[3].size, 5);
+    }
+
+    #[test]
+    fn test_complete_and_average_distances() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 4.0]);
+
+        let mut complete = Agglomerative::new(1, Linkage::Complete);
+        complete.train(&inputs).unwrap();
+        assert_eq!(complete.merges().unwrap()[1].distance, 4.0);
+
+        let mut average = Agglomerative::new(1, Linkage::Average);
+        average.train(&inputs).unwrap();
+        assert_eq!(average.merges().unwrap()[1].distance, 3.5);
+    }
+
+    #[test]
+    fn test_ward_distance() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 4.0]);
+
+        let mut model = Agglomerative::new(1, Linkage::Ward);
+        model.train(&inputs).unwrap();
+
+        // The Ward distance between {0, 1} and {4} is sqrt(2 * 2 * 1 / 3) * 3.5.
+        let expected = (4f64 / 3f64).sqrt() * 3.5;
+        assert!((model.merges().unwrap()[1].distance - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cut() {
+        let inputs = Matrix::new(4, 1, vec![0.0, 0.1, 5.0, 5.2]);
+
+        let mut model = Agglomerative::new(1, Linkage::Average);
+        model.train(&inputs).unwrap();
+
+        assert_eq!(model.clusters().unwrap().data(), &vec![0, 0, 0, 0]);
+        assert_eq!(model.cut(2).unwrap().data(), &vec![0, 0, 1, 1]);
+        assert_eq!(model.cut(3).unwrap().data(), &vec![0, 0, 1, 2]);
+        assert_eq!(model.cut(4).unwrap().data(), &vec![0, 1, 2, 3]);
+        assert!(model.cut(0).is_err());
+        assert!(model.cut(5).is_err());
+    }
+
+    #[test]
+    fn test_predict() {
+        let inputs = Matrix::new(4, 1, vec![0.0, 0.1, 5.0, 5.2]);
+
+        let mut model = Agglomerative::new(2, Linkage::Complete);
+        assert!(model.predict(&inputs).is_err());
+
+        model.train(&inputs).unwrap();
+        let predictions = model.predict(&Matrix::new(2, 1, vec![6.0, -1.0])).unwrap();
+        assert_eq!(predictions.data(), &vec![1, 0]);
+
+        assert!(model.predict(&Matrix::new(1, 2, vec![0.0, 0.0])).is_err());
+    }
+
+    #[test]
+    fn test_invalid_training() {
+        let inputs = Matrix::new(2, 1, vec![0.0, 1.0]);
+
+        let mut model = Agglomerative::new(3, Linkage::Single);
+        assert!(model.train(&inputs).is_err());
+
+        let mut ward = Agglomerative::new(1, Linkage::Ward);
+        ward.set_metric(DistanceMetric::Manhattan);
+        assert!(ward.train(&inputs).is_err());
+
+        let mut kl = Agglomerative::new(1, Linkage::Average);
+        kl.set_metric(DistanceMetric::KullbackLeibler);
+        assert!(kl.train(&inputs).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_clusters() {
+        let _ = Agglomerative::new(0, Linkage::Single);
+    }
+}
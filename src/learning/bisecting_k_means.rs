@@ -0,0 +1,489 @@
+//! Bisecting K-means Clustering
+//!
+//! Provides implementation of bisecting K-Means clustering.
+//!
+//! Starting with all of the data in a single cluster, the cluster
+//! with the largest sum of squared errors (SSE) is repeatedly split
+//! in two using 2-means until the desired number of clusters is
+//! reached. Each split is attempted several times and the split with
+//! the lowest SSE is kept.
+//!
+//! The splits form a binary tree which can be cut to recover a
+//! coarser clustering.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::linalg::Matrix;
+//! use rusty_machine::learning::bisecting_k_means::BisectingKMeans;
+//! use rusty_machine::learning::UnSupModel;
+//!
+//! let inputs = Matrix::new(6, 2, vec![1.0, 2.0, 1.1, 2.1, 5.0, 5.0,
+//!                                     5.1, 4.9, -3.0, -3.0, -3.1, -2.9]);
+//! let test_inputs = Matrix::new(1, 2, vec![1.0, 2.2]);
+//!
+//! // Create model with 3 clusters and a fixed seed.
+//! let mut model = BisectingKMeans::new(3);
+//! model.set_seed(42);
+//!
+//! // Where inputs is a Matrix with features in columns.
+//! model.train(&inputs).unwrap();
+//!
+//! // Where test_inputs is a Matrix with features in columns.
+//! let a = model.predict(&test_inputs).unwrap();
+//!
+//! // The centroids when only a single split has been made.
+//! let coarse = model.cut(2).unwrap();
+//! assert_eq!(coarse.rows(), 2);
+//! ```
+
+use linalg::{Matrix, Vector, Axes, BaseMatrix};
+use learning::{LearningResult, UnSupModel};
+use learning::error::{Error, ErrorKind};
+
+use rand::{Rng, StdRng, SeedableRng, thread_rng};
+
+use std::f64;
+
+/// A node in the bisecting k-means split tree.
+///
+/// The root node contains all of the training data. Each internal
+/// node has been split into exactly two children.
+#[derive(Clone, Debug)]
+pub struct ClusterNode {
+    centroid: Vector<f64>,
+    sse: f64,
+    size: usize,
+    children: Option<(usize, usize)>,
+}
+
+impl ClusterNode {
+    /// The centroid of the data in this node.
+    pub fn centroid(&self) -> &Vector<f64> {
+        &self.centroid
+    }
+
+    /// The sum of squared distances from the data in this node to its centroid.
+    pub fn sse(&self) -> f64 {
+        self.sse
+    }
+
+    /// The number of training points in this node.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The indices of this node's children within the split tree.
+    ///
+    /// Returns `None` if this node was never split.
+    pub fn children(&self) -> Option<(usize, usize)> {
+        self.children
+    }
+}
+
+/// Bisecting K-Means model.
+///
+/// # Usage
+///
+/// This model is used through the `UnSupModel` trait. The model is
+/// trained via the `train` function with a matrix containing rows of
+/// feature vectors. The `predict` function assigns each row to the
+/// nearest leaf centroid.
+#[derive(Debug)]
+pub struct BisectingKMeans {
+    /// The number of clusters.
+    n_clusters: usize,
+    /// The number of 2-means attempts per split.
+    n_bisection_trials: usize,
+    /// Max iterations of each 2-means run.
+    max_iters: usize,
+    /// The seed used for the random number generator.
+    seed: Option<usize>,
+    /// The split tree, the root is the first node.
+    tree: Option<Vec<ClusterNode>>,
+    /// The centroids of the leaves of the split tree.
+    centroids: Option<Matrix<f64>>,
+}
+
+impl UnSupModel<Matrix<f64>, Vector<usize>> for BisectingKMeans {
+    /// Predict clusters from data.
+    ///
+    /// Model must be trained.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        if let Some(ref centroids) = self.centroids {
+            if centroids.cols() != inputs.cols() {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let rows = (0..inputs.rows()).collect::<Vec<usize>>();
+            Ok(closest_centroids(centroids, inputs, &rows).0)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Train the model using input data.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        if self.n_clusters > inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("Number of clusters ({0}) exceeds number of data \
+                                           points ({1}).",
+                                          self.n_clusters,
+                                          inputs.rows())));
+        }
+
+        let seed = match self.seed {
+            Some(s) => s,
+            None => thread_rng().gen(),
+        };
+        let mut rng = StdRng::from_seed(&[seed]);
+
+        let all_rows = (0..inputs.rows()).collect::<Vec<usize>>();
+        let root_centroid = inputs.mean(Axes::Row);
+        let root_sse = sse(inputs, &all_rows, &root_centroid);
+
+        let mut tree = vec![ClusterNode {
+                                centroid: root_centroid,
+                                sse: root_sse,
+                                size: inputs.rows(),
+                                children: None,
+                            }];
+        // The rows belonging to each leaf, indexed by tree node.
+        let mut members = vec![Some(all_rows)];
+
+        for _ in 1..self.n_clusters {
+            // Split the splittable leaf with the largest SSE.
+            let target = (0..tree.len())
+                .filter(|&i| members[i].as_ref().map_or(false, |m| m.len() > 1))
+                .fold(None, |best: Option<usize>, i| match best {
+                    Some(b) if tree[b].sse >= tree[i].sse => Some(b),
+                    _ => Some(i),
+                });
+
+            let target = match target {
+                Some(t) => t,
+                None => {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "No cluster contains enough points to be split."))
+                }
+            };
+
+            let rows = members[target].take().unwrap();
+            let (left, right) = self.best_bisection(inputs, &rows, &mut rng)?;
+
+            let left_idx = tree.len();
+            tree[target].children = Some((left_idx, left_idx + 1));
+
+            for half in vec![left, right] {
+                let centroid = inputs.select_rows(&half).mean(Axes::Row);
+                let half_sse = sse(inputs, &half, &centroid);
+                tree.push(ClusterNode {
+                    centroid: centroid,
+                    sse: half_sse,
+                    size: half.len(),
+                    children: None,
+                });
+                members.push(Some(half));
+            }
+        }
+
+        self.centroids = Some(leaf_centroids(&tree, tree.len(), inputs.cols()));
+        self.tree = Some(tree);
+
+        Ok(())
+    }
+}
+
+impl BisectingKMeans {
+    /// Constructs an untrained bisecting k-means model.
+    ///
+    /// Requires the number of clusters to be specified.
+    /// Defaults to 5 bisection trials, 100 iterations and
+    /// an unseeded random number generator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::bisecting_k_means::BisectingKMeans;
+    ///
+    /// let model = BisectingKMeans::new(5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `n_clusters` is zero.
+    pub fn new(n_clusters: usize) -> BisectingKMeans {
+        assert!(n_clusters > 0, "The number of clusters must be positive.");
+
+        BisectingKMeans {
+            n_clusters: n_clusters,
+            n_bisection_trials: 5,
+            max_iters: 100,
+            seed: None,
+            tree: None,
+            centroids: None,
+        }
+    }
+
+    /// Get the number of clusters.
+    pub fn n_clusters(&self) -> usize {
+        self.n_clusters
+    }
+
+    /// Get the number of 2-means attempts made for each split.
+    pub fn n_bisection_trials(&self) -> usize {
+        self.n_bisection_trials
+    }
+
+    /// Get the maximum number of iterations of each 2-means run.
+    pub fn max_iters(&self) -> usize {
+        self.max_iters
+    }
+
+    /// Get the seed for the random number generator.
+    pub fn seed(&self) -> Option<usize> {
+        self.seed
+    }
+
+    /// Set the number of 2-means attempts made for each split.
+    ///
+    /// # Panics
+    ///
+    /// - `trials` is zero.
+    pub fn set_n_bisection_trials(&mut self, trials: usize) {
+        assert!(trials > 0, "The number of bisection trials must be positive.");
+        self.n_bisection_trials = trials;
+    }
+
+    /// Set the maximum number of iterations of each 2-means run.
+    pub fn set_max_iters(&mut self, iters: usize) {
+        self.max_iters = iters;
+    }
+
+    /// Set the seed for the random number generator.
+    ///
+    /// Training with the same seed and data gives identical results.
+    pub fn set_seed(&mut self, seed: usize) {
+        self.seed = Some(seed);
+    }
+
+    /// Get the centroids of the final clusters.
+    pub fn centroids(&self) -> &Option<Matrix<f64>> {
+        &self.centroids
+    }
+
+    /// Get the split tree.
+    ///
+    /// The root node is at index 0. The `i`th split produces the
+    /// nodes at `2i + 1` and `2i + 2`.
+    pub fn split_tree(&self) -> Option<&[ClusterNode]> {
+        self.tree.as_ref().map(|t| &t[..])
+    }
+
+    /// Get the centroids of the clustering after `n` - 1 splits.
+    ///
+    /// The rows of the returned `Matrix` are the centroids of the
+    /// leaves of the split tree, in tree order.
+    ///
+    /// # Failures
+    ///
+    /// - The model is untrained.
+    /// - `n` is zero or greater than the number of clusters.
+    pub fn cut(&self, n: usize) -> LearningResult<Matrix<f64>> {
+        if let Some(ref tree) = self.tree {
+            if n == 0 || n > self.n_clusters {
+                Err(Error::new(ErrorKind::InvalidParameters,
+                               "The tree can only be cut into between 1 and n_clusters \
+                                clusters."))
+            } else {
+                let d = tree[0].centroid.size();
+                Ok(leaf_centroids(tree, 2 * n - 1, d))
+            }
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Split the given rows in two, keeping the best of several 2-means runs.
+    fn best_bisection<R: Rng>(&self,
+                              inputs: &Matrix<f64>,
+                              rows: &[usize],
+                              rng: &mut R)
+                              -> LearningResult<(Vec<usize>, Vec<usize>)> {
+        let mut best: Option<(f64, Vec<usize>, Vec<usize>)> = None;
+
+        for _ in 0..self.n_bisection_trials {
+            let (left, right, cost) = self.two_means(inputs, rows, rng);
+
+            if left.is_empty() || right.is_empty() {
+                continue;
+            }
+
+            let improved = best.as_ref().map_or(true, |b| cost < b.0);
+            if improved {
+                best = Some((cost, left, right));
+            }
+        }
+
+        match best {
+            Some((_, left, right)) => Ok((left, right)),
+            None => {
+                Err(Error::new(ErrorKind::InvalidData,
+                               "Failed to split a cluster into two non-empty clusters. The \
+                                cluster may contain duplicate points."))
+            }
+        }
+    }
+
+    /// Run 2-means on the given rows.
+    ///
+    /// Returns the rows in each half and the total SSE.
+    fn two_means<R: Rng>(&self,
+                         inputs: &Matrix<f64>,
+                         rows: &[usize],
+                         rng: &mut R)
+                         -> (Vec<usize>, Vec<usize>, f64) {
+        let first = rng.gen_range(0, rows.len());
+        let mut second = rng.gen_range(0, rows.len() - 1);
+        if second >= first {
+            second += 1;
+        }
+
+        let mut centroids = inputs.select_rows(&[rows[first], rows[second]]);
+        let mut cost = f64::INFINITY;
+        let mut assignments = Vector::zeros(rows.len());
+
+        for _ in 0..self.max_iters {
+            let (idx, distances) = closest_centroids(&centroids, inputs, rows);
+            assignments = idx;
+
+            let mut new_centroids = Vec::with_capacity(2 * inputs.cols());
+            for c in 0..2 {
+                let half = split_rows(rows, &assignments, c);
+                if half.is_empty() {
+                    // Keep the old centroid for an empty cluster.
+                    new_centroids.extend_from_slice(centroids.row(c).raw_slice());
+                } else {
+                    new_centroids.extend(inputs.select_rows(&half).mean(Axes::Row).into_vec());
+                }
+            }
+            centroids = Matrix::new(2, inputs.cols(), new_centroids);
+
+            let cost_i = distances.sum();
+            if (cost - cost_i).abs() < 1e-14 {
+                break;
+            }
+            cost = cost_i;
+        }
+
+        let left = split_rows(rows, &assignments, 0);
+        let right = split_rows(rows, &assignments, 1);
+        let total = {
+            let left_cen = inputs.select_rows(&left).mean(Axes::Row);
+            let right_cen = inputs.select_rows(&right).mean(Axes::Row);
+            sse(inputs, &left, &left_cen) + sse(inputs, &right, &right_cen)
+        };
+
+        (left, right, total)
+    }
+}
+
+/// The rows assigned to the given cluster.
+fn split_rows(rows: &[usize], assignments: &Vector<usize>, cluster: usize) -> Vec<usize> {
+    rows.iter()
+        .zip(assignments.iter())
+        .filter(|&(_, c)| *c == cluster)
+        .map(|(r, _)| *r)
+        .collect()
+}
+
+/// The sum of squared distances from the given rows to the centroid.
+fn sse(inputs: &Matrix<f64>, rows: &[usize], centroid: &Vector<f64>) -> f64 {
+    rows.iter()
+        .map(|&r| {
+            inputs.row(r)
+                .raw_slice()
+                .iter()
+                .zip(centroid.iter())
+                .map(|(x, c)| (x - c) * (x - c))
+                .sum::<f64>()
+        })
+        .sum()
+}
+
+/// Find the closest centroid to each of the given rows.
+///
+/// Returns the index of the closest centroid and the squared distance to it.
+fn closest_centroids(centroids: &Matrix<f64>,
+                     inputs: &Matrix<f64>,
+                     rows: &[usize])
+                     -> (Vector<usize>, Vector<f64>) {
+    let mut idx = Vec::with_capacity(rows.len());
+    let mut distances = Vec::with_capacity(rows.len());
+
+    for &r in rows {
+        let x = inputs.row(r);
+        let dist = centroids.row_iter()
+            .map(|c| {
+                c.raw_slice()
+                    .iter()
+                    .zip(x.raw_slice().iter())
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<f64>()
+            })
+            .collect::<Vec<f64>>();
+
+        let (min_idx, min_dist) = Vector::new(dist).argmin();
+        idx.push(min_idx);
+        distances.push(min_dist);
+    }
+
+    (Vector::new(idx), Vector::new(distances))
+}
+
+/// The centroids of the leaves among the first `n_nodes` of the tree.
+fn leaf_centroids(tree: &[ClusterNode], n_nodes: usize, cols: usize) -> Matrix<f64> {
+    let leaves = tree[..n_nodes]
+        .iter()
+        .filter(|node| node.children.map_or(true, |(left, _)| left >= n_nodes))
+        .collect::<Vec<_>>();
+
+    let mut data = Vec::with_capacity(leaves.len() * cols);
+    for node in &leaves {
+        data.extend_from_slice(node.centroid.data());
+    }
+
+    Matrix::new(leaves.len(), cols, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BisectingKMeans;
+    use learning::UnSupModel;
+    use linalg::Matrix;
+
+    #[test]
+    #[should_panic]
+    fn test_zero_clusters() {
+        let _ = BisectingKMeans::new(0);
+    }
+
+    #[test]
+    fn test_untrained() {
+        let model = BisectingKMeans::new(2);
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert!(model.predict(&inputs).is_err());
+        assert!(model.cut(1).is_err());
+    }
+
+    #[test]
+    fn test_too_many_clusters() {
+        let mut model = BisectingKMeans::new(3);
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert!(model.train(&inputs).is_err());
+    }
+}
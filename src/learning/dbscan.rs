@@ -4,8 +4,9 @@
 //! should be treated as experimental.*
 //!
 //! Provides an implementaton of DBSCAN clustering. The model
-//! also implements a `predict` function which uses nearest neighbours
-//! to classify the points. To utilize this function you must use
+//! also implements a `predict` function which assigns each point to the
+//! cluster of its nearest core point, if that core point is within
+//! distance `eps`. To utilize this function you must use
 //! `self.set_predictive(true)` before training the model.
 //!
 //! The algorithm works by specifying `eps` and `min_points` parameters.
@@ -16,6 +17,24 @@
 //! If a point is not within distance `eps` of a cluster it will be classified
 //! as noise. This means that it will be set to `None` in the clusters `Vector`.
 //!
+//! Each training point is also given a `PointRole`. Core points have at least
+//! `min_points` points within distance `eps`. Border points are not core points
+//! but lie within distance `eps` of a core point, and are assigned to that
+//! core point's cluster. All other points are noise.
+//!
+//! The neighbourhood queries use a k-d tree built at the start of training.
+//! For high dimensional data (more than 20 columns), where trees give little
+//! benefit, a brute force search is used instead.
+//!
+//! The brute force search takes time quadratic in the number of points.
+//! Use `set_max_points` to fail quickly instead of starting a search
+//! on more points than you are prepared to wait for.
+//!
+//! With the `rayon` feature enabled the neighbourhood of every point is
+//! computed in parallel before the clusters are expanded. The expansion
+//! itself remains serial so the cluster labels are identical to those of
+//! a serial run.
+//!
 //! # Examples
 //!
 //! ```
@@ -34,15 +53,46 @@
 //! model.train(&inputs).unwrap();
 //!
 //! let clustering = model.clusters().unwrap();
+//! let roles = model.point_roles().unwrap();
 //! ```
 
 use learning::{LearningResult, UnSupModel};
 use learning::error::{Error, ErrorKind};
 
+use learning::knn::{KDTree, KNearestSearch};
+
 use linalg::{Matrix, Vector, BaseMatrix};
 use rulinalg::utils;
 use rulinalg::matrix::Row;
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "rayon")]
+use std::cmp;
+
+/// The maximum dimension for which a k-d tree is used for neighbourhood queries.
+const MAX_TREE_DIM: usize = 20;
+
+/// The number of points whose neighbourhoods are computed in each parallel batch.
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK_SIZE: usize = 4096;
+
+/// The maximum total number of neighbour indices retained from the parallel
+/// precomputation. Neighbourhoods beyond this are recomputed when needed.
+#[cfg(feature = "rayon")]
+const MAX_CACHED_NEIGHBOURS: usize = 1 << 24;
+
+/// The role of a training point in a DBSCAN clustering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointRole {
+    /// The point has at least `min_points` points in its neighbourhood.
+    Core,
+    /// The point is not a core point but is in the neighbourhood of one.
+    Border,
+    /// The point is not in any cluster.
+    Noise,
+}
+
 /// DBSCAN Model
 ///
 /// Implements clustering using the DBSCAN algorithm
@@ -52,9 +102,15 @@ pub struct DBSCAN {
     eps: f64,
     min_points: usize,
     clusters: Option<Vector<Option<usize>>>,
+    point_roles: Option<Vec<PointRole>>,
     predictive: bool,
+    max_points: Option<usize>,
     _visited: Vec<bool>,
-    _cluster_data: Option<Matrix<f64>>,
+    _neighbourhoods: Vec<Option<Vec<usize>>>,
+    _weights: Option<Vec<f64>>,
+    _core_data: Option<Matrix<f64>>,
+    _core_clusters: Vec<usize>,
+    _core_tree: Option<KDTree>,
 }
 
 /// Constructs a non-predictive DBSCAN model with the
@@ -68,62 +124,48 @@ impl Default for DBSCAN {
             eps: 0.5,
             min_points: 5,
             clusters: None,
+            point_roles: None,
             predictive: false,
+            max_points: None,
             _visited: Vec::new(),
-            _cluster_data: None,
+            _neighbourhoods: Vec::new(),
+            _weights: None,
+            _core_data: None,
+            _core_clusters: Vec::new(),
+            _core_tree: None,
         }
     }
 }
 
 impl UnSupModel<Matrix<f64>, Vector<Option<usize>>> for DBSCAN {
     /// Train the classifier using input data.
+    ///
+    /// Fails if the brute force search would be used on more than
+    /// `max_points` points.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
-        self.init_params(inputs.rows());
-        let mut cluster = 0;
-
-        for (idx, point) in inputs.row_iter().enumerate() {
-            let visited = self._visited[idx];
-
-            if !visited {
-                self._visited[idx] = true;
-
-                let neighbours = self.region_query(point, inputs);
-
-                if neighbours.len() >= self.min_points {
-                    self.expand_cluster(inputs, idx, neighbours, cluster);
-                    cluster += 1;
-                }
-            }
-        }
-
-        if self.predictive {
-            self._cluster_data = Some(inputs.clone());
-        }
-
-        Ok(())
+        let use_tree = inputs.rows() > 0 && inputs.cols() <= MAX_TREE_DIM;
+        self.train_with_index(inputs, use_tree, cfg!(feature = "rayon"))
     }
 
+    /// Predict clusters for new points.
+    ///
+    /// Each point is assigned to the cluster of its nearest core point
+    /// if that distance is at most `eps`, and is noise (`None`) otherwise.
+    /// If the nearest core points of different clusters are equidistant
+    /// the lowest cluster id is chosen.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<Option<usize>>> {
         if self.predictive {
-            if let (&Some(ref cluster_data), &Some(ref clusters)) = (&self._cluster_data,
-                                                                     &self.clusters) {
+            if let Some(ref core_data) = self._core_data {
+                if inputs.cols() != core_data.cols() {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "Inputs must have the same number of columns as \
+                                           the training data."));
+                }
+
                 let mut classes = Vec::with_capacity(inputs.rows());
 
                 for input_point in inputs.row_iter() {
-                    let mut distances = Vec::with_capacity(cluster_data.rows());
-
-                    for cluster_point in cluster_data.row_iter() {
-                        let point_distance =
-                            utils::vec_bin_op(input_point.raw_slice(), cluster_point.raw_slice(), |x, y| x - y);
-                        distances.push(utils::dot(&point_distance, &point_distance).sqrt());
-                    }
-
-                    let (closest_idx, closest_dist) = utils::argmin(&distances);
-                    if closest_dist < self.eps {
-                        classes.push(clusters[closest_idx]);
-                    } else {
-                        classes.push(None);
-                    }
+                    classes.push(self.nearest_core_cluster(input_point.raw_slice(), core_data)?);
                 }
 
                 Ok(Vector::new(classes))
@@ -147,9 +189,15 @@ impl DBSCAN {
             eps: eps,
             min_points: min_points,
             clusters: None,
+            point_roles: None,
             predictive: false,
+            max_points: None,
             _visited: Vec::new(),
-            _cluster_data: None,
+            _neighbourhoods: Vec::new(),
+            _weights: None,
+            _core_data: None,
+            _core_clusters: Vec::new(),
+            _core_tree: None,
         }
     }
 
@@ -162,16 +210,223 @@ impl DBSCAN {
         self.predictive = predictive;
     }
 
+    /// Set the maximum number of points to train on with the brute force search.
+    ///
+    /// Training on more points fails before any neighbourhood is computed.
+    /// The limit does not apply to low dimensional data, which is searched
+    /// using a k-d tree. There is no limit by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::dbscan::DBSCAN;
+    /// use rusty_machine::learning::UnSupModel;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let mut model = DBSCAN::new(0.5, 2);
+    /// model.set_max_points(2);
+    ///
+    /// // The 30 dimensional inputs use the brute force search.
+    /// let inputs = Matrix::zeros(3, 30);
+    /// assert!(model.train(&inputs).is_err());
+    /// ```
+    pub fn set_max_points(&mut self, max_points: usize) {
+        self.max_points = Some(max_points);
+    }
+
+    /// The maximum number of points to train on with the brute force search.
+    pub fn max_points(&self) -> Option<usize> {
+        self.max_points
+    }
+
     /// Return an Option pointing to the model clusters.
+    ///
+    /// Noise points are `None`.
     pub fn clusters(&self) -> Option<&Vector<Option<usize>>> {
         self.clusters.as_ref()
     }
 
+    /// Return an Option pointing to the role of each training point.
+    pub fn point_roles(&self) -> Option<&[PointRole]> {
+        self.point_roles.as_ref().map(|r| &r[..])
+    }
+
+    /// Return the indices of the core points in the training data.
+    pub fn core_sample_indices(&self) -> Option<Vec<usize>> {
+        self.point_roles.as_ref().map(|roles| {
+            roles.iter()
+                .enumerate()
+                .filter(|&(_, r)| *r == PointRole::Core)
+                .map(|(i, _)| i)
+                .collect()
+        })
+    }
+
+    /// Train the model using weighted input data.
+    ///
+    /// Each weight acts as the multiplicity of its row. A point is a core
+    /// point if the sum of the weights within distance `eps` is at least
+    /// `min_points`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::dbscan::DBSCAN;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 1.1, 2.2]);
+    /// let weights = Vector::new(vec![3.0, 1.0]);
+    ///
+    /// let mut model = DBSCAN::new(0.5, 4);
+    /// model.train_weighted(&inputs, &weights).unwrap();
+    ///
+    /// assert_eq!(model.clusters().unwrap().data(), &vec![Some(0), Some(0)]);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of weights does not match the number of rows.
+    /// - Any of the weights is negative or not finite.
+    /// - The brute force search would be used on more than `max_points` points.
+    pub fn train_weighted(&mut self, inputs: &Matrix<f64>, weights: &Vector<f64>) -> LearningResult<()> {
+        if weights.size() != inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of weights must match the number of input rows."));
+        }
+
+        if !weights.iter().all(|w| w.is_finite() && *w >= 0f64) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Weights must be finite and non-negative."));
+        }
+
+        self._weights = Some(weights.data().clone());
+        let use_tree = inputs.rows() > 0 && inputs.cols() <= MAX_TREE_DIM;
+        let res = self.train_with_index(inputs, use_tree, cfg!(feature = "rayon"));
+        self._weights = None;
+
+        res
+    }
+
+    /// Train the model, optionally using a k-d tree for the neighbourhood queries.
+    ///
+    /// If `parallel` is true the neighbourhoods are precomputed in parallel.
+    /// This requires the `rayon` feature.
+    fn train_with_index(&mut self,
+                        inputs: &Matrix<f64>,
+                        use_tree: bool,
+                        parallel: bool) -> LearningResult<()> {
+        if let Some(max_points) = self.max_points {
+            if !use_tree && inputs.rows() > max_points {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      format!("Number of data points ({0}) exceeds the maximum \
+                                               for the brute force search ({1}).",
+                                              inputs.rows(),
+                                              max_points)));
+            }
+        }
+
+        self.init_params(inputs.rows());
+        let mut cluster = 0;
+
+        let tree = if use_tree {
+            let mut tree = KDTree::default();
+            tree.build(inputs.clone());
+            Some(tree)
+        } else {
+            None
+        };
+
+        #[cfg(feature = "rayon")]
+        {
+            if parallel {
+                self.precompute_neighbourhoods(inputs, tree.as_ref())?;
+            }
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            debug_assert!(!parallel, "Parallel training requires the rayon feature.");
+        }
+
+        for idx in 0..inputs.rows() {
+            let visited = self._visited[idx];
+
+            if !visited {
+                self._visited[idx] = true;
+
+                let neighbours = self.neighbours(idx, inputs, tree.as_ref())?;
+
+                if self.is_core(&neighbours) {
+                    self.expand_cluster(inputs, idx, neighbours, cluster, tree.as_ref())?;
+                    cluster += 1;
+                }
+            }
+        }
+
+        // Clustered points which are not core points are on the border.
+        if let (&Some(ref clusters), &mut Some(ref mut roles)) = (&self.clusters,
+                                                                  &mut self.point_roles) {
+            for (role, c) in roles.iter_mut().zip(clusters.iter()) {
+                if *role != PointRole::Core && c.is_some() {
+                    *role = PointRole::Border;
+                }
+            }
+        }
+
+        if self.predictive {
+            self.store_core_points(inputs, use_tree);
+        }
+
+        self._neighbourhoods = Vec::new();
+
+        Ok(())
+    }
+
+    /// Compute the neighbourhood of every point in parallel.
+    ///
+    /// The points are processed in batches. Core points are marked as they
+    /// are found, and the neighbourhoods are retained for the cluster
+    /// expansion until `MAX_CACHED_NEIGHBOURS` indices are stored.
+    #[cfg(feature = "rayon")]
+    fn precompute_neighbourhoods(&mut self,
+                                 inputs: &Matrix<f64>,
+                                 tree: Option<&KDTree>) -> LearningResult<()> {
+        let mut cached = 0;
+        let mut start = 0;
+
+        while start < inputs.rows() {
+            let end = cmp::min(start + PARALLEL_CHUNK_SIZE, inputs.rows());
+
+            let batch = {
+                let model = &*self;
+                (start..end)
+                    .into_par_iter()
+                    .map(|idx| model.query_neighbourhood(idx, inputs, tree))
+                    .collect::<LearningResult<Vec<Vec<usize>>>>()?
+            };
+
+            for (idx, neighbours) in (start..end).zip(batch) {
+                if self.is_core(&neighbours) {
+                    self.point_roles.as_mut().map(|r| r[idx] = PointRole::Core);
+                }
+
+                if cached + neighbours.len() <= MAX_CACHED_NEIGHBOURS {
+                    cached += neighbours.len();
+                    self._neighbourhoods[idx] = Some(neighbours);
+                }
+            }
+
+            start = end;
+        }
+
+        Ok(())
+    }
+
     fn expand_cluster(&mut self,
                       inputs: &Matrix<f64>,
                       point_idx: usize,
                       neighbour_pts: Vec<usize>,
-                      cluster: usize) {
+                      cluster: usize,
+                      tree: Option<&KDTree>) -> LearningResult<()> {
         debug_assert!(point_idx < inputs.rows(),
                       "Point index too large for inputs");
         debug_assert!(neighbour_pts.iter().all(|x| *x < inputs.rows()),
@@ -179,20 +434,139 @@ impl DBSCAN {
 
         self.clusters.as_mut().map(|x| x.mut_data()[point_idx] = Some(cluster));
 
-        for data_point_idx in &neighbour_pts {
-            let visited = self._visited[*data_point_idx];
+        // Use an explicit stack to avoid overflow on large clusters.
+        let mut stack = neighbour_pts;
+        while let Some(data_point_idx) = stack.pop() {
+            // Points previously classed as noise become border points.
+            self.clusters.as_mut().map(|x| if x[data_point_idx].is_none() {
+                x.mut_data()[data_point_idx] = Some(cluster);
+            });
+
+            let visited = self._visited[data_point_idx];
             if !visited {
-                self._visited[*data_point_idx] = true;
-                let data_point_row = unsafe { inputs.row_unchecked(*data_point_idx) };
-                let sub_neighbours = self.region_query(data_point_row, inputs);
+                self._visited[data_point_idx] = true;
+                let sub_neighbours = self.neighbours(data_point_idx, inputs, tree)?;
+
+                if self.is_core(&sub_neighbours) {
+                    stack.extend(sub_neighbours);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Retain the core points and their clusters for prediction.
+    fn store_core_points(&mut self, inputs: &Matrix<f64>, use_tree: bool) {
+        let core_idx = self.core_sample_indices().unwrap_or_default();
+        let core_data = inputs.select_rows(&core_idx);
+
+        self._core_clusters = {
+            let clusters = self.clusters.as_ref().unwrap();
+            core_idx.iter().map(|&i| clusters[i].unwrap()).collect()
+        };
+
+        self._core_tree = if use_tree && core_data.rows() > 0 {
+            let mut tree = KDTree::default();
+            tree.build(core_data.clone());
+            Some(tree)
+        } else {
+            None
+        };
 
-                if sub_neighbours.len() >= self.min_points {
-                    self.expand_cluster(inputs, *data_point_idx, sub_neighbours, cluster);
+        self._core_data = Some(core_data);
+    }
+
+    /// Find the cluster of the nearest core point within distance `eps`.
+    fn nearest_core_cluster(&self,
+                            point: &[f64],
+                            core_data: &Matrix<f64>) -> LearningResult<Option<usize>> {
+        if core_data.rows() == 0 {
+            return Ok(None);
+        }
+
+        // Collect the candidate core points and distances
+        let (candidates, distances) = match self._core_tree {
+            Some(ref tree) => {
+                let (_, nearest) = tree.search(point, 1)?;
+                if nearest[0] > self.eps {
+                    return Ok(None);
                 }
+                // Radius search is exclusive, so widen it to include ties.
+                let radius = nearest[0] + nearest[0] * 1e-12 + 1e-300;
+                let candidates = tree.search_radius(point, radius)?;
+                let distances = candidates.iter()
+                    .map(|&i| point_distance(point, core_data.row(i).raw_slice()))
+                    .collect::<Vec<f64>>();
+                (candidates, distances)
+            }
+            None => {
+                let distances = core_data.row_iter()
+                    .map(|row| point_distance(point, row.raw_slice()))
+                    .collect::<Vec<f64>>();
+                ((0..core_data.rows()).collect(), distances)
+            }
+        };
+
+        let mut best: Option<(f64, usize)> = None;
+        for (&i, &d) in candidates.iter().zip(distances.iter()) {
+            if d > self.eps {
+                continue;
+            }
+
+            let cluster = self._core_clusters[i];
+            let better = match best {
+                Some((best_d, best_c)) => d < best_d || (d == best_d && cluster < best_c),
+                None => true,
+            };
+
+            if better {
+                best = Some((d, cluster));
             }
         }
+
+        Ok(best.map(|(_, c)| c))
     }
 
+    /// Find the neighbourhood of the input point at the given index.
+    ///
+    /// The point is marked as a core point if the neighbourhood is large enough.
+    fn neighbours(&mut self,
+                  point_idx: usize,
+                  inputs: &Matrix<f64>,
+                  tree: Option<&KDTree>) -> LearningResult<Vec<usize>> {
+        // Each point is only queried once, so precomputed neighbourhoods are taken.
+        let neighbours = match self._neighbourhoods.get_mut(point_idx).and_then(|n| n.take()) {
+            Some(n) => n,
+            None => self.query_neighbourhood(point_idx, inputs, tree)?,
+        };
+
+        if self.is_core(&neighbours) {
+            self.point_roles.as_mut().map(|r| r[point_idx] = PointRole::Core);
+        }
+
+        Ok(neighbours)
+    }
+
+    /// Compute the neighbourhood of the input point at the given index.
+    fn query_neighbourhood(&self,
+                           point_idx: usize,
+                           inputs: &Matrix<f64>,
+                           tree: Option<&KDTree>) -> LearningResult<Vec<usize>> {
+        let point = unsafe { inputs.row_unchecked(point_idx) };
+        match tree {
+            Some(t) => t.search_radius(point.raw_slice(), self.eps),
+            None => Ok(self.region_query(point, inputs)),
+        }
+    }
+
+    /// Whether a neighbourhood is large enough for a core point.
+    fn is_core(&self, neighbours: &[usize]) -> bool {
+        match self._weights {
+            Some(ref w) => neighbours.iter().map(|&i| w[i]).sum::<f64>() >= self.min_points as f64,
+            None => neighbours.len() >= self.min_points,
+        }
+    }
 
     fn region_query(&self, point: Row<f64>, inputs: &Matrix<f64>) -> Vec<usize> {
         debug_assert!(point.cols() == inputs.cols(),
@@ -200,9 +574,7 @@ impl DBSCAN {
 
         let mut in_neighbourhood = Vec::new();
         for (idx, data_point) in inputs.row_iter().enumerate() {
-            //TODO: Use `MatrixMetric` when rulinalg#154 is fixed.
-            let point_distance = utils::vec_bin_op(data_point.raw_slice(), point.raw_slice(), |x, y| x - y);
-            let dist = utils::dot(&point_distance, &point_distance).sqrt();
+            let dist = point_distance(data_point.raw_slice(), point.raw_slice());
 
             if dist < self.eps {
                 in_neighbourhood.push(idx);
@@ -213,6 +585,10 @@ impl DBSCAN {
     }
 
     fn init_params(&mut self, total_points: usize) {
+        self._core_data = None;
+        self._core_clusters = Vec::new();
+        self._core_tree = None;
+
         unsafe {
             self._visited.reserve(total_points);
             self._visited.set_len(total_points);
@@ -222,14 +598,25 @@ impl DBSCAN {
             self._visited[i] = false;
         }
 
+        self._neighbourhoods = vec![None; total_points];
         self.clusters = Some(Vector::new(vec![None; total_points]));
+        self.point_roles = Some(vec![PointRole::Noise; total_points]);
     }
 }
 
+/// The Euclidean distance between two points.
+fn point_distance(x: &[f64], y: &[f64]) -> f64 {
+    //TODO: Use `MatrixMetric` when rulinalg#154 is fixed.
+    let diff = utils::vec_bin_op(x, y, |a, b| a - b);
+    utils::dot(&diff, &diff).sqrt()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::DBSCAN;
-    use linalg::{Matrix, BaseMatrix};
+    use super::{DBSCAN, PointRole, MAX_TREE_DIM};
+    use learning::UnSupModel;
+    use linalg::{Matrix, Vector, BaseMatrix};
+    use rand::{Rng, SeedableRng, StdRng};
 
     #[test]
     fn test_region_query() {
@@ -256,4 +643,170 @@ mod tests {
 
         assert!(neighbours.len() == 1);
     }
+
+    #[test]
+    fn test_tree_matches_brute_force() {
+        let mut rng = StdRng::from_seed(&[1, 2, 3]);
+        let data = (0..6000).map(|_| rng.gen_range(0f64, 10f64)).collect::<Vec<f64>>();
+        let inputs = Matrix::new(3000, 2, data);
+
+        let mut tree_model = DBSCAN::new(0.25, 4);
+        tree_model.train_with_index(&inputs, true, false).unwrap();
+
+        let mut brute_model = DBSCAN::new(0.25, 4);
+        brute_model.train_with_index(&inputs, false, false).unwrap();
+
+        assert_eq!(tree_model.clusters(), brute_model.clusters());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_matches_serial() {
+        let mut rng = StdRng::from_seed(&[4, 5, 6]);
+
+        for &(n, d, eps) in &[(2000, 2, 0.3), (1500, 3, 0.8), (500, 25, 12.0)] {
+            let data = (0..n * d).map(|_| rng.gen_range(0f64, 10f64)).collect::<Vec<f64>>();
+            let inputs = Matrix::new(n, d, data);
+            let use_tree = d <= MAX_TREE_DIM;
+
+            let mut parallel_model = DBSCAN::new(eps, 4);
+            parallel_model.train_with_index(&inputs, use_tree, true).unwrap();
+
+            let mut serial_model = DBSCAN::new(eps, 4);
+            serial_model.train_with_index(&inputs, use_tree, false).unwrap();
+
+            assert_eq!(parallel_model.clusters(), serial_model.clusters());
+            assert_eq!(parallel_model.point_roles(), serial_model.point_roles());
+        }
+    }
+
+    #[test]
+    fn test_point_roles() {
+        let inputs = Matrix::new(7, 2, vec![0.0, 0.0,
+                                            0.1, 0.0,
+                                            0.0, 0.1,
+                                            -0.1, 0.0,
+                                            0.0, -0.1,
+                                            0.25, 0.0,
+                                            5.0, 5.0]);
+
+        let mut model = DBSCAN::new(0.2, 4);
+        model.train(&inputs).unwrap();
+
+        let roles = model.point_roles().unwrap();
+        assert_eq!(roles[0], PointRole::Core);
+        assert_eq!(roles[1], PointRole::Core);
+        assert_eq!(roles[5], PointRole::Border);
+        assert_eq!(roles[6], PointRole::Noise);
+
+        let clusters = model.clusters().unwrap();
+        assert_eq!(clusters[5], Some(0));
+        assert_eq!(clusters[6], None);
+
+        assert_eq!(model.core_sample_indices().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_predict_nearest_core() {
+        // Two clusters on a line, core points at 0, 1, 2 and 10, 11, 12.
+        let inputs = Matrix::new(6, 1, vec![0.0, 1.0, 2.0, 10.0, 11.0, 12.0]);
+
+        let mut model = DBSCAN::new(1.5, 2);
+        model.set_predictive(true);
+        model.train(&inputs).unwrap();
+
+        // inside, at the boundary of, and far from the clusters
+        let new_points = Matrix::new(4, 1, vec![1.2, 13.5, 6.0, 30.0]);
+        let classes = model.predict(&new_points).unwrap();
+        assert_eq!(classes.into_vec(), vec![Some(0), Some(1), None, None]);
+    }
+
+    #[test]
+    fn test_predict_ties() {
+        // equidistant core points pick the lower cluster id
+        let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 3.0, 4.0]);
+
+        for &use_tree in &[true, false] {
+            let mut model = DBSCAN::new(1.5, 2);
+            model.set_predictive(true);
+            model.train_with_index(&inputs, use_tree, false).unwrap();
+
+            let classes = model.predict(&Matrix::new(1, 1, vec![2.0])).unwrap();
+            assert_eq!(classes.into_vec(), vec![Some(0)]);
+        }
+    }
+
+    #[test]
+    fn test_predict_untrained() {
+        let mut model = DBSCAN::new(1.5, 2);
+        assert!(model.predict(&Matrix::new(1, 1, vec![2.0])).is_err());
+
+        model.set_predictive(true);
+        assert!(model.predict(&Matrix::new(1, 1, vec![2.0])).is_err());
+    }
+
+    #[test]
+    fn test_train_weighted() {
+        let inputs = Matrix::new(4, 1, vec![0.0, 0.4, -0.3, 5.0]);
+        let weights = Vector::new(vec![4.0, 1.0, 1.0, 1.0]);
+
+        let mut model = DBSCAN::new(0.5, 6);
+        model.train_weighted(&inputs, &weights).unwrap();
+
+        // Only the heavy point reaches the weight needed for a core point,
+        // its light neighbours are within reach of it but not of each other.
+        let roles = model.point_roles().unwrap();
+        assert_eq!(roles,
+                   &[PointRole::Core, PointRole::Border, PointRole::Border, PointRole::Noise]);
+
+        // Which matches stacking four identical rows.
+        let stacked = Matrix::new(7, 1, vec![0.0, 0.0, 0.0, 0.0, 0.4, -0.3, 5.0]);
+        let mut stacked_model = DBSCAN::new(0.5, 6);
+        stacked_model.train(&stacked).unwrap();
+
+        let stacked_roles = stacked_model.point_roles().unwrap();
+        assert_eq!(stacked_roles[4], PointRole::Border);
+        assert_eq!(stacked_roles[5], PointRole::Border);
+
+        let stacked_clusters = stacked_model.clusters().unwrap();
+        let clusters = model.clusters().unwrap();
+        assert_eq!(clusters[0], stacked_clusters[0]);
+        assert_eq!(clusters[1], stacked_clusters[4]);
+        assert_eq!(clusters[2], stacked_clusters[5]);
+        assert_eq!(clusters[3], stacked_clusters[6]);
+    }
+
+    #[test]
+    fn test_train_weighted_invalid() {
+        let inputs = Matrix::new(2, 1, vec![0.0, 0.4]);
+        let mut model = DBSCAN::new(0.5, 5);
+
+        assert!(model.train_weighted(&inputs, &Vector::new(vec![1.0])).is_err());
+        assert!(model.train_weighted(&inputs, &Vector::new(vec![1.0, -1.0])).is_err());
+    }
+
+    #[test]
+    fn test_max_points() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 0.4, 5.0]);
+
+        let mut model = DBSCAN::new(0.5, 2);
+        assert_eq!(model.max_points(), None);
+        model.set_max_points(2);
+        assert_eq!(model.max_points(), Some(2));
+
+        assert!(model.train_with_index(&inputs, false, false).is_err());
+        assert!(model.clusters().is_none());
+
+        // The limit only applies to the brute force search.
+        model.train_with_index(&inputs, true, false).unwrap();
+        assert_eq!(model.clusters().unwrap().data(), &vec![Some(0), Some(0), None]);
+
+        model.set_max_points(3);
+        model.train_with_index(&inputs, false, false).unwrap();
+
+        let high_dim = Matrix::zeros(3, MAX_TREE_DIM + 1);
+        model.set_max_points(2);
+        assert!(model.train(&high_dim).is_err());
+        assert!(model.train_weighted(&high_dim, &Vector::ones(3)).is_err());
+    }
 }
@@ -473,3 +473,66 @@ impl Criterion for Poisson {
         mu.to_vec()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Bernoulli, Criterion, Normal, Poisson};
+    use learning::toolkit::grad_check::{grad_check_fn, step_size, tolerance};
+    use linalg::{BaseMatrix, Matrix, Vector};
+    use rand::{Rng, SeedableRng, StdRng};
+
+    /// The negative log-likelihood gradient built from the working weights
+    /// and adjusted targets used by the iteratively reweighted least squares.
+    fn irls_grad<C: Criterion>(criterion: &C,
+                               inputs: &Matrix<f64>,
+                               targets: &Vector<f64>,
+                               params: &[f64])
+                               -> Vec<f64> {
+        let mu = criterion.apply_link_inv(inputs * Vector::new(params.to_vec()));
+        let weights = criterion.compute_working_weight(mu.data());
+        let y_bar = criterion.compute_y_bar(targets.data(), mu.data());
+        let scores = weights.iter().zip(y_bar).map(|(w, y)| -w * y).collect::<Vec<f64>>();
+
+        (inputs.transpose() * Vector::new(scores)).into_vec()
+    }
+
+    /// Check the gradient of the negative log-likelihood `nll` of the mean.
+    fn check_family<C, F>(criterion: C, targets: Vector<f64>, nll: F)
+        where C: Criterion,
+              F: Fn(f64, f64) -> f64
+    {
+        let mut rng = StdRng::from_seed(&[6]);
+        let data = (0..30).map(|_| rng.gen_range(-1.0, 1.0)).collect::<Vec<f64>>();
+        let inputs = Matrix::new(10, 3, data);
+        let params = (0..3).map(|_| rng.gen_range(-0.5, 0.5)).collect::<Vec<f64>>();
+
+        let cost_grad = |p: &[f64]| {
+            let mu = criterion.apply_link_inv(&inputs * Vector::new(p.to_vec()));
+            let cost = mu.iter().zip(targets.iter()).map(|(&m, &y)| nll(m, y)).sum::<f64>();
+            (cost, irls_grad(&criterion, &inputs, &targets, p))
+        };
+
+        let error = grad_check_fn(cost_grad, &params, 3, &mut rng);
+        assert!(error < tolerance(step_size(1.0)), "Gradient error {}", error);
+    }
+
+    #[test]
+    fn test_bernoulli_gradient() {
+        let targets = Vector::new((0..10).map(|i| (i % 2) as f64).collect::<Vec<f64>>());
+        check_family(Bernoulli,
+                     targets,
+                     |m, y| -(y * m.ln() + (1.0 - y) * (1.0 - m).ln()));
+    }
+
+    #[test]
+    fn test_poisson_gradient() {
+        let targets = Vector::new((0..10).map(|i| (i % 4) as f64).collect::<Vec<f64>>());
+        check_family(Poisson, targets, |m, y| m - y * m.ln());
+    }
+
+    #[test]
+    fn test_normal_gradient() {
+        let targets = Vector::new((0..10).map(|i| i as f64 / 3.0).collect::<Vec<f64>>());
+        check_family(Normal, targets, |m, y| 0.5 * (y - m) * (y - m));
+    }
+}
@@ -30,14 +30,67 @@
 //! // Probabilities that each point comes from each Gaussian.
 //! println!("{:?}", post_probs.data());
 //! ```
+//!
+//! # Data orientation
+//!
+//! By default the model expects each row of the inputs to be a sample
+//! and each column a feature. Data with the features in rows must either
+//! be transposed or the model told about it with `set_orientation`:
+//!
+//! ```
+//! use rusty_machine::linalg::Matrix;
+//! use rusty_machine::learning::gmm::{CovOption, GaussianMixtureModel, Orientation};
+//! use rusty_machine::learning::UnSupModel;
+//!
+//! // Each column is a sample with two features.
+//! let inputs = Matrix::new(2, 4, vec![1.0, -3.0, 0.1, -5.0,
+//!                                     2.0, -3.0, 1.5, -2.5]);
+//!
+//! let mut model = GaussianMixtureModel::new(2);
+//! model.cov_option = CovOption::Diagonal;
+//! model.set_orientation(Orientation::SamplesInColumns);
+//! model.train(&inputs).unwrap();
+//!
+//! // One row of probabilities for each of the 4 samples.
+//! let post_probs = model.predict(&inputs).unwrap();
+//! assert_eq!(post_probs.rows(), 4);
+//! ```
+//!
+//! The orientation applies to every function taking inputs. The means
+//! and the membership weights returned by `predict` always have a row
+//! for each component and sample respectively.
+//!
+//! Training fails if there are fewer samples than components, which is
+//! a common symptom of passing data with the wrong orientation.
+//!
+//! Data which does not fit in memory can be trained in chunks using
+//! `partial_fit`.
+//!
+//! The number of components can also be chosen automatically by
+//! minimizing the Bayesian information criterion (BIC) using
+//! `select_by_bic`.
+//!
+//! Full covariance models on strongly correlated features can be numerically
+//! fragile. With `set_whiten` the inputs are decorrelated before training,
+//! and the means and covariances are reported back in the original space.
+//!
+//! With the `rayon` feature enabled the membership weights of the
+//! points are computed in parallel.
 use linalg::{Matrix, MatrixSlice, Vector, BaseMatrix, BaseMatrixMut, Axes};
 use rulinalg::utils;
 use rulinalg::matrix::decomposition::{PartialPivLu};
 
-use learning::{LearningResult, UnSupModel};
+use learning::{LearningResult, Parametric, UnSupModel};
+use learning::toolkit::covariance::covariance_matrix;
 use learning::toolkit::rand_utils;
 use learning::error::{Error, ErrorKind};
 
+use std::f64::consts::PI;
+use std::ops::Range;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
 /// Covariance options for GMMs.
 ///
 /// - Full : The full covariance structure.
@@ -53,6 +106,14 @@ pub enum CovOption {
     Diagonal,
 }
 
+/// The layout of the samples in the input matrices of a GMM.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    /// Each row is a sample and each column a feature.
+    SamplesInRows,
+    /// Each column is a sample and each row a feature.
+    SamplesInColumns,
+}
 
 /// A Gaussian Mixture Model
 #[derive(Debug)]
@@ -65,11 +126,39 @@ pub struct GaussianMixtureModel {
     max_iters: usize,
     /// The covariance options for the GMM.
     pub cov_option: CovOption,
+    orientation: Orientation,
+    /// Whether to whiten the inputs before training.
+    whiten: bool,
+    /// The mean and whitening matrix of the training inputs.
+    whitening: Option<(Vector<f64>, Matrix<f64>)>,
+    /// Sufficient statistics accumulated by `partial_fit`.
+    suff_stats: Option<SufficientStats>,
+}
+
+/// The responsibility weighted sums accumulated over chunks of data.
+#[derive(Debug)]
+struct SufficientStats {
+    /// The sum of responsibilities for each component.
+    weights: Vector<f64>,
+    /// The responsibility weighted sum of the inputs for each component.
+    sums: Matrix<f64>,
+    /// The responsibility weighted sum of the input outer products for each component.
+    sq_sums: Vec<Matrix<f64>>,
 }
 
 impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
     /// Train the model using inputs.
+    ///
+    /// # Failures
+    ///
+    /// - There is only one sample.
+    /// - There are fewer samples than components.
+    /// - The inputs are whitened with a diagonal covariance structure.
+    /// - The inputs are whitened and their covariance is singular.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        let transposed = self.transpose_samples(inputs);
+        let inputs = transposed.as_ref().unwrap_or(inputs);
+
         let reg_value = if inputs.rows() > 1 {
             1f64 / (inputs.rows() - 1) as f64
         } else {
@@ -78,6 +167,30 @@ impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
 
         // Initialization:
         let k = self.comp_count;
+        if inputs.rows() < k {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be at least as many samples as components. \
+                                   Check the orientation of the inputs."));
+        }
+
+        self.suff_stats = None;
+
+        // The square root of the input covariance undoes the whitening.
+        let whitened;
+        let (inputs, unwhitening) = if self.whiten {
+            if let CovOption::Diagonal = self.cov_option {
+                return Err(Error::new(ErrorKind::InvalidParameters,
+                                      "Whitening requires a full covariance structure."));
+            }
+
+            let (mean, transform, inverse) = whitening_transform(inputs)?;
+            whitened = centered_rows(inputs, &mean) * &transform;
+            self.whitening = Some((mean.clone(), transform));
+            (&whitened, Some((mean, inverse)))
+        } else {
+            self.whitening = None;
+            (inputs, None)
+        };
 
         self.model_covars = {
             let cov_mat = self.initialize_covariances(inputs, reg_value)?;
@@ -91,7 +204,8 @@ impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
         for _ in 0..self.max_iters {
             let log_lik_0 = self.log_lik;
 
-            let (weights, log_lik_1) = self.membership_weights(inputs)?;
+            let (weights, log_dens) = self.membership_weights(inputs)?;
+            let log_lik_1 = log_dens.sum();
 
             if (log_lik_1 - log_lik_0).abs() < 1e-15 {
                 break;
@@ -102,12 +216,27 @@ impl UnSupModel<Matrix<f64>, Matrix<f64>> for GaussianMixtureModel {
             self.update_params(inputs, weights);
         }
 
+        if let Some((mean, inverse)) = unwhitening {
+            let mut means = self.model_means.take().unwrap() * &inverse;
+            for mut row in means.row_iter_mut() {
+                utils::in_place_vec_bin_op(row.raw_slice_mut(), mean.data(), |x, &m| *x += m);
+            }
+            self.model_means = Some(means);
+
+            self.model_covars = self.model_covars
+                .take()
+                .map(|covs| covs.iter().map(|cov| &inverse * cov * &inverse).collect());
+        }
+
         Ok(())
     }
 
     /// Predict output from inputs.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
         if let (&Some(_), &Some(_)) = (&self.model_means, &self.model_covars) {
+            let transposed = self.transpose_samples(inputs);
+            let inputs = transposed.as_ref().unwrap_or(inputs);
+
             Ok(self.membership_weights(inputs)?.0)
         } else {
             Err(Error::new_untrained())
@@ -137,6 +266,10 @@ impl GaussianMixtureModel {
             log_lik: 0f64,
             max_iters: 100,
             cov_option: CovOption::Full,
+            orientation: Orientation::SamplesInRows,
+            whiten: false,
+            whitening: None,
+            suff_stats: None,
         }
     }
 
@@ -179,6 +312,10 @@ impl GaussianMixtureModel {
                 log_lik: 0f64,
                 max_iters: 100,
                 cov_option: CovOption::Full,
+                orientation: Orientation::SamplesInRows,
+                whiten: false,
+                whitening: None,
+                suff_stats: None,
             })
         }
     }
@@ -224,6 +361,308 @@ impl GaussianMixtureModel {
         self.max_iters = iters;
     }
 
+    /// The layout of the samples in the inputs.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Sets the layout of the samples in the inputs.
+    ///
+    /// With `SamplesInColumns` the inputs to every function are
+    /// transposed before they are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gmm::{GaussianMixtureModel, Orientation};
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.set_orientation(Orientation::SamplesInColumns);
+    /// ```
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation;
+    }
+
+    /// Whether the inputs are whitened before training.
+    pub fn whiten(&self) -> bool {
+        self.whiten
+    }
+
+    /// Sets whether the inputs are whitened before training.
+    ///
+    /// The inputs are centered and multiplied by the inverse square root
+    /// of their covariance, so the EM algorithm runs on uncorrelated
+    /// features with unit variance. The means and covariances are
+    /// transformed back to the original space after training.
+    ///
+    /// With `CovOption::Regularized` the regularization is added to the
+    /// covariances of the whitened inputs. The whitening only applies to
+    /// `train` and requires a full covariance structure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::gmm::GaussianMixtureModel;
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// // The second feature is close to a multiple of the first.
+    /// let inputs = Matrix::new(4, 2, vec![1.0, 1000.0, 2.0, 2001.0, 3.0, 2999.0, 4.0, 4000.0]);
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(1);
+    /// gmm.set_whiten(true);
+    /// gmm.train(&inputs).unwrap();
+    ///
+    /// // The mean is in the original space.
+    /// assert!((gmm.means().unwrap()[[0, 1]] - 2500.0).abs() < 1e-8);
+    /// ```
+    pub fn set_whiten(&mut self, whiten: bool) {
+        self.whiten = whiten;
+    }
+
+    /// The mean and the whitening matrix of the inputs of the last training.
+    ///
+    /// The whitened inputs are `(x - mean) * W` for each input row `x`.
+    /// Returns `None` if the model was not trained with whitening.
+    pub fn whitening(&self) -> Option<(&Vector<f64>, &Matrix<f64>)> {
+        self.whitening.as_ref().map(|&(ref mean, ref transform)| (mean, transform))
+    }
+
+    /// Returns the inputs with samples in rows if they must be transposed.
+    fn transpose_samples(&self, inputs: &Matrix<f64>) -> Option<Matrix<f64>> {
+        match self.orientation {
+            Orientation::SamplesInRows => None,
+            Orientation::SamplesInColumns => Some(inputs.transpose()),
+        }
+    }
+
+    /// Update the model from a single chunk of data.
+    ///
+    /// Performs one EM step where the responsibility weighted sums
+    /// from this chunk are accumulated with those of all previous
+    /// chunks. The parameters are then computed from the accumulated
+    /// sums, so the model is not reset between calls.
+    ///
+    /// If the model is untrained it is first initialized from the chunk.
+    /// Calling `train` discards the accumulated sums.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::gmm::{CovOption, GaussianMixtureModel};
+    ///
+    /// let chunk_1 = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+    /// let chunk_2 = Matrix::new(2, 2, vec![1.2, 1.8, -4.0, -2.5]);
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.cov_option = CovOption::Diagonal;
+    ///
+    /// gmm.partial_fit(&chunk_1).unwrap();
+    /// gmm.partial_fit(&chunk_2).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The model is untrained and the chunk has fewer than two rows
+    /// or fewer rows than components.
+    /// - The chunk has a different number of columns than previous data.
+    pub fn partial_fit(&mut self, chunk: &Matrix<f64>) -> LearningResult<()> {
+        let transposed = self.transpose_samples(chunk);
+        let chunk = transposed.as_ref().unwrap_or(chunk);
+        let d = chunk.cols();
+
+        if self.model_means.is_none() || self.model_covars.is_none() {
+            if chunk.rows() < 2 || chunk.rows() < self.comp_count {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "The first chunk must have at least two rows and at \
+                                       least as many rows as components."));
+            }
+
+            let reg_value = 1f64 / (chunk.rows() - 1) as f64;
+            let cov_mat = self.initialize_covariances(chunk, reg_value)?;
+            self.model_covars = Some(vec![cov_mat; self.comp_count]);
+
+            let random_rows: Vec<usize> =
+                rand_utils::reservoir_sample(&(0..chunk.rows()).collect::<Vec<usize>>(),
+                                             self.comp_count);
+            self.model_means = Some(chunk.select_rows(&random_rows));
+            self.suff_stats = None;
+        } else if self.model_means.as_ref().map_or(false, |m| m.cols() != d) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The chunk must have the same number of columns as \
+                                   previous data."));
+        }
+
+        let (weights, _) = self.membership_weights(chunk)?;
+
+        let k = self.comp_count;
+        let mut stats = self.suff_stats.take().unwrap_or_else(|| {
+            SufficientStats {
+                weights: Vector::zeros(k),
+                sums: Matrix::zeros(k, d),
+                sq_sums: vec![Matrix::zeros(d, d); k],
+            }
+        });
+
+        stats.weights = stats.weights + weights.sum_rows();
+        stats.sums += weights.transpose() * chunk;
+
+        for i in 0..chunk.rows() {
+            let chunk_i = chunk.select_rows(&[i]);
+            for j in 0..k {
+                stats.sq_sums[j] += self.compute_cov(chunk_i.clone(), weights[[i, j]]);
+            }
+        }
+
+        // Compute the parameters from the accumulated sums
+        self.mix_weights = &stats.weights / stats.weights.sum();
+
+        let mut new_means = stats.sums.clone();
+        for (mut mean, w) in new_means.row_iter_mut().zip(stats.weights.data().iter()) {
+            *mean /= *w;
+        }
+
+        let mut new_covs = Vec::with_capacity(k);
+        for j in 0..k {
+            let mean_j = new_means.select_rows(&[j]);
+            let mut cov_mat = &stats.sq_sums[j] - self.compute_cov(mean_j, stats.weights[j]);
+
+            if let CovOption::Regularized(eps) = self.cov_option {
+                cov_mat += Matrix::<f64>::identity(d) * eps;
+            }
+
+            new_covs.push(cov_mat / stats.weights[j]);
+        }
+
+        self.model_means = Some(new_means);
+        self.model_covars = Some(new_covs);
+        self.suff_stats = Some(stats);
+
+        Ok(())
+    }
+
+    /// The Bayesian information criterion of the model on the given data.
+    ///
+    /// Computed as `p ln(n) - 2 ln(L)` where `p` is the number of free
+    /// parameters in the model, `n` the number of inputs and `L` the
+    /// likelihood of the inputs under the model. Lower values are better.
+    ///
+    /// The model must be trained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::gmm::{CovOption, GaussianMixtureModel};
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.cov_option = CovOption::Diagonal;
+    /// gmm.train(&inputs).unwrap();
+    ///
+    /// let bic = gmm.bic(&inputs).unwrap();
+    /// assert!(bic.is_finite());
+    /// ```
+    pub fn bic(&self, inputs: &Matrix<f64>) -> LearningResult<f64> {
+        if let (&Some(_), &Some(_)) = (&self.model_means, &self.model_covars) {
+            let log_lik = self.log_density(inputs)?.sum();
+            let n = match self.orientation {
+                Orientation::SamplesInRows => inputs.rows() as f64,
+                Orientation::SamplesInColumns => inputs.cols() as f64,
+            };
+            let p = self.n_parameters() as f64;
+
+            Ok(p * n.ln() - 2f64 * log_lik)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// The log probability density of each input row under the mixture.
+    ///
+    /// Rows with a low density are unlikely under the model and
+    /// thresholding these scores can be used to flag anomalies.
+    ///
+    /// The model must be trained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::gmm::{CovOption, GaussianMixtureModel};
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.cov_option = CovOption::Diagonal;
+    /// gmm.train(&inputs).unwrap();
+    ///
+    /// let test_inputs = Matrix::new(2, 2, vec![0.5, 1.8, 50.0, -40.0]);
+    /// let log_dens = gmm.log_density(&test_inputs).unwrap();
+    ///
+    /// // The far away point is far less likely.
+    /// assert!(log_dens[1] < log_dens[0]);
+    /// ```
+    pub fn log_density(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(_), &Some(_)) = (&self.model_means, &self.model_covars) {
+            let transposed = self.transpose_samples(inputs);
+            let inputs = transposed.as_ref().unwrap_or(inputs);
+
+            let (_, log_dens) = self.membership_weights(inputs)?;
+
+            // Account for the normalizing constant omitted from the pdfs.
+            let log_norm = 0.5 * (inputs.cols() as f64) * (2f64 * PI).ln();
+            Ok(log_dens - log_norm)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// The Shannon entropy of the membership weights of each input row.
+    ///
+    /// Points which are confidently assigned to a single component have an
+    /// entropy close to zero, while points which sit between components have
+    /// a higher entropy. The entropy is at most `ln(k)` for `k` components.
+    ///
+    /// The model must be trained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::gmm::{CovOption, GaussianMixtureModel};
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+    ///
+    /// let mut gmm = GaussianMixtureModel::new(2);
+    /// gmm.cov_option = CovOption::Diagonal;
+    /// gmm.train(&inputs).unwrap();
+    ///
+    /// let entropy = gmm.assignment_entropy(&inputs).unwrap();
+    /// assert!(entropy.iter().all(|&h| h >= 0.0 && h <= 2f64.ln() + 1e-12));
+    /// ```
+    pub fn assignment_entropy(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let weights = self.predict(inputs)?;
+        Ok(row_entropy(&weights))
+    }
+
+    /// The number of free parameters for a model with `d` features.
+    fn free_params(&self, d: usize) -> usize {
+        let k = self.comp_count;
+        let cov_params = match self.cov_option {
+            CovOption::Full | CovOption::Regularized(_) => d * (d + 1) / 2,
+            CovOption::Diagonal => d,
+        };
+
+        // Means, covariances and the mixture weights (which sum to one).
+        k * d + k * cov_params + k - 1
+    }
+
     fn initialize_covariances(&self, inputs: &Matrix<f64>, reg_value: f64) -> LearningResult<Matrix<f64>> {
         match self.cov_option {
             CovOption::Diagonal => {
@@ -232,16 +671,9 @@ impl GaussianMixtureModel {
             }
 
             CovOption::Full | CovOption::Regularized(_) => {
-                let means = inputs.mean(Axes::Row);
-                let mut cov_mat = Matrix::zeros(inputs.cols(), inputs.cols());
-                for (j, mut row) in cov_mat.row_iter_mut().enumerate() {
-                    for (k, elem) in row.iter_mut().enumerate() {
-                        *elem = inputs.row_iter().map(|r| {
-                            (r[j] - means[j]) * (r[k] - means[k])
-                        }).sum::<f64>();
-                    }
-                }
-                cov_mat *= reg_value;
+                // The sample covariance is normalized by `n - 1`, rescale it by `reg_value`.
+                let n = inputs.rows() as f64;
+                let mut cov_mat = covariance_matrix(inputs) * ((n - 1f64) * reg_value);
                 if let CovOption::Regularized(eps) = self.cov_option {
                     cov_mat += Matrix::<f64>::identity(cov_mat.cols()) * eps;
                 }
@@ -250,10 +682,16 @@ impl GaussianMixtureModel {
         }
     }
 
-    fn membership_weights(&self, inputs: &Matrix<f64>) -> LearningResult<(Matrix<f64>, f64)> {
-        let n = inputs.rows();
+    /// Returns the membership weights and the (unnormalized) log density
+    /// of each input row.
+    fn membership_weights(&self, inputs: &Matrix<f64>) -> LearningResult<(Matrix<f64>, Vector<f64>)> {
+        if self.model_means.as_ref().map_or(false, |m| m.cols() != inputs.cols()) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Inputs must have the same number of columns as the \
+                                   training data."));
+        }
 
-        let mut member_weights_data = Vec::with_capacity(n * self.comp_count);
+        let n = inputs.rows();
 
         // We compute the determinants and inverses now
         let mut cov_sqrt_dets = Vec::with_capacity(self.comp_count);
@@ -271,11 +709,13 @@ impl GaussianMixtureModel {
             }
         }
 
-        let mut log_lik = 0f64;
+        let mut member_weights_data = Vec::with_capacity(n * self.comp_count);
+        let mut log_dens = Vec::with_capacity(n);
 
         // Now we compute the membership weights
         if let Some(ref means) = self.model_means {
-            for i in 0..n {
+            // The weights and log density of the point at index i
+            let point_weights = |i: usize| {
                 let mut pdfs = Vec::with_capacity(self.comp_count);
                 let x_i = MatrixSlice::from_matrix(inputs, [i, 0], 1, inputs.cols());
 
@@ -290,15 +730,25 @@ impl GaussianMixtureModel {
 
                 let weighted_pdf_sum = utils::dot(&pdfs, self.mix_weights.data());
 
-                for (idx, pdf) in pdfs.iter().enumerate() {
-                    member_weights_data.push(self.mix_weights[idx] * pdf / (weighted_pdf_sum));
+                for (idx, pdf) in pdfs.iter_mut().enumerate() {
+                    *pdf = self.mix_weights[idx] * *pdf / weighted_pdf_sum;
                 }
 
-                log_lik += weighted_pdf_sum.ln();
+                (pdfs, weighted_pdf_sum.ln())
+            };
+
+            #[cfg(feature = "rayon")]
+            let rows = (0..n).into_par_iter().map(point_weights).collect::<Vec<_>>();
+            #[cfg(not(feature = "rayon"))]
+            let rows = (0..n).map(point_weights).collect::<Vec<_>>();
+
+            for (weights, log_den) in rows {
+                member_weights_data.extend(weights);
+                log_dens.push(log_den);
             }
         }
 
-        Ok((Matrix::new(n, self.comp_count, member_weights_data), log_lik))
+        Ok((Matrix::new(n, self.comp_count, member_weights_data), Vector::new(log_dens)))
     }
 
     fn update_params(&mut self, inputs: &Matrix<f64>, membership_weights: Matrix<f64>) {
@@ -347,10 +797,133 @@ impl GaussianMixtureModel {
     }
 }
 
+/// Compute the mean of the inputs, and the inverse square root
+/// and square root of their covariance.
+///
+/// The covariance is symmetric so the roots are computed from its SVD.
+fn whitening_transform(inputs: &Matrix<f64>)
+                       -> LearningResult<(Vector<f64>, Matrix<f64>, Matrix<f64>)> {
+    let (sigma, _, v) = covariance_matrix(inputs).svd()?;
+    let sigma = sigma.diag().cloned().collect::<Vec<f64>>();
+    let max_sigma = sigma.iter().fold(0f64, |a, &b| a.max(b));
+
+    if sigma.iter().any(|&s| s <= max_sigma * 1e-12) {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The covariance of the inputs is singular so they cannot be \
+                               whitened."));
+    }
+
+    let inv_sqrt = Matrix::from_diag(&sigma.iter().map(|s| 1f64 / s.sqrt()).collect::<Vec<_>>());
+    let sqrt = Matrix::from_diag(&sigma.iter().map(|s| s.sqrt()).collect::<Vec<_>>());
+
+    let transform = &v * inv_sqrt * v.transpose();
+    let inverse = &v * sqrt * v.transpose();
+    Ok((inputs.mean(Axes::Row), transform, inverse))
+}
+
+/// Subtract the mean from each row of the inputs.
+fn centered_rows(inputs: &Matrix<f64>, mean: &Vector<f64>) -> Matrix<f64> {
+    let mut centered = inputs.clone();
+    for mut row in centered.row_iter_mut() {
+        utils::in_place_vec_bin_op(row.raw_slice_mut(), mean.data(), |x, &m| *x -= m);
+    }
+    centered
+}
+
+/// The Shannon entropy of each row of a matrix of probabilities.
+///
+/// Zero probabilities contribute nothing to the entropy.
+fn row_entropy(probs: &Matrix<f64>) -> Vector<f64> {
+    let entropy = probs.row_iter()
+        .map(|row| {
+            -row.raw_slice()
+                .iter()
+                .filter(|&&p| p > 0f64)
+                .map(|&p| p * p.ln())
+                .sum::<f64>()
+        })
+        .collect::<Vec<f64>>();
+
+    Vector::new(entropy)
+}
+
+/// Trains a GMM for each component count in `k_range` and returns
+/// the trained model with the lowest BIC.
+///
+/// Each model is trained on `inputs` using the given covariance
+/// option and maximum number of EM iterations.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::linalg::Matrix;
+/// use rusty_machine::learning::gmm::{self, CovOption};
+///
+/// let inputs = Matrix::new(6, 2, vec![1.0, 2.0, 1.1, 2.1, 0.9, 1.9,
+///                                     -3.0, -3.0, -3.1, -2.9, -2.9, -3.1]);
+///
+/// let model = gmm::select_by_bic(&inputs, 1..3, CovOption::Diagonal, 10).unwrap();
+/// println!("Chose {} components", model.means().unwrap().rows());
+/// ```
+///
+/// # Failures
+///
+/// - The range of component counts is empty or contains zero.
+/// - Any of the models fails to train.
+pub fn select_by_bic(inputs: &Matrix<f64>,
+                     k_range: Range<usize>,
+                     cov_option: CovOption,
+                     max_iters: usize)
+                     -> LearningResult<GaussianMixtureModel> {
+    if k_range.start == 0 || k_range.start >= k_range.end {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "The range of component counts must be non-empty and positive."));
+    }
+
+    let mut best: Option<(f64, GaussianMixtureModel)> = None;
+
+    for k in k_range {
+        let mut model = GaussianMixtureModel::new(k);
+        model.cov_option = cov_option;
+        model.set_max_iters(max_iters);
+        model.train(inputs)?;
+
+        let bic = model.bic(inputs)?;
+
+        let improved = match best {
+            Some((best_bic, _)) => bic < best_bic,
+            None => true,
+        };
+
+        if improved {
+            best = Some((bic, model));
+        }
+    }
+
+    // The range is non-empty so a model has been chosen.
+    Ok(best.unwrap().1)
+}
+
+impl Parametric for GaussianMixtureModel {
+    /// The number of free parameters in the mixture.
+    ///
+    /// This counts the means, the covariances as determined by the
+    /// `cov_option` and the mixture weights. The input dimension is
+    /// taken from the trained means, so an untrained model reports zero.
+    fn n_parameters(&self) -> usize {
+        match self.model_means {
+            Some(ref means) => self.free_params(means.cols()),
+            None => 0,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::GaussianMixtureModel;
-    use linalg::Vector;
+    use super::{GaussianMixtureModel, CovOption, Orientation, select_by_bic, row_entropy,
+                centered_rows};
+    use learning::{Parametric, UnSupModel};
+    use linalg::{Matrix, Vector, BaseMatrix, Axes};
 
     #[test]
     fn test_means_none() {
@@ -379,4 +952,212 @@ mod tests {
         let gmm_res = GaussianMixtureModel::with_weights(3, mix_weights);
         assert!(gmm_res.is_err());
     }
+
+    #[test]
+    fn test_partial_fit() {
+        let inputs = Matrix::new(8, 2, vec![1.0, 2.0, 1.2, 1.9, 0.9, 2.1, 1.1, 2.2,
+                                            -3.0, -3.0, -3.1, -2.8, -2.9, -3.2, -3.1, -3.0]);
+
+        // A single chunk gives the same parameters as a single EM step
+        let mut model = GaussianMixtureModel::new(1);
+        model.cov_option = CovOption::Diagonal;
+        model.partial_fit(&inputs).unwrap();
+
+        let mean = model.means().unwrap();
+        assert!((mean[[0, 0]] - inputs.mean(Axes::Row)[0]).abs() < 1e-10);
+
+        // Two chunks of the data match the single chunk
+        let mut chunked = GaussianMixtureModel::new(1);
+        chunked.cov_option = CovOption::Diagonal;
+        chunked.partial_fit(&inputs.select_rows(&[0, 1, 4, 5])).unwrap();
+        let means_before = chunked.means().unwrap().clone();
+        chunked.partial_fit(&inputs.select_rows(&[2, 3, 6, 7])).unwrap();
+
+        assert!(means_before != *chunked.means().unwrap());
+        for (a, b) in chunked.means().unwrap().iter().zip(mean.iter()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+        let (cov_a, cov_b) = (&chunked.covariances().unwrap()[0], &model.covariances().unwrap()[0]);
+        for (a, b) in cov_a.iter().zip(cov_b.iter()) {
+            assert!((a - b).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_partial_fit_errors() {
+        let mut model = GaussianMixtureModel::new(3);
+        assert!(model.partial_fit(&Matrix::new(2, 1, vec![1.0, 2.0])).is_err());
+
+        model.partial_fit(&Matrix::new(3, 1, vec![1.0, 2.0, 5.0])).unwrap();
+        assert!(model.partial_fit(&Matrix::new(3, 2, vec![1.0; 6])).is_err());
+    }
+
+    #[test]
+    fn test_samples_in_columns() {
+        let inputs = Matrix::new(8, 2, vec![1.0, 2.0, 1.2, 1.9, 0.9, 2.1, 1.1, 2.2,
+                                            -3.0, -3.0, -3.1, -2.8, -2.9, -3.2, -3.1, -3.0]);
+        let transposed = inputs.transpose();
+
+        let mut model = GaussianMixtureModel::new(1);
+        model.cov_option = CovOption::Diagonal;
+        model.set_orientation(Orientation::SamplesInColumns);
+        model.train(&transposed).unwrap();
+
+        let mean = model.means().unwrap();
+        assert_eq!(mean.cols(), 2);
+        assert!((mean[[0, 0]] - inputs.mean(Axes::Row)[0]).abs() < 1e-10);
+        assert!((mean[[0, 1]] - inputs.mean(Axes::Row)[1]).abs() < 1e-10);
+
+        assert_eq!(model.predict(&transposed).unwrap().rows(), 8);
+        assert_eq!(model.log_density(&transposed).unwrap().size(), 8);
+        assert!(model.predict(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_fewer_samples_than_components() {
+        let inputs = Matrix::new(2, 4, vec![1.0, -3.0, 0.1, -5.0, 2.0, -3.0, 1.5, -2.5]);
+
+        let mut model = GaussianMixtureModel::new(3);
+        model.cov_option = CovOption::Diagonal;
+        assert!(model.train(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_row_entropy() {
+        let probs = Matrix::new(3, 2, vec![1.0, 0.0, 0.5, 0.5, 0.9, 0.1]);
+        let entropy = row_entropy(&probs);
+
+        assert_eq!(entropy[0], 0.0);
+        assert!((entropy[1] - 2f64.ln()).abs() < 1e-12);
+        assert!(entropy[2] > 0.0 && entropy[2] < entropy[1]);
+    }
+
+    #[test]
+    fn test_assignment_entropy_untrained() {
+        let model = GaussianMixtureModel::new(2);
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert!(model.assignment_entropy(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_predict_wrong_columns() {
+        let inputs = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+
+        let mut model = GaussianMixtureModel::new(2);
+        model.cov_option = CovOption::Diagonal;
+        model.train(&inputs).unwrap();
+
+        let wrong = Matrix::new(2, 3, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert!(model.predict(&wrong).is_err());
+        assert!(model.log_density(&wrong).is_err());
+    }
+
+    #[test]
+    fn test_bic_untrained() {
+        let model = GaussianMixtureModel::new(2);
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert!(model.bic(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_log_density_untrained() {
+        let model = GaussianMixtureModel::new(2);
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert!(model.log_density(&inputs).is_err());
+    }
+
+    #[test]
+    fn test_free_params() {
+        let mut model = GaussianMixtureModel::new(3);
+        assert_eq!(model.free_params(2), 3 * 2 + 3 * 3 + 2);
+
+        model.cov_option = CovOption::Diagonal;
+        assert_eq!(model.free_params(2), 3 * 2 + 3 * 2 + 2);
+    }
+
+    #[test]
+    fn test_n_parameters() {
+        let inputs = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+
+        let mut model = GaussianMixtureModel::new(2);
+        assert_eq!(model.n_parameters(), 0);
+
+        model.cov_option = CovOption::Diagonal;
+        model.train(&inputs).unwrap();
+        assert_eq!(model.n_parameters(), model.free_params(2));
+        assert_eq!(model.n_parameters(), 2 * 2 + 2 * 2 + 1);
+    }
+
+    #[test]
+    fn test_select_by_bic_empty_range() {
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+
+        assert!(select_by_bic(&inputs, 0..3, CovOption::Diagonal, 10).is_err());
+        assert!(select_by_bic(&inputs, 2..2, CovOption::Diagonal, 10).is_err());
+    }
+
+    #[test]
+    fn test_select_by_bic_single_k() {
+        let inputs = Matrix::new(4, 2, vec![1.0, 2.0, -3.0, -3.0, 0.1, 1.5, -5.0, -2.5]);
+
+        let model = select_by_bic(&inputs, 2..3, CovOption::Diagonal, 10).unwrap();
+        assert_eq!(model.means().unwrap().rows(), 2);
+    }
+
+    #[test]
+    fn test_whitening_reports_original_space() {
+        // Strongly correlated features on different scales
+        let inputs = Matrix::new(6, 2, vec![1.0, 102.0, 2.0, 195.0, 3.0, 310.0,
+                                            4.0, 398.0, 5.0, 505.0, 6.0, 597.0]);
+
+        let mut plain = GaussianMixtureModel::new(1);
+        plain.train(&inputs).unwrap();
+
+        let mut whitened = GaussianMixtureModel::new(1);
+        whitened.set_whiten(true);
+        whitened.train(&inputs).unwrap();
+
+        // A single component is fit in one step, whatever the space.
+        let (a, b) = (plain.means().unwrap(), whitened.means().unwrap());
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-8 * x.abs());
+        }
+        let (a, b) = (&plain.covariances().unwrap()[0], &whitened.covariances().unwrap()[0]);
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-6 * x.abs());
+        }
+
+        // The whitened inputs have identity covariance.
+        let (mean, transform) = whitened.whitening().unwrap();
+        let white = centered_rows(&inputs, mean) * transform;
+        let cov = (white.transpose() * &white) / 5.0;
+        for i in 0..2 {
+            for j in 0..2 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((cov[[i, j]] - expected).abs() < 1e-6);
+            }
+        }
+
+        assert!(plain.whitening().is_none());
+    }
+
+    #[test]
+    fn test_whitening_errors() {
+        let mut model = GaussianMixtureModel::new(1);
+        model.set_whiten(true);
+
+        // The second feature is a multiple of the first.
+        let singular = Matrix::new(3, 2, vec![1.0, 2.0, 2.0, 4.0, 3.0, 6.0]);
+        assert!(model.train(&singular).is_err());
+
+        let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 2.0, 1.0, 3.0, 5.0]);
+        model.cov_option = CovOption::Diagonal;
+        assert!(model.train(&inputs).is_err());
+
+        model.cov_option = CovOption::Full;
+        assert!(model.train(&inputs).is_ok());
+    }
 }
@@ -22,15 +22,57 @@
 //!
 //! let outputs = gaussp.predict(&test_data).unwrap();
 //! ```
-//! Alternatively one could use `gaussp.get_posterior()` which would return both
-//! the predictive mean and covariance. However, this is likely to change in
-//! a future release.
+//!
+//! The predictive uncertainty is available through `predict_with_variance`,
+//! which returns the posterior mean and the variance at each input, and
+//! `predict_with_covariance`, which returns the full posterior covariance
+//! for joint queries.
+//!
+//! The kernel parameters and noise can be fit to the data by maximizing
+//! the log marginal likelihood using `optimize_hyperparameters`.
+//!
+//! For large datasets the `SparseGaussianProcess` approximates the GP
+//! using a small number of inducing points, which are initialized by
+//! k-means clustering of the training inputs.
+//!
+//! The prior mean of the GP is given by a `MeanFunc`. The `ConstMean` and
+//! `LinearMean` functions can be fit to the targets by least squares using
+//! `train_with_mean`, after which the GP models the residuals.
+//!
+//! Binary classification is provided by the `GaussianProcessClassifier`,
+//! which uses the Laplace approximation with a logistic likelihood.
 
+use learning::toolkit::activ_fn::{ActivationFunc, Sigmoid};
 use learning::toolkit::kernel::{Kernel, SquaredExp};
 use linalg::{Matrix, BaseMatrix, Decomposition, Cholesky};
 use linalg::Vector;
-use learning::{LearningResult, SupModel};
+use learning::{LearningResult, SupModel, UnSupModel};
+use learning::k_means::KMeansClassifier;
 use learning::error::{Error, ErrorKind};
+use learning::optim::{Optimizable, OptimAlgorithm};
+use learning::optim::fmincg::ConjugateGD;
+
+use rand::Rng;
+use rand::distributions::IndependentSample;
+use rand::distributions::normal::Normal;
+
+use std::f64;
+use std::f64::consts::PI;
+
+/// The step used for the finite difference gradients of the marginal likelihood.
+const FD_STEP: f64 = 1e-5;
+
+/// The number of times the jitter is increased when factorizing a covariance matrix.
+const MAX_JITTER_TRIES: usize = 10;
+
+/// The starting noise for hyperparameter optimization when the noise is zero.
+const MIN_START_NOISE: f64 = 1e-6;
+
+/// The maximum number of Newton iterations used to fit the classifier.
+const MAX_NEWTON_ITERS: usize = 100;
+
+/// The change in the Laplace objective at which Newton iteration stops.
+const NEWTON_TOL: f64 = 1e-10;
 
 /// Trait for GP mean functions.
 pub trait MeanFunc {
@@ -38,7 +80,15 @@ pub trait MeanFunc {
     fn func(&self, x: Matrix<f64>) -> Vector<f64>;
 }
 
+/// Trait for GP mean functions whose parameters can be fit to data.
+pub trait FittableMean: MeanFunc {
+    /// Fit the parameters of the mean function to the targets.
+    fn fit(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()>;
+}
+
 /// Constant mean function
+///
+/// The default is the zero function.
 #[derive(Clone, Copy, Debug)]
 pub struct ConstMean {
     a: f64,
@@ -51,12 +101,104 @@ impl Default for ConstMean {
     }
 }
 
+impl ConstMean {
+    /// Construct a constant mean function with value `a`.
+    pub fn new(a: f64) -> ConstMean {
+        ConstMean { a: a }
+    }
+
+    /// Get the value of the mean function.
+    pub fn value(&self) -> f64 {
+        self.a
+    }
+}
+
 impl MeanFunc for ConstMean {
     fn func(&self, x: Matrix<f64>) -> Vector<f64> {
         Vector::zeros(x.rows()) + self.a
     }
 }
 
+impl FittableMean for ConstMean {
+    /// Set the constant to the mean of the targets.
+    fn fit(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() || targets.size() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be a non-zero number of matching inputs and targets."));
+        }
+
+        self.a = targets.sum() / targets.size() as f64;
+        Ok(())
+    }
+}
+
+/// Linear mean function
+///
+/// The mean at an input `x` is `w·x + b`.
+#[derive(Clone, Debug)]
+pub struct LinearMean {
+    weights: Vector<f64>,
+    bias: f64,
+}
+
+impl LinearMean {
+    /// Construct a linear mean function with the given weights and bias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::LinearMean;
+    /// use rusty_machine::linalg::Vector;
+    ///
+    /// let mean = LinearMean::new(Vector::new(vec![2.0]), 1.0);
+    /// ```
+    pub fn new(weights: Vector<f64>, bias: f64) -> LinearMean {
+        LinearMean {
+            weights: weights,
+            bias: bias,
+        }
+    }
+
+    /// Get the weights of the mean function.
+    pub fn weights(&self) -> &Vector<f64> {
+        &self.weights
+    }
+
+    /// Get the bias of the mean function.
+    pub fn bias(&self) -> f64 {
+        self.bias
+    }
+}
+
+impl MeanFunc for LinearMean {
+    /// # Panics
+    ///
+    /// - The inputs do not have one column per weight.
+    fn func(&self, x: Matrix<f64>) -> Vector<f64> {
+        x * &self.weights + self.bias
+    }
+}
+
+impl FittableMean for LinearMean {
+    /// Fit the weights and bias to the targets by least squares.
+    ///
+    /// The number of weights is set to the number of input columns.
+    fn fit(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let full_inputs = Matrix::<f64>::ones(inputs.rows(), 1).hcat(inputs);
+        let xt = full_inputs.transpose();
+        let coefs = (&xt * full_inputs).solve(&xt * targets)?.into_vec();
+
+        self.bias = coefs[0];
+        self.weights = Vector::new(&coefs[1..]);
+        Ok(())
+    }
+}
+
 /// Gaussian Process struct
 ///
 /// Gaussian process with generic kernel and deterministic mean function.
@@ -82,6 +224,7 @@ pub struct GaussianProcess<T: Kernel, U: MeanFunc> {
 /// - Zero noise.
 ///
 /// Note that zero noise can often lead to numerical instability.
+/// In this case a small jitter is added to the training covariance.
 /// A small value for the noise may be a better alternative.
 impl Default for GaussianProcess<SquaredExp, ConstMean> {
     fn default() -> GaussianProcess<SquaredExp, ConstMean> {
@@ -120,26 +263,326 @@ impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
         }
     }
 
+    /// Set the observation noise variance of the GP.
+    ///
+    /// The noise is added to the diagonal of the training covariance
+    /// and so takes effect the next time the model is trained.
+    ///
+    /// # Panics
+    ///
+    /// - `sigma2` is negative.
+    pub fn set_noise(&mut self, sigma2: f64) {
+        assert!(sigma2 >= 0f64, "The noise variance must be non-negative.");
+        self.noise = sigma2;
+    }
+
+    /// Get the kernel of the GP.
+    pub fn kernel(&self) -> &T {
+        &self.ker
+    }
+
+    /// Get the mean function of the GP.
+    pub fn mean(&self) -> &U {
+        &self.mean
+    }
+
     /// Construct a kernel matrix
     fn ker_mat(&self, m1: &Matrix<f64>, m2: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
-        if m1.cols() != m2.cols() {
-            Err(Error::new(ErrorKind::InvalidState,
-                           "Inputs to kernel matrices have different column counts."))
-        } else {
-            let dim1 = m1.rows();
-            let dim2 = m2.rows();
+        kernel_matrix(&self.ker, m1, m2)
+    }
+
+    /// The log marginal likelihood of the targets under the GP prior.
+    ///
+    /// log p(y|X) = -½(y-m)<sup>T</sup>(K + σ<sup>2</sup>I)<sup>-1</sup>(y-m)
+    /// - ½ log|K + σ<sup>2</sup>I| - ½n log 2π
+    ///
+    /// The model does not need to be trained.
+    pub fn log_marginal_likelihood(&self,
+                                   inputs: &Matrix<f64>,
+                                   targets: &Vector<f64>)
+                                   -> LearningResult<f64> {
+        log_marginal_likelihood(&self.ker, &self.mean, self.noise, inputs, targets)
+    }
+}
+
+impl<T: Kernel, U: FittableMean> GaussianProcess<T, U> {
+    /// Fit the mean function by least squares and then train the GP on the residuals.
+    ///
+    /// This is useful when the data has a trend, as predictions away
+    /// from the training data revert to the fitted mean rather than to
+    /// the initial mean function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::{GaussianProcess, LinearMean};
+    /// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let mean = LinearMean::new(Vector::zeros(1), 0.0);
+    /// let mut gp = GaussianProcess::new(SquaredExp::default(), mean, 1e-2);
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![0.0, 1.0, 2.0, 3.0]);
+    /// let targets = Vector::new(vec![1.0, 3.1, 4.9, 7.0]);
+    /// gp.train_with_mean(&inputs, &targets).unwrap();
+    ///
+    /// assert!((gp.mean().weights()[0] - 2.0).abs() < 0.1);
+    /// ```
+    pub fn train_with_mean(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        self.mean.fit(inputs, targets)?;
+        self.train(inputs, targets)
+    }
+}
 
-            let mut ker_data = Vec::with_capacity(dim1 * dim2);
-            ker_data.extend(m1.row_iter().flat_map(|row1| {
-                m2.row_iter()
-                    .map(move |row2| self.ker.kernel(row1.raw_slice(), row2.raw_slice()))
-            }));
+impl<T: Kernel + Clone, U: MeanFunc> GaussianProcess<T, U> {
+    /// Fit the kernel parameters and noise by maximizing the log marginal likelihood.
+    ///
+    /// The optimization is carried out in log-parameter space using
+    /// conjugate gradient descent with finite difference gradients. It is
+    /// first run from the current parameters and then from `restarts`
+    /// random perturbations of them drawn from `rng`. The best parameters
+    /// found are kept and the model is trained with them.
+    ///
+    /// The noise is optimized along with the kernel parameters. If the noise
+    /// is zero the optimization starts from a small positive noise instead.
+    ///
+    /// Returns the log marginal likelihood of the chosen parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    /// use rand::{StdRng, SeedableRng};
+    ///
+    /// # fn main() {
+    /// let mut gp = GaussianProcess::default();
+    /// gp.noise = 0.1;
+    ///
+    /// let inputs = Matrix::new(5, 1, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    /// let targets = Vector::new(vec![0.0, 0.8, 0.9, 0.1, -0.8]);
+    ///
+    /// let mut rng = StdRng::from_seed(&[1, 2, 3]);
+    /// let log_lik = gp.optimize_hyperparameters(&inputs, &targets, 2, &mut rng).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The kernel parameters are not all strictly positive.
+    /// - The model cannot be trained with the chosen parameters.
+    pub fn optimize_hyperparameters<R: Rng>(&mut self,
+                                            inputs: &Matrix<f64>,
+                                            targets: &Vector<f64>,
+                                            restarts: usize,
+                                            rng: &mut R)
+                                            -> LearningResult<f64> {
+        let mut params = self.ker.get_params();
 
-            Ok(Matrix::new(dim1, dim2, ker_data))
+        if params.iter().any(|&p| p <= 0f64) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Hyperparameter optimization requires strictly positive \
+                                   kernel parameters."));
         }
+
+        params.push(if self.noise > 0f64 { self.noise } else { MIN_START_NOISE });
+
+        let start = params.iter().map(|p| p.ln()).collect::<Vec<f64>>();
+
+        let (best_params, best_cost) = {
+            let objective = HyperparamObjective {
+                ker: &self.ker,
+                mean: &self.mean,
+            };
+            let optimizer = ConjugateGD::default();
+
+            let mut best_params = start.clone();
+            let mut best_cost = objective.cost(&start, inputs, targets);
+
+            for restart in 0..restarts + 1 {
+                let init = if restart == 0 {
+                    start.clone()
+                } else {
+                    start.iter().map(|p| p + rng.gen_range(-2f64, 2f64)).collect()
+                };
+
+                let opt_params = optimizer.optimize(&objective, &init, inputs, targets);
+                let cost = objective.cost(&opt_params, inputs, targets);
+
+                if cost < best_cost {
+                    best_cost = cost;
+                    best_params = opt_params;
+                }
+            }
+
+            (best_params, best_cost)
+        };
+
+        if !best_cost.is_finite() {
+            return Err(Error::new(ErrorKind::InvalidState,
+                                  "Could not compute the marginal likelihood for any parameters."));
+        }
+
+        let n = best_params.len();
+        let best = best_params.iter().map(|p| p.exp()).collect::<Vec<f64>>();
+        self.ker.set_params(&best[..n - 1]);
+        self.noise = best[n - 1];
+
+        self.train(inputs, targets)?;
+
+        Ok(-best_cost)
+    }
+}
+
+/// The negative log marginal likelihood as a function of the
+/// log kernel parameters followed by the log noise.
+struct HyperparamObjective<'a, T: 'a + Kernel + Clone, U: 'a + MeanFunc> {
+    ker: &'a T,
+    mean: &'a U,
+}
+
+impl<'a, T: Kernel + Clone, U: MeanFunc> HyperparamObjective<'a, T, U> {
+    fn cost(&self, log_params: &[f64], inputs: &Matrix<f64>, targets: &Vector<f64>) -> f64 {
+        let n = log_params.len();
+        let mut ker = self.ker.clone();
+        ker.set_params(&log_params[..n - 1].iter().map(|p| p.exp()).collect::<Vec<f64>>());
+        let noise = log_params[n - 1].exp();
+
+        log_marginal_likelihood(&ker, self.mean, noise, inputs, targets)
+            .map(|l| -l)
+            .unwrap_or(f64::INFINITY)
+    }
+}
+
+impl<'a, T: Kernel + Clone, U: MeanFunc> Optimizable for HyperparamObjective<'a, T, U> {
+    type Inputs = Matrix<f64>;
+    type Targets = Vector<f64>;
+
+    fn compute_grad(&self,
+                    params: &[f64],
+                    inputs: &Matrix<f64>,
+                    targets: &Vector<f64>)
+                    -> (f64, Vec<f64>) {
+        finite_difference_grad(|p| self.cost(p, inputs, targets), params)
+    }
+}
+
+/// Compute a cost and its central finite difference gradient.
+///
+/// The gradient is left at zero in any direction where the cost
+/// cannot be computed.
+fn finite_difference_grad<F: Fn(&[f64]) -> f64>(cost: F, params: &[f64]) -> (f64, Vec<f64>) {
+    let value = cost(params);
+    let mut grad = vec![0f64; params.len()];
+
+    let mut shifted = params.to_vec();
+    for (i, &p) in params.iter().enumerate() {
+        shifted[i] = p + FD_STEP;
+        let forward = cost(&shifted);
+        shifted[i] = p - FD_STEP;
+        let backward = cost(&shifted);
+        shifted[i] = p;
+
+        if forward.is_finite() && backward.is_finite() {
+            grad[i] = (forward - backward) / (2f64 * FD_STEP);
+        }
+    }
+
+    (value, grad)
+}
+
+/// Compute the lower Cholesky factor of a covariance matrix.
+///
+/// If the decomposition fails a jitter is added to the diagonal,
+/// starting from a small fraction of the mean variance and growing
+/// by a factor of ten on each failure.
+fn jittered_cholesky(cov: Matrix<f64>) -> LearningResult<Matrix<f64>> {
+    let n = cov.rows();
+    if n == 0 {
+        return Ok(cov);
+    }
+
+    let mean_var = cov.diag().sum::<f64>() / n as f64;
+    let mut jitter = 1e-10 * (if mean_var > 0f64 { mean_var } else { 1f64 });
+
+    if let Ok(chol) = Cholesky::decompose(cov.clone()) {
+        return Ok(chol.unpack());
+    }
+
+    for _ in 0..MAX_JITTER_TRIES {
+        let jittered = &cov + Matrix::identity(n) * jitter;
+        if let Ok(chol) = Cholesky::decompose(jittered) {
+            return Ok(chol.unpack());
+        }
+        jitter *= 10f64;
+    }
+
+    Err(Error::new(ErrorKind::LinearAlgebra,
+                   "Could not compute Cholesky decomposition of the covariance, even with jitter."))
+}
+
+/// Construct the kernel matrix between the rows of two matrices.
+fn kernel_matrix<K: Kernel>(ker: &K, m1: &Matrix<f64>, m2: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+    if m1.cols() != m2.cols() {
+        Err(Error::new(ErrorKind::InvalidState,
+                       "Inputs to kernel matrices have different column counts."))
+    } else {
+        let dim1 = m1.rows();
+        let dim2 = m2.rows();
+
+        let mut ker_data = Vec::with_capacity(dim1 * dim2);
+        ker_data.extend(m1.row_iter().flat_map(|row1| {
+            m2.row_iter()
+                .map(move |row2| ker.kernel(row1.raw_slice(), row2.raw_slice()))
+        }));
+
+        Ok(Matrix::new(dim1, dim2, ker_data))
     }
 }
 
+/// Compute the lower Cholesky factor of the training covariance `K + σ²I`.
+///
+/// A jitter is added if the decomposition fails, for example with zero noise.
+fn train_cholesky<K: Kernel>(ker: &K,
+                             noise: f64,
+                             inputs: &Matrix<f64>,
+                             targets: &Vector<f64>)
+                             -> LearningResult<Matrix<f64>> {
+    if inputs.rows() != targets.size() {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The number of inputs and targets must match."));
+    }
+
+    let noise_mat = Matrix::identity(inputs.rows()) * noise;
+    let ker_mat = ker.gram(inputs);
+
+    jittered_cholesky(ker_mat + noise_mat)
+        .map_err(|_| {
+            Error::new(ErrorKind::LinearAlgebra,
+                       "Could not compute the Cholesky decomposition of the training \
+                        covariance. The kernel matrix may not be positive definite; \
+                        try increasing the noise.")
+        })
+}
+
+/// The log marginal likelihood of the targets for the given GP parameters.
+fn log_marginal_likelihood<K: Kernel, U: MeanFunc>(ker: &K,
+                                                   mean: &U,
+                                                   noise: f64,
+                                                   inputs: &Matrix<f64>,
+                                                   targets: &Vector<f64>)
+                                                   -> LearningResult<f64> {
+    let n = inputs.rows();
+    let chol = train_cholesky(ker, noise, inputs, targets)?;
+
+    let x = chol.solve_l_triangular(targets - mean.func(inputs.clone()))?;
+    let log_det = 2f64 * chol.diag().map(|d| d.ln()).sum::<f64>();
+
+    Ok(-0.5 * x.dot(&x) - 0.5 * log_det - 0.5 * (n as f64) * (2f64 * PI).ln())
+}
+
 impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Vector<f64>> for GaussianProcess<T, U> {
     /// Predict output from inputs.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
@@ -155,18 +598,15 @@ impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Vector<f64>> for GaussianProc
     }
 
     /// Train the model using data and outputs.
+    ///
+    /// The Cholesky factor of the training covariance and the weights
+    /// `(K + σ²I)⁻¹(y - m)` are stored so that predictions do not
+    /// need to factorize the covariance again.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
-        let noise_mat = Matrix::identity(inputs.rows()) * self.noise;
-
-        let ker_mat = self.ker_mat(inputs, inputs).unwrap();
+        let train_mat = train_cholesky(&self.ker, self.noise, inputs, targets)?;
 
-        let train_mat = Cholesky::decompose(ker_mat + noise_mat).map_err(|_| {
-            Error::new(ErrorKind::InvalidState,
-                       "Could not compute Cholesky decomposition.")
-        })?.unpack();
-
-        let x = train_mat.solve_l_triangular(targets - self.mean.func(inputs.clone())).unwrap();
-        let alpha = train_mat.transpose().solve_u_triangular(x).unwrap();
+        let x = train_mat.solve_l_triangular(targets - self.mean.func(inputs.clone()))?;
+        let alpha = train_mat.transpose().solve_u_triangular(x)?;
 
         self.train_mat = Some(train_mat);
         self.train_data = Some(inputs.clone());
@@ -182,30 +622,770 @@ impl<T: Kernel, U: MeanFunc> GaussianProcess<T, U> {
     /// Requires the model to be trained first.
     ///
     /// Outputs the posterior mean and covariance matrix.
+    /// This is equivalent to `predict_with_covariance`.
     pub fn get_posterior(&self,
                          inputs: &Matrix<f64>)
                          -> LearningResult<(Vector<f64>, Matrix<f64>)> {
+        self.predict_with_covariance(inputs)
+    }
+
+    /// Predict the posterior mean and variance at each input.
+    ///
+    /// The variance is that of the latent function, `k(x, x) - k*ᵀ K⁻¹ k*`.
+    /// The variance of a noisy observation is given by `predict_with_noisy_variance`.
+    /// Negative variances caused by numerical error are clamped to zero.
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let mut gp = GaussianProcess::default();
+    /// gp.noise = 1e-2;
+    ///
+    /// let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+    /// let targets = Vector::new(vec![0.0, 1.0, 0.0]);
+    /// gp.train(&inputs, &targets).unwrap();
+    ///
+    /// let test_inputs = Matrix::new(2, 1, vec![1.0, 10.0]);
+    /// let (mean, var) = gp.predict_with_variance(&test_inputs).unwrap();
+    ///
+    /// // The model is more certain close to the training data.
+    /// assert!(var[0] < var[1]);
+    /// ```
+    pub fn predict_with_variance(&self,
+                                 inputs: &Matrix<f64>)
+                                 -> LearningResult<(Vector<f64>, Vector<f64>)> {
+        let (post_mean, v_mat) = self.posterior_factors(inputs)?;
+
+        let variances = inputs.row_iter()
+            .zip(v_mat.row_iter())
+            .map(|(x, v)| {
+                let prior_var = self.ker.kernel(x.raw_slice(), x.raw_slice());
+                let explained = v.raw_slice().iter().map(|a| a * a).sum::<f64>();
+                (prior_var - explained).max(0f64)
+            })
+            .collect::<Vec<f64>>();
+
+        Ok((post_mean, Vector::new(variances)))
+    }
+
+    /// Predict the posterior mean and the variance of a noisy observation at each input.
+    ///
+    /// This is the latent variance from `predict_with_variance` plus the noise.
+    ///
+    /// Requires the model to be trained first.
+    pub fn predict_with_noisy_variance(&self,
+                                       inputs: &Matrix<f64>)
+                                       -> LearningResult<(Vector<f64>, Vector<f64>)> {
+        let (post_mean, variances) = self.predict_with_variance(inputs)?;
+        Ok((post_mean, variances + self.noise))
+    }
+
+    /// Predict the posterior mean and the full posterior covariance matrix.
+    ///
+    /// The covariance is that of the latent function at the inputs, so
+    /// the noise is not included. Negative variances on the diagonal caused
+    /// by numerical error are clamped to zero.
+    ///
+    /// Requires the model to be trained first.
+    pub fn predict_with_covariance(&self,
+                                   inputs: &Matrix<f64>)
+                                   -> LearningResult<(Vector<f64>, Matrix<f64>)> {
+        let (post_mean, v_mat) = self.posterior_factors(inputs)?;
+
+        let mut post_var = self.ker.gram(inputs) - &v_mat * v_mat.transpose();
+        for i in 0..post_var.rows() {
+            if post_var[[i, i]] < 0f64 {
+                post_var[[i, i]] = 0f64;
+            }
+        }
+
+        Ok((post_mean, post_var))
+    }
+
+    /// Draw joint samples from the posterior at the inputs.
+    ///
+    /// Each row of the returned matrix is one sample of the latent function
+    /// evaluated at every input. The samples are drawn using a Cholesky
+    /// factor of the posterior covariance. If the covariance is close
+    /// to singular, for example when there are duplicate inputs, a small
+    /// jitter is added to its diagonal.
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::learning::gp::GaussianProcess;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector, BaseMatrix};
+    /// use rand::{StdRng, SeedableRng};
+    ///
+    /// # fn main() {
+    /// let mut gp = GaussianProcess::default();
+    /// gp.noise = 1e-2;
+    ///
+    /// let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+    /// let targets = Vector::new(vec![0.0, 1.0, 0.0]);
+    /// gp.train(&inputs, &targets).unwrap();
+    ///
+    /// let mut rng = StdRng::from_seed(&[1, 2, 3]);
+    /// let test_inputs = Matrix::new(4, 1, vec![0.5, 1.5, 2.5, 3.5]);
+    /// let samples = gp.sample_posterior(&test_inputs, 10, &mut rng).unwrap();
+    ///
+    /// assert_eq!(samples.rows(), 10);
+    /// assert_eq!(samples.cols(), 4);
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The model is untrained.
+    /// - The posterior covariance could not be factorized even with jitter.
+    pub fn sample_posterior<R: Rng>(&self,
+                                    inputs: &Matrix<f64>,
+                                    n_samples: usize,
+                                    rng: &mut R)
+                                    -> LearningResult<Matrix<f64>> {
+        let (post_mean, post_cov) = self.predict_with_covariance(inputs)?;
+        let chol = jittered_cholesky(post_cov)?;
+        let n = inputs.rows();
+
+        let normal = Normal::new(0f64, 1f64);
+        let mut sample_data = Vec::with_capacity(n_samples * n);
+        for _ in 0..n_samples {
+            let z = Vector::new((0..n).map(|_| normal.ind_sample(rng)).collect::<Vec<f64>>());
+            sample_data.extend((&chol * z + &post_mean).into_vec());
+        }
+
+        Ok(Matrix::new(n_samples, n, sample_data))
+    }
+
+    /// Compute the posterior mean and the matrix `V = K*ᵀ L⁻ᵀ`,
+    /// where `L` is the Cholesky factor of the training covariance.
+    ///
+    /// The posterior covariance is then `K** - V Vᵀ`.
+    fn posterior_factors(&self, inputs: &Matrix<f64>) -> LearningResult<(Vector<f64>, Matrix<f64>)> {
         if let (&Some(ref t_mat), &Some(ref alpha), &Some(ref t_data)) = (&self.train_mat,
                                                                           &self.alpha,
                                                                           &self.train_data) {
-            let mean = self.mean.func(inputs.clone());
-
-            let post_mean = mean + self.ker_mat(inputs, t_data)? * alpha;
-
             let test_mat = self.ker_mat(inputs, t_data)?;
-            let mut var_data = Vec::with_capacity(inputs.rows() * inputs.cols());
+            let post_mean = self.mean.func(inputs.clone()) + &test_mat * alpha;
+
+            let mut v_data = Vec::with_capacity(test_mat.rows() * test_mat.cols());
             for row in test_mat.row_iter() {
                 let test_point = Vector::new(row.raw_slice());
-                var_data.append(&mut t_mat.solve_l_triangular(test_point).unwrap().into_vec());
+                v_data.append(&mut t_mat.solve_l_triangular(test_point)?.into_vec());
             }
 
-            let v_mat = Matrix::new(test_mat.rows(), test_mat.cols(), var_data);
+            let v_mat = Matrix::new(test_mat.rows(), test_mat.cols(), v_data);
 
-            let post_var = self.ker_mat(inputs, inputs)? - &v_mat * v_mat.transpose();
+            Ok((post_mean, v_mat))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+/// Sparse Gaussian Process struct
+///
+/// Gaussian process regression which summarizes the training data using
+/// a small set of inducing points. The model uses the variational free
+/// energy (VFE) approximation of Titsias (2009), which is built from the
+/// Nyström approximation of the kernel matrix at the inducing points.
+///
+/// With `n` training points and `k` inducing points training costs
+/// O(nk²) and prediction at `m` points costs O(mk²), compared with the
+/// O(n³) training of the exact `GaussianProcess`.
+///
+/// The inducing points are initialized by k-means clustering of the
+/// training inputs unless they have been set with `set_inducing_points`
+/// or optimized with `optimize_hyperparameters`.
+#[derive(Debug)]
+pub struct SparseGaussianProcess<T: Kernel, U: MeanFunc> {
+    ker: T,
+    mean: U,
+    noise: f64,
+    n_inducing: usize,
+    inducing: Option<Matrix<f64>>,
+    fixed_inducing: bool,
+    chol_uu: Option<Matrix<f64>>,
+    chol_b: Option<Matrix<f64>>,
+    weights: Option<Vector<f64>>,
+}
+
+impl<T: Kernel, U: MeanFunc> SparseGaussianProcess<T, U> {
+    /// Construct a new sparse Gaussian Process with `n_inducing` inducing points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp;
+    /// use rusty_machine::learning::toolkit::kernel;
+    ///
+    /// let ker = kernel::SquaredExp::default();
+    /// let mean = gp::ConstMean::default();
+    /// let gaussp = gp::SparseGaussianProcess::new(ker, mean, 1e-2f64, 20);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - The noise is not strictly positive.
+    /// - `n_inducing` is zero.
+    pub fn new(ker: T, mean: U, noise: f64, n_inducing: usize) -> SparseGaussianProcess<T, U> {
+        assert!(noise > 0f64, "The noise variance must be strictly positive.");
+        assert!(n_inducing > 0, "There must be at least one inducing point.");
+
+        SparseGaussianProcess {
+            ker: ker,
+            mean: mean,
+            noise: noise,
+            n_inducing: n_inducing,
+            inducing: None,
+            fixed_inducing: false,
+            chol_uu: None,
+            chol_b: None,
+            weights: None,
+        }
+    }
+
+    /// Set the observation noise variance of the GP.
+    ///
+    /// Takes effect the next time the model is trained.
+    ///
+    /// # Panics
+    ///
+    /// - `sigma2` is not strictly positive.
+    pub fn set_noise(&mut self, sigma2: f64) {
+        assert!(sigma2 > 0f64, "The noise variance must be strictly positive.");
+        self.noise = sigma2;
+    }
+
+    /// Get the observation noise variance of the GP.
+    pub fn noise(&self) -> f64 {
+        self.noise
+    }
+
+    /// Get the kernel of the GP.
+    pub fn kernel(&self) -> &T {
+        &self.ker
+    }
+
+    /// Get the number of inducing points.
+    pub fn n_inducing(&self) -> usize {
+        self.n_inducing
+    }
+
+    /// Get the inducing points, one per row.
+    ///
+    /// These are `None` until the model is trained or the
+    /// points are set with `set_inducing_points`.
+    pub fn inducing_points(&self) -> &Option<Matrix<f64>> {
+        &self.inducing
+    }
+
+    /// Set the inducing points, one per row.
+    ///
+    /// The points are kept fixed when the model is trained instead
+    /// of being initialized by k-means.
+    ///
+    /// # Panics
+    ///
+    /// - `points` has no rows.
+    pub fn set_inducing_points(&mut self, points: Matrix<f64>) {
+        assert!(points.rows() > 0, "There must be at least one inducing point.");
+        self.n_inducing = points.rows();
+        self.inducing = Some(points);
+        self.fixed_inducing = true;
+    }
+
+    /// The variational lower bound on the log marginal likelihood.
+    ///
+    /// The bound is computed at the current inducing points, or at
+    /// the k-means initialization on the inputs if there are none.
+    /// The model does not need to be trained.
+    pub fn log_marginal_bound(&self,
+                              inputs: &Matrix<f64>,
+                              targets: &Vector<f64>)
+                              -> LearningResult<f64> {
+        let inducing = self.current_inducing(inputs)?;
+        vfe_factors(&self.ker, &self.mean, self.noise, &inducing, inputs, targets)
+            .map(|factors| factors.bound)
+    }
+
+    /// Predict the posterior mean and variance at each input.
+    ///
+    /// The variance is that of the latent function under the sparse
+    /// approximation. Negative variances caused by numerical error are
+    /// clamped to zero.
+    ///
+    /// Requires the model to be trained first.
+    pub fn predict_with_variance(&self,
+                                 inputs: &Matrix<f64>)
+                                 -> LearningResult<(Vector<f64>, Vector<f64>)> {
+        if let (&Some(ref inducing), &Some(ref chol_uu), &Some(ref chol_b), &Some(ref weights)) =
+               (&self.inducing, &self.chol_uu, &self.chol_b, &self.weights) {
+            let test_mat = kernel_matrix(&self.ker, inputs, inducing)?;
+            let post_mean = self.mean.func(inputs.clone()) + &test_mat * weights;
+
+            let mut variances = Vec::with_capacity(inputs.rows());
+            for (x, row) in inputs.row_iter().zip(test_mat.row_iter()) {
+                let a = chol_uu.solve_l_triangular(Vector::new(row.raw_slice()))?;
+                let b = chol_b.solve_l_triangular(a.clone())?;
+                let prior_var = self.ker.kernel(x.raw_slice(), x.raw_slice());
+                variances.push((prior_var - a.dot(&a) + b.dot(&b)).max(0f64));
+            }
+
+            Ok((post_mean, Vector::new(variances)))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// The inducing points used to evaluate the bound.
+    fn current_inducing(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        match self.inducing {
+            Some(ref inducing) => Ok(inducing.clone()),
+            None => self.initial_inducing(inputs),
+        }
+    }
+
+    /// Initialize the inducing points using k-means on the inputs.
+    ///
+    /// If there are no more inputs than inducing points the inputs are used directly.
+    fn initial_inducing(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if inputs.rows() <= self.n_inducing {
+            return Ok(inputs.clone());
+        }
+
+        let mut k_means = KMeansClassifier::new(self.n_inducing);
+        k_means.train(inputs)?;
+        k_means.centroids()
+            .clone()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidState, "Could not initialize inducing points."))
+    }
+}
+
+impl<T: Kernel + Clone, U: MeanFunc> SparseGaussianProcess<T, U> {
+    /// Fit the kernel parameters and noise by maximizing the variational bound.
+    ///
+    /// If `optimize_inducing` is true the inducing point locations are
+    /// optimized as well, and are kept fixed in later training.
+    ///
+    /// The optimization uses conjugate gradient descent with finite difference
+    /// gradients, so optimizing the inducing points is expensive for large
+    /// numbers of points or dimensions. The model is trained with the
+    /// resulting parameters.
+    ///
+    /// Returns the variational bound at the chosen parameters.
+    ///
+    /// # Failures
+    ///
+    /// - The kernel parameters are not all strictly positive.
+    /// - The model cannot be trained with the chosen parameters.
+    pub fn optimize_hyperparameters(&mut self,
+                                    inputs: &Matrix<f64>,
+                                    targets: &Vector<f64>,
+                                    optimize_inducing: bool)
+                                    -> LearningResult<f64> {
+        let ker_params = self.ker.get_params();
+
+        if ker_params.iter().any(|&p| p <= 0f64) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "Hyperparameter optimization requires strictly positive \
+                                   kernel parameters."));
+        }
+
+        let inducing = self.current_inducing(inputs)?;
+
+        let mut start = ker_params.iter().map(|p| p.ln()).collect::<Vec<f64>>();
+        start.push(self.noise.ln());
+        if optimize_inducing {
+            start.extend_from_slice(inducing.data());
+        }
+
+        let (ker, noise, inducing, best_cost) = {
+            let objective = SparseHyperparamObjective {
+                ker: &self.ker,
+                mean: &self.mean,
+                inducing: &inducing,
+                n_ker_params: ker_params.len(),
+                optimize_inducing: optimize_inducing,
+            };
+
+            let opt_params = ConjugateGD::default().optimize(&objective, &start, inputs, targets);
+            let opt_cost = objective.cost(&opt_params, inputs, targets);
+            let start_cost = objective.cost(&start, inputs, targets);
+
+            let (best_params, best_cost) = if opt_cost < start_cost {
+                (opt_params, opt_cost)
+            } else {
+                (start, start_cost)
+            };
+
+            let (ker, noise, inducing) = objective.unpack(&best_params);
+            (ker, noise, inducing, best_cost)
+        };
+
+        if !best_cost.is_finite() {
+            return Err(Error::new(ErrorKind::InvalidState,
+                                  "Could not compute the variational bound for any parameters."));
+        }
+
+        self.ker = ker;
+        self.noise = noise;
+        if optimize_inducing {
+            self.inducing = Some(inducing);
+            self.fixed_inducing = true;
+        }
+
+        self.train(inputs, targets)?;
+
+        Ok(-best_cost)
+    }
+}
+
+impl<T: Kernel, U: MeanFunc> SupModel<Matrix<f64>, Vector<f64>> for SparseGaussianProcess<T, U> {
+    /// Predict output from inputs.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(ref inducing), &Some(ref weights)) = (&self.inducing, &self.weights) {
+            let test_mat = kernel_matrix(&self.ker, inputs, inducing)?;
+            Ok(self.mean.func(inputs.clone()) + &test_mat * weights)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Train the model using data and outputs.
+    ///
+    /// The inducing points are initialized by k-means unless they are fixed.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let inducing = match self.inducing {
+            Some(ref inducing) if self.fixed_inducing => inducing.clone(),
+            _ => self.initial_inducing(inputs)?,
+        };
+
+        let factors = vfe_factors(&self.ker, &self.mean, self.noise, &inducing, inputs, targets)?;
+
+        self.inducing = Some(inducing);
+        self.chol_uu = Some(factors.chol_uu);
+        self.chol_b = Some(factors.chol_b);
+        self.weights = Some(factors.weights);
+
+        Ok(())
+    }
+}
+
+/// The factorization of a trained sparse GP.
+///
+/// With `Kᵤᵤ = LᵤLᵤᵀ` and `V = Lᵤ⁻¹Kᵤf`, `chol_b` is the Cholesky
+/// factor of `B = I + VVᵀ/σ²` and the posterior mean at a point is
+/// `m(x) + k*ᵤ weights`.
+struct VfeFactors {
+    chol_uu: Matrix<f64>,
+    chol_b: Matrix<f64>,
+    weights: Vector<f64>,
+    bound: f64,
+}
+
+/// Compute the VFE factorization and variational bound for the given parameters.
+fn vfe_factors<K: Kernel, U: MeanFunc>(ker: &K,
+                                       mean: &U,
+                                       noise: f64,
+                                       inducing: &Matrix<f64>,
+                                       inputs: &Matrix<f64>,
+                                       targets: &Vector<f64>)
+                                       -> LearningResult<VfeFactors> {
+    if inputs.rows() != targets.size() {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The number of inputs and targets must match."));
+    }
+
+    if noise <= 0f64 {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "The sparse GP requires a strictly positive noise."));
+    }
+
+    let n = inputs.rows();
+    let k = inducing.rows();
+
+    let chol_uu = jittered_cholesky(ker.gram(inducing))?;
+    let v = chol_uu.inverse()? * kernel_matrix(ker, inducing, inputs)?;
+
+    let chol_b = jittered_cholesky(Matrix::identity(k) + (&v * v.transpose()) / noise)?;
+
+    let resid = targets - mean.func(inputs.clone());
+    let c = chol_b.solve_l_triangular((&v * &resid) / noise)?;
+    let weights = chol_uu.transpose()
+        .solve_u_triangular(chol_b.transpose().solve_u_triangular(c.clone())?)?;
+
+    let log_det = (n as f64) * noise.ln() + 2f64 * chol_b.diag().map(|d| d.ln()).sum::<f64>();
+    let quad = resid.dot(&resid) / noise - c.dot(&c);
+
+    // The trace of the error in the Nyström approximation, tr(K - VᵀV).
+    let prior_var = inputs.row_iter()
+        .map(|x| ker.kernel(x.raw_slice(), x.raw_slice()))
+        .sum::<f64>();
+    let explained = v.data().iter().map(|a| a * a).sum::<f64>();
+    let trace = (prior_var - explained).max(0f64);
+
+    let bound = -0.5 * quad - 0.5 * log_det - 0.5 * (n as f64) * (2f64 * PI).ln() -
+                0.5 * trace / noise;
+
+    Ok(VfeFactors {
+        chol_uu: chol_uu,
+        chol_b: chol_b,
+        weights: weights,
+        bound: bound,
+    })
+}
+
+/// The negative variational bound as a function of the log kernel
+/// parameters, the log noise and optionally the inducing points.
+struct SparseHyperparamObjective<'a, T: 'a + Kernel + Clone, U: 'a + MeanFunc> {
+    ker: &'a T,
+    mean: &'a U,
+    inducing: &'a Matrix<f64>,
+    n_ker_params: usize,
+    optimize_inducing: bool,
+}
+
+impl<'a, T: Kernel + Clone, U: MeanFunc> SparseHyperparamObjective<'a, T, U> {
+    fn unpack(&self, params: &[f64]) -> (T, f64, Matrix<f64>) {
+        let n = self.n_ker_params;
+        let mut ker = self.ker.clone();
+        ker.set_params(&params[..n].iter().map(|p| p.exp()).collect::<Vec<f64>>());
+        let noise = params[n].exp();
+
+        let inducing = if self.optimize_inducing {
+            Matrix::new(self.inducing.rows(), self.inducing.cols(), &params[n + 1..])
+        } else {
+            self.inducing.clone()
+        };
+
+        (ker, noise, inducing)
+    }
+
+    fn cost(&self, params: &[f64], inputs: &Matrix<f64>, targets: &Vector<f64>) -> f64 {
+        let (ker, noise, inducing) = self.unpack(params);
+
+        vfe_factors(&ker, self.mean, noise, &inducing, inputs, targets)
+            .map(|factors| -factors.bound)
+            .unwrap_or(f64::INFINITY)
+    }
+}
+
+impl<'a, T: Kernel + Clone, U: MeanFunc> Optimizable for SparseHyperparamObjective<'a, T, U> {
+    type Inputs = Matrix<f64>;
+    type Targets = Vector<f64>;
+
+    fn compute_grad(&self,
+                    params: &[f64],
+                    inputs: &Matrix<f64>,
+                    targets: &Vector<f64>)
+                    -> (f64, Vec<f64>) {
+        finite_difference_grad(|p| self.cost(p, inputs, targets), params)
+    }
+}
+
+/// Gaussian Process Classifier struct
+///
+/// Binary gaussian process classification with a logistic likelihood
+/// and zero mean latent function. The latent posterior is approximated
+/// by the Laplace approximation, whose mode is found by Newton iteration.
+/// Class probabilities are computed using the probit approximation of
+/// the logistic likelihood averaged over the latent predictive distribution.
+///
+/// The targets must be the labels `0` and `1`. The probabilities returned
+/// by `predict_proba` are those of class `1`.
+#[derive(Debug)]
+pub struct GaussianProcessClassifier<T: Kernel> {
+    ker: T,
+    train_data: Option<Matrix<f64>>,
+    grad_log_lik: Option<Vector<f64>>,
+    sqrt_w: Option<Vector<f64>>,
+    chol: Option<Matrix<f64>>,
+}
+
+/// Construct a default Gaussian Process Classifier
+///
+/// The defaults are:
+///
+/// - Squared Exponential kernel.
+impl Default for GaussianProcessClassifier<SquaredExp> {
+    fn default() -> GaussianProcessClassifier<SquaredExp> {
+        GaussianProcessClassifier::new(SquaredExp::default())
+    }
+}
+
+impl<T: Kernel> GaussianProcessClassifier<T> {
+    /// Construct a new Gaussian Process Classifier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcessClassifier;
+    /// use rusty_machine::learning::toolkit::kernel::SquaredExp;
+    ///
+    /// let gpc = GaussianProcessClassifier::new(SquaredExp::new(1.0, 2.0));
+    /// ```
+    pub fn new(ker: T) -> GaussianProcessClassifier<T> {
+        GaussianProcessClassifier {
+            ker: ker,
+            train_data: None,
+            grad_log_lik: None,
+            sqrt_w: None,
+            chol: None,
+        }
+    }
+
+    /// Get the kernel of the GP.
+    pub fn kernel(&self) -> &T {
+        &self.ker
+    }
 
-            Ok((post_mean, post_var))
+    /// Predict the probability that each input belongs to class `1`.
+    ///
+    /// Requires the model to be trained first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::gp::GaussianProcessClassifier;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![-2.0, -1.0, 1.0, 2.0]);
+    /// let targets = Vector::new(vec![0, 0, 1, 1]);
+    ///
+    /// let mut gpc = GaussianProcessClassifier::default();
+    /// gpc.train(&inputs, &targets).unwrap();
+    ///
+    /// let probs = gpc.predict_proba(&Matrix::new(2, 1, vec![-1.5, 1.5])).unwrap();
+    /// assert!(probs[0] < 0.5 && probs[1] > 0.5);
+    /// ```
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(ref t_data), &Some(ref grad), &Some(ref sqrt_w), &Some(ref chol)) =
+               (&self.train_data, &self.grad_log_lik, &self.sqrt_w, &self.chol) {
+            let test_mat = kernel_matrix(&self.ker, inputs, t_data)?;
+            let latent_means = &test_mat * grad;
+
+            let mut probs = Vec::with_capacity(inputs.rows());
+            for ((x, row), mean) in inputs.row_iter().zip(test_mat.row_iter()).zip(latent_means.iter()) {
+                let v = chol.solve_l_triangular(Vector::new(row.raw_slice()).elemul(sqrt_w))?;
+                let prior_var = self.ker.kernel(x.raw_slice(), x.raw_slice());
+                let var = (prior_var - v.dot(&v)).max(0f64);
+
+                let kappa = 1f64 / (1f64 + PI * var / 8f64).sqrt();
+                probs.push(Sigmoid::func(kappa * mean));
+            }
+
+            Ok(Vector::new(probs))
         } else {
             Err(Error::new_untrained())
         }
     }
 }
+
+impl<T: Kernel> SupModel<Matrix<f64>, Vector<usize>> for GaussianProcessClassifier<T> {
+    /// Predict the class of each input.
+    ///
+    /// Inputs are assigned to class `1` when its probability exceeds one half.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let probs = self.predict_proba(inputs)?;
+        Ok(Vector::new(probs.iter().map(|&p| if p > 0.5 { 1 } else { 0 }).collect::<Vec<usize>>()))
+    }
+
+    /// Train the model using data and binary labels.
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    /// - The targets are not all `0` or `1`.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        if targets.iter().any(|&t| t > 1) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The targets must be binary labels, 0 or 1."));
+        }
+
+        let labels = Vector::new(targets.iter().map(|&t| t as f64).collect::<Vec<f64>>());
+        let ker_mat = self.ker.gram(inputs);
+
+        let mut latent = Vector::zeros(inputs.rows());
+        let mut objective = f64::NEG_INFINITY;
+
+        for _ in 0..MAX_NEWTON_ITERS {
+            let (probs, sqrt_w, chol) = laplace_factors(&ker_mat, &latent)?;
+
+            // b = W f + ∇log p(y|f)
+            let b = sqrt_w.elemul(&sqrt_w).elemul(&latent) + (&labels - probs);
+            let x = chol.solve_l_triangular(sqrt_w.elemul(&(&ker_mat * &b)))?;
+            let a = &b - sqrt_w.elemul(&chol.transpose().solve_u_triangular(x)?);
+            latent = &ker_mat * &a;
+
+            let log_lik = labels.iter()
+                .zip(latent.iter())
+                .map(|(&y, &f)| log_sigmoid(if y > 0.5 { f } else { -f }))
+                .sum::<f64>();
+            let new_objective = log_lik - 0.5 * a.dot(&latent);
+
+            let converged = (new_objective - objective).abs() < NEWTON_TOL;
+            objective = new_objective;
+            if converged {
+                break;
+            }
+        }
+
+        let (probs, sqrt_w, chol) = laplace_factors(&ker_mat, &latent)?;
+
+        self.train_data = Some(inputs.clone());
+        self.grad_log_lik = Some(labels - probs);
+        self.sqrt_w = Some(sqrt_w);
+        self.chol = Some(chol);
+
+        Ok(())
+    }
+}
+
+/// Compute the class probabilities, the square root of the negative
+/// log likelihood Hessian `W` and the Cholesky factor of
+/// `B = I + W^½ K W^½` at the latent values.
+fn laplace_factors(ker_mat: &Matrix<f64>,
+                   latent: &Vector<f64>)
+                   -> LearningResult<(Vector<f64>, Vector<f64>, Matrix<f64>)> {
+    let n = latent.size();
+    let probs = Vector::new(latent.iter().map(|&f| Sigmoid::func(f)).collect::<Vec<f64>>());
+    let sqrt_w = Vector::new(probs.iter().map(|&p| (p * (1f64 - p)).sqrt()).collect::<Vec<f64>>());
+
+    let mut b_data = Vec::with_capacity(n * n);
+    for (i, row) in ker_mat.row_iter().enumerate() {
+        for (j, k) in row.raw_slice().iter().enumerate() {
+            let identity = if i == j { 1f64 } else { 0f64 };
+            b_data.push(identity + sqrt_w[i] * k * sqrt_w[j]);
+        }
+    }
+
+    let chol = jittered_cholesky(Matrix::new(n, n, b_data))?;
+    Ok((probs, sqrt_w, chol))
+}
+
+/// Compute `log σ(x)` without overflow.
+fn log_sigmoid(x: f64) -> f64 {
+    if x > 0f64 {
+        -(-x).exp().ln_1p()
+    } else {
+        x - x.exp().ln_1p()
+    }
+}
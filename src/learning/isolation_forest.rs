@@ -0,0 +1,391 @@
+//! Isolation Forest
+//!
+//! Provides an implementation of the isolation forest anomaly detector.
+//!
+//! The model builds an ensemble of random trees, each on a random subsample
+//! of the training data. Every node splits on a randomly chosen feature at a
+//! threshold drawn uniformly between that feature's minimum and maximum over
+//! the points at the node. Anomalies are few and different, so they tend to
+//! be isolated close to the root of the trees.
+//!
+//! The `predict` function returns an anomaly score for each input computed
+//! from its average path length `E[h(x)]` over the trees:
+//!
+//! `s(x) = 2^(-E[h(x)] / c(n))`
+//!
+//! where `c(n)` is the average path length of an unsuccessful search in a
+//! binary search tree built from the `n` subsampled points. Scores lie in
+//! `(0, 1]`; shorter paths give higher scores. Points with scores close to
+//! one are very likely anomalies, while scores well below `0.5` indicate
+//! normal points.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::isolation_forest::IsolationForest;
+//! use rusty_machine::learning::UnSupModel;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let inputs = Matrix::new(6, 2, vec![1.0, 2.0,
+//!                                     1.1, 2.2,
+//!                                     0.9, 1.9,
+//!                                     1.0, 2.1,
+//!                                     1.2, 2.0,
+//!                                     8.0, -3.0]);
+//!
+//! let mut model = IsolationForest::new(50, 6);
+//! model.train(&inputs).unwrap();
+//!
+//! let scores = model.predict(&inputs).unwrap();
+//! ```
+
+use learning::{LearningResult, UnSupModel};
+use learning::error::{Error, ErrorKind};
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use rand::{Rng, StdRng, SeedableRng, thread_rng};
+
+/// The Euler-Mascheroni constant, used to approximate harmonic numbers.
+const EULER_GAMMA: f64 = 0.5772156649015329;
+
+/// A node in an isolation tree.
+#[derive(Debug)]
+enum IsolationNode {
+    /// A node containing the remaining points.
+    Leaf {
+        size: usize,
+    },
+    /// A node splitting points on a single feature.
+    ///
+    /// Points with `x[feature] < threshold` are sent left.
+    Split {
+        feature: usize,
+        threshold: f64,
+        left: Box<IsolationNode>,
+        right: Box<IsolationNode>,
+    },
+}
+
+impl IsolationNode {
+    /// Build a tree isolating the given rows of the inputs.
+    fn build<R: Rng>(inputs: &Matrix<f64>,
+                     rows: Vec<usize>,
+                     depth: usize,
+                     max_depth: usize,
+                     rng: &mut R)
+                     -> IsolationNode {
+        if depth >= max_depth || rows.len() <= 1 {
+            return IsolationNode::Leaf { size: rows.len() };
+        }
+
+        // Only features which vary over the rows can split them.
+        let mut candidates = Vec::new();
+        for feature in 0..inputs.cols() {
+            let mut min = inputs[[rows[0], feature]];
+            let mut max = min;
+            for &r in &rows[1..] {
+                let x = inputs[[r, feature]];
+                if x < min {
+                    min = x;
+                } else if x > max {
+                    max = x;
+                }
+            }
+
+            if max > min {
+                candidates.push((feature, min, max));
+            }
+        }
+
+        if candidates.is_empty() {
+            return IsolationNode::Leaf { size: rows.len() };
+        }
+
+        let (feature, min, max) = candidates[rng.gen_range(0, candidates.len())];
+        let threshold = rng.gen_range(min, max);
+
+        let (left, right): (Vec<usize>, Vec<usize>) = rows.into_iter()
+            .partition(|&r| inputs[[r, feature]] < threshold);
+
+        IsolationNode::Split {
+            feature: feature,
+            threshold: threshold,
+            left: Box::new(IsolationNode::build(inputs, left, depth + 1, max_depth, rng)),
+            right: Box::new(IsolationNode::build(inputs, right, depth + 1, max_depth, rng)),
+        }
+    }
+
+    /// The path length of a point through the tree.
+    ///
+    /// Points ending in a leaf with more than one point have the
+    /// expected path length of the unbuilt subtree added.
+    fn path_length(&self, x: &[f64]) -> f64 {
+        let mut node = self;
+        let mut depth = 0f64;
+
+        loop {
+            match *node {
+                IsolationNode::Leaf { size } => return depth + average_path_length(size),
+                IsolationNode::Split { feature, threshold, ref left, ref right } => {
+                    node = if x[feature] < threshold { left } else { right };
+                    depth += 1f64;
+                }
+            }
+        }
+    }
+}
+
+/// The average path length of an unsuccessful search in a
+/// binary search tree with `n` points.
+fn average_path_length(n: usize) -> f64 {
+    match n {
+        0 | 1 => 0f64,
+        2 => 1f64,
+        _ => {
+            let n = n as f64;
+            2f64 * ((n - 1f64).ln() + EULER_GAMMA) - 2f64 * (n - 1f64) / n
+        }
+    }
+}
+
+/// Isolation Forest Model
+///
+/// Scores anomalies using an ensemble of random isolation
+/// trees via the `UnSupModel` trait.
+#[derive(Debug)]
+pub struct IsolationForest {
+    n_estimators: usize,
+    max_samples: usize,
+    seed: Option<usize>,
+    trees: Option<Vec<IsolationNode>>,
+    sample_size: usize,
+    n_features: usize,
+}
+
+/// Constructs an untrained isolation forest with the
+/// following parameters:
+///
+/// - `n_estimators` : `100`
+/// - `max_samples` : `256`
+/// - unseeded sampling
+impl Default for IsolationForest {
+    fn default() -> IsolationForest {
+        IsolationForest::new(100, 256)
+    }
+}
+
+impl IsolationForest {
+    /// Constructs an untrained isolation forest.
+    ///
+    /// Requires the number of trees and the maximum number of
+    /// points subsampled to build each tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::isolation_forest::IsolationForest;
+    ///
+    /// let model = IsolationForest::new(200, 128);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `n_estimators` is zero.
+    /// - `max_samples` is less than two.
+    pub fn new(n_estimators: usize, max_samples: usize) -> IsolationForest {
+        assert!(n_estimators > 0, "The number of estimators must be greater than 0.");
+        assert!(max_samples > 1, "The maximum number of samples must be at least 2.");
+
+        IsolationForest {
+            n_estimators: n_estimators,
+            max_samples: max_samples,
+            seed: None,
+            trees: None,
+            sample_size: 0,
+            n_features: 0,
+        }
+    }
+
+    /// Get the number of trees.
+    pub fn n_estimators(&self) -> usize {
+        self.n_estimators
+    }
+
+    /// Get the maximum number of points subsampled for each tree.
+    pub fn max_samples(&self) -> usize {
+        self.max_samples
+    }
+
+    /// Set the seed used to subsample the data and build the trees.
+    ///
+    /// With a seed, training on the same data always builds the same forest.
+    pub fn set_seed(&mut self, seed: usize) {
+        self.seed = Some(seed);
+    }
+}
+
+impl UnSupModel<Matrix<f64>, Vector<f64>> for IsolationForest {
+    /// Train the model using input data.
+    ///
+    /// Each tree is built from `min(max_samples, inputs.rows())`
+    /// points sampled without replacement.
+    ///
+    /// # Failures
+    ///
+    /// - The inputs have fewer than two rows.
+    fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        if inputs.rows() < 2 {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Isolation forests require at least two training points."));
+        }
+
+        let seed = match self.seed {
+            Some(s) => s,
+            None => thread_rng().gen(),
+        };
+        let mut rng = StdRng::from_seed(&[seed]);
+
+        let n = inputs.rows();
+        let sample_size = if self.max_samples < n { self.max_samples } else { n };
+        let max_depth = (sample_size as f64).log2().ceil() as usize;
+
+        let mut indices = (0..n).collect::<Vec<usize>>();
+        let mut trees = Vec::with_capacity(self.n_estimators);
+        for _ in 0..self.n_estimators {
+            // Partial Fisher-Yates shuffle
+            for i in 0..sample_size {
+                let j = rng.gen_range(i, n);
+                indices.swap(i, j);
+            }
+
+            let rows = indices[..sample_size].to_vec();
+            trees.push(IsolationNode::build(inputs, rows, 0, max_depth, &mut rng));
+        }
+
+        self.trees = Some(trees);
+        self.sample_size = sample_size;
+        self.n_features = inputs.cols();
+        Ok(())
+    }
+
+    /// Predict the anomaly scores of the inputs.
+    ///
+    /// Higher scores indicate more anomalous points.
+    ///
+    /// The model must be trained.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(ref trees) = self.trees {
+            if inputs.cols() != self.n_features {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let norm = average_path_length(self.sample_size) * trees.len() as f64;
+            let scores = inputs.row_iter()
+                .map(|x| {
+                    let total = trees.iter()
+                        .map(|t| t.path_length(x.raw_slice()))
+                        .sum::<f64>();
+                    2f64.powf(-total / norm)
+                })
+                .collect::<Vec<f64>>();
+
+            Ok(Vector::new(scores))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IsolationForest, average_path_length};
+    use learning::UnSupModel;
+    use linalg::Matrix;
+
+    /// A 10 by 10 grid on the unit square followed by an outlier.
+    fn grid_with_outlier() -> Matrix<f64> {
+        let mut data = Vec::with_capacity(202);
+        for i in 0..10 {
+            for j in 0..10 {
+                data.push(i as f64 / 9.0);
+                data.push(j as f64 / 9.0);
+            }
+        }
+        data.push(5.0);
+        data.push(5.0);
+
+        Matrix::new(101, 2, data)
+    }
+
+    #[test]
+    fn test_average_path_length() {
+        assert_eq!(average_path_length(1), 0.0);
+        assert_eq!(average_path_length(2), 1.0);
+        assert!((average_path_length(256) - 10.2448).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_outlier_scores_highest() {
+        let inputs = grid_with_outlier();
+
+        let mut model = IsolationForest::new(100, 64);
+        model.set_seed(7);
+        model.train(&inputs).unwrap();
+
+        let scores = model.predict(&inputs).unwrap().into_vec();
+        let outlier = scores[100];
+        assert!(outlier > 0.65);
+        assert!(scores[..100].iter().all(|s| *s < outlier));
+    }
+
+    #[test]
+    fn test_seeded_training_is_reproducible() {
+        let inputs = grid_with_outlier();
+
+        let mut a = IsolationForest::new(20, 32);
+        a.set_seed(3);
+        a.train(&inputs).unwrap();
+
+        let mut b = IsolationForest::new(20, 32);
+        b.set_seed(3);
+        b.train(&inputs).unwrap();
+
+        assert_eq!(a.predict(&inputs).unwrap(), b.predict(&inputs).unwrap());
+    }
+
+    #[test]
+    fn test_constant_inputs() {
+        let inputs = Matrix::new(4, 2, vec![1.0; 8]);
+
+        let mut model = IsolationForest::new(10, 4);
+        model.train(&inputs).unwrap();
+
+        // No splits are possible so every point has the same score.
+        let scores = model.predict(&inputs).unwrap();
+        assert!(scores.iter().all(|s| (s - 0.5).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let mut model = IsolationForest::default();
+        assert!(model.predict(&Matrix::new(1, 2, vec![0.0, 0.0])).is_err());
+        assert!(model.train(&Matrix::new(1, 2, vec![0.0, 0.0])).is_err());
+
+        model.train(&Matrix::new(2, 2, vec![0.0, 0.0, 1.0, 1.0])).unwrap();
+        assert!(model.predict(&Matrix::new(1, 3, vec![0.0, 0.0, 0.0])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_estimators() {
+        let _ = IsolationForest::new(0, 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_single_sample() {
+        let _ = IsolationForest::new(10, 1);
+    }
+}
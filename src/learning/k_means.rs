@@ -41,15 +41,76 @@
 //! ## K-means++ initialization
 //!
 //! The [k-means++](https://en.wikipedia.org/wiki/K-means%2B%2B) scheme.
+//!
+//! # Restarts
+//!
+//! A single initialization can converge to a poor local optimum. With
+//! `set_n_init` the model is trained from several initializations and
+//! the clustering with the lowest inertia is kept. Use `set_seed` to make
+//! the initializations, and so the training, reproducible.
+//!
+//! # Training in chunks
+//!
+//! Data which does not fit in memory can be trained in chunks using
+//! `partial_fit`. Each call assigns the chunk to the current centroids
+//! and moves each centroid to the running mean of all points assigned
+//! to it so far.
+//!
+//! # Weighted training
+//!
+//! Pre-aggregated data, where each row stands for many observations,
+//! can be trained using `train_with_weights`. The centroids are the
+//! weighted means of their assigned points. Weighted training is
+//! supported for the metrics whose centroid is the mean.
+//!
+//! # Distance metrics
+//!
+//! By default the Euclidean distance is used. The Manhattan and cosine
+//! distances and the Kullback-Leibler divergence are also supported
+//! through `set_metric`, each with its own centroid update:
+//!
+//! - Euclidean : The centroid is the mean of the assigned points.
+//! - Manhattan : The centroid is the coordinate-wise median of the
+//! assigned points.
+//! - Cosine : The centroid is the normalized mean direction, i.e. the
+//! mean of the assigned points after scaling each to unit length, itself
+//! scaled to unit length. Only the direction of the centroids is meaningful.
+//! - Kullback-Leibler : The centroid is the mean of the assigned points.
+//! Each point is assigned to the centroid with the lowest divergence from
+//! the point. This clusters probability distributions, such as the topic
+//! proportions of documents, and the centroids are distributions too.
+//!
+//! The Kullback-Leibler divergence is a Bregman divergence, for which the
+//! mean of the assigned points is the centroid with the lowest total
+//! divergence. The inputs must be non-negative. A point has an infinite
+//! divergence from a centroid which is zero where the point is not, so
+//! distributions with many zeros may need smoothing first.
+//!
+//! The initialization algorithms always use the Euclidean distance.
+//!
+//! # Choosing k
+//!
+//! The `kmeans_sweep` function trains a model for each k in a range
+//! and records the inertia of each. Plotting the inertia against k
+//! and looking for an "elbow" is a common heuristic for choosing k.
+//!
+//! The `gap_statistic` function gives a less subjective criterion. It
+//! compares the inertia for each k to that of uniformly random data
+//! spanning the same bounding box. `gap_optimal_k` picks k from the
+//! gaps and standard errors of `gap_statistic_with_std_err`.
 
 use linalg::{Matrix, MatrixSlice, Axes, Vector, BaseMatrix};
 use learning::{LearningResult, UnSupModel};
 use learning::error::{Error, ErrorKind};
+use learning::toolkit::distance::DistanceMetric;
+use analysis::score::silhouette_score;
 
-use rand::{Rng, thread_rng};
+use rand::{Rng, StdRng, SeedableRng, thread_rng};
 use libnum::abs;
 
+use std::f64;
 use std::fmt::Debug;
+use std::ops::Range;
 
 /// K-Means Classification model.
 ///
@@ -72,8 +133,18 @@ pub struct KMeansClassifier<InitAlg: Initializer> {
     k: usize,
     /// The fitted centroids .
     centroids: Option<Matrix<f64>>,
+    /// The sum of squared distances to the fitted centroids.
+    inertia: Option<f64>,
+    /// The number of training points assigned to each centroid.
+    cluster_sizes: Option<Vec<usize>>,
     /// The initial algorithm to use.
     init_algorithm: InitAlg,
+    /// The distance metric to use.
+    metric: DistanceMetric,
+    /// The number of initializations to try when training.
+    n_init: usize,
+    /// The seed used for the random number generator.
+    seed: Option<usize>,
 }
 
 impl<InitAlg: Initializer> UnSupModel<Matrix<f64>, Vector<usize>> for KMeansClassifier<InitAlg> {
@@ -81,8 +152,8 @@ impl<InitAlg: Initializer> UnSupModel<Matrix<f64>, Vector<usize>> for KMeansClas
     ///
     /// Model must be trained.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
-        if let Some(ref centroids) = self.centroids {
-            Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(centroids.as_slice(), inputs).0)
+        if self.centroids.is_some() {
+            Ok(self.get_closest_centroids(inputs)?.0)
         } else {
             Err(Error::new_untrained())
         }
@@ -90,23 +161,15 @@ impl<InitAlg: Initializer> UnSupModel<Matrix<f64>, Vector<usize>> for KMeansClas
 
     /// Train the classifier using input data.
     fn train(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
-        self.init_centroids(inputs)?;
-        let mut cost = 0.0;
-        let eps = 1e-14;
-
-        for _i in 0..self.iters {
-            let (idx, distances) = self.get_closest_centroids(inputs)?;
-            self.update_centroids(inputs, idx);
-
-            let cost_i = distances.sum();
-            if abs(cost - cost_i) < eps {
-                break;
-            }
-
-            cost = cost_i;
-        }
+        self.train_restarts(inputs, None).map(|_| ())
+    }
 
-        Ok(())
+    /// Train the classifier and return the classes of the input data.
+    ///
+    /// The classes are the assignments of the final training step, so
+    /// the inputs are not assigned a second time.
+    fn fit_predict(&mut self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        self.train_restarts(inputs, None)
     }
 }
 
@@ -128,7 +191,12 @@ impl KMeansClassifier<KPlusPlus> {
             iters: 100,
             k: k,
             centroids: None,
+            inertia: None,
+            cluster_sizes: None,
             init_algorithm: KPlusPlus,
+            metric: DistanceMetric::Euclidean,
+            n_init: 1,
+            seed: None,
         }
     }
 }
@@ -151,7 +219,12 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
             iters: iters,
             k: k,
             centroids: None,
+            inertia: None,
+            cluster_sizes: None,
             init_algorithm: algo,
+            metric: DistanceMetric::Euclidean,
+            n_init: 1,
+            seed: None,
         }
     }
 
@@ -175,15 +248,375 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
         &self.centroids
     }
 
+    /// Get the inertia of the trained model.
+    ///
+    /// This is the sum of squared distances from each training
+    /// point to its closest centroid. For metrics other than
+    /// the Euclidean distance the distances are not squared.
+    pub fn inertia(&self) -> Option<f64> {
+        self.inertia
+    }
+
+    /// Get the number of training points assigned to each centroid.
+    ///
+    /// These are the counts from the last training pass, or the
+    /// accumulated counts over all chunks seen by `partial_fit`.
+    pub fn cluster_sizes(&self) -> Option<Vec<usize>> {
+        self.cluster_sizes.clone()
+    }
+
+    /// Predict classes from data along with the distance to the assigned centroid.
+    ///
+    /// Points far from every centroid can be flagged as outliers.
+    /// Model must be trained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 1.0, 3.0, 1.0, 4.0]);
+    /// let mut model = KMeansClassifier::new(1);
+    /// model.train(&inputs).unwrap();
+    ///
+    /// let test_inputs = Matrix::new(2, 2, vec![1.0, 3.0, 100.0, 3.0]);
+    /// let (classes, distances) = model.predict_with_distances(&test_inputs).unwrap();
+    ///
+    /// assert_eq!(distances[0], 0.0);
+    /// assert_eq!(distances[1], 99.0);
+    /// ```
+    pub fn predict_with_distances(&self,
+                                  inputs: &Matrix<f64>)
+                                  -> LearningResult<(Vector<usize>, Vector<f64>)> {
+        if self.centroids.is_some() {
+            let (idx, distances) = self.get_closest_centroids(inputs)?;
+            match self.metric {
+                // The Euclidean distances are squared.
+                DistanceMetric::Euclidean => Ok((idx, distances.apply(&|x| x.sqrt()))),
+                _ => Ok((idx, distances)),
+            }
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Get the distance metric.
+    pub fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    /// Get the number of initializations tried when training.
+    pub fn n_init(&self) -> usize {
+        self.n_init
+    }
+
+    /// Get the seed for the random number generator.
+    pub fn seed(&self) -> Option<usize> {
+        self.seed
+    }
+
     /// Set the number of iterations.
     pub fn set_iters(&mut self, iters: usize) {
         self.iters = iters;
     }
 
+    /// Set the number of initializations tried when training.
+    ///
+    /// The model is trained from `n_init` initializations and the run
+    /// with the lowest inertia is kept. Defaults to 1.
+    ///
+    /// # Panics
+    ///
+    /// - `n_init` is zero.
+    pub fn set_n_init(&mut self, n_init: usize) {
+        assert!(n_init > 0, "The number of initializations must be positive.");
+        self.n_init = n_init;
+    }
+
+    /// Set the seed for the random number generator.
+    ///
+    /// Training with the same seed and data gives identical results.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    /// use rusty_machine::learning::UnSupModel;
+    ///
+    /// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 1.1, 2.1, -3.0, -3.0, -3.1, -2.9]);
+    ///
+    /// let mut model = KMeansClassifier::new(2);
+    /// model.set_seed(7);
+    /// model.set_n_init(10);
+    /// model.train(&inputs).unwrap();
+    /// ```
+    pub fn set_seed(&mut self, seed: usize) {
+        self.seed = Some(seed);
+    }
+
+    /// Set the distance metric.
+    ///
+    /// This affects both the assignment of points to centroids and
+    /// the centroid update. See the module documentation for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    /// use rusty_machine::learning::toolkit::distance::DistanceMetric;
+    ///
+    /// let mut model = KMeansClassifier::new(5);
+    /// model.set_metric(DistanceMetric::Cosine);
+    /// ```
+    pub fn set_metric(&mut self, metric: DistanceMetric) {
+        self.metric = metric;
+    }
+
+    /// Update the centroids from a single chunk of data.
+    ///
+    /// Each point in the chunk is assigned to its closest centroid
+    /// and each centroid is moved to the running mean of all points
+    /// assigned to it across calls. The model is not reset between
+    /// calls, but calling `train` discards the accumulated counts.
+    ///
+    /// If the model is untrained the centroids are first initialized
+    /// from the chunk using the initialization algorithm.
+    ///
+    /// The inertia is not tracked when training in chunks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    ///
+    /// let chunk_1 = Matrix::new(3, 2, vec![1.0, 2.0, 1.0, 3.0, 1.0, 4.0]);
+    /// let chunk_2 = Matrix::new(2, 2, vec![1.0, 2.5, 1.0, 3.5]);
+    ///
+    /// let mut model = KMeansClassifier::new(2);
+    /// model.partial_fit(&chunk_1).unwrap();
+    /// model.partial_fit(&chunk_2).unwrap();
+    ///
+    /// assert_eq!(model.cluster_sizes().unwrap().iter().sum::<usize>(), 5);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The distance metric is not Euclidean or Kullback-Leibler.
+    /// - The model is untrained and the chunk has fewer rows than k.
+    /// - The chunk has a different number of columns than the centroids.
+    /// - The metric is Kullback-Leibler and the chunk has a negative value.
+    pub fn partial_fit(&mut self, chunk: &Matrix<f64>) -> LearningResult<()> {
+        if !self.has_mean_centroids() {
+            return Err(Error::new(ErrorKind::InvalidState,
+                                  "Training in chunks is only supported for the Euclidean \
+                                   distance and the Kullback-Leibler divergence."));
+        }
+        self.check_inputs(chunk)?;
+
+        if self.centroids.is_none() {
+            let mut rng = self.new_rng();
+            self.init_centroids(chunk, &mut rng)?;
+            self.cluster_sizes = None;
+        }
+
+        let d = chunk.cols();
+        if self.centroids.as_ref().map_or(false, |c| c.cols() != d) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The chunk must have the same number of columns as the \
+                                   centroids."));
+        }
+
+        let (idx, _) = self.get_closest_centroids(chunk)?;
+        let mut sizes = self.cluster_sizes.take().unwrap_or_else(|| vec![0; self.k]);
+
+        let mut centroids = self.centroids.take().unwrap().into_vec();
+        for (i, &c) in idx.data().iter().enumerate() {
+            sizes[c] += 1;
+            let n = sizes[c] as f64;
+
+            // Incremental update of the mean of centroid c
+            for j in 0..d {
+                let mean = &mut centroids[c * d + j];
+                *mean += (chunk[[i, j]] - *mean) / n;
+            }
+        }
+
+        self.centroids = Some(Matrix::new(self.k, d, centroids));
+        self.cluster_sizes = Some(sizes);
+        self.inertia = None;
+
+        Ok(())
+    }
+
+    /// Train the model with a weight for each input row.
+    ///
+    /// Each centroid is the weighted mean of the points assigned to it
+    /// and the inertia is the weighted sum of squared distances. This is
+    /// equivalent to training on the data with each row repeated in
+    /// proportion to its weight, without duplicating the rows.
+    ///
+    /// The centroids are initialized from the unweighted inputs. A
+    /// centroid whose assigned points have zero total weight is not moved.
+    /// The cluster sizes count the assigned rows and ignore the weights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    /// use rusty_machine::learning::k_means::KMeansClassifier;
+    ///
+    /// let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 10.0]);
+    /// let weights = Vector::new(vec![3.0, 1.0, 2.0]);
+    ///
+    /// let mut model = KMeansClassifier::new(2);
+    /// model.train_with_weights(&inputs, &weights).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The distance metric is not Euclidean or Kullback-Leibler.
+    /// - The number of weights does not match the number of rows.
+    /// - A weight is negative or not finite.
+    /// - The inputs have fewer rows than k.
+    /// - The metric is Kullback-Leibler and an input is negative.
+    pub fn train_with_weights(&mut self,
+                              inputs: &Matrix<f64>,
+                              weights: &Vector<f64>)
+                              -> LearningResult<()> {
+        if !self.has_mean_centroids() {
+            return Err(Error::new(ErrorKind::InvalidState,
+                                  "Weighted training is only supported for the Euclidean \
+                                   distance and the Kullback-Leibler divergence."));
+        }
+
+        if weights.size() != inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of weights must match the number of inputs."));
+        }
+
+        if weights.iter().any(|w| !w.is_finite() || *w < 0f64) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Weights must be finite and non-negative."));
+        }
+
+        self.train_restarts(inputs, Some(weights.data())).map(|_| ())
+    }
+
+    /// Train the model from `n_init` initializations and keep the
+    /// run with the lowest inertia.
+    ///
+    /// Returns the classes of the inputs under the kept run.
+    fn train_restarts(&mut self,
+                      inputs: &Matrix<f64>,
+                      weights: Option<&[f64]>)
+                      -> LearningResult<Vector<usize>> {
+        self.check_inputs(inputs)?;
+        let mut rng = self.new_rng();
+        let mut best: Option<(Option<Matrix<f64>>, f64, Option<Vec<usize>>, Vector<usize>)> =
+            None;
+
+        for _ in 0..self.n_init {
+            let (_, classes) = self.train_with_rng(inputs, weights, &mut rng)?;
+            let inertia = self.inertia.unwrap();
+
+            let improved = match best {
+                // A NaN inertia, from an empty cluster, is replaced by any other run.
+                Some((_, best_inertia, _, _)) => inertia < best_inertia || best_inertia.is_nan(),
+                None => true,
+            };
+
+            if improved {
+                best = Some((self.centroids.take(), inertia, self.cluster_sizes.take(), classes));
+            }
+        }
+
+        let (centroids, inertia, cluster_sizes, classes) = best.unwrap();
+        self.centroids = centroids;
+        self.inertia = Some(inertia);
+        self.cluster_sizes = cluster_sizes;
+        Ok(classes)
+    }
+
+    /// Whether the centroids are the means of their assigned points.
+    fn has_mean_centroids(&self) -> bool {
+        match self.metric {
+            DistanceMetric::Euclidean | DistanceMetric::KullbackLeibler => true,
+            DistanceMetric::Manhattan | DistanceMetric::Cosine => false,
+        }
+    }
+
+    /// Check that the inputs are valid for the distance metric.
+    fn check_inputs(&self, inputs: &Matrix<f64>) -> LearningResult<()> {
+        if self.metric == DistanceMetric::KullbackLeibler && inputs.iter().any(|x| !(*x >= 0f64)) {
+            Err(Error::new(ErrorKind::InvalidData,
+                           "The Kullback-Leibler divergence requires non-negative inputs."))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The random number generator for initialization.
+    ///
+    /// Seeded by the model seed if there is one.
+    fn new_rng(&self) -> StdRng {
+        let seed = match self.seed {
+            Some(s) => s,
+            None => thread_rng().gen(),
+        };
+        StdRng::from_seed(&[seed])
+    }
+
+    /// Train the model using the given random number generator
+    /// for initialization.
+    ///
+    /// If weights are given the centroids are the weighted means of
+    /// the assigned points and the inertia is weighted.
+    ///
+    /// Returns the number of iterations run and the classes of the
+    /// inputs under the final centroids.
+    fn train_with_rng<R: Rng>(&mut self,
+                              inputs: &Matrix<f64>,
+                              weights: Option<&[f64]>,
+                              rng: &mut R)
+                              -> LearningResult<(usize, Vector<usize>)> {
+        self.init_centroids(inputs, rng)?;
+        let mut cost = 0.0;
+        let eps = 1e-14;
+        let mut iterations = 0;
+
+        for _i in 0..self.iters {
+            iterations += 1;
+            let (idx, distances) = self.get_closest_centroids(inputs)?;
+            self.update_centroids(inputs, idx, weights);
+
+            let cost_i = weighted_sum(&distances, weights);
+            if abs(cost - cost_i) < eps {
+                break;
+            }
+
+            cost = cost_i;
+        }
+
+        let (idx, distances) = self.get_closest_centroids(inputs)?;
+        self.inertia = Some(weighted_sum(&distances, weights));
+
+        let mut sizes = vec![0; self.k];
+        for &c in idx.data() {
+            sizes[c] += 1;
+        }
+        self.cluster_sizes = Some(sizes);
+
+        Ok((iterations, idx))
+    }
+
     /// Initialize the centroids.
     ///
     /// Used internally within model.
-    fn init_centroids(&mut self, inputs: &Matrix<f64>) -> LearningResult<()> {
+    fn init_centroids<R: Rng>(&mut self, inputs: &Matrix<f64>, rng: &mut R) -> LearningResult<()> {
         if self.k > inputs.rows() {
             Err(Error::new(ErrorKind::InvalidData,
                            format!("Number of clusters ({0}) exceeds number of data points \
@@ -191,7 +624,7 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
                                    self.k,
                                    inputs.rows())))
         } else {
-            let centroids = self.init_algorithm.init_centroids(self.k, inputs)?;
+            let centroids = self.init_algorithm.init_centroids_with_rng(self.k, inputs, rng)?;
 
             if centroids.rows() != self.k {
                 Err(Error::new(ErrorKind::InvalidState,
@@ -209,8 +642,14 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
 
     /// Updated the centroids by computing means of assigned classes.
     ///
+    /// If weights are given the mean centroids are weighted means
+    /// and a centroid with no assigned weight is left where it is.
+    ///
     /// Used internally within model.
-    fn update_centroids(&mut self, inputs: &Matrix<f64>, classes: Vector<usize>) {
+    fn update_centroids(&mut self,
+                        inputs: &Matrix<f64>,
+                        classes: Vector<usize>,
+                        weights: Option<&[f64]>) {
         let mut new_centroids = Vec::with_capacity(self.k * inputs.cols());
 
         let mut row_indexes = vec![Vec::new(); self.k];
@@ -218,9 +657,32 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
             row_indexes.get_mut(c as usize).map(|v| v.push(i));
         }
 
-        for vec_i in row_indexes {
+        for (c, vec_i) in row_indexes.into_iter().enumerate() {
             let mat_i = inputs.select_rows(&vec_i);
-            new_centroids.extend(mat_i.mean(Axes::Row).into_vec());
+            match (self.metric, weights) {
+                (DistanceMetric::Euclidean, None) |
+                (DistanceMetric::KullbackLeibler, None) => {
+                    new_centroids.extend(mat_i.mean(Axes::Row).into_vec());
+                }
+                (DistanceMetric::Euclidean, Some(w)) |
+                (DistanceMetric::KullbackLeibler, Some(w)) => {
+                    let w_i = vec_i.iter().map(|&i| w[i]).collect::<Vec<f64>>();
+                    match weighted_mean(&mat_i, &w_i) {
+                        Some(mean) => new_centroids.extend(mean),
+                        None => {
+                            let d = inputs.cols();
+                            let old = self.centroids.as_ref().unwrap().data();
+                            new_centroids.extend_from_slice(&old[c * d..(c + 1) * d]);
+                        }
+                    }
+                }
+                (DistanceMetric::Manhattan, _) => {
+                    new_centroids.extend(column_medians(&mat_i));
+                }
+                (DistanceMetric::Cosine, _) => {
+                    new_centroids.extend(mean_direction(&mat_i));
+                }
+            }
         }
 
         self.centroids = Some(Matrix::new(self.k, inputs.cols(), new_centroids));
@@ -230,7 +692,12 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
                              inputs: &Matrix<f64>)
                              -> LearningResult<(Vector<usize>, Vector<f64>)> {
         if let Some(ref c) = self.centroids {
-            Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(c.as_slice(), inputs))
+            match self.metric {
+                DistanceMetric::Euclidean => {
+                    Ok(KMeansClassifier::<InitAlg>::find_closest_centroids(c.as_slice(), inputs))
+                }
+                metric => Ok(find_closest_by_metric(metric, c, inputs)),
+            }
         } else {
             Err(Error::new(ErrorKind::InvalidState,
                            "Centroids not correctly initialized."))
@@ -262,12 +729,123 @@ impl<InitAlg: Initializer> KMeansClassifier<InitAlg> {
     }
 }
 
+/// Find the centroid closest to each data point using the given metric.
+///
+/// Returns the index of the closest centroid and the distance to it.
+fn find_closest_by_metric(metric: DistanceMetric,
+                          centroids: &Matrix<f64>,
+                          inputs: &Matrix<f64>)
+                          -> (Vector<usize>, Vector<f64>) {
+    let mut idx = Vec::with_capacity(inputs.rows());
+    let mut distances = Vec::with_capacity(inputs.rows());
+
+    for row in inputs.row_iter() {
+        // The divergence of the centroid from the point, for asymmetric metrics.
+        let dist = centroids.row_iter()
+            .map(|c| metric.dist(row.raw_slice(), c.raw_slice()))
+            .collect::<Vec<f64>>();
+
+        let (min_idx, min_dist) = Vector::new(dist).argmin();
+        idx.push(min_idx);
+        distances.push(min_dist);
+    }
+
+    (Vector::new(idx), Vector::new(distances))
+}
+
+/// The sum of the distances, weighted by the sample weights if given.
+fn weighted_sum(distances: &Vector<f64>, weights: Option<&[f64]>) -> f64 {
+    match weights {
+        Some(w) => distances.iter().zip(w.iter()).map(|(d, w)| d * w).sum(),
+        None => distances.sum(),
+    }
+}
+
+/// The weighted mean of the rows of a matrix.
+///
+/// Returns `None` if the weights sum to zero.
+fn weighted_mean(mat: &Matrix<f64>, weights: &[f64]) -> Option<Vec<f64>> {
+    let total = weights.iter().sum::<f64>();
+    if total <= 0f64 {
+        return None;
+    }
+
+    let mut mean = vec![0f64; mat.cols()];
+    for (row, w) in mat.row_iter().zip(weights.iter()) {
+        for (m, x) in mean.iter_mut().zip(row.raw_slice().iter()) {
+            *m += w * x;
+        }
+    }
+
+    for m in &mut mean {
+        *m /= total;
+    }
+
+    Some(mean)
+}
+
+/// The coordinate-wise median of the rows of a matrix.
+fn column_medians(mat: &Matrix<f64>) -> Vec<f64> {
+    (0..mat.cols())
+        .map(|j| {
+            let mut col = mat.row_iter().map(|r| r[j]).collect::<Vec<f64>>();
+            col.sort_by(|a, b| a.partial_cmp(b).unwrap_or(::std::cmp::Ordering::Equal));
+
+            let n = col.len();
+            if n == 0 {
+                f64::NAN
+            } else if n % 2 == 1 {
+                col[n / 2]
+            } else {
+                (col[n / 2 - 1] + col[n / 2]) / 2f64
+            }
+        })
+        .collect()
+}
+
+/// The normalized mean direction of the rows of a matrix.
+///
+/// Zero rows are ignored. If the mean direction is zero it is returned as is.
+fn mean_direction(mat: &Matrix<f64>) -> Vec<f64> {
+    let mut mean = vec![0f64; mat.cols()];
+
+    for row in mat.row_iter() {
+        let norm = row.raw_slice().iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > 0f64 {
+            for (m, x) in mean.iter_mut().zip(row.raw_slice().iter()) {
+                *m += x / norm;
+            }
+        }
+    }
+
+    let norm = mean.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > 0f64 {
+        for m in &mut mean {
+            *m /= norm;
+        }
+    }
+
+    mean
+}
+
 /// Trait for algorithms initializing the K-means centroids.
 pub trait Initializer: Debug {
     /// Initialize the centroids for the initial state of the K-Means model.
     ///
     /// The `Matrix` returned must have `k` rows and the same column count as `inputs`.
     fn init_centroids(&self, k: usize, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>>;
+
+    /// Initialize the centroids using the given random number generator.
+    ///
+    /// This is used when the model is trained with a fixed seed. The
+    /// default implementation ignores the generator and calls `init_centroids`.
+    fn init_centroids_with_rng<R: Rng>(&self,
+                                       k: usize,
+                                       inputs: &Matrix<f64>,
+                                       _rng: &mut R)
+                                       -> LearningResult<Matrix<f64>> {
+        self.init_centroids(k, inputs)
+    }
 }
 
 /// The Forgy initialization scheme.
@@ -276,8 +854,15 @@ pub struct Forgy;
 
 impl Initializer for Forgy {
     fn init_centroids(&self, k: usize, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.init_centroids_with_rng(k, inputs, &mut thread_rng())
+    }
+
+    fn init_centroids_with_rng<R: Rng>(&self,
+                                       k: usize,
+                                       inputs: &Matrix<f64>,
+                                       rng: &mut R)
+                                       -> LearningResult<Matrix<f64>> {
         let mut random_choices = Vec::with_capacity(k);
-        let mut rng = thread_rng();
         while random_choices.len() < k {
             let r = rng.gen_range(0, inputs.rows());
 
@@ -296,10 +881,17 @@ pub struct RandomPartition;
 
 impl Initializer for RandomPartition {
     fn init_centroids(&self, k: usize, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.init_centroids_with_rng(k, inputs, &mut thread_rng())
+    }
+
+    fn init_centroids_with_rng<R: Rng>(&self,
+                                       k: usize,
+                                       inputs: &Matrix<f64>,
+                                       rng: &mut R)
+                                       -> LearningResult<Matrix<f64>> {
 
         // Populate so we have something in each class.
         let mut random_assignments = (0..k).map(|i| vec![i]).collect::<Vec<Vec<usize>>>();
-        let mut rng = thread_rng();
         for i in k..inputs.rows() {
             let idx = rng.gen_range(0, k);
             unsafe {
@@ -324,8 +916,14 @@ pub struct KPlusPlus;
 
 impl Initializer for KPlusPlus {
     fn init_centroids(&self, k: usize, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
-        let mut rng = thread_rng();
+        self.init_centroids_with_rng(k, inputs, &mut thread_rng())
+    }
 
+    fn init_centroids_with_rng<R: Rng>(&self,
+                                       k: usize,
+                                       inputs: &Matrix<f64>,
+                                       rng: &mut R)
+                                       -> LearningResult<Matrix<f64>> {
         let mut init_centroids = Vec::with_capacity(k * inputs.cols());
         let first_cen = rng.gen_range(0usize, inputs.rows());
 
@@ -349,7 +947,7 @@ impl Initializer for KPlusPlus {
                                            initialization."));
                 }
 
-                let next_cen = sample_discretely(&dist);
+                let next_cen = sample_discretely(&dist, rng);
                 init_centroids.extend_from_slice(inputs.row_unchecked(next_cen).raw_slice());
             }
         }
@@ -361,12 +959,12 @@ impl Initializer for KPlusPlus {
 /// Sample from an unnormalized distribution.
 ///
 /// The input to this function is assumed to have all positive entries.
-fn sample_discretely(unnorm_dist: &Vector<f64>) -> usize {
+fn sample_discretely<R: Rng>(unnorm_dist: &Vector<f64>, rng: &mut R) -> usize {
     assert!(unnorm_dist.size() > 0, "No entries in distribution vector.");
 
     let sum = unnorm_dist.sum();
 
-    let rand = thread_rng().gen_range(0.0f64, sum);
+    let rand = rng.gen_range(0.0f64, sum);
 
     let mut tempsum = 0.0;
     for (i, p) in unnorm_dist.data().iter().enumerate() {
@@ -379,3 +977,349 @@ fn sample_discretely(unnorm_dist: &Vector<f64>) -> usize {
 
     panic!("No random value was sampled! There may be more clusters than unique data points.");
 }
+
+/// Configuration for training k-means models in `kmeans_sweep`.
+///
+/// Models are initialized using k-means++.
+#[derive(Clone, Copy, Debug)]
+pub struct KMeansConfig {
+    /// Max iterations of algorithm to run.
+    pub iters: usize,
+    /// The number of initializations to try for each k.
+    ///
+    /// The run with the lowest inertia is kept.
+    pub n_init: usize,
+    /// The seed for the random number generator.
+    ///
+    /// When set the sweep is reproducible.
+    pub seed: Option<usize>,
+    /// Whether to compute the silhouette score of each run.
+    pub silhouette: bool,
+}
+
+/// The defaults are:
+///
+/// - `iters` = 100
+/// - `n_init` = 1
+/// - `seed` = None
+/// - `silhouette` = false
+impl Default for KMeansConfig {
+    fn default() -> KMeansConfig {
+        KMeansConfig {
+            iters: 100,
+            n_init: 1,
+            seed: None,
+            silhouette: false,
+        }
+    }
+}
+
+/// The result of training a k-means model within `kmeans_sweep`.
+#[derive(Clone, Debug)]
+pub struct KMeansRun {
+    /// The number of clusters.
+    pub k: usize,
+    /// The sum of squared distances from each point to its closest centroid.
+    ///
+    /// This is `NaN` if the run was skipped.
+    pub inertia: f64,
+    /// The number of iterations run.
+    pub iterations: usize,
+    /// The silhouette score of the clustering.
+    ///
+    /// Only computed when requested in the config and `k` is between 2
+    /// and the number of rows minus one.
+    pub silhouette: Option<f64>,
+    /// The reason this value of `k` was skipped.
+    pub warning: Option<String>,
+}
+
+impl KMeansRun {
+    /// Whether this value of `k` was skipped.
+    pub fn is_skipped(&self) -> bool {
+        self.warning.is_some()
+    }
+}
+
+/// Trains a k-means model for each k in the range and records
+/// the results.
+///
+/// Values of k larger than the number of rows are skipped and
+/// recorded with a warning.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::linalg::Matrix;
+/// use rusty_machine::learning::k_means::{kmeans_sweep, KMeansConfig};
+///
+/// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 1.1, 2.1, -3.0, -3.0, -3.1, -2.9]);
+///
+/// let mut config = KMeansConfig::default();
+/// config.seed = Some(1);
+///
+/// let runs = kmeans_sweep(&inputs, 1..6, &config).unwrap();
+///
+/// for run in runs.iter().filter(|r| !r.is_skipped()) {
+///     println!("k = {}, inertia = {}", run.k, run.inertia);
+/// }
+///
+/// assert!(runs[4].is_skipped());
+/// ```
+///
+/// # Failures
+///
+/// - The config has `n_init` equal to zero.
+/// - Any of the models fails to train.
+pub fn kmeans_sweep(inputs: &Matrix<f64>,
+                    ks: Range<usize>,
+                    config: &KMeansConfig)
+                    -> LearningResult<Vec<KMeansRun>> {
+    if config.n_init == 0 {
+        return Err(Error::new(ErrorKind::InvalidParameters,
+                              "The number of initializations must be positive."));
+    }
+
+    let seed = match config.seed {
+        Some(s) => s,
+        None => thread_rng().gen(),
+    };
+
+    let mut runs = Vec::with_capacity(ks.len());
+
+    for k in ks {
+        if k == 0 || k > inputs.rows() {
+            runs.push(KMeansRun {
+                k: k,
+                inertia: f64::NAN,
+                iterations: 0,
+                silhouette: None,
+                warning: Some(format!("Number of clusters ({0}) must be between 1 and the \
+                                       number of data points ({1}).",
+                                      k,
+                                      inputs.rows())),
+            });
+            continue;
+        }
+
+        // Seed each k separately so it does not depend on the range.
+        let mut rng = StdRng::from_seed(&[seed, k]);
+        let mut best: Option<(KMeansClassifier<KPlusPlus>, usize)> = None;
+
+        for _ in 0..config.n_init {
+            let mut model = KMeansClassifier::new_specified(k, config.iters, KPlusPlus);
+            let (iterations, _) = model.train_with_rng(inputs, None, &mut rng)?;
+
+            let improved = match best {
+                Some((ref b, _)) => model.inertia < b.inertia,
+                None => true,
+            };
+
+            if improved {
+                best = Some((model, iterations));
+            }
+        }
+
+        let (model, iterations) = best.unwrap();
+
+        let silhouette = if config.silhouette && k > 1 && k < inputs.rows() {
+            let labels = model.predict(inputs)?;
+            Some(silhouette_score(inputs, labels.data()))
+        } else {
+            None
+        };
+
+        runs.push(KMeansRun {
+            k: k,
+            inertia: model.inertia.unwrap(),
+            iterations: iterations,
+            silhouette: silhouette,
+            warning: None,
+        });
+    }
+
+    Ok(runs)
+}
+
+/// The gap statistic for a single value of k, computed by
+/// `gap_statistic_with_std_err`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GapStatistic {
+    /// The number of clusters.
+    pub k: usize,
+    /// The mean reference log inertia minus the log inertia of the inputs.
+    pub gap: f64,
+    /// The standard error of the reference log inertias.
+    ///
+    /// This is `sd * sqrt(1 + 1 / B)` where `sd` is the standard deviation
+    /// of the log inertias of the `B` reference datasets.
+    pub std_err: f64,
+}
+
+/// Computes the gap statistic for each k in the range.
+///
+/// The gap for k is the mean log inertia of k-means fits to `n_refs`
+/// reference datasets, drawn uniformly from the bounding box of the
+/// inputs, minus the log inertia of the fit to the inputs. A large gap
+/// means the inputs are much better clustered than random data. The
+/// smallest k whose gap is close to the largest is a good choice, see
+/// `gap_statistic_with_std_err` for a rule to choose it.
+///
+/// The models are initialized using k-means++. Values of k which are
+/// zero or larger than the number of rows have a gap of `NaN`.
+///
+/// See [Tibshirani et al. (2001)](https://doi.org/10.1111/1467-9868.00293).
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::linalg::Matrix;
+/// use rusty_machine::learning::k_means::gap_statistic;
+///
+/// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 1.1, 2.1, -3.0, -3.0, -3.1, -2.9]);
+///
+/// for (k, gap) in gap_statistic(&inputs, 1..4, 10, Some(1)) {
+///     println!("k = {}, gap = {}", k, gap);
+/// }
+/// ```
+///
+/// # Panics
+///
+/// - `n_refs` is zero.
+pub fn gap_statistic(inputs: &Matrix<f64>,
+                     k_range: Range<usize>,
+                     n_refs: usize,
+                     seed: Option<usize>)
+                     -> Vec<(usize, f64)> {
+    gap_statistic_with_std_err(inputs, k_range, n_refs, seed)
+        .into_iter()
+        .map(|g| (g.k, g.gap))
+        .collect()
+}
+
+/// Computes the gap statistic and its standard error for each k in the range.
+///
+/// The gaps are those of `gap_statistic` with the same arguments. The
+/// standard error of the reference log inertias is returned alongside
+/// each gap, and `gap_optimal_k` uses both to choose k. Values of k which
+/// are zero or larger than the number of rows have a gap and standard
+/// error of `NaN`.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::linalg::Matrix;
+/// use rusty_machine::learning::k_means::{gap_optimal_k, gap_statistic_with_std_err};
+///
+/// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 1.1, 2.1, -3.0, -3.0, -3.1, -2.9]);
+///
+/// let gaps = gap_statistic_with_std_err(&inputs, 1..4, 10, Some(1));
+/// for g in &gaps {
+///     println!("k = {}, gap = {}, std err = {}", g.k, g.gap, g.std_err);
+/// }
+///
+/// println!("Chosen k: {:?}", gap_optimal_k(&gaps));
+/// ```
+///
+/// # Panics
+///
+/// - `n_refs` is zero.
+pub fn gap_statistic_with_std_err(inputs: &Matrix<f64>,
+                                  k_range: Range<usize>,
+                                  n_refs: usize,
+                                  seed: Option<usize>)
+                                  -> Vec<GapStatistic> {
+    assert!(n_refs > 0, "The number of reference datasets must be positive.");
+
+    let seed = match seed {
+        Some(s) => s,
+        None => thread_rng().gen(),
+    };
+
+    let (n, d) = (inputs.rows(), inputs.cols());
+    let mut lower = vec![f64::INFINITY; d];
+    let mut upper = vec![f64::NEG_INFINITY; d];
+    for row in inputs.row_iter() {
+        for (j, &x) in row.raw_slice().iter().enumerate() {
+            lower[j] = lower[j].min(x);
+            upper[j] = upper[j].max(x);
+        }
+    }
+
+    let mut gaps = Vec::with_capacity(k_range.len());
+
+    for k in k_range {
+        if k == 0 || k > n {
+            gaps.push(GapStatistic {
+                k: k,
+                gap: f64::NAN,
+                std_err: f64::NAN,
+            });
+            continue;
+        }
+
+        // Seed each k separately so it does not depend on the range.
+        let mut rng = StdRng::from_seed(&[seed, k]);
+        let data_log_inertia = log_inertia(inputs, k, &mut rng);
+
+        let ref_log_inertias = (0..n_refs)
+            .map(|_| {
+                let reference = (0..n * d)
+                    .map(|i| lower[i % d] + (upper[i % d] - lower[i % d]) * rng.gen::<f64>())
+                    .collect::<Vec<f64>>();
+                log_inertia(&Matrix::new(n, d, reference), k, &mut rng)
+            })
+            .collect::<Vec<f64>>();
+
+        let b = n_refs as f64;
+        let ref_mean = ref_log_inertias.iter().sum::<f64>() / b;
+        let ref_var = ref_log_inertias.iter()
+            .map(|x| (x - ref_mean) * (x - ref_mean))
+            .sum::<f64>() / b;
+
+        gaps.push(GapStatistic {
+            k: k,
+            gap: ref_mean - data_log_inertia,
+            std_err: ref_var.sqrt() * (1f64 + 1f64 / b).sqrt(),
+        });
+    }
+
+    gaps
+}
+
+/// Chooses the number of clusters from the output of `gap_statistic_with_std_err`.
+///
+/// Returns the smallest k such that `Gap(k) >= Gap(k + 1) - s(k + 1)`,
+/// where `s` is the standard error of the reference log inertias.
+/// Only consecutive values of k are compared, so `None` is returned if
+/// no value of k in the range satisfies the rule.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::linalg::Matrix;
+/// use rusty_machine::learning::k_means::{gap_optimal_k, gap_statistic_with_std_err};
+///
+/// let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 1.1, 2.1, -3.0, -3.0, -3.1, -2.9]);
+/// let gaps = gap_statistic_with_std_err(&inputs, 1..4, 10, Some(1));
+///
+/// if let Some(k) = gap_optimal_k(&gaps) {
+///     println!("Chosen k: {}", k);
+/// }
+/// ```
+pub fn gap_optimal_k(gaps: &[GapStatistic]) -> Option<usize> {
+    gaps.windows(2)
+        .find(|w| w[1].k == w[0].k + 1 && w[0].gap >= w[1].gap - w[1].std_err)
+        .map(|w| w[0].k)
+}
+
+/// The log inertia of a k-means++ model trained on the inputs.
+///
+/// Returns `NaN` if the model fails to train.
+fn log_inertia<R: Rng>(inputs: &Matrix<f64>, k: usize, rng: &mut R) -> f64 {
+    let mut model = KMeansClassifier::new(k);
+    match model.train_with_rng(inputs, None, rng) {
+        Ok(_) => model.inertia().unwrap().ln(),
+        Err(_) => f64::NAN,
+    }
+}
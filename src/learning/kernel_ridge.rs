@@ -0,0 +1,208 @@
+//! Kernel Ridge Regression
+//!
+//! Provides an implementation of kernel ridge regression.
+//!
+//! The model finds weights `α` solving `(K + λI)α = y`, where `K` is
+//! the kernel Gram matrix of the training inputs. A new input `x` is
+//! predicted as `Σᵢ αᵢ k(x, xᵢ)`.
+//!
+//! Any kernel from the `toolkit::kernel` module can be used, including
+//! boxed kernels chosen at runtime.
+//!
+//! # Usage
+//!
+//! ```
+//! use rusty_machine::learning::kernel_ridge::KernelRidge;
+//! use rusty_machine::learning::toolkit::kernel::{Kernel, SquaredExp};
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(5, 1, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+//! let targets = Vector::new(vec![0.0, 0.8, 0.9, 0.1, -0.8]);
+//!
+//! let ker: Box<dyn Kernel> = Box::new(SquaredExp::default());
+//! let mut model = KernelRidge::new(ker, 1e-3);
+//! model.train(&inputs, &targets).unwrap();
+//!
+//! let outputs = model.predict(&Matrix::new(2, 1, vec![0.5, 2.5])).unwrap();
+//! ```
+
+use linalg::{Matrix, BaseMatrix, Vector};
+use learning::{LearningResult, SupModel};
+use learning::error::{Error, ErrorKind};
+use learning::toolkit::kernel::Kernel;
+
+/// Kernel Ridge Regression model.
+#[derive(Debug)]
+pub struct KernelRidge<K: Kernel> {
+    ker: K,
+    lambda: f64,
+    alpha: Option<Vector<f64>>,
+    train_inputs: Option<Matrix<f64>>,
+}
+
+impl<K: Kernel> KernelRidge<K> {
+    /// Construct a kernel ridge regression model.
+    ///
+    /// `lambda` is the strength of the ridge penalty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::kernel_ridge::KernelRidge;
+    /// use rusty_machine::learning::toolkit::kernel::Matern52;
+    ///
+    /// let model = KernelRidge::new(Matern52::default(), 0.1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `lambda` is negative.
+    pub fn new(ker: K, lambda: f64) -> KernelRidge<K> {
+        assert!(lambda >= 0f64, "The regularization strength (lambda) must be non-negative.");
+
+        KernelRidge {
+            ker: ker,
+            lambda: lambda,
+            alpha: None,
+            train_inputs: None,
+        }
+    }
+
+    /// Get the kernel of the model.
+    pub fn kernel(&self) -> &K {
+        &self.ker
+    }
+
+    /// Get the regularization strength.
+    pub fn lambda(&self) -> f64 {
+        self.lambda
+    }
+
+    /// Get the weights of the training inputs.
+    ///
+    /// Returns `None` if the model has not been trained.
+    pub fn weights(&self) -> Option<&Vector<f64>> {
+        self.alpha.as_ref()
+    }
+}
+
+impl<K: Kernel> SupModel<Matrix<f64>, Vector<f64>> for KernelRidge<K> {
+    /// Predict output from inputs.
+    ///
+    /// The model must be trained.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let (&Some(ref alpha), &Some(ref train_inputs)) = (&self.alpha, &self.train_inputs) {
+            if inputs.cols() != train_inputs.cols() {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "The inputs must have the same number of columns as \
+                                       the training data."));
+            }
+
+            let outputs = inputs.row_iter()
+                .map(|x| {
+                    train_inputs.row_iter()
+                        .zip(alpha.iter())
+                        .map(|(xi, a)| a * self.ker.kernel(x.raw_slice(), xi.raw_slice()))
+                        .sum::<f64>()
+                })
+                .collect::<Vec<f64>>();
+
+            Ok(Vector::new(outputs))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Train the model using inputs and targets.
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    /// - The regularized Gram matrix is singular, which
+    ///   can happen when `lambda` is zero.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let regularized = self.ker.gram(inputs) + Matrix::identity(inputs.rows()) * self.lambda;
+
+        self.alpha = Some(regularized.solve(targets.clone())?);
+        self.train_inputs = Some(inputs.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KernelRidge;
+    use learning::SupModel;
+    use learning::toolkit::kernel::{Kernel, Linear, SquaredExp};
+    use linalg::{Matrix, Vector};
+
+    #[test]
+    fn test_fits_nonlinear_function() {
+        let x = (0..30).map(|i| i as f64 * 0.2).collect::<Vec<f64>>();
+        let y = x.iter().map(|x| x.sin()).collect::<Vec<f64>>();
+
+        let mut model = KernelRidge::new(SquaredExp::default(), 1e-6);
+        model.train(&Matrix::new(30, 1, x), &Vector::new(y)).unwrap();
+
+        let test_x = vec![0.5, 1.7, 3.3, 4.9];
+        let outputs = model.predict(&Matrix::new(4, 1, test_x.clone())).unwrap();
+        for (o, x) in outputs.iter().zip(test_x.iter()) {
+            assert!((o - x.sin()).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_boxed_kernel() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+        let targets = Vector::new(vec![2.0, 4.0, 6.0]);
+
+        // A linear kernel without offset fits a line through the origin.
+        let ker: Box<dyn Kernel> = Box::new(Linear::new(0.0));
+        let mut model = KernelRidge::new(ker, 1e-8);
+        model.train(&inputs, &targets).unwrap();
+
+        let outputs = model.predict(&Matrix::new(1, 1, vec![10.0])).unwrap();
+        assert!((outputs[0] - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_large_lambda_shrinks() {
+        let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+        let targets = Vector::new(vec![1.0, -1.0, 1.0]);
+
+        let mut model = KernelRidge::new(SquaredExp::default(), 1e8);
+        model.train(&inputs, &targets).unwrap();
+
+        let outputs = model.predict(&inputs).unwrap();
+        assert!(outputs.iter().all(|o| o.abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_untrained() {
+        let model = KernelRidge::new(SquaredExp::default(), 1.0);
+        assert!(model.predict(&Matrix::new(1, 1, vec![0.0])).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_inputs() {
+        let mut model = KernelRidge::new(SquaredExp::default(), 1.0);
+        let inputs = Matrix::new(2, 1, vec![0.0, 1.0]);
+
+        assert!(model.train(&inputs, &Vector::new(vec![1.0])).is_err());
+
+        model.train(&inputs, &Vector::new(vec![1.0, 2.0])).unwrap();
+        assert!(model.predict(&Matrix::new(1, 2, vec![0.0, 1.0])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_lambda() {
+        let _ = KernelRidge::new(SquaredExp::default(), -1.0);
+    }
+}
@@ -266,6 +266,55 @@ impl<B: BinarySplit> BinaryTree<B> {
         }
     }
 
+    /// Search all items within the radius of the point.
+    /// Returns the indices of the items whose distances are less than the radius.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::{KDTree, KNearestSearch};
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let mut tree = KDTree::new(2);
+    /// tree.build(Matrix::new(4, 1, vec![0., 1., 2., 3.]));
+    ///
+    /// let mut res = tree.search_radius(&[1.2], 1.).unwrap();
+    /// res.sort();
+    /// assert_eq!(res, vec![1, 2]);
+    /// ```
+    pub fn search_radius(&self, point: &[f64], radius: f64) -> Result<Vec<usize>, Error> {
+        if let (&Some(ref root), &Some(ref data)) = (&self.root, &self.data) {
+            let mut results: Vec<usize> = Vec::new();
+
+            // avoid recursive call
+            let mut stack: Vec<&Node<B>> = vec![root];
+            while let Some(current) = stack.pop() {
+                match *current {
+                    Node::Leaf(ref l) => {
+                        if l.children.is_empty() {
+                            continue;
+                        }
+                        let distances = get_distances(data, point, &l.children);
+                        for (&i, d) in l.children.iter().zip(distances.into_iter()) {
+                            if d < radius {
+                                results.push(i);
+                            }
+                        }
+                    },
+                    Node::Branch(ref b) => {
+                        if b.dist(point) < radius {
+                            stack.push(b.right());
+                            stack.push(b.left());
+                        }
+                    }
+                }
+            }
+            Ok(results)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
     /// Select next split dimension and value. Returns tuple with 6 elements
     /// - split dim
     /// - split value
@@ -273,14 +322,29 @@ impl<B: BinarySplit> BinaryTree<B> {
     /// - remains for right node
     /// - updated max for left node
     /// - updated min for right node
+    ///
+    /// Returns `None` if all remains are identical and can't be split.
     fn select_split(&self, data: &Matrix<f64>, mut remains: Vec<usize>,
                     mut dmin: Vector<f64>, mut dmax: Vector<f64>)
-        -> (usize, f64, Vec<usize>, Vec<usize>, Vector<f64>, Vector<f64>){
+        -> Option<(usize, f64, Vec<usize>, Vec<usize>, Vector<f64>, Vector<f64>)> {
+
+        // identical rows can't be split, the loop below would not terminate
+        if let Some(&first) = remains.first() {
+            let identical = remains.iter().all(|&r| {
+                (0..data.cols()).all(|c| data[[r, c]] == data[[first, c]])
+            });
+            if identical {
+                return None;
+            }
+        }
 
         // avoid recursive call
         loop {
             // split columns which has the widest range
             let (dim, d) = (&dmax - &dmin).argmax();
+            if !(d > 0.) {
+                return None;
+            }
 
             // Use midpoint rule, see "On the Efficiency of Nearest Neighbor Searching
             // with Data Clustered in Lower Dimensions (Maneewongvatan and Mount, 1999)"
@@ -323,7 +387,7 @@ impl<B: BinarySplit> BinaryTree<B> {
                 let mut r_min = dmin.clone();
                 r_min[dim] = split;
 
-                return (dim, split, l_remains, r_remains, l_max, r_min);
+                return Some((dim, split, l_remains, r_remains, l_max, r_min));
             }
         };
     }
@@ -338,7 +402,10 @@ impl<B: BinarySplit> BinaryTree<B> {
 
             // ToDo: avoid this clone
             let (dim, split, l_remains, r_remains, l_max, r_min) =
-                self.select_split(data, remains.clone(), dmin.clone(), dmax.clone());
+                match self.select_split(data, remains.clone(), dmin.clone(), dmax.clone()) {
+                    Some(s) => s,
+                    None => return Node::Leaf(Leaf::new(remains))
+                };
 
             let l_node = self.split(data, l_remains, dmin.clone(), l_max);
             let g_node = self.split(data, r_remains, r_min, dmax.clone());
@@ -835,4 +902,43 @@ mod tests {
         assert_eq!(min(&data), Vector::new(vec![1., 1.]));
         assert_eq!(max(&data), Vector::new(vec![3., 4.]));
     }
+
+    #[test]
+    fn test_search_radius() {
+        let m = Matrix::new(5, 2, vec![1., 2.,
+                                       8., 0.,
+                                       6., 10.,
+                                       3., 6.,
+                                       0., 3.]);
+        let mut kd = KDTree::new(2);
+        kd.build(m.clone());
+        let mut ball = BallTree::new(2);
+        ball.build(m);
+
+        let mut res = kd.search_radius(&[1., 2.5], 2.).unwrap();
+        res.sort();
+        assert_eq!(res, vec![0, 4]);
+
+        let mut res = ball.search_radius(&[1., 2.5], 2.).unwrap();
+        res.sort();
+        assert_eq!(res, vec![0, 4]);
+
+        // radius is exclusive
+        let res = kd.search_radius(&[1., 2.], 0.).unwrap();
+        assert!(res.is_empty());
+
+        let e = KDTree::default().search_radius(&[1., 2.], 1.);
+        assert!(e.is_err());
+    }
+
+    #[test]
+    fn test_identical_rows() {
+        let m = Matrix::new(40, 2, vec![1.; 80]);
+        let mut tree = KDTree::new(5);
+        tree.build(m);
+
+        let (ind, _) = tree.search(&[1., 1.], 3).unwrap();
+        assert_eq!(ind.len(), 3);
+        assert_eq!(tree.search_radius(&[1., 1.], 0.5).unwrap().len(), 40);
+    }
 }
@@ -26,6 +26,9 @@
 //! assert_eq!(res, Vector::new(vec![1, 0]));
 //! # }
 //! ```
+//!
+//! By default each neighbor gets an equal vote. Closer neighbors can be
+//! given a larger vote using `KNNWeights::Distance`.
 use std::f64;
 use std::collections::BTreeMap;
 
@@ -39,10 +42,23 @@ mod brute_force;
 pub use self::binary_tree::{KDTree, BallTree};
 pub use self::brute_force::BruteForce;
 
+/// Weighting of the neighbor votes in a KNN Classifier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KNNWeights {
+    /// Each neighbor has an equal vote.
+    Uniform,
+    /// Each neighbor's vote is weighted by the inverse of its distance.
+    ///
+    /// If any neighbors coincide with the point then only
+    /// those neighbors vote.
+    Distance,
+}
+
 /// k-Nearest Neighbor Classifier
 #[derive(Debug)]
 pub struct KNNClassifier<S: KNearestSearch> {
     k: usize,
+    weights: KNNWeights,
 
     searcher: S,
     target: Option<Vector<usize>>,
@@ -60,6 +76,7 @@ impl Default for KNNClassifier<KDTree> {
     fn default() -> Self {
         KNNClassifier {
             k: 5,
+            weights: KNNWeights::Uniform,
             searcher: KDTree::default(),
             target: None
         }
@@ -79,6 +96,7 @@ impl KNNClassifier<KDTree> {
     pub fn new(k: usize) -> Self {
         KNNClassifier {
             k: k,
+            weights: KNNWeights::Uniform,
             searcher: KDTree::default(),
             target: None
         }
@@ -98,10 +116,35 @@ impl<S: KNearestSearch> KNNClassifier<S> {
     pub fn new_specified(k: usize, searcher: S) -> Self {
         KNNClassifier {
             k: k,
+            weights: KNNWeights::Uniform,
             searcher: searcher,
             target: None
         }
     }
+
+    /// Get the number of neighbors to search.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Get the weighting of the neighbor votes.
+    pub fn weights(&self) -> KNNWeights {
+        self.weights
+    }
+
+    /// Set the weighting of the neighbor votes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::knn::{KNNClassifier, KNNWeights};
+    ///
+    /// let mut knn = KNNClassifier::new(3);
+    /// knn.set_weights(KNNWeights::Distance);
+    /// ```
+    pub fn set_weights(&mut self, weights: KNNWeights) {
+        self.weights = weights;
+    }
 }
 
 impl<S: KNearestSearch> SupModel<Matrix<f64>, Vector<usize>> for KNNClassifier<S> {
@@ -112,11 +155,21 @@ impl<S: KNearestSearch> SupModel<Matrix<f64>, Vector<usize>> for KNNClassifier<S
 
                 let mut results: Vec<usize> = Vec::with_capacity(inputs.rows());
                 for row in inputs.row_iter() {
-                    let (idx, _) = self.searcher.search(row.raw_slice(), self.k)?;
+                    let (idx, distances) = self.searcher.search(row.raw_slice(), self.k)?;
                     let res = target.select(&idx);
-                    let (uniques, counts) = freq(res.data());
-                    let (id, _) = counts.argmax();
-                    results.push(uniques[id]);
+                    let label = match self.weights {
+                        KNNWeights::Uniform => {
+                            let (uniques, counts) = freq(res.data());
+                            let (id, _) = counts.argmax();
+                            uniques[id]
+                        },
+                        KNNWeights::Distance => {
+                            let (uniques, votes) = weighted_freq(res.data(), &distances);
+                            let (id, _) = votes.argmax();
+                            uniques[id]
+                        }
+                    };
+                    results.push(label);
                 }
                 Ok(Vector::new(results))
             },
@@ -260,6 +313,32 @@ fn freq(labels: &[usize]) -> (Vector<usize>, Vector<usize>) {
     (Vector::new(uniques), Vector::new(counts))
 }
 
+/// Sum the inverse distance weighted votes for each label
+///
+/// If any distances are zero only those labels receive votes.
+fn weighted_freq(labels: &[usize], distances: &[f64]) -> (Vector<usize>, Vector<f64>) {
+    let exact = distances.iter().any(|&d| d == 0.);
+
+    let mut map: BTreeMap<usize, f64> = BTreeMap::new();
+    for (l, &d) in labels.iter().zip(distances.iter()) {
+        let w = if exact {
+            if d == 0. { 1. } else { 0. }
+        } else {
+            1. / d
+        };
+        let e = map.entry(*l).or_insert(0.);
+        *e += w;
+    }
+
+    let mut uniques: Vec<usize> = Vec::with_capacity(map.len());
+    let mut votes: Vec<f64> = Vec::with_capacity(map.len());
+    for (&k, &v) in &map {
+        uniques.push(k);
+        votes.push(v);
+    }
+    (Vector::new(uniques), Vector::new(votes))
+}
+
 /// Return distances between given point and data specified with row ids
 fn get_distances(data: &Matrix<f64>, point: &[f64], ids: &[usize]) -> Vec<f64> {
     assert!(!ids.is_empty(), "target ids is empty");
@@ -288,7 +367,7 @@ fn dist(v1: &[f64], v2: &[f64]) -> f64 {
 mod tests {
 
     use std::f64;
-    use super::KNearest;
+    use super::{KNearest, weighted_freq};
 
     #[test]
     fn test_knearest() {
@@ -336,4 +415,16 @@ mod tests {
         assert_eq!(kn.pairs, vec![(7, 0.5), (3, 1.), (5, 1.5), (2, 2.)]);
         assert_eq!(kn.dist(), 2.);
     }
+
+    #[test]
+    fn test_weighted_freq() {
+        let (uniques, votes) = weighted_freq(&[0, 1, 1], &[1., 4., 4.]);
+        assert_eq!(uniques.into_vec(), vec![0, 1]);
+        assert_eq!(votes.into_vec(), vec![1., 0.5]);
+
+        // exact matches take all votes
+        let (uniques, votes) = weighted_freq(&[0, 1, 1], &[1., 0., 4.]);
+        assert_eq!(uniques.into_vec(), vec![0, 1]);
+        assert_eq!(votes.into_vec(), vec![0., 1.]);
+    }
 }
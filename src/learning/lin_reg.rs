@@ -6,6 +6,20 @@
 //! The regressor will automatically add the intercept term
 //! so you do not need to format the input matrices yourself.
 //!
+//! Data which does not fit in memory can be streamed through a
+//! `LinRegressor` in batches using `partial_train`.
+//!
+//! By default the least squares problem is solved with a QR decomposition,
+//! which remains accurate for ill-conditioned inputs. The `Solver` enum
+//! also allows the normal equations or the SVD to be used.
+//!
+//! The `RidgeRegressor` adds an L2 penalty on the coefficients,
+//! which stabilizes the fit when input columns are collinear, and the
+//! `HuberRegressor` downweights outliers using the Huber loss.
+//!
+//! The `MultiLinRegressor` fits several target columns at once,
+//! factorizing the inputs a single time for all of them.
+//!
 //! # Usage
 //!
 //! ```
@@ -32,12 +46,52 @@
 
 use linalg::{Matrix, BaseMatrix};
 use linalg::Vector;
-use learning::{LearningResult, SupModel};
+use learning::{LearningResult, Parametric, SupModel};
 use learning::toolkit::cost_fn::CostFunc;
 use learning::toolkit::cost_fn::MeanSqError;
 use learning::optim::grad_desc::GradientDesc;
 use learning::optim::{OptimAlgorithm, Optimizable};
-use learning::error::Error;
+use learning::toolkit::rand_utils;
+use learning::error::{Error, ErrorKind};
+
+use std::cmp::Ordering;
+
+/// Relative size below which diagonal entries of `R` or singular
+/// values are treated as zero when solving least squares problems.
+const RANK_TOL: f64 = 1e-10;
+
+/// The median absolute deviation of a standard normal distribution.
+const MAD_NORMALIZER: f64 = 0.6745;
+
+/// The relative parameter change at which Huber regression has converged.
+const HUBER_TOL: f64 = 1e-8;
+
+/// The method used to solve the least squares problem.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Solver {
+    /// Solve the normal equations `XᵀXβ = Xᵀy`.
+    ///
+    /// This is fast but squares the condition number of `X`,
+    /// so it loses accuracy on ill-conditioned inputs.
+    NormalEquations,
+    /// Solve using a Householder QR decomposition of `X`.
+    ///
+    /// Training fails if `X` is rank deficient.
+    Qr,
+    /// Solve using the singular value decomposition of `X`.
+    ///
+    /// Singular values that are small relative to the largest are
+    /// treated as zero, which gives the minimum norm solution
+    /// when `X` is rank deficient.
+    Svd,
+}
+
+/// The default solver is `Solver::Qr`.
+impl Default for Solver {
+    fn default() -> Solver {
+        Solver::Qr
+    }
+}
 
 /// Linear Regression Model.
 ///
@@ -46,21 +100,312 @@ use learning::error::Error;
 pub struct LinRegressor {
     /// The parameters for the regression model.
     parameters: Option<Vector<f64>>,
+    /// The method used to solve the least squares problem.
+    solver: Solver,
+    /// The effective rank of the inputs found by the SVD solver.
+    rank: Option<usize>,
+    /// The step size used by `partial_train`.
+    learning_rate: f64,
+    /// Whether an intercept is fitted.
+    fit_intercept: bool,
+    /// Whether the inputs are standardized before solving.
+    normalize: bool,
 }
 
+/// The centering and scaling applied to each input column before solving.
+struct Standardization {
+    means: Vec<f64>,
+    scales: Vec<f64>,
+}
+
+/// The default linear regressor.
+///
+/// The defaults are:
+///
+/// - solver = `Solver::Qr`
+/// - learning_rate = 0.1
+/// - fit_intercept = true
+/// - normalize = false
 impl Default for LinRegressor {
     fn default() -> LinRegressor {
-        LinRegressor { parameters: None }
+        LinRegressor::new(Solver::default())
     }
 }
 
 impl LinRegressor {
+    /// Construct a linear regressor which trains using the given solver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::{LinRegressor, Solver};
+    ///
+    /// let lin_mod = LinRegressor::new(Solver::Svd);
+    /// ```
+    pub fn new(solver: Solver) -> LinRegressor {
+        LinRegressor {
+            parameters: None,
+            solver: solver,
+            rank: None,
+            learning_rate: 0.1,
+            fit_intercept: true,
+            normalize: false,
+        }
+    }
+
+    /// Get whether an intercept is fitted.
+    pub fn fit_intercept(&self) -> bool {
+        self.fit_intercept
+    }
+
+    /// Set whether an intercept is fitted.
+    ///
+    /// Without an intercept the first parameter is always zero,
+    /// so the inputs should be centered beforehand.
+    pub fn set_fit_intercept(&mut self, fit_intercept: bool) {
+        self.fit_intercept = fit_intercept;
+    }
+
+    /// Get whether the inputs are standardized before solving.
+    pub fn normalize(&self) -> bool {
+        self.normalize
+    }
+
+    /// Set whether the inputs are standardized before solving.
+    ///
+    /// The input columns are centered and scaled to unit variance, the
+    /// problem is solved and the parameters are transformed back to the
+    /// scale of the original inputs. The parameters and predictions are
+    /// therefore unchanged, but gradient descent training converges much
+    /// faster when the inputs have very different scales.
+    ///
+    /// Columns are only centered when an intercept is fitted. This
+    /// does not affect `partial_train`, which sees one batch at a time.
+    pub fn set_normalize(&mut self, normalize: bool) {
+        self.normalize = normalize;
+    }
+
+    /// Get the step size used by `partial_train`.
+    pub fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    /// Set the step size used by `partial_train`.
+    ///
+    /// # Panics
+    ///
+    /// - `learning_rate` is not positive.
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        assert!(learning_rate > 0f64, "The learning rate must be positive.");
+        self.learning_rate = learning_rate;
+    }
+
     /// Get the parameters from the model.
     ///
+    /// The first parameter is the intercept, which is
+    /// zero when no intercept is fitted.
+    ///
     /// Returns an option that is None if the model has not been trained.
     pub fn parameters(&self) -> Option<&Vector<f64>> {
         self.parameters.as_ref()
     }
+
+    /// Get the solver used to train the model.
+    pub fn solver(&self) -> Solver {
+        self.solver
+    }
+
+    /// Get the effective rank of the inputs, including
+    /// the intercept column if an intercept is fitted.
+    ///
+    /// This is only computed when training with `Solver::Svd`
+    /// and is `None` otherwise.
+    pub fn rank(&self) -> Option<usize> {
+        self.rank
+    }
+
+    /// Train the linear regression model using weighted least squares.
+    ///
+    /// The parameters minimize `Σ wᵢ (yᵢ - xᵢβ)²`. The rows of the inputs
+    /// and the targets are scaled by the square roots of the weights and
+    /// the result solved with the model's solver. Rows with zero weight
+    /// are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::LinRegressor;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+    /// let targets = Vector::new(vec![2.0, 4.0, 10.0]);
+    ///
+    /// // Ignore the last point.
+    /// let weights = Vector::new(vec![1.0, 1.0, 0.0]);
+    ///
+    /// let mut lin_mod = LinRegressor::default();
+    /// lin_mod.train_weighted(&inputs, &targets, &weights).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs, targets and weights differ.
+    /// - Any of the weights is negative or not finite.
+    /// - The weighted inputs are rank deficient and the solver is not `Solver::Svd`.
+    pub fn train_weighted(&mut self,
+                          inputs: &Matrix<f64>,
+                          targets: &Vector<f64>,
+                          weights: &Vector<f64>)
+                          -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        if weights.size() != inputs.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of weights must match the number of input rows."));
+        }
+
+        if !weights.iter().all(|w| w.is_finite() && *w >= 0f64) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Weights must be finite and non-negative."));
+        }
+
+        let sqrt_w = weights.iter().map(|w| w.sqrt()).collect::<Vec<f64>>();
+        let (design, standardization) = self.design_matrix(inputs, Some(&sqrt_w));
+
+        let beta = self.solve(design, &targets.elemul(&Vector::new(sqrt_w)))?;
+        self.parameters = Some(self.restore_scale(beta, &standardization));
+        Ok(())
+    }
+
+    /// Build the matrix the least squares problem is solved with.
+    ///
+    /// The inputs are standardized if `normalize` is set and the intercept
+    /// column is prepended if an intercept is fitted. Each row is multiplied
+    /// by the corresponding entry of `row_scales`, if given.
+    fn design_matrix(&self,
+                     inputs: &Matrix<f64>,
+                     row_scales: Option<&[f64]>)
+                     -> (Matrix<f64>, Standardization) {
+        let standardization = if self.normalize {
+            standardize_columns(inputs, self.fit_intercept)
+        } else {
+            Standardization {
+                means: vec![0f64; inputs.cols()],
+                scales: vec![1f64; inputs.cols()],
+            }
+        };
+
+        let cols = inputs.cols() + if self.fit_intercept { 1 } else { 0 };
+        let mut data = Vec::with_capacity(inputs.rows() * cols);
+        for (i, row) in inputs.row_iter().enumerate() {
+            let s = row_scales.map_or(1f64, |r| r[i]);
+            if self.fit_intercept {
+                data.push(s);
+            }
+
+            for (x, (m, sd)) in row.raw_slice()
+                .iter()
+                .zip(standardization.means.iter().zip(standardization.scales.iter())) {
+                data.push(s * ((x - m) / sd));
+            }
+        }
+
+        (Matrix::new(inputs.rows(), cols, data), standardization)
+    }
+
+    /// Transform the solution for the design matrix into
+    /// parameters for the original inputs, intercept first.
+    fn restore_scale(&self, beta: Vector<f64>, standardization: &Standardization) -> Vector<f64> {
+        let (intercept, coefs) = if self.fit_intercept {
+            (beta[0], &beta.data()[1..])
+        } else {
+            (0f64, &beta.data()[..])
+        };
+
+        if !self.normalize {
+            let mut params = Vec::with_capacity(coefs.len() + 1);
+            params.push(intercept);
+            params.extend_from_slice(coefs);
+            return Vector::new(params);
+        }
+
+        let coefs = coefs.iter()
+            .zip(standardization.scales.iter())
+            .map(|(b, sd)| b / sd)
+            .collect::<Vec<f64>>();
+        let shift = coefs.iter()
+            .zip(standardization.means.iter())
+            .map(|(b, m)| b * m)
+            .sum::<f64>();
+
+        let mut params = Vec::with_capacity(coefs.len() + 1);
+        params.push(intercept - shift);
+        params.extend(coefs);
+        Vector::new(params)
+    }
+
+    /// Solve the least squares problem for the design matrix.
+    fn solve(&mut self, design: Matrix<f64>, targets: &Vector<f64>) -> LearningResult<Vector<f64>> {
+        self.rank = None;
+        Ok(match self.solver {
+            Solver::NormalEquations => {
+                let xt = design.transpose();
+                (&xt * design).solve(&xt * targets)?
+            }
+            Solver::Qr => qr_least_squares(&design, targets)?,
+            Solver::Svd => {
+                let (params, rank) = svd_least_squares(design, targets)?;
+                self.rank = Some(rank);
+                params
+            }
+        })
+    }
+}
+
+/// Compute the means and standard deviations of the input columns.
+///
+/// The means are zero if `center` is false, in which case the scale is the
+/// root mean square of the column. Constant columns are given a scale of one.
+fn standardize_columns(inputs: &Matrix<f64>, center: bool) -> Standardization {
+    let n = inputs.rows() as f64;
+    let mut means = vec![0f64; inputs.cols()];
+    if center {
+        for row in inputs.row_iter() {
+            for (m, x) in means.iter_mut().zip(row.raw_slice()) {
+                *m += x / n;
+            }
+        }
+    }
+
+    let mut scales = vec![0f64; inputs.cols()];
+    for row in inputs.row_iter() {
+        for (sd, (x, m)) in scales.iter_mut().zip(row.raw_slice().iter().zip(means.iter())) {
+            *sd += (x - m) * (x - m) / n;
+        }
+    }
+
+    let scales = scales.into_iter()
+        .map(|v| if v > 0f64 { v.sqrt() } else { 1f64 })
+        .collect();
+
+    Standardization {
+        means: means,
+        scales: scales,
+    }
+}
+
+impl Parametric for LinRegressor {
+    /// The number of regression coefficients, including
+    /// the intercept if one is fitted.
+    ///
+    /// An untrained model reports zero.
+    fn n_parameters(&self) -> usize {
+        let intercept = if self.fit_intercept { 0 } else { 1 };
+        self.parameters.as_ref().map_or(0, |p| p.size() - intercept)
+    }
 }
 
 impl SupModel<Matrix<f64>, Vector<f64>> for LinRegressor {
@@ -82,12 +427,21 @@ impl SupModel<Matrix<f64>, Vector<f64>> for LinRegressor {
     ///
     /// lin_mod.train(&inputs, &targets).unwrap();
     /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    /// - The inputs are rank deficient and the solver is not `Solver::Svd`.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
-        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
-        let full_inputs = ones.hcat(inputs);
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
 
-        let xt = full_inputs.transpose();
-        self.parameters = Some((&xt * full_inputs).solve(&xt * targets)?);
+        let (design, standardization) = self.design_matrix(inputs, None);
+
+        let beta = self.solve(design, targets)?;
+        self.parameters = Some(self.restore_scale(beta, &standardization));
         Ok(())
     }
 
@@ -96,6 +450,12 @@ impl SupModel<Matrix<f64>, Vector<f64>> for LinRegressor {
     /// Model must be trained before prediction can be made.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
         if let Some(ref v) = self.parameters {
+            if v.size() != inputs.cols() + 1 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
             let ones = Matrix::<f64>::ones(inputs.rows(), 1);
             let full_inputs = ones.hcat(inputs);
             Ok(full_inputs * v)
@@ -105,6 +465,109 @@ impl SupModel<Matrix<f64>, Vector<f64>> for LinRegressor {
     }
 }
 
+/// Solve the least squares problem `min ‖y - Xβ‖` using Householder QR.
+///
+/// `X` is reduced to upper triangular `R` by Householder reflections,
+/// which are also applied to `y`, and `β` is found by back substitution.
+/// The product `XᵀX` is never formed.
+fn qr_least_squares(x: &Matrix<f64>, y: &Vector<f64>) -> LearningResult<Vector<f64>> {
+    let mut beta = qr_least_squares_multi(x, vec![y.data().clone()])?;
+    Ok(Vector::new(beta.swap_remove(0)))
+}
+
+/// Solve the least squares problem for several targets using Householder QR.
+///
+/// Each entry of `ys` is a target column. The reflections reducing `X` are
+/// applied to every target so `X` is only factorized once. Returns the
+/// solution for each target column.
+fn qr_least_squares_multi(x: &Matrix<f64>, mut ys: Vec<Vec<f64>>) -> LearningResult<Vec<Vec<f64>>> {
+    let (n, p) = (x.rows(), x.cols());
+    if n < p {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The QR solver requires at least as many rows as parameters. \
+                               Use Solver::Svd for the minimum norm solution."));
+    }
+
+    // Work on the columns of X.
+    let mut cols = vec![Vec::with_capacity(n); p];
+    for row in x.row_iter() {
+        for (col, &a) in cols.iter_mut().zip(row.raw_slice()) {
+            col.push(a);
+        }
+    }
+
+    for k in 0..p {
+        let norm = cols[k][k..].iter().map(|a| a * a).sum::<f64>().sqrt();
+        if norm == 0f64 {
+            continue;
+        }
+
+        // The reflection maps the column below the diagonal onto -sign(a_kk)‖a‖e₁.
+        let alpha = if cols[k][k] >= 0f64 { -norm } else { norm };
+        let mut v = cols[k][k..].to_vec();
+        v[0] -= alpha;
+        let v_norm_sq = v.iter().map(|a| a * a).sum::<f64>();
+        if v_norm_sq == 0f64 {
+            continue;
+        }
+
+        for col in cols[k..].iter_mut().chain(ys.iter_mut()) {
+            let dot = v.iter().zip(col[k..].iter()).map(|(a, b)| a * b).sum::<f64>();
+            let scale = 2f64 * dot / v_norm_sq;
+            for (c, a) in col[k..].iter_mut().zip(v.iter()) {
+                *c -= scale * a;
+            }
+        }
+    }
+
+    let max_diag = (0..p).map(|k| cols[k][k].abs()).fold(0f64, |a, b| a.max(b));
+    if (0..p).any(|k| cols[k][k].abs() <= RANK_TOL * max_diag) {
+        return Err(Error::new(ErrorKind::InvalidData,
+                              "The inputs are rank deficient. Use Solver::Svd for the \
+                               minimum norm solution."));
+    }
+
+    let betas = ys.iter()
+        .map(|qty| {
+            let mut beta = vec![0f64; p];
+            for i in (0..p).rev() {
+                let known = (i + 1..p).map(|j| cols[j][i] * beta[j]).sum::<f64>();
+                beta[i] = (qty[i] - known) / cols[i][i];
+            }
+            beta
+        })
+        .collect();
+
+    Ok(betas)
+}
+
+/// Solve the least squares problem `min ‖y - Xβ‖` using the SVD.
+///
+/// Singular values below `RANK_TOL` times the largest are treated as zero,
+/// giving the minimum norm solution. Returns the solution and the number
+/// of singular values kept.
+fn svd_least_squares(x: Matrix<f64>, y: &Vector<f64>) -> LearningResult<(Vector<f64>, usize)> {
+    let (sigma, u, v) = x.svd()?;
+    let sigma = sigma.diag().cloned().collect::<Vec<f64>>();
+    let max_sigma = sigma.iter().fold(0f64, |a, &b| a.max(b));
+
+    let uty = u.transpose() * y;
+    let mut rank = 0;
+    let coefs = sigma.iter()
+        .zip(uty.iter())
+        .map(|(&s, &c)| {
+            if s > RANK_TOL * max_sigma {
+                rank += 1;
+                c / s
+            } else {
+                0f64
+            }
+        })
+        .collect::<Vec<f64>>();
+
+    Ok((v * Vector::new(coefs), rank))
+}
+
 impl Optimizable for LinRegressor {
     type Inputs = Matrix<f64>;
     type Targets = Vector<f64>;
@@ -149,13 +612,590 @@ impl LinRegressor {
     /// let _ = lin_mod.predict(&new_point).unwrap();
     /// ```
     pub fn train_with_optimization(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) {
+        let (design, standardization) = self.design_matrix(inputs, None);
+
+        let initial_params = vec![0.; design.cols()];
+
+        let gd = GradientDesc::default();
+        let optimal_w = gd.optimize(self, &initial_params[..], &design, targets);
+        self.parameters = Some(self.restore_scale(Vector::new(optimal_w), &standardization));
+    }
+
+    /// Update the linear regressor with a batch of data.
+    ///
+    /// Takes a single gradient descent step of size `learning_rate`
+    /// on the mean squared error of the batch. Repeated calls continue
+    /// from the current parameters, so data which does not fit in memory
+    /// can be streamed through the model. The model can be used for
+    /// prediction after any call.
+    ///
+    /// The first call on an untrained model starts from zero parameters
+    /// and fixes the number of input columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::LinRegressor;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::linalg::Vector;
+    ///
+    /// let mut lin_mod = LinRegressor::default();
+    ///
+    /// for _ in 0..100 {
+    ///     let batch = Matrix::new(2, 1, vec![1.0, 2.0]);
+    ///     lin_mod.partial_train(&batch, &Vector::new(vec![3.0, 5.0])).unwrap();
+    /// }
+    ///
+    /// let _ = lin_mod.predict(&Matrix::new(1, 1, vec![3.0])).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The batch is empty.
+    /// - The number of inputs and targets in the batch differ.
+    /// - The batch has a different number of columns to earlier training data.
+    pub fn partial_train(&mut self,
+                         batch_inputs: &Matrix<f64>,
+                         batch_targets: &Vector<f64>)
+                         -> LearningResult<()> {
+        if batch_inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "The batch must not be empty."));
+        }
+
+        if batch_inputs.rows() != batch_targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let n_params = batch_inputs.cols() + 1;
+        if self.parameters.as_ref().map_or(false, |p| p.size() != n_params) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "Inputs must have the same number of columns as the \
+                                   training data."));
+        }
+
+        let mut params = match self.parameters.take() {
+            Some(p) => p.into_vec(),
+            None => vec![0f64; n_params],
+        };
+
+        // Without an intercept the first parameter is fixed at zero.
+        let (design, offset) = if self.fit_intercept {
+            (Matrix::<f64>::ones(batch_inputs.rows(), 1).hcat(batch_inputs), 0)
+        } else {
+            (batch_inputs.clone(), 1)
+        };
+        let (_, grad) = self.compute_grad(&params[offset..], &design, batch_targets);
+
+        for (p, g) in params[offset..].iter_mut().zip(grad) {
+            *p -= g * self.learning_rate;
+        }
+
+        self.rank = None;
+        self.parameters = Some(Vector::new(params));
+        Ok(())
+    }
+
+    /// Train the linear regressor with `partial_train` over shuffled mini-batches.
+    ///
+    /// Each epoch shuffles the rows and passes them to `partial_train` in
+    /// batches of `batch_size`, the last batch holding any remainder.
+    /// Training continues from the current parameters.
+    ///
+    /// # Failures
+    ///
+    /// - `batch_size` is zero.
+    /// - Any of the failures of `partial_train`.
+    pub fn train_epochs(&mut self,
+                        inputs: &Matrix<f64>,
+                        targets: &Vector<f64>,
+                        batch_size: usize,
+                        epochs: usize)
+                        -> LearningResult<()> {
+        if batch_size == 0 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The batch size must be greater than 0."));
+        }
+
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let mut permutation = (0..inputs.rows()).collect::<Vec<usize>>();
+        for _ in 0..epochs {
+            rand_utils::in_place_fisher_yates(&mut permutation);
+
+            for batch in permutation.chunks(batch_size) {
+                let batch_inputs = inputs.select_rows(batch);
+                let batch_targets = batch.iter().map(|&i| targets[i]).collect::<Vec<f64>>();
+                self.partial_train(&batch_inputs, &Vector::new(batch_targets))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Ridge Regression Model.
+///
+/// Linear regression with an L2 penalty on the coefficients. The
+/// parameters minimize `‖y - Xβ‖² + α‖β‖²`, where the intercept is
+/// not penalized. The penalty keeps the problem well posed when
+/// the inputs have collinear columns.
+#[derive(Debug)]
+pub struct RidgeRegressor {
+    /// The regularization strength.
+    alpha: f64,
+    /// The parameters for the regression model.
+    parameters: Option<Vector<f64>>,
+}
+
+/// The default ridge regressor.
+///
+/// The defaults are:
+///
+/// - alpha = 1.0
+impl Default for RidgeRegressor {
+    fn default() -> RidgeRegressor {
+        RidgeRegressor::new(1f64)
+    }
+}
+
+impl RidgeRegressor {
+    /// Construct a ridge regressor with regularization strength `alpha`.
+    ///
+    /// An `alpha` of zero is ordinary least squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::RidgeRegressor;
+    ///
+    /// let ridge = RidgeRegressor::new(0.5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `alpha` is negative.
+    pub fn new(alpha: f64) -> RidgeRegressor {
+        assert!(alpha >= 0f64, "The regularization strength (alpha) must be non-negative.");
+
+        RidgeRegressor {
+            alpha: alpha,
+            parameters: None,
+        }
+    }
+
+    /// Get the regularization strength.
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Get the parameters from the model.
+    ///
+    /// The first parameter is the intercept.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn parameters(&self) -> Option<&Vector<f64>> {
+        self.parameters.as_ref()
+    }
+}
+
+impl Parametric for RidgeRegressor {
+    /// The number of regression coefficients, including the intercept.
+    ///
+    /// An untrained model reports zero.
+    fn n_parameters(&self) -> usize {
+        self.parameters.as_ref().map_or(0, |p| p.size())
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<f64>> for RidgeRegressor {
+    /// Train the ridge regression model.
+    ///
+    /// Solves `(XᵀX + αI)β = Xᵀy`, with a zero penalty on the intercept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::RidgeRegressor;
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::linalg::Vector;
+    /// use rusty_machine::learning::SupModel;
+    ///
+    /// // The two columns are identical.
+    /// let inputs = Matrix::new(3, 2, vec![2.0, 2.0, 3.0, 3.0, 4.0, 4.0]);
+    /// let targets = Vector::new(vec![5.0, 6.0, 7.0]);
+    ///
+    /// let mut ridge = RidgeRegressor::new(0.1);
+    /// ridge.train(&inputs, &targets).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
         let ones = Matrix::<f64>::ones(inputs.rows(), 1);
         let full_inputs = ones.hcat(inputs);
 
-        let initial_params = vec![0.; full_inputs.cols()];
+        let mut penalty = vec![self.alpha; full_inputs.cols()];
+        penalty[0] = 0f64;
 
-        let gd = GradientDesc::default();
-        let optimal_w = gd.optimize(self, &initial_params[..], &full_inputs, targets);
-        self.parameters = Some(Vector::new(optimal_w));
+        let xt = full_inputs.transpose();
+        let gram = &xt * full_inputs + Matrix::from_diag(&penalty);
+        self.parameters = Some(gram.solve(&xt * targets)?);
+        Ok(())
+    }
+
+    /// Predict output value from input data.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(ref v) = self.parameters {
+            if v.size() != inputs.cols() + 1 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+            let full_inputs = ones.hcat(inputs);
+            Ok(full_inputs * v)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+/// Huber Regression Model.
+///
+/// Linear regression which is robust to outliers. The parameters minimize
+/// the Huber loss of the residuals, which is quadratic for residuals up to
+/// `delta` and linear beyond. The loss is minimized by iteratively
+/// reweighted least squares: points with residuals larger than `delta`
+/// are given the weight `delta / |r|` and the weighted problem is solved
+/// again until the parameters converge.
+///
+/// By default `delta` is scaled at each iteration by a robust estimate
+/// of the residual scale, the median absolute deviation divided by `0.6745`.
+#[derive(Debug)]
+pub struct HuberRegressor {
+    /// The residual size at which the loss becomes linear.
+    delta: f64,
+    /// Whether `delta` is scaled by the residual scale.
+    scale_delta: bool,
+    /// The maximum number of reweighting iterations.
+    iters: usize,
+    /// The parameters for the regression model.
+    parameters: Option<Vector<f64>>,
+    /// The weights of the training points from the final iteration.
+    weights: Option<Vector<f64>>,
+}
+
+/// The default Huber regressor.
+///
+/// The defaults are:
+///
+/// - delta = 1.345
+/// - scale_delta = true
+/// - iters = 100
+impl Default for HuberRegressor {
+    fn default() -> HuberRegressor {
+        HuberRegressor::new(1.345)
+    }
+}
+
+impl HuberRegressor {
+    /// Construct a Huber regressor with the given transition point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::HuberRegressor;
+    ///
+    /// let huber = HuberRegressor::new(2.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `delta` is not positive.
+    pub fn new(delta: f64) -> HuberRegressor {
+        assert!(delta > 0f64, "The transition point (delta) must be positive.");
+
+        HuberRegressor {
+            delta: delta,
+            scale_delta: true,
+            iters: 100,
+            parameters: None,
+            weights: None,
+        }
+    }
+
+    /// Get the transition point.
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    /// Set whether `delta` is scaled by the median absolute
+    /// deviation of the residuals at each iteration.
+    ///
+    /// Without scaling `delta` is in the units of the targets.
+    pub fn set_scale_delta(&mut self, scale_delta: bool) {
+        self.scale_delta = scale_delta;
+    }
+
+    /// Set the maximum number of reweighting iterations.
+    pub fn set_iters(&mut self, iters: usize) {
+        self.iters = iters;
+    }
+
+    /// Get the parameters from the model.
+    ///
+    /// The first parameter is the intercept.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn parameters(&self) -> Option<&Vector<f64>> {
+        self.parameters.as_ref()
+    }
+
+    /// Get the weights given to the training points in the final fit.
+    ///
+    /// Points within `delta` of the fit have weight one,
+    /// downweighted points have smaller weights.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn weights(&self) -> Option<&Vector<f64>> {
+        self.weights.as_ref()
+    }
+}
+
+/// Compute the median of the values.
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) / 2f64
+    }
+}
+
+impl Parametric for HuberRegressor {
+    /// The number of regression coefficients, including the intercept.
+    ///
+    /// An untrained model reports zero.
+    fn n_parameters(&self) -> usize {
+        self.parameters.as_ref().map_or(0, |p| p.size())
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<f64>> for HuberRegressor {
+    /// Train the Huber regression model.
+    ///
+    /// The model starts from the ordinary least squares fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::HuberRegressor;
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::linalg::Vector;
+    /// use rusty_machine::learning::SupModel;
+    ///
+    /// // The fourth target is an outlier.
+    /// let inputs = Matrix::new(8, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    /// let targets = Vector::new(vec![2.1, 3.9, 6.0, 30.0, 10.1, 11.8, 14.2, 15.9]);
+    ///
+    /// let mut huber = HuberRegressor::default();
+    /// huber.train(&inputs, &targets).unwrap();
+    ///
+    /// let weights = huber.weights().unwrap();
+    /// assert!(weights[3] < 0.5);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    /// - The inputs are rank deficient.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
+        let mut lin_mod = LinRegressor::default();
+        let mut weights = Vector::ones(inputs.rows());
+        lin_mod.train_weighted(inputs, targets, &weights)?;
+
+        for _ in 0..self.iters {
+            let residuals = targets - lin_mod.predict(inputs)?;
+
+            let threshold = if self.scale_delta {
+                let center = median(residuals.data().clone());
+                let mad = median(residuals.iter().map(|r| (r - center).abs()).collect());
+
+                // The residuals are (almost) all exactly zero.
+                if mad == 0f64 {
+                    break;
+                }
+                self.delta * mad / MAD_NORMALIZER
+            } else {
+                self.delta
+            };
+
+            weights = residuals.apply(&|r: f64| {
+                if r.abs() <= threshold { 1f64 } else { threshold / r.abs() }
+            });
+
+            let old_params = lin_mod.parameters().cloned().unwrap();
+            lin_mod.train_weighted(inputs, targets, &weights)?;
+            let params = lin_mod.parameters().unwrap();
+
+            let change = params.iter()
+                .zip(old_params.iter())
+                .fold(0f64, |m, (a, b)| m.max((a - b).abs()));
+            let size = params.iter().fold(0f64, |m, a| m.max(a.abs()));
+            if change < HUBER_TOL * (1f64 + size) {
+                break;
+            }
+        }
+
+        self.parameters = lin_mod.parameters().cloned();
+        self.weights = Some(weights);
+        Ok(())
+    }
+
+    /// Predict output value from input data.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(ref v) = self.parameters {
+            if v.size() != inputs.cols() + 1 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+            let full_inputs = ones.hcat(inputs);
+            Ok(full_inputs * v)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+/// Multi-output Linear Regression Model.
+///
+/// Fits an independent linear regression to each column of the targets.
+/// The inputs are factorized once with a Householder QR decomposition and
+/// every target column is solved against the same factorization, so the
+/// parameters match those of a `LinRegressor` trained on each column.
+#[derive(Debug, Default)]
+pub struct MultiLinRegressor {
+    /// The parameters for the regression model, one column per output.
+    parameters: Option<Matrix<f64>>,
+}
+
+impl MultiLinRegressor {
+    /// Get the parameters from the model.
+    ///
+    /// The matrix has one row per input column plus the intercept,
+    /// which is the first row, and one column per output.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn parameters(&self) -> Option<&Matrix<f64>> {
+        self.parameters.as_ref()
+    }
+}
+
+impl Parametric for MultiLinRegressor {
+    /// The number of regression coefficients over all outputs,
+    /// including the intercepts.
+    ///
+    /// An untrained model reports zero.
+    fn n_parameters(&self) -> usize {
+        self.parameters.as_ref().map_or(0, |p| p.rows() * p.cols())
+    }
+}
+
+impl SupModel<Matrix<f64>, Matrix<f64>> for MultiLinRegressor {
+    /// Train the multi-output linear regression model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::lin_reg::MultiLinRegressor;
+    /// use rusty_machine::linalg::Matrix;
+    /// use rusty_machine::learning::SupModel;
+    ///
+    /// let inputs = Matrix::new(4, 1, vec![1.0, 2.0, 3.0, 4.0]);
+    /// let targets = Matrix::new(4, 2, vec![3.0, -1.0,
+    ///                                      5.0, -2.0,
+    ///                                      7.0, -3.0,
+    ///                                      9.0, -4.0]);
+    ///
+    /// let mut lin_mod = MultiLinRegressor::default();
+    /// lin_mod.train(&inputs, &targets).unwrap();
+    ///
+    /// // One row per input column plus the intercept.
+    /// let params = lin_mod.parameters().unwrap();
+    /// assert_eq!(params.rows(), 2);
+    /// assert_eq!(params.cols(), 2);
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of input and target rows differ.
+    /// - The inputs are rank deficient.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
+        if inputs.rows() != targets.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        let mut ys = vec![Vec::with_capacity(targets.rows()); targets.cols()];
+        for row in targets.row_iter() {
+            for (y, &t) in ys.iter_mut().zip(row.raw_slice()) {
+                y.push(t);
+            }
+        }
+
+        let betas = qr_least_squares_multi(&full_inputs, ys)?;
+
+        let (p, m) = (full_inputs.cols(), targets.cols());
+        let mut data = Vec::with_capacity(p * m);
+        for i in 0..p {
+            data.extend(betas.iter().map(|beta| beta[i]));
+        }
+
+        self.parameters = Some(Matrix::new(p, m, data));
+        Ok(())
+    }
+
+    /// Predict output values from input data.
+    ///
+    /// Returns a matrix with one column per output.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if let Some(ref params) = self.parameters {
+            if params.rows() != inputs.cols() + 1 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+            let full_inputs = ones.hcat(inputs);
+            Ok(full_inputs * params)
+        } else {
+            Err(Error::new_untrained())
+        }
     }
 }
@@ -27,21 +27,124 @@
 //! let output = log_mod.predict(&new_point).unwrap();
 //!
 //! // Hopefully we classified our new point correctly!
-//! assert!(output[0] > 0.5, "Our classifier isn't very good!");
+//! assert!(output[0] == 1.0, "Our classifier isn't very good!");
 //! ```
 //!
+//! The `predict` function returns hard `0` or `1` labels by thresholding
+//! the class probabilities. The probabilities themselves are available
+//! from `predict_proba`, and the linear scores before the sigmoid from
+//! `decision_function`.
+//!
 //! We could have been more specific about the learning of the model
 //! by using the `new` constructor instead. This allows us to provide
 //! a `GradientDesc` object with custom parameters.
+//!
+//! # Convergence
+//!
+//! The cost at each iteration of the last training run is available from
+//! `training_cost_history`. Training can be stopped before the optimization
+//! algorithm's iteration limit by setting a tolerance with `set_tolerance`.
+//! The optimization then stops once the relative decrease in the cost over
+//! the last `patience` iterations falls below the tolerance, and `converged`
+//! reports whether this happened.
+//!
+//! # Warm starts and mini-batches
+//!
+//! By default every call to `train` starts from the same initial
+//! parameters. With `set_warm_start` training starts from the parameters
+//! of the previous run instead, so retraining after a few rows are added
+//! takes far fewer iterations. The `train_epochs` function trains with
+//! stochastic gradient descent over shuffled mini-batches.
+//!
+//! # Sparse inputs
+//!
+//! Very sparse data, such as bag of words features, can be trained and
+//! predicted without a dense matrix using `train_sparse` and
+//! `predict_sparse`, which take a `CsrMatrix` from the `linalg::sparse`
+//! module.
+//!
+//! # Class weights
+//!
+//! When one class is much rarer than the other the model may learn to
+//! always predict the common class. The `set_class_weight` function scales
+//! the contribution of each sample to the cost by a weight depending on
+//! its class, so that mistakes on the rare class cost more.
+//!
+//! Weighting the classes changes the probabilities returned by
+//! `predict_proba`. They are no longer calibrated estimates of the
+//! class frequencies and will overestimate the probability of any
+//! class whose weight has been increased.
+//!
+//! # Multiple classes
+//!
+//! The `SoftmaxRegressor` generalizes logistic regression to any number
+//! of classes. It learns a column of parameters for each class and
+//! predicts the class with the largest softmax probability.
+//!
+//! ```
+//! use rusty_machine::learning::logistic_reg::SoftmaxRegressor;
+//! use rusty_machine::learning::SupModel;
+//! use rusty_machine::linalg::{Matrix, Vector};
+//!
+//! let inputs = Matrix::new(6, 1, vec![1.0, 1.5, 4.0, 4.5, 7.0, 7.5]);
+//! let targets = Vector::new(vec![0, 0, 1, 1, 2, 2]);
+//!
+//! let mut softmax_mod = SoftmaxRegressor::default();
+//! softmax_mod.train(&inputs, &targets).unwrap();
+//!
+//! // Each row of probabilities sums to one.
+//! let probs = softmax_mod.predict_proba(&inputs).unwrap();
+//! let classes = softmax_mod.predict(&inputs).unwrap();
+//! ```
 
 use linalg::{Matrix, BaseMatrix};
 use linalg::Vector;
+use linalg::sparse::CsrMatrix;
 use learning::{LearningResult, SupModel};
 use learning::toolkit::activ_fn::{ActivationFunc, Sigmoid};
 use learning::toolkit::cost_fn::{CostFunc, CrossEntropyError};
-use learning::optim::grad_desc::GradientDesc;
+use learning::optim::grad_desc::{GradientDesc, StochasticGD};
 use learning::optim::{OptimAlgorithm, Optimizable};
-use learning::error::Error;
+use learning::error::{Error, ErrorKind};
+
+use rand::Rng;
+
+use std::cell::{Cell, RefCell};
+use std::f64;
+use std::mem;
+
+/// The weighting of the classes in a `LogisticRegressor`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClassWeight {
+    /// Weights inversely proportional to the class frequencies
+    /// in the training targets.
+    ///
+    /// A class with `n_c` of the `n` training samples has weight
+    /// `n / (2 n_c)`.
+    Balanced,
+    /// The weights of the negative and positive classes, in that order.
+    Custom(f64, f64),
+}
+
+impl ClassWeight {
+    /// Compute the weight of each sample from its target.
+    fn sample_weights(&self, targets: &Vector<f64>) -> Vector<f64> {
+        let (neg_weight, pos_weight) = match *self {
+            ClassWeight::Balanced => {
+                let n = targets.size() as f64;
+                let n_pos = targets.sum();
+                let n_neg = n - n_pos;
+
+                // A class without samples does not contribute to the cost.
+                let weight = |n_c: f64| if n_c > 0f64 { n / (2f64 * n_c) } else { 1f64 };
+                (weight(n_neg), weight(n_pos))
+            }
+            ClassWeight::Custom(neg_weight, pos_weight) => (neg_weight, pos_weight),
+        };
+
+        targets.clone().apply(&|y| y * pos_weight + (1f64 - y) * neg_weight)
+    }
+}
 
 /// Logistic Regression Model.
 ///
@@ -52,16 +155,20 @@ pub struct LogisticRegressor<A>
 {
     base: BaseLogisticRegressor,
     alg: A,
+    threshold: f64,
+    class_weight: Option<ClassWeight>,
+    cost_history: Vec<f64>,
+    converged: bool,
+    warm_start: bool,
+    learning_rate: f64,
+    momentum: f64,
 }
 
 /// Constructs a default Logistic Regression model
-/// using standard gradient descent.
+/// using standard gradient descent and a threshold of `0.5`.
 impl Default for LogisticRegressor<GradientDesc> {
     fn default() -> LogisticRegressor<GradientDesc> {
-        LogisticRegressor {
-            base: BaseLogisticRegressor::new(),
-            alg: GradientDesc::default(),
-        }
+        LogisticRegressor::new(GradientDesc::default())
     }
 }
 
@@ -81,6 +188,13 @@ impl<A: OptimAlgorithm<BaseLogisticRegressor>> LogisticRegressor<A> {
         LogisticRegressor {
             base: BaseLogisticRegressor::new(),
             alg: alg,
+            threshold: 0.5,
+            class_weight: None,
+            cost_history: Vec::new(),
+            converged: false,
+            warm_start: false,
+            learning_rate: 0.1,
+            momentum: 0.1,
         }
     }
 
@@ -90,6 +204,358 @@ impl<A: OptimAlgorithm<BaseLogisticRegressor>> LogisticRegressor<A> {
     pub fn parameters(&self) -> Option<&Vector<f64>> {
         self.base.parameters()
     }
+
+    /// Get the probability above which `predict` returns the positive class.
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Set the probability above which `predict` returns the positive class.
+    ///
+    /// # Panics
+    ///
+    /// - `threshold` is not between 0 and 1.
+    pub fn set_threshold(&mut self, threshold: f64) {
+        assert!(threshold >= 0f64 && threshold <= 1f64,
+                "The threshold must be between 0 and 1.");
+        self.threshold = threshold;
+    }
+
+    /// Get the class weights used in training.
+    ///
+    /// Returns `None` if the classes are not weighted.
+    pub fn class_weight(&self) -> Option<ClassWeight> {
+        self.class_weight
+    }
+
+    /// Set the class weights used in training.
+    ///
+    /// The weights are computed from the targets each time the model is
+    /// trained and are used by whichever optimization algorithm is chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::logistic_reg::{ClassWeight, LogisticRegressor};
+    ///
+    /// let mut logistic_mod = LogisticRegressor::default();
+    /// logistic_mod.set_class_weight(ClassWeight::Balanced);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - A `Custom` weight is negative.
+    pub fn set_class_weight(&mut self, class_weight: ClassWeight) {
+        if let ClassWeight::Custom(neg_weight, pos_weight) = class_weight {
+            assert!(neg_weight >= 0f64 && pos_weight >= 0f64,
+                    "The class weights must be non-negative.");
+        }
+        self.class_weight = Some(class_weight);
+    }
+
+    /// Get the tolerance used to stop training early.
+    ///
+    /// Returns `None` if training is not stopped early.
+    pub fn tolerance(&self) -> Option<f64> {
+        self.base.tolerance
+    }
+
+    /// Stop training once the relative decrease in cost over the
+    /// last `patience` iterations is less than `tolerance`.
+    ///
+    /// Training is not stopped early by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    ///
+    /// let mut logistic_mod = LogisticRegressor::default();
+    /// logistic_mod.set_tolerance(1e-6);
+    /// logistic_mod.set_patience(10);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `tolerance` is negative.
+    pub fn set_tolerance(&mut self, tolerance: f64) {
+        assert!(tolerance >= 0f64, "The tolerance must be non-negative.");
+        self.base.tolerance = Some(tolerance);
+    }
+
+    /// Get the number of iterations over which the decrease in cost
+    /// is compared to the tolerance.
+    pub fn patience(&self) -> usize {
+        self.base.patience
+    }
+
+    /// Set the number of iterations over which the decrease in cost
+    /// is compared to the tolerance. The default is `5`.
+    ///
+    /// # Panics
+    ///
+    /// - `patience` is zero.
+    pub fn set_patience(&mut self, patience: usize) {
+        assert!(patience > 0, "The patience must be greater than 0.");
+        self.base.patience = patience;
+    }
+
+    /// The cost at each iteration of the last training run.
+    ///
+    /// The history is empty if the model has not been trained.
+    pub fn training_cost_history(&self) -> &[f64] {
+        &self.cost_history
+    }
+
+    /// Whether the last training run was stopped early
+    /// because the cost converged.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// Get whether training starts from the current parameters.
+    pub fn warm_start(&self) -> bool {
+        self.warm_start
+    }
+
+    /// Set whether training starts from the current parameters.
+    ///
+    /// When the model has been trained before and `warm_start` is true
+    /// the optimization starts from the existing parameters. The inputs
+    /// must then have the same number of columns as the earlier data.
+    pub fn set_warm_start(&mut self, warm_start: bool) {
+        self.warm_start = warm_start;
+    }
+
+    /// Get the step size used by `train_epochs`.
+    pub fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    /// Set the step size used by `train_epochs`. The default is `0.1`.
+    ///
+    /// # Panics
+    ///
+    /// - `learning_rate` is not positive.
+    pub fn set_learning_rate(&mut self, learning_rate: f64) {
+        assert!(learning_rate > 0f64, "The learning rate must be positive.");
+        self.learning_rate = learning_rate;
+    }
+
+    /// Get the momentum used by `train_epochs`.
+    pub fn momentum(&self) -> f64 {
+        self.momentum
+    }
+
+    /// Set the momentum used by `train_epochs`. The default is `0.1`.
+    ///
+    /// # Panics
+    ///
+    /// - `momentum` is not positive.
+    pub fn set_momentum(&mut self, momentum: f64) {
+        assert!(momentum > 0f64, "The momentum must be positive.");
+        self.momentum = momentum;
+    }
+
+    /// Train the model with stochastic gradient descent over mini-batches.
+    ///
+    /// The parameters are optimized by `StochasticGD` with step size
+    /// `learning_rate` and momentum `momentum`, running `epochs` passes
+    /// over batches of `batch_size` rows, the last batch holding any
+    /// remainder. The rows are shuffled with a seed drawn from `rng`.
+    /// The training cost history holds the mean cost of the batches in
+    /// each epoch, and training stops early as in `set_tolerance`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    /// use rand::thread_rng;
+    ///
+    /// # fn main() {
+    /// let inputs = Matrix::new(4, 1, vec![1.0, 3.0, 5.0, 7.0]);
+    /// let targets = Vector::new(vec![0., 0., 1., 1.]);
+    ///
+    /// let mut logistic_mod = LogisticRegressor::default();
+    /// logistic_mod.train_epochs(&inputs, &targets, 50, 2, &mut thread_rng()).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - `batch_size` is zero.
+    /// - The number of inputs and targets differ.
+    /// - Warm starting with inputs of a different number of columns.
+    pub fn train_epochs<R: Rng>(&mut self,
+                                inputs: &Matrix<f64>,
+                                targets: &Vector<f64>,
+                                epochs: usize,
+                                batch_size: usize,
+                                rng: &mut R)
+                                -> LearningResult<()> {
+        if batch_size == 0 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The batch size must be greater than 0."));
+        }
+
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+        let params = self.initial_params(full_inputs.cols())?;
+
+        self.start_training(targets);
+
+        // Keep the sample weights next to the targets so they follow the rows into each batch.
+        let weighted_targets = match self.base.sample_weights {
+            Some(ref w) => {
+                targets.iter().zip(w.iter()).flat_map(|(&t, &w)| vec![t, w]).collect::<Vec<f64>>()
+            }
+            None => targets.iter().flat_map(|&t| vec![t, 1f64]).collect::<Vec<f64>>(),
+        };
+        let weighted_targets = Matrix::new(targets.size(), 2, weighted_targets);
+
+        let mut sgd = StochasticGD::new(self.momentum, self.learning_rate, epochs);
+        sgd.set_batch_size(batch_size);
+        sgd.set_seed(rng.gen());
+
+        let params = sgd.optimize(&MiniBatchLogisticRegressor { base: &self.base },
+                                  &params,
+                                  &full_inputs,
+                                  &weighted_targets);
+        self.finish_training(params);
+        Ok(())
+    }
+
+    /// Compute the linear scores `Xβ` of the inputs, before the sigmoid.
+    ///
+    /// The model must be trained.
+    pub fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        if let Some(v) = self.base.parameters() {
+            if v.size() != inputs.cols() + 1 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+            let full_inputs = ones.hcat(inputs);
+            Ok(full_inputs * v)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// Predict the probability of the positive class for the inputs.
+    ///
+    /// The model must be trained.
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        Ok(self.decision_function(inputs)?.apply(&Sigmoid::func))
+    }
+
+    /// Train the model using sparse inputs.
+    ///
+    /// This is equivalent to `train` on the dense inputs but never
+    /// builds a dense matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    /// use rusty_machine::linalg::Vector;
+    /// use rusty_machine::linalg::sparse::CsrMatrix;
+    ///
+    /// let inputs = CsrMatrix::from_triplets(4, 3, &[(0, 0, 1.0), (1, 0, 1.0),
+    ///                                               (2, 2, 1.0), (3, 2, 1.0)]).unwrap();
+    /// let targets = Vector::new(vec![0., 0., 1., 1.]);
+    ///
+    /// let mut logistic_mod = LogisticRegressor::default();
+    /// logistic_mod.train_sparse(&inputs, &targets).unwrap();
+    ///
+    /// let classes = logistic_mod.predict_sparse(&inputs).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    pub fn train_sparse(&mut self, inputs: &CsrMatrix, targets: &Vector<f64>) -> LearningResult<()>
+        where A: OptimAlgorithm<BaseSparseLogisticRegressor>
+    {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let initial_params = self.initial_params(inputs.cols() + 1)?;
+
+        self.start_training(targets);
+        let objective = BaseSparseLogisticRegressor {
+            base: mem::replace(&mut self.base, BaseLogisticRegressor::new()),
+        };
+        let optimal_w = self.alg.optimize(&objective, &initial_params[..], inputs, targets);
+        self.base = objective.base;
+        self.finish_training(optimal_w);
+        Ok(())
+    }
+
+    /// Predict the class of sparse inputs.
+    ///
+    /// The model must be trained.
+    pub fn predict_sparse(&self, inputs: &CsrMatrix) -> LearningResult<Vector<f64>> {
+        if let Some(v) = self.base.parameters() {
+            if v.size() != inputs.cols() + 1 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let threshold = self.threshold;
+            Ok(sparse_scores(inputs, v.data())
+                .apply(&Sigmoid::func)
+                .apply(&|p| if p > threshold { 1f64 } else { 0f64 }))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// The parameters to start the optimization from.
+    ///
+    /// These are the current parameters when warm starting
+    /// a trained model.
+    fn initial_params(&self, n_params: usize) -> LearningResult<Vec<f64>> {
+        match self.base.parameters() {
+            Some(v) if self.warm_start => {
+                if v.size() != n_params {
+                    Err(Error::new(ErrorKind::InvalidData,
+                                   "Inputs must have the same number of columns as the \
+                                    warm start parameters."))
+                } else {
+                    Ok(v.data().clone())
+                }
+            }
+            _ => Ok(vec![0.5; n_params]),
+        }
+    }
+
+    /// Prepare the base model for the optimization.
+    fn start_training(&mut self, targets: &Vector<f64>) {
+        self.base.sample_weights = self.class_weight.map(|w| w.sample_weights(targets));
+        self.base.cost_history.borrow_mut().clear();
+        self.base.converged.set(false);
+    }
+
+    /// Store the optimized parameters and the optimization history.
+    fn finish_training(&mut self, params: Vec<f64>) {
+        self.base.set_parameters(Vector::new(params));
+        self.cost_history = self.base.cost_history.replace(Vec::new());
+        self.converged = self.base.converged.get();
+    }
 }
 
 impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
@@ -117,24 +583,23 @@ impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
         let ones = Matrix::<f64>::ones(inputs.rows(), 1);
         let full_inputs = ones.hcat(inputs);
 
-        let initial_params = vec![0.5; full_inputs.cols()];
+        let initial_params = self.initial_params(full_inputs.cols())?;
 
+        self.start_training(targets);
         let optimal_w = self.alg.optimize(&self.base, &initial_params[..], &full_inputs, targets);
-        self.base.set_parameters(Vector::new(optimal_w));
+        self.finish_training(optimal_w);
         Ok(())
     }
 
-    /// Predict output value from input data.
+    /// Predict the class of the inputs.
+    ///
+    /// Returns `1` where the probability of the positive class
+    /// is greater than the threshold and `0` otherwise.
     ///
     /// Model must be trained before prediction can be made.
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
-        if let Some(v) = self.base.parameters() {
-            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
-            let full_inputs = ones.hcat(inputs);
-            Ok((full_inputs * v).apply(&Sigmoid::func))
-        } else {
-            Err(Error::new_untrained())
-        }
+        let threshold = self.threshold;
+        Ok(self.predict_proba(inputs)?.apply(&|p| if p > threshold { 1f64 } else { 0f64 }))
     }
 }
 
@@ -144,13 +609,26 @@ impl<A> SupModel<Matrix<f64>, Vector<f64>> for LogisticRegressor<A>
 #[derive(Debug)]
 pub struct BaseLogisticRegressor {
     parameters: Option<Vector<f64>>,
+    sample_weights: Option<Vector<f64>>,
+    tolerance: Option<f64>,
+    patience: usize,
+    /// The costs reported by the optimization algorithm.
+    cost_history: RefCell<Vec<f64>>,
+    converged: Cell<bool>,
 }
 
 impl BaseLogisticRegressor {
     /// Construct a new BaseLogisticRegressor
     /// with parameters set to None.
     fn new() -> BaseLogisticRegressor {
-        BaseLogisticRegressor { parameters: None }
+        BaseLogisticRegressor {
+            parameters: None,
+            sample_weights: None,
+            tolerance: None,
+            patience: 5,
+            cost_history: RefCell::new(Vec::new()),
+            converged: Cell::new(false),
+        }
     }
 }
 
@@ -164,6 +642,35 @@ impl BaseLogisticRegressor {
     fn set_parameters(&mut self, params: Vector<f64>) {
         self.parameters = Some(params);
     }
+
+    /// Compute the cost of the outputs and the residuals `h(Xb) - y`,
+    /// scaled by the sample weights if there are any.
+    fn cost_and_residuals(&self,
+                          outputs: Vector<f64>,
+                          targets: &Vector<f64>)
+                          -> (f64, Vector<f64>) {
+        weighted_cost_and_residuals(outputs, targets, self.sample_weights.as_ref())
+    }
+}
+
+/// Compute the cost of the outputs and the residuals `h(Xb) - y`,
+/// scaled by the given sample weights if there are any.
+fn weighted_cost_and_residuals(outputs: Vector<f64>,
+                               targets: &Vector<f64>,
+                               sample_weights: Option<&Vector<f64>>)
+                               -> (f64, Vector<f64>) {
+    match sample_weights {
+        Some(weights) => {
+            let cost = -outputs.iter()
+                .zip(targets.iter())
+                .zip(weights.iter())
+                .map(|((h, y), w)| w * (y * h.ln() + (1f64 - y) * (1f64 - h).ln()))
+                .sum::<f64>() / (outputs.size() as f64);
+
+            (cost, (outputs - targets).elemul(weights))
+        }
+        None => (CrossEntropyError::cost(&outputs, targets), outputs - targets),
+    }
 }
 
 /// Computing the gradient of the underlying Logistic
@@ -174,6 +681,9 @@ impl BaseLogisticRegressor {
 /// X<sup>T</sup>(h(Xb) - y) / m
 ///
 /// where `h` is the sigmoid function and `b` the underlying model parameters.
+///
+/// When the classes are weighted each sample's contribution
+/// to the cost and gradient is scaled by its weight.
 impl Optimizable for BaseLogisticRegressor {
     type Inputs = Matrix<f64>;
     type Targets = Vector<f64>;
@@ -187,9 +697,805 @@ impl Optimizable for BaseLogisticRegressor {
         let beta_vec = Vector::new(params.to_vec());
         let outputs = (inputs * beta_vec).apply(&Sigmoid::func);
 
-        let cost = CrossEntropyError::cost(&outputs, targets);
-        let grad = (inputs.transpose() * (outputs - targets)) / (inputs.rows() as f64);
+        let (cost, residuals) = self.cost_and_residuals(outputs, targets);
+        let grad = (inputs.transpose() * residuals) / (inputs.rows() as f64);
+
+        (cost, grad.into_vec())
+    }
+
+    /// Records the cost and stops once the relative decrease
+    /// over the patience window is below the tolerance.
+    fn end_iteration(&self, _iter: usize, cost: f64) -> bool {
+        let mut history = self.cost_history.borrow_mut();
+        history.push(cost);
+
+        if let Some(tolerance) = self.tolerance {
+            let n = history.len();
+            if n > self.patience {
+                let prev = history[n - 1 - self.patience];
+                let decrease = if prev == 0f64 { 0f64 } else { (prev - cost) / prev.abs() };
+
+                if decrease < tolerance {
+                    self.converged.set(true);
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// The Logistic Regression model trained on mini-batches by `train_epochs`.
+///
+/// The first column of the targets holds the target of each row and
+/// the second column its sample weight.
+struct MiniBatchLogisticRegressor<'a> {
+    base: &'a BaseLogisticRegressor,
+}
+
+impl<'a> Optimizable for MiniBatchLogisticRegressor<'a> {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self,
+                    params: &[f64],
+                    inputs: &Matrix<f64>,
+                    targets: &Matrix<f64>)
+                    -> (f64, Vec<f64>) {
+        let beta_vec = Vector::new(params.to_vec());
+        let outputs = (inputs * beta_vec).apply(&Sigmoid::func);
+
+        let column = |j: usize| {
+            Vector::new(targets.data().chunks(2).map(|t| t[j]).collect::<Vec<f64>>())
+        };
+        let weights = self.base.sample_weights.as_ref().map(|_| column(1));
+
+        let (cost, residuals) = weighted_cost_and_residuals(outputs, &column(0), weights.as_ref());
+        let grad = (inputs.transpose() * residuals) / (inputs.rows() as f64);
+
+        (cost, grad.into_vec())
+    }
+
+    fn end_iteration(&self, iter: usize, cost: f64) -> bool {
+        self.base.end_iteration(iter, cost)
+    }
+}
+
+/// The Logistic Regression model trained on sparse inputs.
+///
+/// This struct cannot be instantianated and is used internally only.
+#[derive(Debug)]
+pub struct BaseSparseLogisticRegressor {
+    base: BaseLogisticRegressor,
+}
+
+/// Compute the scores `b₀ + Xb` of sparse inputs, where the
+/// first parameter is the intercept.
+fn sparse_scores(inputs: &CsrMatrix, params: &[f64]) -> Vector<f64> {
+    inputs.mul_vec(&Vector::new(params[1..].to_vec())) + params[0]
+}
+
+/// Computing the gradient of the underlying Logistic
+/// Regression model on sparse inputs.
+///
+/// The intercept is handled separately so that the inputs
+/// never need a column of ones.
+impl Optimizable for BaseSparseLogisticRegressor {
+    type Inputs = CsrMatrix;
+    type Targets = Vector<f64>;
+
+    fn compute_grad(&self,
+                    params: &[f64],
+                    inputs: &CsrMatrix,
+                    targets: &Vector<f64>)
+                    -> (f64, Vec<f64>) {
+        let outputs = sparse_scores(inputs, params).apply(&Sigmoid::func);
+
+        let (cost, residuals) = self.base.cost_and_residuals(outputs, targets);
+        let n = inputs.rows() as f64;
+
+        let mut grad = Vec::with_capacity(params.len());
+        grad.push(residuals.sum() / n);
+        grad.extend(inputs.transpose_mul_vec(&residuals).into_vec().into_iter().map(|g| g / n));
+
+        (cost, grad)
+    }
+
+    fn end_iteration(&self, iter: usize, cost: f64) -> bool {
+        self.base.end_iteration(iter, cost)
+    }
+}
+
+/// Multinomial Logistic Regression Model.
+///
+/// The model has a column of parameters for each class, including the
+/// intercept in the first row. The probability of each class is the
+/// softmax of the class scores `Xβ`, and the parameters minimize the
+/// cross entropy of these probabilities.
+///
+/// The classes are the distinct labels of the training targets, in
+/// increasing order. Labels do not need to be contiguous: training on
+/// the labels `{0, 2}` gives two classes, with the first column of the
+/// parameters and probabilities for `0` and the second for `2`. This
+/// mapping is available from `classes`, and `predict` always returns
+/// one of the training labels.
+#[derive(Debug)]
+pub struct SoftmaxRegressor<A>
+    where A: OptimAlgorithm<BaseSoftmaxRegressor>
+{
+    base: BaseSoftmaxRegressor,
+    alg: A,
+    classes: Option<Vec<usize>>,
+}
+
+/// Constructs a default Softmax Regression model
+/// using standard gradient descent.
+impl Default for SoftmaxRegressor<GradientDesc> {
+    fn default() -> SoftmaxRegressor<GradientDesc> {
+        SoftmaxRegressor::new(GradientDesc::default())
+    }
+}
+
+impl<A: OptimAlgorithm<BaseSoftmaxRegressor>> SoftmaxRegressor<A> {
+    /// Constructs untrained softmax regression model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::logistic_reg::SoftmaxRegressor;
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    ///
+    /// let gd = GradientDesc::new(0.1, 1000);
+    /// let mut softmax_mod = SoftmaxRegressor::new(gd);
+    /// ```
+    pub fn new(alg: A) -> SoftmaxRegressor<A> {
+        SoftmaxRegressor {
+            base: BaseSoftmaxRegressor::new(),
+            alg: alg,
+            classes: None,
+        }
+    }
+
+    /// Get the parameters from the model.
+    ///
+    /// The matrix has one row per input column plus the intercept,
+    /// which is the first row, and one column per class.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn parameters(&self) -> Option<&Matrix<f64>> {
+        self.base.parameters()
+    }
+
+    /// Get the class labels corresponding to the parameter
+    /// and probability columns, in increasing order.
+    ///
+    /// Returns an option that is None if the model has not been trained.
+    pub fn classes(&self) -> Option<&[usize]> {
+        self.classes.as_ref().map(|c| &c[..])
+    }
+
+    /// Predict the probability of each class for the inputs.
+    ///
+    /// Returns a matrix with a row for each input and a column for
+    /// each class, ordered as in `classes`. Each row sums to one.
+    ///
+    /// The model must be trained.
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if let Some(params) = self.base.parameters() {
+            if params.rows() != inputs.cols() + 1 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+            let full_inputs = ones.hcat(inputs);
+            Ok(softmax_rows(full_inputs * params))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+impl<A> SupModel<Matrix<f64>, Vector<usize>> for SoftmaxRegressor<A>
+    where A: OptimAlgorithm<BaseSoftmaxRegressor>
+{
+    /// Train the softmax regression model.
+    ///
+    /// The classes are inferred from the targets.
+    ///
+    /// # Failures
+    ///
+    /// - The targets are empty.
+    /// - The number of inputs and targets differ.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let mut classes = targets.data().clone();
+        classes.sort();
+        classes.dedup();
+        if classes.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidData, "The targets must not be empty."));
+        }
+
+        // One-hot encode the targets by their position in the classes.
+        let k = classes.len();
+        let mut one_hot = vec![0f64; targets.size() * k];
+        for (i, t) in targets.iter().enumerate() {
+            let c = classes.binary_search(t).unwrap();
+            one_hot[i * k + c] = 1f64;
+        }
+        let one_hot = Matrix::new(targets.size(), k, one_hot);
+
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        let initial_params = vec![0.; full_inputs.cols() * k];
+
+        let optimal_w = self.alg.optimize(&self.base, &initial_params[..], &full_inputs, &one_hot);
+        self.base.set_parameters(Matrix::new(full_inputs.cols(), k, optimal_w));
+        self.classes = Some(classes);
+        Ok(())
+    }
+
+    /// Predict the most probable class of each input.
+    ///
+    /// Model must be trained before prediction can be made.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let probs = self.predict_proba(inputs)?;
+        let classes = self.classes.as_ref().unwrap();
+
+        let predictions = probs.row_iter()
+            .map(|row| {
+                let (best, _) = row.raw_slice()
+                    .iter()
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY),
+                          |(a, p_a), (b, &p_b)| if p_b > p_a { (b, p_b) } else { (a, p_a) });
+                classes[best]
+            })
+            .collect::<Vec<usize>>();
+
+        Ok(Vector::new(predictions))
+    }
+}
+
+/// Apply the softmax function to each row of the class scores.
+fn softmax_rows(scores: Matrix<f64>) -> Matrix<f64> {
+    let (rows, cols) = (scores.rows(), scores.cols());
+    let mut data = scores.into_vec();
+
+    for row in data.chunks_mut(cols) {
+        // Subtract the largest score to avoid overflow.
+        let max = row.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+        let mut total = 0f64;
+        for x in row.iter_mut() {
+            *x = (*x - max).exp();
+            total += *x;
+        }
+        for x in row.iter_mut() {
+            *x /= total;
+        }
+    }
+
+    Matrix::new(rows, cols, data)
+}
+
+/// The Base Softmax Regression model.
+///
+/// This struct cannot be instantianated and is used internally only.
+#[derive(Debug)]
+pub struct BaseSoftmaxRegressor {
+    parameters: Option<Matrix<f64>>,
+}
+
+impl BaseSoftmaxRegressor {
+    /// Construct a new BaseSoftmaxRegressor
+    /// with parameters set to None.
+    fn new() -> BaseSoftmaxRegressor {
+        BaseSoftmaxRegressor { parameters: None }
+    }
+
+    /// Returns a reference to the parameters.
+    fn parameters(&self) -> Option<&Matrix<f64>> {
+        self.parameters.as_ref()
+    }
+
+    /// Set the parameters to `Some` matrix.
+    fn set_parameters(&mut self, params: Matrix<f64>) {
+        self.parameters = Some(params);
+    }
+}
+
+/// Computing the gradient of the underlying Softmax
+/// Regression model.
+///
+/// The targets are one-hot encoded with a column per class, and the
+/// parameters are the row-major entries of the parameter matrix.
+/// The gradient is given by
+///
+/// X<sup>T</sup>(S(XB) - Y) / m
+///
+/// where `S` is the row-wise softmax function and `B` the parameter matrix.
+impl Optimizable for BaseSoftmaxRegressor {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self,
+                    params: &[f64],
+                    inputs: &Matrix<f64>,
+                    targets: &Matrix<f64>)
+                    -> (f64, Vec<f64>) {
+        let beta_mat = Matrix::new(inputs.cols(), targets.cols(), params.to_vec());
+        let outputs = softmax_rows(inputs * beta_mat);
+
+        let n = inputs.rows() as f64;
+        let cost = -outputs.data()
+            .iter()
+            .zip(targets.data().iter())
+            .map(|(p, y)| y * p.max(f64::MIN_POSITIVE).ln())
+            .sum::<f64>() / n;
+        let grad = (inputs.transpose() * (outputs - targets)) / n;
 
         (cost, grad.into_vec())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BaseLogisticRegressor, BaseSoftmaxRegressor, ClassWeight, LogisticRegressor,
+                SoftmaxRegressor};
+    use learning::SupModel;
+    use learning::optim::grad_desc::GradientDesc;
+    use learning::toolkit::grad_check::{grad_check, step_size, tolerance};
+    use linalg::{Matrix, BaseMatrix, Vector};
+    use linalg::sparse::CsrMatrix;
+    use rand::{Rng, SeedableRng, StdRng};
+
+    #[test]
+    fn test_predict_wrong_columns() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 3.0, 5.0, 7.0]);
+        let targets = Vector::new(vec![0., 0., 1., 1.]);
+
+        let mut model = LogisticRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        assert!(model.predict(&Matrix::new(1, 1, vec![2.0])).is_ok());
+        assert!(model.predict(&Matrix::new(1, 2, vec![2.0, 3.0])).is_err());
+    }
+
+    #[test]
+    fn test_scores_agree() {
+        let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let targets = Vector::new(vec![0., 0., 1., 0., 1., 1.]);
+
+        let mut model = LogisticRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        let margins = model.decision_function(&inputs).unwrap();
+        let probs = model.predict_proba(&inputs).unwrap();
+        let classes = model.predict(&inputs).unwrap();
+
+        // Larger margins give larger probabilities.
+        let mut pairs = margins.iter().zip(probs.iter()).collect::<Vec<_>>();
+        pairs.sort_by(|a, b| a.0.partial_cmp(b.0).unwrap());
+        for w in pairs.windows(2) {
+            assert!(w[0].1 <= w[1].1);
+        }
+
+        for (p, c) in probs.iter().zip(classes.iter()) {
+            assert_eq!(*c, if *p > 0.5 { 1.0 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn test_threshold_flips_predictions() {
+        let inputs = Matrix::new(4, 1, vec![1.0, 3.0, 5.0, 7.0]);
+        let targets = Vector::new(vec![0., 0., 1., 1.]);
+
+        let mut model = LogisticRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        let point = Matrix::new(1, 1, vec![4.0]);
+        let p = model.predict_proba(&point).unwrap()[0];
+
+        model.set_threshold((p - 0.01).max(0.0));
+        assert_eq!(model.predict(&point).unwrap()[0], 1.0);
+
+        model.set_threshold((p + 0.01).min(1.0));
+        assert_eq!(model.predict(&point).unwrap()[0], 0.0);
+    }
+
+    #[test]
+    fn test_untrained_scores() {
+        let model = LogisticRegressor::default();
+        let inputs = Matrix::new(1, 1, vec![1.0]);
+
+        assert!(model.decision_function(&inputs).is_err());
+        assert!(model.predict_proba(&inputs).is_err());
+        assert!(model.predict(&inputs).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_threshold() {
+        let mut model = LogisticRegressor::default();
+        model.set_threshold(1.5);
+    }
+
+    #[test]
+    fn test_cost_history() {
+        let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let targets = Vector::new(vec![0., 0., 1., 0., 1., 1.]);
+
+        let mut model = LogisticRegressor::default();
+        assert!(model.training_cost_history().is_empty());
+
+        model.train(&inputs, &targets).unwrap();
+        assert_eq!(model.training_cost_history().len(), 100);
+        assert!(!model.converged());
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let inputs = Matrix::new(6, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let targets = Vector::new(vec![0., 0., 1., 0., 1., 1.]);
+
+        let mut model = LogisticRegressor::new(GradientDesc::new(0.3, 10000));
+        model.set_tolerance(1e-6);
+        model.set_patience(10);
+        model.train(&inputs, &targets).unwrap();
+
+        assert!(model.converged());
+
+        let n_iters = model.training_cost_history().len();
+        assert!(n_iters < 2000);
+        for w in model.training_cost_history()[5..].windows(2) {
+            assert!(w[1] <= w[0]);
+        }
+
+        // Retraining resets the history.
+        model.train(&inputs, &targets).unwrap();
+        assert_eq!(model.training_cost_history().len(), n_iters);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_patience() {
+        let mut model = LogisticRegressor::default();
+        model.set_patience(0);
+    }
+
+    #[test]
+    fn test_sparse_matches_dense() {
+        let dense = Matrix::new(6, 3, vec![1.0, 0.0, 0.0,
+                                           0.0, 2.0, 0.0,
+                                           1.5, 0.0, 0.0,
+                                           0.0, 0.0, 1.0,
+                                           0.0, 1.0, 3.0,
+                                           0.0, 0.0, 2.5]);
+        let mut triplets = Vec::new();
+        for i in 0..6 {
+            for j in 0..3 {
+                if dense[[i, j]] != 0.0 {
+                    triplets.push((i, j, dense[[i, j]]));
+                }
+            }
+        }
+        let sparse = CsrMatrix::from_triplets(6, 3, &triplets).unwrap();
+        let targets = Vector::new(vec![0., 0., 0., 1., 1., 1.]);
+
+        let mut dense_model = LogisticRegressor::default();
+        dense_model.train(&dense, &targets).unwrap();
+
+        let mut sparse_model = LogisticRegressor::default();
+        sparse_model.train_sparse(&sparse, &targets).unwrap();
+
+        let dense_params = dense_model.parameters().unwrap();
+        let sparse_params = sparse_model.parameters().unwrap();
+        for (a, b) in dense_params.iter().zip(sparse_params.iter()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+
+        assert_eq!(dense_model.predict(&dense).unwrap(),
+                   sparse_model.predict_sparse(&sparse).unwrap());
+    }
+
+    #[test]
+    fn test_sparse_invalid_data() {
+        let inputs = CsrMatrix::from_triplets(2, 2, &[(0, 0, 1.0), (1, 1, 1.0)]).unwrap();
+
+        let mut model = LogisticRegressor::default();
+        assert!(model.predict_sparse(&inputs).is_err());
+        assert!(model.train_sparse(&inputs, &Vector::new(vec![0.])).is_err());
+
+        model.train_sparse(&inputs, &Vector::new(vec![0., 1.])).unwrap();
+        let wider = CsrMatrix::from_triplets(1, 3, &[(0, 2, 1.0)]).unwrap();
+        assert!(model.predict_sparse(&wider).is_err());
+    }
+
+    #[test]
+    fn test_large_sparse_problem() {
+        let (rows, cols) = (100000, 50000);
+        let mut rng = StdRng::from_seed(&[11]);
+
+        let mut triplets = Vec::with_capacity(rows * 10);
+        let mut targets = Vec::with_capacity(rows);
+        for i in 0..rows {
+            let mut score = 0.0;
+            for _ in 0..10 {
+                let j = rng.gen_range(0, cols);
+                triplets.push((i, j, 1.0));
+                score += if j < cols / 2 { 1.0 } else { -1.0 };
+            }
+            targets.push(if score > 0.0 { 1.0 } else { 0.0 });
+        }
+
+        let inputs = CsrMatrix::from_triplets(rows, cols, &triplets).unwrap();
+        let targets = Vector::new(targets);
+
+        let mut model = LogisticRegressor::new(GradientDesc::new(1.0, 20));
+        model.train_sparse(&inputs, &targets).unwrap();
+
+        let params = model.parameters().unwrap();
+        assert_eq!(params.size(), cols + 1);
+        assert!(params.iter().all(|p| p.is_finite()));
+
+        // The training cost decreases from the start.
+        let history = model.training_cost_history();
+        assert!(history[history.len() - 1] < history[0]);
+    }
+
+    /// A one dimensional dataset of `n` noisy labels which
+    /// are mostly positive above `5`.
+    fn noisy_data(n: usize) -> (Matrix<f64>, Vector<f64>) {
+        let x = (0..n).map(|i| i as f64 / 20.0).collect::<Vec<f64>>();
+        let y = x.iter()
+            .enumerate()
+            .map(|(i, x)| if x + 2.0 * (i as f64 * 1.7).sin() > 5.0 { 1.0 } else { 0.0 })
+            .collect::<Vec<f64>>();
+
+        (Matrix::new(n, 1, x), Vector::new(y))
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let (inputs, targets) = noisy_data(210);
+        let first = inputs.select_rows(&(0..200).collect::<Vec<usize>>());
+        let first_targets = Vector::new(targets.data()[..200].to_vec());
+
+        let new_model = || {
+            let mut model = LogisticRegressor::new(GradientDesc::new(0.3, 10000));
+            model.set_tolerance(1e-6);
+            model.set_patience(10);
+            model
+        };
+
+        let mut warm = new_model();
+        warm.set_warm_start(true);
+        warm.train(&first, &first_targets).unwrap();
+        warm.train(&inputs, &targets).unwrap();
+
+        let mut cold = new_model();
+        cold.train(&inputs, &targets).unwrap();
+
+        assert!(warm.converged() && cold.converged());
+        assert!(warm.training_cost_history().len() * 5 < cold.training_cost_history().len());
+    }
+
+    #[test]
+    fn test_warm_start_wrong_columns() {
+        let mut model = LogisticRegressor::default();
+        model.set_warm_start(true);
+        model.train(&Matrix::new(2, 1, vec![1.0, 2.0]), &Vector::new(vec![0., 1.])).unwrap();
+
+        let wider = Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]);
+        let targets = Vector::new(vec![0., 1.]);
+        assert!(model.train(&wider, &targets).is_err());
+        assert!(model.train_epochs(&wider, &targets, 1, 1, &mut StdRng::from_seed(&[1])).is_err());
+
+        // Without a warm start the model is trained from scratch.
+        model.set_warm_start(false);
+        assert!(model.train(&wider, &targets).is_ok());
+    }
+
+    #[test]
+    fn test_train_epochs_accuracy() {
+        let (inputs, targets) = noisy_data(210);
+        let accuracy = |outputs: Vector<f64>| {
+            outputs.iter().zip(targets.iter()).filter(|&(o, t)| o == t).count() as f64 / 210.0
+        };
+
+        let mut full_batch = LogisticRegressor::new(GradientDesc::new(0.3, 3000));
+        full_batch.train(&inputs, &targets).unwrap();
+        let full_accuracy = accuracy(full_batch.predict(&inputs).unwrap());
+
+        let mut mini_batch = LogisticRegressor::default();
+        mini_batch.train_epochs(&inputs, &targets, 20, 10, &mut StdRng::from_seed(&[5])).unwrap();
+        let mini_accuracy = accuracy(mini_batch.predict(&inputs).unwrap());
+
+        assert!((full_accuracy - mini_accuracy).abs() < 0.05);
+        assert_eq!(mini_batch.training_cost_history().len(), 20);
+        assert!(mini_batch.train_epochs(&inputs, &targets, 1, 0, &mut StdRng::from_seed(&[5]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_train_epochs_unit_class_weight() {
+        let (inputs, targets) = imbalanced_data();
+
+        let mut model = LogisticRegressor::default();
+        model.train_epochs(&inputs, &targets, 5, 50, &mut StdRng::from_seed(&[3])).unwrap();
+
+        let mut weighted = LogisticRegressor::default();
+        weighted.set_class_weight(ClassWeight::Custom(1.0, 1.0));
+        weighted.train_epochs(&inputs, &targets, 5, 50, &mut StdRng::from_seed(&[3])).unwrap();
+
+        let params = model.parameters().unwrap();
+        let weighted_params = weighted.parameters().unwrap();
+        for (a, b) in params.iter().zip(weighted_params.iter()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+    }
+
+    /// A one dimensional dataset with 490 negatives spread over `[0, 4)`
+    /// and 10 positives spread over `[3, 5)`.
+    fn imbalanced_data() -> (Matrix<f64>, Vector<f64>) {
+        let mut x = (0..490).map(|i| i as f64 * 4.0 / 490.0).collect::<Vec<f64>>();
+        x.extend((0..10).map(|j| 3.0 + j as f64 * 0.2));
+
+        let mut y = vec![0.0; 490];
+        y.extend(vec![1.0; 10]);
+
+        (Matrix::new(500, 1, x), Vector::new(y))
+    }
+
+    #[test]
+    fn test_balanced_class_weight_recall() {
+        let (inputs, targets) = imbalanced_data();
+        let positives = inputs.select_rows(&(490..500).collect::<Vec<usize>>());
+
+        let mut model = LogisticRegressor::new(GradientDesc::new(0.5, 1000));
+        model.train(&inputs, &targets).unwrap();
+        let recall = model.predict(&positives).unwrap().sum() / 10.0;
+        assert!(recall < 0.2);
+
+        let mut balanced = LogisticRegressor::new(GradientDesc::new(0.5, 1000));
+        balanced.set_class_weight(ClassWeight::Balanced);
+        balanced.train(&inputs, &targets).unwrap();
+        let balanced_recall = balanced.predict(&positives).unwrap().sum() / 10.0;
+        assert!(balanced_recall >= 0.8);
+    }
+
+    #[test]
+    fn test_unit_class_weight() {
+        let (inputs, targets) = imbalanced_data();
+
+        let mut model = LogisticRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        let mut weighted = LogisticRegressor::default();
+        weighted.set_class_weight(ClassWeight::Custom(1.0, 1.0));
+        weighted.train(&inputs, &targets).unwrap();
+
+        let params = model.parameters().unwrap();
+        let weighted_params = weighted.parameters().unwrap();
+        for (a, b) in params.iter().zip(weighted_params.iter()) {
+            assert!((a - b).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_class_weight() {
+        let mut model = LogisticRegressor::default();
+        model.set_class_weight(ClassWeight::Custom(1.0, -1.0));
+    }
+
+    #[test]
+    fn test_softmax_probabilities() {
+        let inputs = Matrix::new(6, 1, vec![1.0, 1.5, 4.0, 4.5, 7.0, 7.5]);
+        let targets = Vector::new(vec![0, 0, 1, 1, 2, 2]);
+
+        let mut model = SoftmaxRegressor::new(GradientDesc::new(0.1, 2000));
+        model.train(&inputs, &targets).unwrap();
+
+        let params = model.parameters().unwrap();
+        assert_eq!(params.rows(), 2);
+        assert_eq!(params.cols(), 3);
+
+        let probs = model.predict_proba(&inputs).unwrap();
+        assert_eq!(probs.cols(), 3);
+        for row in probs.row_iter() {
+            assert!((row.raw_slice().iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+
+        assert!(model.predict(&Matrix::new(1, 2, vec![1.0, 2.0])).is_err());
+    }
+
+    #[test]
+    fn test_softmax_binary_matches_logistic() {
+        let inputs = Matrix::new(8, 1, vec![0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0]);
+        let targets = vec![0, 0, 1, 0, 1, 0, 1, 1];
+
+        let mut softmax = SoftmaxRegressor::new(GradientDesc::new(0.5, 5000));
+        softmax.train(&inputs, &Vector::new(targets.clone())).unwrap();
+
+        let mut logistic = LogisticRegressor::new(GradientDesc::new(0.5, 5000));
+        let float_targets = targets.iter().map(|&t| t as f64).collect::<Vec<f64>>();
+        logistic.train(&inputs, &Vector::new(float_targets)).unwrap();
+
+        let probs = softmax.predict_proba(&inputs).unwrap();
+        let logistic_probs = logistic.predict_proba(&inputs).unwrap();
+        for (row, p) in probs.row_iter().zip(logistic_probs.iter()) {
+            assert!((row.raw_slice()[1] - p).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_softmax_label_gaps() {
+        let inputs = Matrix::new(4, 1, vec![-2.0, -1.0, 1.0, 2.0]);
+        let targets = Vector::new(vec![0, 0, 2, 2]);
+
+        let mut model = SoftmaxRegressor::default();
+        model.train(&inputs, &targets).unwrap();
+
+        assert_eq!(model.classes().unwrap(), &[0, 2]);
+        assert_eq!(model.predict_proba(&inputs).unwrap().cols(), 2);
+        assert_eq!(model.predict(&inputs).unwrap(), targets);
+    }
+
+    #[test]
+    fn test_softmax_invalid_data() {
+        let mut model = SoftmaxRegressor::default();
+        let inputs = Matrix::new(2, 1, vec![0.0, 1.0]);
+
+        assert!(model.predict(&inputs).is_err());
+        assert!(model.train(&inputs, &Vector::new(vec![0])).is_err());
+    }
+
+    #[test]
+    fn test_gradient_check() {
+        let mut rng = StdRng::from_seed(&[4]);
+        let data = (0..30).map(|_| rng.gen_range(-2.0, 2.0)).collect::<Vec<f64>>();
+        let inputs = Matrix::new(10, 3, data);
+        let labels = (0..10).map(|i| i % 3).collect::<Vec<usize>>();
+        let tol = tolerance(step_size(1.0));
+
+        let binary = Vector::new(labels.iter().map(|&l| (l % 2) as f64).collect::<Vec<f64>>());
+        let params = (0..3).map(|_| rng.gen_range(-1.0, 1.0)).collect::<Vec<f64>>();
+        let mut base = BaseLogisticRegressor::new();
+        assert!(grad_check(&base, &params, &inputs, &binary, 3, &mut rng) < tol);
+
+        let weights = (0..10).map(|i| 0.5 + i as f64 / 10.0).collect::<Vec<f64>>();
+        base.sample_weights = Some(Vector::new(weights));
+        assert!(grad_check(&base, &params, &inputs, &binary, 3, &mut rng) < tol);
+
+        let mut one_hot = Matrix::zeros(10, 3);
+        for (i, &l) in labels.iter().enumerate() {
+            one_hot[[i, l]] = 1.0;
+        }
+        let params = (0..9).map(|_| rng.gen_range(-1.0, 1.0)).collect::<Vec<f64>>();
+        let base = BaseSoftmaxRegressor::new();
+        assert!(grad_check(&base, &params, &inputs, &one_hot, 9, &mut rng) < tol);
+    }
+
+    #[cfg(feature = "datasets")]
+    #[test]
+    fn test_softmax_iris() {
+        use analysis::score::accuracy;
+        use datasets::iris;
+
+        let iris = iris::load();
+
+        let mut model = SoftmaxRegressor::new(GradientDesc::new(0.1, 1000));
+        model.train(iris.data(), iris.target()).unwrap();
+
+        let predictions = model.predict(iris.data()).unwrap();
+        assert!(accuracy(predictions.iter(), iris.target().iter()) >= 0.9);
+    }
+}
@@ -39,12 +39,24 @@
 //! // Will output the target classes - otherwise our classifier is bad!
 //! println!("Final outputs --\n{}", outputs);
 //! ```
+//!
+//! The class priors default to the proportion of each class in the training
+//! data. When the class balance at prediction time is known to differ, the
+//! priors can be replaced using `set_priors`.
+//!
+//! For count data, such as bag-of-words document matrices, the `MultinomialNB`
+//! classifier accepts `Matrix<usize>` inputs and `Vector<usize>` class labels
+//! directly.
+//!
+//! Tabular data which mixes continuous and categorical features can be
+//! classified with `HybridNB`, which models each column given its `FeatureKind`.
 
-use linalg::{Matrix, Axes, BaseMatrix, BaseMatrixMut};
+use linalg::{Matrix, Axes, BaseMatrix, BaseMatrixMut, Vector};
 use learning::{LearningResult, SupModel};
 use learning::error::{Error, ErrorKind};
 use rulinalg::utils;
 
+use std::f64;
 use std::f64::consts::PI;
 
 /// The Naive Bayes model.
@@ -53,6 +65,7 @@ pub struct NaiveBayes<T: Distribution> {
     distr: Option<T>,
     cluster_count: Option<usize>,
     class_prior: Option<Vec<f64>>,
+    prior_override: Option<Vec<f64>>,
     class_counts: Vec<usize>,
 }
 
@@ -73,6 +86,7 @@ impl<T: Distribution> NaiveBayes<T> {
             distr: None,
             cluster_count: None,
             class_prior: None,
+            prior_override: None,
             class_counts: Vec::new(),
         }
     }
@@ -86,11 +100,55 @@ impl<T: Distribution> NaiveBayes<T> {
 
     /// Get the class prior distribution for this model.
     ///
+    /// This is the proportion of each class in the training data. Priors
+    /// set using `set_priors` are used in its place for prediction.
+    ///
     /// Returns an option which is `None` until the model has been trained.
     pub fn class_prior(&self) -> Option<&Vec<f64>> {
         self.class_prior.as_ref()
     }
 
+    /// Set the class priors used for prediction.
+    ///
+    /// These replace the proportions of each class in the training data,
+    /// and are kept when the model is retrained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::naive_bayes::{NaiveBayes, Gaussian};
+    /// use rusty_machine::linalg::Vector;
+    ///
+    /// let mut model = NaiveBayes::<Gaussian>::new();
+    ///
+    /// // The first class is known to be four times as common as the second.
+    /// model.set_priors(Vector::new(vec![0.8, 0.2])).unwrap();
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - A prior is negative or not finite.
+    /// - The priors do not sum to one.
+    /// - The model is trained and the number of priors is not the number of classes.
+    pub fn set_priors(&mut self, priors: Vector<f64>) -> LearningResult<()> {
+        if priors.iter().any(|p| !p.is_finite() || *p < 0f64) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The priors must be finite and non-negative."));
+        }
+
+        if (priors.sum() - 1f64).abs() > 1e-8 {
+            return Err(Error::new(ErrorKind::InvalidParameters, "The priors must sum to one."));
+        }
+
+        if self.cluster_count.map_or(false, |c| c != priors.size()) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The number of priors must match the number of classes."));
+        }
+
+        self.prior_override = Some(priors.into_vec());
+        Ok(())
+    }
+
     /// Get the distribution for this model.
     ///
     /// Returns an option which is `None` until the model has been trained.
@@ -138,6 +196,7 @@ impl<T: Distribution> NaiveBayes<T> {
     pub fn get_log_probs(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
 
         if let (&Some(ref distr), &Some(ref prior)) = (&self.distr, &self.class_prior) {
+            let prior = self.prior_override.as_ref().unwrap_or(prior);
             // Get the joint log likelihood from the distribution
             distr.joint_log_lik(inputs, prior)
         } else {
@@ -145,10 +204,39 @@ impl<T: Distribution> NaiveBayes<T> {
         }
     }
 
+    /// Get the probability of each class for each input.
+    ///
+    /// Each row of the output sums to one.
+    pub fn predict_proba(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        let log_probs = self.get_log_probs(inputs)?;
+        let cols = log_probs.cols();
+        let mut probs = log_probs.into_vec();
+
+        for row in probs.chunks_mut(cols) {
+            // Subtract the largest log-probability to avoid underflow
+            let max = row.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+            let mut total = 0f64;
+            for p in row.iter_mut() {
+                *p = (*p - max).exp();
+                total += *p;
+            }
+            for p in row.iter_mut() {
+                *p /= total;
+            }
+        }
+
+        Ok(Matrix::new(inputs.rows(), cols, probs))
+    }
+
     fn update_params(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
         let class_count = targets.cols();
         let total_data = inputs.rows();
 
+        if self.prior_override.as_ref().map_or(false, |p| p.len() != class_count) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The number of priors must match the number of classes."));
+        }
+
         self.class_counts = vec![0; class_count];
         let mut class_data = vec![Vec::new(); class_count];
 
@@ -443,16 +531,481 @@ impl Distribution for Multinomial {
     }
 }
 
+/// Multinomial Naive Bayes classifier for count data.
+///
+/// Per class word probabilities are estimated using additive (Laplace)
+/// smoothing, and predictions are the class with the largest log-posterior.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::naive_bayes::MultinomialNB;
+/// use rusty_machine::linalg::{Matrix, Vector};
+/// use rusty_machine::learning::SupModel;
+///
+/// // Word counts for four documents over a vocabulary of three words.
+/// let inputs = Matrix::new(4, 3, vec![3, 0, 1,
+///                                     4, 1, 0,
+///                                     0, 2, 5,
+///                                     1, 3, 4]);
+/// let targets = Vector::new(vec![0, 0, 1, 1]);
+///
+/// let mut model = MultinomialNB::new(1.0);
+/// model.train(&inputs, &targets).unwrap();
+///
+/// let outputs = model.predict(&inputs).unwrap();
+/// assert_eq!(outputs, targets);
+/// ```
+#[derive(Debug)]
+pub struct MultinomialNB {
+    alpha: f64,
+    classes: Option<Vec<usize>>,
+    class_log_prior: Option<Vec<f64>>,
+    log_probs: Option<Matrix<f64>>,
+}
+
+/// The default is `alpha` = 1, i.e. Laplace smoothing.
+impl Default for MultinomialNB {
+    fn default() -> MultinomialNB {
+        MultinomialNB::new(1f64)
+    }
+}
+
+impl MultinomialNB {
+    /// Create a new Multinomial Naive Bayes model with the
+    /// given smoothing parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::naive_bayes::MultinomialNB;
+    ///
+    /// let _ = MultinomialNB::new(0.5);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `alpha` is not positive.
+    pub fn new(alpha: f64) -> MultinomialNB {
+        assert!(alpha > 0f64, "The smoothing parameter must be positive.");
+
+        MultinomialNB {
+            alpha: alpha,
+            classes: None,
+            class_log_prior: None,
+            log_probs: None,
+        }
+    }
+
+    /// Get the smoothing parameter.
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Get the class labels seen in training, in ascending order.
+    ///
+    /// Returns an option which is `None` until the model has been trained.
+    pub fn classes(&self) -> Option<&Vec<usize>> {
+        self.classes.as_ref()
+    }
+
+    /// Get the log probability matrix.
+    ///
+    /// A matrix of class by feature model log-probabilities, with
+    /// classes ordered as in `classes`.
+    ///
+    /// Returns an option which is `None` until the model has been trained.
+    pub fn log_probs(&self) -> Option<&Matrix<f64>> {
+        self.log_probs.as_ref()
+    }
+
+    /// Get the log-posterior (up to a constant) per class for each input.
+    pub fn get_log_probs(&self, inputs: &Matrix<usize>) -> LearningResult<Matrix<f64>> {
+        if let (&Some(ref log_probs), &Some(ref prior)) = (&self.log_probs,
+                                                           &self.class_log_prior) {
+            if inputs.cols() != log_probs.cols() {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let mut data = Vec::with_capacity(inputs.rows() * prior.len());
+            for row in inputs.row_iter() {
+                for (c, class_row) in log_probs.row_iter().enumerate() {
+                    let lik = row.raw_slice()
+                        .iter()
+                        .zip(class_row.raw_slice().iter())
+                        .map(|(&x, &p)| x as f64 * p)
+                        .sum::<f64>();
+                    data.push(prior[c] + lik);
+                }
+            }
+
+            Ok(Matrix::new(inputs.rows(), prior.len(), data))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+impl SupModel<Matrix<usize>, Vector<usize>> for MultinomialNB {
+    /// Train the model using inputs and targets.
+    fn train(&mut self, inputs: &Matrix<usize>, targets: &Vector<usize>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "inputs and targets must be the same length"));
+        }
+        if inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "No training data provided."));
+        }
+
+        let mut classes = targets.data().clone();
+        classes.sort();
+        classes.dedup();
+
+        let features = inputs.cols();
+        let mut counts = vec![self.alpha; classes.len() * features];
+        let mut class_counts = vec![0usize; classes.len()];
+
+        for (row, t) in inputs.row_iter().zip(targets.iter()) {
+            // The classes are sorted so we can search for this target
+            let c = classes.binary_search(t).unwrap();
+            class_counts[c] += 1;
+
+            for (count, &x) in counts[c * features..(c + 1) * features]
+                .iter_mut()
+                .zip(row.raw_slice().iter()) {
+                *count += x as f64;
+            }
+        }
+
+        for c in 0..classes.len() {
+            let class_row = &mut counts[c * features..(c + 1) * features];
+            let total = class_row.iter().sum::<f64>().ln();
+            for count in class_row.iter_mut() {
+                *count = count.ln() - total;
+            }
+        }
+
+        let n = inputs.rows() as f64;
+        self.class_log_prior = Some(class_counts.iter().map(|&c| (c as f64 / n).ln()).collect());
+        self.log_probs = Some(Matrix::new(classes.len(), features, counts));
+        self.classes = Some(classes);
+
+        Ok(())
+    }
+
+    /// Predict output from inputs.
+    fn predict(&self, inputs: &Matrix<usize>) -> LearningResult<Vector<usize>> {
+        let log_probs = self.get_log_probs(inputs)?;
+
+        if let Some(ref classes) = self.classes {
+            Ok(Vector::new(log_probs.row_iter()
+                .map(|row| classes[utils::argmax(row.raw_slice()).0])
+                .collect::<Vec<usize>>()))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
+/// The smallest variance of the continuous features of `HybridNB`.
+///
+/// Keeps features which are constant within a class from breaking the likelihood.
+const MIN_VARIANCE: f64 = 1e-9;
+
+/// The kind of each feature of a `HybridNB` classifier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeatureKind {
+    /// A real valued feature, modelled by a Gaussian in each class.
+    Continuous,
+    /// A feature whose values are the category codes `0, 1, 2, ...`,
+    /// modelled by the smoothed frequency of each category in each class.
+    Categorical,
+}
+
+/// Naive Bayes classifier for a mix of continuous and categorical features.
+///
+/// Each continuous feature is modelled by a Gaussian in each class, as by
+/// the `Gaussian` distribution, and each categorical feature by the
+/// frequencies of its categories in each class with additive smoothing.
+/// The log-likelihoods of all features are summed with the class log-prior.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::naive_bayes::{FeatureKind, HybridNB};
+/// use rusty_machine::linalg::{Matrix, Vector};
+/// use rusty_machine::learning::SupModel;
+///
+/// // A height in metres and a color code for each row.
+/// let inputs = Matrix::new(6, 2, vec![1.2, 0.0,
+///                                     1.1, 0.0,
+///                                     1.3, 1.0,
+///                                     1.8, 2.0,
+///                                     1.7, 2.0,
+///                                     1.9, 1.0]);
+/// let targets = Vector::new(vec![0, 0, 0, 1, 1, 1]);
+///
+/// let mut model = HybridNB::new(vec![FeatureKind::Continuous, FeatureKind::Categorical]);
+/// model.train(&inputs, &targets).unwrap();
+///
+/// let outputs = model.predict(&inputs).unwrap();
+/// assert_eq!(outputs, targets);
+/// ```
+#[derive(Debug)]
+pub struct HybridNB {
+    feature_types: Vec<FeatureKind>,
+    alpha: f64,
+    classes: Option<Vec<usize>>,
+    class_log_prior: Option<Vec<f64>>,
+    theta: Option<Matrix<f64>>,
+    sigma: Option<Matrix<f64>>,
+    category_log_probs: Option<Vec<Matrix<f64>>>,
+}
+
+impl HybridNB {
+    /// Create a new hybrid Naive Bayes model with the kind of each feature.
+    ///
+    /// The categorical features use Laplace smoothing by default.
+    pub fn new(feature_types: Vec<FeatureKind>) -> HybridNB {
+        HybridNB {
+            feature_types: feature_types,
+            alpha: 1f64,
+            classes: None,
+            class_log_prior: None,
+            theta: None,
+            sigma: None,
+            category_log_probs: None,
+        }
+    }
+
+    /// Get the kind of each feature.
+    pub fn feature_types(&self) -> &[FeatureKind] {
+        &self.feature_types
+    }
+
+    /// Get the smoothing parameter of the categorical features.
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Set the smoothing parameter of the categorical features.
+    ///
+    /// # Panics
+    ///
+    /// - `alpha` is not positive.
+    pub fn set_alpha(&mut self, alpha: f64) {
+        assert!(alpha > 0f64, "The smoothing parameter must be positive.");
+        self.alpha = alpha;
+    }
+
+    /// Get the class labels seen in training, in ascending order.
+    ///
+    /// Returns an option which is `None` until the model has been trained.
+    pub fn classes(&self) -> Option<&Vec<usize>> {
+        self.classes.as_ref()
+    }
+
+    /// Get the means of the continuous features.
+    ///
+    /// This is a matrix of class by continuous feature means, with the
+    /// features in the order of their columns.
+    ///
+    /// Returns an option which is `None` until the model has been trained.
+    pub fn theta(&self) -> Option<&Matrix<f64>> {
+        self.theta.as_ref()
+    }
+
+    /// Get the variances of the continuous features.
+    ///
+    /// This is a matrix of class by continuous feature variances, with the
+    /// features in the order of their columns.
+    ///
+    /// Returns an option which is `None` until the model has been trained.
+    pub fn sigma(&self) -> Option<&Matrix<f64>> {
+        self.sigma.as_ref()
+    }
+
+    /// Get the category log-probabilities of the categorical features.
+    ///
+    /// Each categorical feature, in the order of their columns, has a
+    /// matrix of class by category log-probabilities. The last column is
+    /// the log-probability of any category not seen in training.
+    ///
+    /// Returns an option which is `None` until the model has been trained.
+    pub fn category_log_probs(&self) -> Option<&Vec<Matrix<f64>>> {
+        self.category_log_probs.as_ref()
+    }
+
+    /// Get the log-posterior (up to a constant) per class for each input.
+    pub fn get_log_probs(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        if let (&Some(ref prior), &Some(ref theta), &Some(ref sigma), &Some(ref cat_probs)) =
+               (&self.class_log_prior, &self.theta, &self.sigma, &self.category_log_probs) {
+            if inputs.cols() != self.feature_types.len() {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Inputs must have the same number of columns as the \
+                                       training data."));
+            }
+
+            let (continuous, categorical) = self.split_features();
+            let mut data = Vec::with_capacity(inputs.rows() * prior.len());
+            for row in inputs.row_iter() {
+                let row = row.raw_slice();
+                let categories = categorical.iter()
+                    .map(|&j| category(row[j]))
+                    .collect::<LearningResult<Vec<usize>>>()?;
+
+                for (c, log_prior) in prior.iter().enumerate() {
+                    let mut log_prob = *log_prior;
+                    for (k, &j) in continuous.iter().enumerate() {
+                        let (mean, var) = (theta[[c, k]], sigma[[c, k]]);
+                        log_prob -= 0.5 * ((2f64 * PI * var).ln() + (row[j] - mean).powi(2) / var);
+                    }
+                    for (probs, &x) in cat_probs.iter().zip(categories.iter()) {
+                        // Unseen categories share the last column.
+                        log_prob += probs[[c, x.min(probs.cols() - 1)]];
+                    }
+                    data.push(log_prob);
+                }
+            }
+
+            Ok(Matrix::new(inputs.rows(), prior.len(), data))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
+    /// The column indices of the continuous and the categorical features.
+    fn split_features(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut continuous = Vec::new();
+        let mut categorical = Vec::new();
+        for (j, kind) in self.feature_types.iter().enumerate() {
+            match *kind {
+                FeatureKind::Continuous => continuous.push(j),
+                FeatureKind::Categorical => categorical.push(j),
+            }
+        }
+        (continuous, categorical)
+    }
+}
+
+/// The category code of a categorical feature value.
+fn category(x: f64) -> LearningResult<usize> {
+    if x >= 0f64 && x.is_finite() && x.fract() == 0f64 {
+        Ok(x as usize)
+    } else {
+        Err(Error::new(ErrorKind::InvalidData,
+                       "Categorical features must be non-negative integers."))
+    }
+}
+
+impl SupModel<Matrix<f64>, Vector<usize>> for HybridNB {
+    /// Train the model using inputs and targets.
+    fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<usize>) -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "inputs and targets must be the same length"));
+        }
+        if inputs.rows() == 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "No training data provided."));
+        }
+        if inputs.cols() != self.feature_types.len() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "There must be one feature type for each input column."));
+        }
+
+        let mut classes = targets.data().clone();
+        classes.sort();
+        classes.dedup();
+
+        let mut class_rows = vec![Vec::new(); classes.len()];
+        for (i, t) in targets.iter().enumerate() {
+            // The classes are sorted so we can search for this target
+            class_rows[classes.binary_search(t).unwrap()].push(i);
+        }
+
+        let (continuous, categorical) = self.split_features();
+
+        let mut theta = Vec::with_capacity(classes.len() * continuous.len());
+        let mut sigma = Vec::with_capacity(classes.len() * continuous.len());
+        for rows in &class_rows {
+            if !continuous.is_empty() && rows.len() < 2 {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Each class needs at least two rows to estimate the \
+                                       variances of the continuous features."));
+            }
+
+            let n = rows.len() as f64;
+            for &j in &continuous {
+                let mean = rows.iter().map(|&i| inputs[[i, j]]).sum::<f64>() / n;
+                let var = rows.iter().map(|&i| (inputs[[i, j]] - mean).powi(2)).sum::<f64>() /
+                          (n - 1f64);
+                theta.push(mean);
+                sigma.push(var.max(MIN_VARIANCE));
+            }
+        }
+
+        let mut category_log_probs = Vec::with_capacity(categorical.len());
+        for &j in &categorical {
+            let codes = (0..inputs.rows())
+                .map(|i| category(inputs[[i, j]]))
+                .collect::<LearningResult<Vec<usize>>>()?;
+            // One more column for the categories not seen in training
+            let cols = codes.iter().max().unwrap() + 2;
+
+            let mut counts = vec![self.alpha; classes.len() * cols];
+            for (c, rows) in class_rows.iter().enumerate() {
+                for &i in rows {
+                    counts[c * cols + codes[i]] += 1f64;
+                }
+            }
+
+            for class_row in counts.chunks_mut(cols) {
+                let total = class_row.iter().sum::<f64>().ln();
+                for count in class_row.iter_mut() {
+                    *count = count.ln() - total;
+                }
+            }
+            category_log_probs.push(Matrix::new(classes.len(), cols, counts));
+        }
+
+        let n = inputs.rows() as f64;
+        self.class_log_prior = Some(class_rows.iter().map(|r| (r.len() as f64 / n).ln()).collect());
+        self.theta = Some(Matrix::new(classes.len(), continuous.len(), theta));
+        self.sigma = Some(Matrix::new(classes.len(), continuous.len(), sigma));
+        self.category_log_probs = Some(category_log_probs);
+        self.classes = Some(classes);
+
+        Ok(())
+    }
+
+    /// Predict output from inputs.
+    fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let log_probs = self.get_log_probs(inputs)?;
+
+        if let Some(ref classes) = self.classes {
+            Ok(Vector::new(log_probs.row_iter()
+                .map(|row| classes[utils::argmax(row.raw_slice()).0])
+                .collect::<Vec<usize>>()))
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::NaiveBayes;
     use super::Gaussian;
     use super::Bernoulli;
     use super::Multinomial;
+    use super::MultinomialNB;
+    use super::{FeatureKind, HybridNB};
 
     use learning::SupModel;
 
-    use linalg::Matrix;
+    use linalg::{Matrix, BaseMatrix, Vector};
 
     #[test]
     fn test_gaussian() {
@@ -472,6 +1025,71 @@ mod tests {
         assert_eq!(outputs.into_vec(), targets.into_vec());
     }
 
+    #[test]
+    fn test_gaussian_priors() {
+        // The point at zero is equally likely under both classes.
+        let inputs = Matrix::new(4, 1, vec![-2.0, -1.0, 1.0, 2.0]);
+        let targets = Matrix::new(4, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+        let test_point = Matrix::new(1, 1, vec![0.0]);
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.set_priors(Vector::new(vec![0.9, 0.1])).unwrap();
+        model.train(&inputs, &targets).unwrap();
+
+        assert_eq!(model.class_prior().unwrap(), &vec![0.5, 0.5]);
+        assert_eq!(model.predict(&test_point).unwrap().into_vec(), vec![1.0, 0.0]);
+
+        let probs = model.predict_proba(&test_point).unwrap();
+        assert!((probs[[0, 0]] - 0.9).abs() < 1e-10);
+        assert!((probs[[0, 1]] - 0.1).abs() < 1e-10);
+
+        model.set_priors(Vector::new(vec![0.1, 0.9])).unwrap();
+        assert_eq!(model.predict(&test_point).unwrap().into_vec(), vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_predict_proba_rows_sum_to_one() {
+        let inputs = Matrix::new(6,
+                                 2,
+                                 vec![1.0, 1.1, 1.1, 0.9, 2.2, 2.3, 2.5, 2.7, 5.2, 4.3, 6.2, 7.3]);
+        let targets = Matrix::new(6,
+                                  3,
+                                  vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+                                       0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        assert!(model.predict_proba(&inputs).is_err());
+
+        model.train(&inputs, &targets).unwrap();
+
+        let probs = model.predict_proba(&inputs).unwrap();
+        for (i, row) in probs.row_iter().enumerate() {
+            let total = row.raw_slice().iter().sum::<f64>();
+            assert!((total - 1f64).abs() < 1e-12);
+            assert!(row.raw_slice()[i / 2] > 0.5);
+        }
+    }
+
+    #[test]
+    fn test_invalid_priors() {
+        let inputs = Matrix::new(4, 1, vec![-2.0, -1.0, 1.0, 2.0]);
+        let targets = Matrix::new(4, 2, vec![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        assert!(model.set_priors(Vector::new(vec![0.5, 0.6])).is_err());
+        assert!(model.set_priors(Vector::new(vec![1.5, -0.5])).is_err());
+        assert!(model.set_priors(Vector::new(vec![::std::f64::NAN, 1.0])).is_err());
+
+        // The priors must match the number of classes when training
+        model.set_priors(Vector::new(vec![0.2, 0.3, 0.5])).unwrap();
+        assert!(model.train(&inputs, &targets).is_err());
+
+        let mut model = NaiveBayes::<Gaussian>::new();
+        model.train(&inputs, &targets).unwrap();
+        assert!(model.set_priors(Vector::new(vec![0.2, 0.3, 0.5])).is_err());
+        assert!(model.set_priors(Vector::new(vec![0.2, 0.8])).is_ok());
+    }
+
     #[test]
     fn test_bernoulli() {
         let inputs = Matrix::new(4,
@@ -502,4 +1120,135 @@ mod tests {
         let outputs = model.predict(&inputs).unwrap();
         assert_eq!(outputs.into_vec(), targets.into_vec());
     }
+
+    #[test]
+    fn test_multinomial_nb() {
+        let inputs = Matrix::new(4, 3, vec![1, 0, 5, 0, 0, 11, 13, 1, 0, 12, 3, 0]);
+        let targets = Vector::new(vec![2, 2, 7, 7]);
+
+        let mut model = MultinomialNB::default();
+        model.train(&inputs, &targets).unwrap();
+
+        assert_eq!(model.classes().unwrap(), &vec![2, 7]);
+
+        let outputs = model.predict(&inputs).unwrap();
+        assert_eq!(outputs, targets);
+
+        // The smoothed probabilities sum to one for each class
+        for row in model.log_probs().unwrap().row_iter() {
+            let total = row.raw_slice().iter().map(|p| p.exp()).sum::<f64>();
+            assert!((total - 1f64).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_multinomial_nb_errors() {
+        let inputs = Matrix::new(2, 2, vec![1, 0, 0, 1]);
+
+        let model = MultinomialNB::new(1.0);
+        assert!(model.predict(&inputs).is_err());
+
+        let mut model = MultinomialNB::new(1.0);
+        assert!(model.train(&inputs, &Vector::new(vec![0])).is_err());
+
+        model.train(&inputs, &Vector::new(vec![0, 1])).unwrap();
+        assert!(model.predict(&Matrix::new(1, 3, vec![1, 0, 0])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_multinomial_nb_zero_alpha() {
+        let _ = MultinomialNB::new(0.0);
+    }
+
+    #[test]
+    fn test_hybrid_nb_matches_gaussian() {
+        let inputs = Matrix::new(6,
+                                 2,
+                                 vec![1.0, 1.1, 1.1, 0.9, 2.2, 2.3, 2.5, 2.7, 5.2, 4.3, 6.2, 7.3]);
+        let targets = Matrix::new(6,
+                                  3,
+                                  vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0,
+                                       0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]);
+
+        let mut gaussian = NaiveBayes::<Gaussian>::new();
+        gaussian.train(&inputs, &targets).unwrap();
+
+        let mut hybrid = HybridNB::new(vec![FeatureKind::Continuous; 2]);
+        hybrid.train(&inputs, &Vector::new(vec![0, 0, 1, 1, 2, 2])).unwrap();
+
+        let expected = gaussian.get_log_probs(&inputs).unwrap();
+        let log_probs = hybrid.get_log_probs(&inputs).unwrap();
+        for (x, y) in log_probs.iter().zip(expected.iter()) {
+            assert!((x - y).abs() < 1e-9);
+        }
+        assert_eq!(hybrid.predict(&inputs).unwrap(), Vector::new(vec![0, 0, 1, 1, 2, 2]));
+    }
+
+    #[test]
+    fn test_hybrid_nb_combines_features() {
+        let inputs = Matrix::new(8, 2, vec![1.0, 0.0, 1.4, 0.0, 0.6, 1.0, 1.2, 0.0,
+                                            2.0, 1.0, 2.4, 2.0, 1.6, 1.0, 2.2, 1.0]);
+        let targets = Vector::new(vec![0, 0, 0, 0, 1, 1, 1, 1]);
+
+        let mut hybrid = HybridNB::new(vec![FeatureKind::Continuous, FeatureKind::Categorical]);
+        hybrid.train(&inputs, &targets).unwrap();
+        assert_eq!(hybrid.predict(&inputs).unwrap(), targets);
+
+        let mut continuous = HybridNB::new(vec![FeatureKind::Continuous]);
+        continuous.train(&inputs.select_cols(&[0]), &targets).unwrap();
+        let mut categorical = HybridNB::new(vec![FeatureKind::Categorical]);
+        categorical.train(&inputs.select_cols(&[1]), &targets).unwrap();
+
+        // The log-likelihoods of the features are summed with a single prior.
+        let test_inputs = Matrix::new(3, 2, vec![1.5, 1.0, 0.9, 2.0, 1.8, 7.0]);
+        let log_probs = hybrid.get_log_probs(&test_inputs).unwrap();
+        let cont_probs = continuous.get_log_probs(&test_inputs.select_cols(&[0])).unwrap();
+        let cat_probs = categorical.get_log_probs(&test_inputs.select_cols(&[1])).unwrap();
+        for i in 0..3 {
+            for c in 0..2 {
+                let expected = cont_probs[[i, c]] + cat_probs[[i, c]] - 0.5f64.ln();
+                assert!((log_probs[[i, c]] - expected).abs() < 1e-12);
+            }
+        }
+
+        // The smoothed category probabilities sum to one for each class,
+        // including the column for unseen categories.
+        let probs = &hybrid.category_log_probs().unwrap()[0];
+        assert_eq!(probs.cols(), 4);
+        for row in probs.row_iter() {
+            let total = row.raw_slice().iter().map(|p| p.exp()).sum::<f64>();
+            assert!((total - 1f64).abs() < 1e-12);
+        }
+        assert!((probs[[0, 0]] - (4.0f64 / 8.0).ln()).abs() < 1e-12);
+        assert!((probs[[1, 3]] - (1.0f64 / 8.0).ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hybrid_nb_errors() {
+        let inputs = Matrix::new(4, 2, vec![1.0, 0.0, 2.0, 1.0, 3.0, 0.0, 4.0, 1.0]);
+        let targets = Vector::new(vec![0, 0, 1, 1]);
+
+        let mut model = HybridNB::new(vec![FeatureKind::Continuous, FeatureKind::Categorical]);
+        assert!(model.predict(&inputs).is_err());
+        assert!(model.train(&inputs, &Vector::new(vec![0, 1])).is_err());
+        assert!(model.train(&inputs, &Vector::new(vec![0, 0, 0, 1])).is_err());
+
+        let bad_codes = Matrix::new(4, 2, vec![1.0, 0.0, 2.0, -1.0, 3.0, 0.0, 4.0, 1.5]);
+        assert!(model.train(&bad_codes, &targets).is_err());
+
+        let mut wrong_types = HybridNB::new(vec![FeatureKind::Continuous]);
+        assert!(wrong_types.train(&inputs, &targets).is_err());
+
+        model.train(&inputs, &targets).unwrap();
+        assert!(model.predict(&Matrix::new(1, 1, vec![1.0])).is_err());
+        assert!(model.predict(&Matrix::new(1, 2, vec![1.0, 0.5])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hybrid_nb_zero_alpha() {
+        let mut model = HybridNB::new(vec![FeatureKind::Categorical]);
+        model.set_alpha(0.0);
+    }
 }
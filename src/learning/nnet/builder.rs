@@ -0,0 +1,345 @@
+//! Neural Network Builder
+//!
+//! The `NetBuilder` constructs a fully connected network layer by layer,
+//! with a separate activation function for each hidden layer and for
+//! the output layer.
+//!
+//! A builder created with `NetBuilder::sequence` takes sequences with a
+//! number of channels at each position as input. The sequences pass
+//! through 1-D convolution and max pooling layers, and are flattened
+//! before any fully connected layers. The shapes are checked when the
+//! network is built.
+//!
+//! The output activation is checked against the criterion when the
+//! network is built: a `Linear` output for the `MSECriterion`, a `Sigmoid`
+//! output for the `BCECriterion`, and a `Softmax` output for the
+//! `CategoricalCECriterion`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::nnet::{BCECriterion, MSECriterion};
+//! use rusty_machine::learning::nnet::builder::NetBuilder;
+//! use rusty_machine::learning::toolkit::activ_fn::{Linear, Relu, Sigmoid};
+//! use rusty_machine::learning::optim::grad_desc::GradientDesc;
+//!
+//! // A regression network with two rectified hidden layers.
+//! let net = NetBuilder::new(4)
+//!     .layer(16, Relu)
+//!     .layer(16, Relu)
+//!     .output(1, Linear)
+//!     .build(MSECriterion::default(), GradientDesc::default())
+//!     .unwrap();
+//!
+//! // A classifier of sequences of length 32 with two channels.
+//! let net = NetBuilder::sequence(32, 2)
+//!     .conv1d(8, 5, 1, Relu)
+//!     .max_pool1d(4)
+//!     .flatten()
+//!     .layer(16, Relu)
+//!     .output(1, Sigmoid)
+//!     .build(BCECriterion::default(), GradientDesc::default())
+//!     .unwrap();
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use learning::optim::OptimAlgorithm;
+use learning::toolkit::activ_fn::{self, ActivationFunc};
+
+use super::{BaseNeuralNet, Criterion, NeuralNet};
+use super::net_layer::{self, NetLayer};
+
+/// The activation function of the output layer of a network
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputActivation {
+    /// The identity, for regression
+    Linear,
+    /// The sigmoid function, for binary classification
+    Sigmoid,
+    /// The softmax function, for multi-class classification
+    Softmax,
+}
+
+/// Trait for the layers which can follow the final linear layer of a network
+pub trait OutputLayer: NetLayer + 'static {
+    /// The activation function computed by this layer
+    fn output_activation(&self) -> OutputActivation;
+}
+
+impl OutputLayer for activ_fn::Linear {
+    fn output_activation(&self) -> OutputActivation {
+        OutputActivation::Linear
+    }
+}
+
+impl OutputLayer for activ_fn::Sigmoid {
+    fn output_activation(&self) -> OutputActivation {
+        OutputActivation::Sigmoid
+    }
+}
+
+impl OutputLayer for net_layer::Softmax {
+    fn output_activation(&self) -> OutputActivation {
+        OutputActivation::Softmax
+    }
+}
+
+/// The shape of the data passed between the steps of a network
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    /// A number of features
+    Flat(usize),
+    /// A sequence length and the number of channels at each position
+    Sequence(usize, usize),
+}
+
+/// A step of the network before the output layer
+#[derive(Debug)]
+enum Step {
+    /// A fully connected layer with its size and activation
+    Dense(usize, Box<dyn NetLayer>),
+    /// A convolution with its filters, width, stride and activation
+    Conv(usize, usize, usize, Box<dyn NetLayer>),
+    /// A max pooling with its size
+    MaxPool(usize),
+    /// A batch normalization
+    BatchNorm,
+    /// The end of the sequence layers
+    Flatten,
+}
+
+/// Neural Network Builder
+///
+/// Each hidden layer is a `Linear` layer followed by its activation
+/// function, and each convolution a `Conv1D` layer followed by its
+/// activation function. The output layer is always the last layer of
+/// the network.
+#[derive(Debug)]
+pub struct NetBuilder {
+    input: Shape,
+    /// The hidden layers, in order.
+    steps: Vec<Step>,
+    /// The size, activation and activation kind of the output layer.
+    output: Option<(usize, Box<dyn NetLayer>, OutputActivation)>,
+}
+
+impl NetBuilder {
+    /// Construct a new builder for a network with `input_size` inputs
+    ///
+    /// # Panics
+    ///
+    /// - `input_size` is zero.
+    pub fn new(input_size: usize) -> NetBuilder {
+        assert!(input_size > 0, "The input size must be greater than 0.");
+        NetBuilder {
+            input: Shape::Flat(input_size),
+            steps: Vec::new(),
+            output: None,
+        }
+    }
+
+    /// Construct a new builder for a network of sequences
+    ///
+    /// Each input row holds `length` positions with `channels` values at
+    /// each position, stored position by position. See `net_layer::Conv1D`.
+    ///
+    /// # Panics
+    ///
+    /// - `length` or `channels` is zero.
+    pub fn sequence(length: usize, channels: usize) -> NetBuilder {
+        assert!(length > 0 && channels > 0,
+                "The sequence length and channels must be greater than 0.");
+        NetBuilder {
+            input: Shape::Sequence(length, channels),
+            steps: Vec::new(),
+            output: None,
+        }
+    }
+
+    /// Add a hidden layer with `size` outputs and the given activation function
+    ///
+    /// # Panics
+    ///
+    /// - `size` is zero.
+    pub fn layer<U>(mut self, size: usize, activ_fn: U) -> NetBuilder
+        where U: ActivationFunc + 'static
+    {
+        assert!(size > 0, "The layer size must be greater than 0.");
+        let layer: Box<dyn NetLayer> = Box::new(activ_fn);
+        self.steps.push(Step::Dense(size, layer));
+        self
+    }
+
+    /// Add a 1-D convolution with the given activation function
+    ///
+    /// The convolution has `filters` kernels of `width` positions, moving
+    /// `stride` positions at a time.
+    ///
+    /// # Panics
+    ///
+    /// - `filters`, `width` or `stride` is zero.
+    pub fn conv1d<U>(mut self,
+                     filters: usize,
+                     width: usize,
+                     stride: usize,
+                     activ_fn: U)
+                     -> NetBuilder
+        where U: ActivationFunc + 'static
+    {
+        assert!(filters > 0 && width > 0 && stride > 0,
+                "The filters, width and stride must be greater than 0.");
+        let layer: Box<dyn NetLayer> = Box::new(activ_fn);
+        self.steps.push(Step::Conv(filters, width, stride, layer));
+        self
+    }
+
+    /// Add a 1-D max pooling over windows of `size` positions
+    ///
+    /// # Panics
+    ///
+    /// - `size` is zero.
+    pub fn max_pool1d(mut self, size: usize) -> NetBuilder {
+        assert!(size > 0, "The pooling size must be greater than 0.");
+        self.steps.push(Step::MaxPool(size));
+        self
+    }
+
+    /// Add a batch normalization of the outputs of the previous layer
+    ///
+    /// The layer uses the default momentum of `net_layer::BatchNorm`.
+    pub fn batch_norm(mut self) -> NetBuilder {
+        self.steps.push(Step::BatchNorm);
+        self
+    }
+
+    /// Flatten the sequence so it can be followed by fully connected layers
+    pub fn flatten(mut self) -> NetBuilder {
+        self.steps.push(Step::Flatten);
+        self
+    }
+
+    /// Set the output layer with `size` outputs and the given activation
+    ///
+    /// Replaces any previously set output layer.
+    ///
+    /// # Panics
+    ///
+    /// - `size` is zero.
+    pub fn output<U: OutputLayer>(mut self, size: usize, activation: U) -> NetBuilder {
+        assert!(size > 0, "The output size must be greater than 0.");
+        let kind = activation.output_activation();
+        let layer: Box<dyn NetLayer> = Box::new(activation);
+        self.output = Some((size, layer, kind));
+        self
+    }
+
+    /// Build the network using the given criterion and optimization algorithm
+    ///
+    /// # Failures
+    ///
+    /// - No output layer was set.
+    /// - The output activation does not match the criterion.
+    /// - A softmax output layer has fewer than two outputs.
+    /// - A convolution, pooling or flatten step does not follow a sequence.
+    /// - A hidden, batch normalization or output layer follows a sequence which
+    ///   was not flattened.
+    /// - A convolution width or pooling size is longer than its input sequence.
+    pub fn build<T, A>(self, criterion: T, alg: A) -> LearningResult<NeuralNet<T, A>>
+        where T: Criterion,
+              A: OptimAlgorithm<BaseNeuralNet<T>>
+    {
+        let (output_size, output_layer, kind) = match self.output {
+            Some(output) => output,
+            None => {
+                return Err(Error::new(ErrorKind::InvalidState,
+                                      "The network must have an output layer."))
+            }
+        };
+
+        if let Some(expected) = criterion.output_activation() {
+            if expected != kind {
+                return Err(Error::new(ErrorKind::InvalidParameters,
+                                      format!("The criterion requires a {:?} output layer but \
+                                               the output layer is {:?}.",
+                                              expected,
+                                              kind)));
+            }
+        }
+
+        if kind == OutputActivation::Softmax && output_size < 2 {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "A softmax output layer must have at least two outputs."));
+        }
+
+        let mut net = NeuralNet::new(criterion, alg);
+        let mut shape = self.input;
+        for step in self.steps {
+            shape = match (step, shape) {
+                (Step::Dense(size, activ_fn), Shape::Flat(input_size)) => {
+                    net.add(Box::new(net_layer::Linear::new(input_size, size)))
+                        .add(activ_fn);
+                    Shape::Flat(size)
+                }
+                (Step::Conv(filters, width, stride, activ_fn),
+                 Shape::Sequence(length, channels)) => {
+                    if width > length {
+                        return Err(Error::new(ErrorKind::InvalidParameters,
+                                              format!("The convolution width {} is longer than \
+                                                       its input sequence of length {}.",
+                                                      width,
+                                                      length)));
+                    }
+                    let conv = net_layer::Conv1D::new(length, channels, filters, width, stride);
+                    let out_length = conv.output_length();
+                    net.add(Box::new(conv)).add(activ_fn);
+                    Shape::Sequence(out_length, filters)
+                }
+                (Step::MaxPool(size), Shape::Sequence(length, channels)) => {
+                    if size > length {
+                        return Err(Error::new(ErrorKind::InvalidParameters,
+                                              format!("The pooling size {} is longer than its \
+                                                       input sequence of length {}.",
+                                                      size,
+                                                      length)));
+                    }
+                    let pool = net_layer::MaxPool1D::new(length, channels, size);
+                    let out_length = pool.output_length();
+                    net.add(Box::new(pool));
+                    Shape::Sequence(out_length, channels)
+                }
+                (Step::BatchNorm, Shape::Flat(size)) => {
+                    net.add(Box::new(net_layer::BatchNorm::new(size)));
+                    Shape::Flat(size)
+                }
+                (Step::Flatten, Shape::Sequence(length, channels)) => {
+                    // The sequences are already stored as rows.
+                    Shape::Flat(length * channels)
+                }
+                (Step::Dense(..), Shape::Sequence(..)) |
+                (Step::BatchNorm, Shape::Sequence(..)) => {
+                    return Err(Error::new(ErrorKind::InvalidParameters,
+                                          "Hidden and batch normalization layers must follow \
+                                           a flattened sequence."))
+                }
+                (_, Shape::Flat(_)) => {
+                    return Err(Error::new(ErrorKind::InvalidParameters,
+                                          "Convolution, pooling and flatten steps must \
+                                           follow a sequence."))
+                }
+            };
+        }
+
+        let input_size = match shape {
+            Shape::Flat(size) => size,
+            Shape::Sequence(..) => {
+                return Err(Error::new(ErrorKind::InvalidParameters,
+                                      "The output layer must follow a flattened sequence."))
+            }
+        };
+        net.add(Box::new(net_layer::Linear::new(input_size, output_size)))
+            .add(output_layer);
+
+        Ok(net)
+    }
+}
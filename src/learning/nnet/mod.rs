@@ -41,11 +41,43 @@
 //!
 //! You can define your own criterion by implementing the `Criterion`
 //! trait with a concrete `CostFunc`.
+//!
+//! For multi-class classification use `NeuralNet::softmax_mlp`, which ends
+//! the network with a `Softmax` layer trained by the `CategoricalCECriterion`.
+//! The targets can be class labels passed to `train_classes`.
+//!
+//! Overfitting can be reduced by adding `Dropout` layers to the network.
+//! The dropout masks are only applied while training, and can be made
+//! reproducible by training with a seeded generator through `train_with_rng`.
+//!
+//! A fraction of the training rows can be held out with `set_validation_fraction`
+//! to record the validation loss after each iteration of the optimization algorithm.
+//! With `set_early_stopping` the training also stops once the validation loss stops
+//! improving, and the weights with the lowest validation loss are kept.
+//!
+//! The weights and biases of new layers are drawn from a gaussian scaled by the
+//! layer sizes. Deep networks with rectified linear activations can train faster
+//! after reinitializing the weights with `init_weights` and `WeightInit::He`.
+//! The weights of a trained network can be read with `weights` and loaded
+//! into a network with the same layers using `set_weights`.
+//!
+//! The weights, but not the biases, can be penalized with `set_regularization`,
+//! and the norm of the incoming weights of each unit bounded with `set_max_norm`.
+//!
+//! Deep networks can also train faster with `BatchNorm` layers, which normalize
+//! their input with the statistics of each training batch. The running averages
+//! of the statistics used for prediction are stored with the weights.
+//!
+//! Networks with a different activation function in each layer can be
+//! constructed using the `NetBuilder` in the `builder` module.
+//! The builder also constructs networks of fixed-length sequences from the
+//! `Conv1D` and `MaxPool1D` layers, followed by fully connected layers.
 
 
 pub mod net_layer;
+pub mod builder;
 
-use linalg::{Matrix, MatrixSlice};
+use linalg::{Matrix, MatrixSlice, BaseMatrix, Vector};
 use rulinalg::utils;
 
 use learning::{LearningResult, SupModel};
@@ -58,7 +90,16 @@ use learning::toolkit::regularization::Regularization;
 use learning::optim::{Optimizable, OptimAlgorithm};
 use learning::optim::grad_desc::StochasticGD;
 
-use self::net_layer::NetLayer;
+use self::builder::OutputActivation;
+use self::net_layer::{NetLayer, WeightInit};
+
+use rand::{Rng, SeedableRng, StdRng, thread_rng};
+
+use learning::toolkit::rand_utils::in_place_fisher_yates_with_rng;
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::f64;
 
 /// Neural Network Model
 ///
@@ -71,6 +112,11 @@ pub struct NeuralNet<T, A>
 {
     base: BaseNeuralNet<T>,
     alg: A,
+    validation_fraction: Option<f64>,
+    early_stopping: Option<(usize, f64)>,
+    training_loss_history: Vec<f64>,
+    validation_loss_history: Vec<f64>,
+    best_epoch: Option<usize>,
 }
 
 /// Supervised learning for the Neural Network.
@@ -87,9 +133,7 @@ impl<T, A> SupModel<Matrix<f64>, Matrix<f64>> for NeuralNet<T, A>
 
     /// Train the model using gradient optimization and back propagation.
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Matrix<f64>) -> LearningResult<()> {
-        let optimal_w = self.alg.optimize(&self.base, &self.base.weights, inputs, targets);
-        self.base.weights = optimal_w;
-        Ok(())
+        self.train_with_rng(inputs, targets, &mut thread_rng())
     }
 }
 
@@ -111,10 +155,8 @@ impl NeuralNet<BCECriterion, StochasticGD> {
     /// let mut net = NeuralNet::default(layers);
     /// ```
     pub fn default(layer_sizes: &[usize]) -> NeuralNet<BCECriterion, StochasticGD> {
-        NeuralNet {
-            base: BaseNeuralNet::default(layer_sizes, activ_fn::Sigmoid),
-            alg: StochasticGD::default(),
-        }
+        NeuralNet::from_base(BaseNeuralNet::default(layer_sizes, activ_fn::Sigmoid),
+                             StochasticGD::default())
     }
 }
 
@@ -135,9 +177,19 @@ impl<T, A> NeuralNet<T, A>
     /// let mut net = NeuralNet::new(BCECriterion::default(), StochasticGD::default());
     /// ```
     pub fn new(criterion: T, alg: A) -> NeuralNet<T, A> {
+        NeuralNet::from_base(BaseNeuralNet::new(criterion), alg)
+    }
+
+    /// Create a neural network from its base network and algorithm.
+    fn from_base(base: BaseNeuralNet<T>, alg: A) -> NeuralNet<T, A> {
         NeuralNet {
-            base: BaseNeuralNet::new(criterion),
+            base: base,
             alg: alg,
+            validation_fraction: None,
+            early_stopping: None,
+            training_loss_history: Vec::new(),
+            validation_loss_history: Vec::new(),
+            best_epoch: None,
         }
     }
 
@@ -162,10 +214,7 @@ impl<T, A> NeuralNet<T, A>
     /// ```
     pub fn mlp<U>(layer_sizes: &[usize], criterion: T, alg: A, activ_fn: U) -> NeuralNet<T, A> 
         where U: ActivationFunc + 'static {
-        NeuralNet {
-            base: BaseNeuralNet::mlp(layer_sizes, criterion, activ_fn),
-            alg: alg,
-        }
+        NeuralNet::from_base(BaseNeuralNet::mlp(layer_sizes, criterion, activ_fn), alg)
     }
 
     /// Adds the specified layer to the end of the network
@@ -239,6 +288,508 @@ impl<T, A> NeuralNet<T, A>
     pub fn get_net_weights(&self, idx: usize) -> MatrixSlice<f64> {
         self.base.get_layer_weights(&self.base.weights[..], idx)
     }
+
+    /// Get the weights of each layer with parameters.
+    ///
+    /// The matrices have the parameter shapes of the layers. The weights
+    /// of a `Linear` layer with a bias term have a row for each input plus
+    /// a first row holding the biases.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::linalg::BaseMatrix;
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    ///
+    /// let net = NeuralNet::default(&[3, 4, 2]);
+    ///
+    /// // The sigmoid layers have no weights.
+    /// let weights = net.weights();
+    /// assert_eq!(weights.len(), 2);
+    /// assert_eq!((weights[1].rows(), weights[1].cols()), (5, 2));
+    /// ```
+    pub fn weights(&self) -> Vec<Matrix<f64>> {
+        let mut index = 0;
+        let mut weights = Vec::new();
+        for layer in self.base.layers.iter().filter(|l| l.num_params() > 0) {
+            let (rows, cols) = layer.param_shape();
+            let data = self.base.weights[index..index + layer.num_params()].to_vec();
+            weights.push(Matrix::new(rows, cols, data));
+            index += layer.num_params();
+        }
+        weights
+    }
+
+    /// Set the weights of each layer with parameters.
+    ///
+    /// The weights are given in the layout returned by `weights`, so
+    /// the weights of a network can be loaded into a new network with
+    /// the same layers.
+    ///
+    /// # Failures
+    ///
+    /// - The number of matrices is not the number of layers with parameters.
+    /// - A matrix does not have the parameter shape of its layer.
+    pub fn set_weights(&mut self, weights: Vec<Matrix<f64>>) -> LearningResult<()> {
+        let shapes = self.base
+            .layers
+            .iter()
+            .filter(|l| l.num_params() > 0)
+            .map(|l| l.param_shape())
+            .collect::<Vec<_>>();
+
+        if weights.len() != shapes.len() {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The number of weight matrices must match the number of \
+                                   layers with parameters."));
+        }
+
+        if weights.iter().zip(shapes).any(|(w, shape)| (w.rows(), w.cols()) != shape) {
+            return Err(Error::new(ErrorKind::InvalidParameters,
+                                  "The weight matrices must match the layer parameter shapes."));
+        }
+
+        self.base.weights = weights.into_iter().flat_map(|w| w.into_vec()).collect();
+        Ok(())
+    }
+
+    /// Reinitialize the weights of every layer using the given scheme.
+    ///
+    /// Layers which do not support the scheme are given their default parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::learning::nnet::{NeuralNet, MSECriterion};
+    /// use rusty_machine::learning::nnet::net_layer::WeightInit;
+    /// use rusty_machine::learning::toolkit::activ_fn::Relu;
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    /// use rand::{SeedableRng, StdRng};
+    ///
+    /// # fn main() {
+    /// let mut net = NeuralNet::mlp(&[4, 16, 16, 16, 1],
+    ///                              MSECriterion::default(),
+    ///                              GradientDesc::default(),
+    ///                              Relu);
+    /// net.init_weights(WeightInit::He, &mut StdRng::from_seed(&[1]));
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - The scale of `WeightInit::Uniform` is not positive and finite.
+    pub fn init_weights<R: Rng>(&mut self, init: WeightInit, rng: &mut R) {
+        self.base.init_weights(init, rng);
+    }
+
+    /// Set the penalty on the weights of the network.
+    ///
+    /// Unlike the regularization of the criterion the penalty excludes
+    /// the biases. For `L2(lambda)` the cost is increased by
+    /// `lambda / 2 * Σ w²` and for `L1(lambda)` by `lambda * Σ |w|`,
+    /// averaged over the training rows like the criterion cost.
+    ///
+    /// There is no penalty by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::NeuralNet;
+    /// use rusty_machine::learning::toolkit::regularization::Regularization;
+    ///
+    /// let mut net = NeuralNet::default(&[3, 4, 2]);
+    /// net.set_regularization(Regularization::L2(0.01));
+    /// ```
+    pub fn set_regularization(&mut self, regularization: Regularization<f64>) {
+        self.base.regularization = regularization;
+    }
+
+    /// Get the penalty on the weights of the network.
+    pub fn regularization(&self) -> Regularization<f64> {
+        self.base.regularization
+    }
+
+    /// Constrain the norm of the incoming weights of each unit.
+    ///
+    /// After each update of the algorithms in the `optim` module, the
+    /// incoming weights of each output of a layer, excluding the bias,
+    /// are rescaled to have norm at most `max_norm`. The final weights
+    /// are constrained for other algorithms.
+    ///
+    /// There is no constraint by default.
+    ///
+    /// # Panics
+    ///
+    /// - `max_norm` is not positive and finite.
+    pub fn set_max_norm(&mut self, max_norm: f64) {
+        assert!(max_norm > 0f64 && max_norm.is_finite(),
+                "The max norm must be positive and finite.");
+        self.base.max_norm = Some(max_norm);
+    }
+
+    /// Get the maximum norm of the incoming weights of each unit.
+    pub fn max_norm(&self) -> Option<f64> {
+        self.base.max_norm
+    }
+
+    /// Set the fraction of the training rows held out for validation.
+    ///
+    /// The validation loss is recorded after each iteration of the
+    /// optimization algorithm, using the weights after the latest update,
+    /// and is used for early stopping. When the targets are class labels,
+    /// either a single column of zeros and ones or one-hot rows, each class
+    /// is split in this proportion.
+    ///
+    /// There is no validation by default.
+    ///
+    /// # Panics
+    ///
+    /// - `fraction` is not strictly between zero and one.
+    pub fn set_validation_fraction(&mut self, fraction: f64) {
+        assert!(fraction > 0f64 && fraction < 1f64,
+                "The validation fraction must be between 0 and 1.");
+        self.validation_fraction = Some(fraction);
+    }
+
+    /// Get the fraction of the training rows held out for validation.
+    pub fn validation_fraction(&self) -> Option<f64> {
+        self.validation_fraction
+    }
+
+    /// Stop training once the validation loss has not improved for `patience` iterations.
+    ///
+    /// The validation loss improves when it is more than `min_delta` below the
+    /// lowest loss so far. When training stops the weights are restored to those
+    /// with the lowest validation loss. This requires a validation fraction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::{NeuralNet, MSECriterion};
+    /// use rusty_machine::learning::toolkit::activ_fn::Sigmoid;
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    /// use rusty_machine::learning::SupModel;
+    /// use rusty_machine::linalg::Matrix;
+    ///
+    /// let mut net = NeuralNet::mlp(&[1, 4, 1],
+    ///                              MSECriterion::default(),
+    ///                              GradientDesc::new(0.1, 500),
+    ///                              Sigmoid);
+    /// net.set_validation_fraction(0.25);
+    /// net.set_early_stopping(10, 1e-6);
+    ///
+    /// let inputs = Matrix::new(8, 1, vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7]);
+    /// let targets = Matrix::new(8, 1, vec![0.1, 0.2, 0.2, 0.4, 0.4, 0.5, 0.7, 0.7]);
+    /// net.train(&inputs, &targets).unwrap();
+    ///
+    /// let best = net.best_epoch().unwrap();
+    /// println!("Best validation loss: {}", net.validation_loss_history()[best]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `patience` is zero.
+    /// - `min_delta` is negative.
+    pub fn set_early_stopping(&mut self, patience: usize, min_delta: f64) {
+        assert!(patience > 0, "The patience must be greater than 0.");
+        assert!(min_delta >= 0f64, "The minimum improvement cannot be negative.");
+        self.early_stopping = Some((patience, min_delta));
+    }
+
+    /// Get the patience and minimum improvement used for early stopping.
+    pub fn early_stopping(&self) -> Option<(usize, f64)> {
+        self.early_stopping
+    }
+
+    /// The training cost at each iteration of the last training run.
+    pub fn training_loss_history(&self) -> &[f64] {
+        &self.training_loss_history
+    }
+
+    /// The validation loss at each iteration of the last training run.
+    ///
+    /// The history is empty if there was no validation.
+    pub fn validation_loss_history(&self) -> &[f64] {
+        &self.validation_loss_history
+    }
+
+    /// The iteration of the last training run with the lowest validation loss.
+    ///
+    /// This indexes the loss histories.
+    pub fn best_epoch(&self) -> Option<usize> {
+        self.best_epoch
+    }
+
+    /// Train the model using the given random number generator for the dropout masks.
+    ///
+    /// The generator also chooses the validation rows. Training twice from the
+    /// same weights with identically seeded generators gives the same model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate rand;
+    /// # extern crate rusty_machine;
+    /// use rusty_machine::learning::nnet::{NeuralNet, MSECriterion};
+    /// use rusty_machine::learning::nnet::net_layer::{Dropout, Linear};
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    /// use rusty_machine::linalg::Matrix;
+    /// use rand::{SeedableRng, StdRng};
+    ///
+    /// # fn main() {
+    /// let mut net = NeuralNet::new(MSECriterion::default(), GradientDesc::default());
+    /// net.add(Box::new(Linear::new(2, 8)))
+    ///    .add(Box::new(Dropout::new(0.5)))
+    ///    .add(Box::new(Linear::new(8, 1)));
+    ///
+    /// let inputs = Matrix::new(2, 2, vec![0.0, 1.0, 1.0, 0.0]);
+    /// let targets = Matrix::new(2, 1, vec![1.0, -1.0]);
+    /// net.train_with_rng(&inputs, &targets, &mut StdRng::from_seed(&[1])).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    /// - Early stopping is set without a validation fraction.
+    /// - The validation split leaves no training or no validation rows.
+    pub fn train_with_rng<R: Rng>(&mut self,
+                                  inputs: &Matrix<f64>,
+                                  targets: &Matrix<f64>,
+                                  rng: &mut R)
+                                  -> LearningResult<()> {
+        if inputs.rows() != targets.rows() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        self.base.dropout_seed.set(rng.gen());
+        self.base.batch_states.borrow_mut().clear();
+
+        let split;
+        let (train_inputs, train_targets) = match self.validation_fraction {
+            Some(fraction) => {
+                let (train_idx, val_idx) = validation_split(targets, fraction, rng);
+                if train_idx.is_empty() || val_idx.is_empty() {
+                    return Err(Error::new(ErrorKind::InvalidData,
+                                          "The validation split must leave both training \
+                                           and validation rows."));
+                }
+
+                *self.base.validation.borrow_mut() = Some(Validation {
+                    inputs: inputs.select_rows(&val_idx),
+                    targets: targets.select_rows(&val_idx),
+                    early_stopping: self.early_stopping,
+                    weights: Vec::new(),
+                    losses: Vec::new(),
+                    best: None,
+                });
+
+                split = (inputs.select_rows(&train_idx), targets.select_rows(&train_idx));
+                (&split.0, &split.1)
+            }
+            None => {
+                if self.early_stopping.is_some() {
+                    return Err(Error::new(ErrorKind::InvalidState,
+                                          "Early stopping requires a validation fraction."));
+                }
+                (inputs, targets)
+            }
+        };
+
+        self.base.train_losses.borrow_mut().clear();
+        let mut optimal_w = self.alg.optimize(&self.base,
+                                          &self.base.weights,
+                                          train_inputs,
+                                          train_targets);
+        self.training_loss_history = self.base.train_losses.replace(Vec::new());
+
+        if let Some(validation) = self.base.validation.replace(None) {
+            self.validation_loss_history = validation.losses;
+            self.best_epoch = validation.best.as_ref().map(|b| b.0);
+            if let (Some(_), Some((_, _, best_w))) = (validation.early_stopping, validation.best) {
+                optimal_w = best_w;
+                // The latest batch statistics do not belong to the restored weights.
+                self.base.batch_states.borrow_mut().clear();
+            }
+        } else {
+            self.validation_loss_history = Vec::new();
+            self.best_epoch = None;
+        }
+
+        // Not every algorithm constrains the parameters after each update.
+        // The state of the layers is also updated if the algorithm did not.
+        self.base.constrain_params(&mut optimal_w);
+        self.base.weights = optimal_w;
+
+        Ok(())
+    }
+}
+
+/// Split the rows into training and validation rows.
+///
+/// Rows with each class label are split separately when the targets
+/// are labels, and all rows together otherwise. Both sets of indices
+/// are returned in increasing order.
+fn validation_split<R: Rng>(targets: &Matrix<f64>,
+                            fraction: f64,
+                            rng: &mut R)
+                            -> (Vec<usize>, Vec<usize>) {
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    match class_labels(targets) {
+        Some(labels) => {
+            for (i, c) in labels.into_iter().enumerate() {
+                groups.entry(c).or_insert_with(Vec::new).push(i);
+            }
+        }
+        None => {
+            groups.insert(0, (0..targets.rows()).collect());
+        }
+    }
+
+    let mut train_idx = Vec::with_capacity(targets.rows());
+    let mut val_idx = Vec::with_capacity(targets.rows());
+    for (_, mut members) in groups {
+        in_place_fisher_yates_with_rng(&mut members, rng);
+
+        let n_val = (members.len() as f64 * fraction).round() as usize;
+        val_idx.extend_from_slice(&members[..n_val]);
+        train_idx.extend_from_slice(&members[n_val..]);
+    }
+    train_idx.sort();
+    val_idx.sort();
+
+    (train_idx, val_idx)
+}
+
+/// The class label of each row if the targets are a single column
+/// of zeros and ones, or one-hot rows.
+fn class_labels(targets: &Matrix<f64>) -> Option<Vec<usize>> {
+    if targets.data().iter().any(|&t| t != 0f64 && t != 1f64) {
+        return None;
+    }
+
+    if targets.cols() == 1 {
+        return Some(targets.data().iter().map(|&t| t as usize).collect());
+    }
+
+    targets.row_iter()
+        .map(|row| {
+            let row = row.raw_slice();
+            if row.iter().sum::<f64>() == 1f64 {
+                row.iter().position(|&t| t == 1f64)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+impl<A> NeuralNet<CategoricalCECriterion, A>
+    where A: OptimAlgorithm<BaseNeuralNet<CategoricalCECriterion>>
+{
+    /// Create a multilayer perceptron classifier with a softmax output layer.
+    ///
+    /// The layer sizes slice should include the input, hidden layers, and output layer sizes.
+    /// The output layer has one neuron per class. The activation function is applied to
+    /// the hidden layers only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::{NeuralNet, CategoricalCECriterion};
+    /// use rusty_machine::learning::toolkit::activ_fn::Relu;
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    /// use rusty_machine::linalg::{Matrix, Vector};
+    ///
+    /// // Classify 2 dimensional inputs into 3 classes.
+    /// let mut net = NeuralNet::softmax_mlp(&[2, 8, 3],
+    ///                                      CategoricalCECriterion::default(),
+    ///                                      GradientDesc::new(0.01, 100),
+    ///                                      Relu);
+    ///
+    /// let inputs = Matrix::new(3, 2, vec![0.0, 0.0, 5.0, 0.0, 0.0, 5.0]);
+    /// net.train_classes(&inputs, &Vector::new(vec![0, 1, 2])).unwrap();
+    ///
+    /// let classes = net.predict_classes(&inputs).unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - There are fewer than two layer sizes.
+    pub fn softmax_mlp<U>(layer_sizes: &[usize],
+                          criterion: CategoricalCECriterion,
+                          alg: A,
+                          activ_fn: U)
+                          -> NeuralNet<CategoricalCECriterion, A>
+        where U: ActivationFunc + 'static
+    {
+        assert!(layer_sizes.len() > 1, "The network must have at least two layer sizes.");
+
+        let mut base = BaseNeuralNet::new(criterion);
+        let n_hidden = layer_sizes.len() - 2;
+        for (i, shape) in layer_sizes.windows(2).enumerate() {
+            base.add(Box::new(net_layer::Linear::new(shape[0], shape[1])));
+            if i < n_hidden {
+                base.add(Box::new(activ_fn.clone()));
+            }
+        }
+        base.add(Box::new(net_layer::Softmax));
+
+        NeuralNet::from_base(base, alg)
+    }
+
+    /// Train the network using class labels.
+    ///
+    /// The labels are one-hot encoded with a column per network output,
+    /// so class `c` corresponds to output column `c`.
+    ///
+    /// # Failures
+    ///
+    /// - The number of inputs and targets differ.
+    /// - A label is not less than the number of network outputs.
+    pub fn train_classes(&mut self,
+                         inputs: &Matrix<f64>,
+                         targets: &Vector<usize>)
+                         -> LearningResult<()> {
+        if inputs.rows() != targets.size() {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The number of inputs and targets must match."));
+        }
+
+        let k = self.base.output_size();
+        if targets.iter().any(|&t| t >= k) {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The class labels must be less than the number of outputs."));
+        }
+
+        let mut one_hot = vec![0f64; targets.size() * k];
+        for (i, &t) in targets.iter().enumerate() {
+            one_hot[i * k + t] = 1f64;
+        }
+
+        self.train(inputs, &Matrix::new(targets.size(), k, one_hot))
+    }
+
+    /// Predict the most probable class of each input.
+    pub fn predict_classes(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<usize>> {
+        let probs = self.predict(inputs)?;
+
+        let classes = probs.row_iter()
+            .map(|row| {
+                row.raw_slice()
+                    .iter()
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY),
+                          |(a, p_a), (b, &p_b)| if p_b > p_a { (b, p_b) } else { (a, p_a) })
+                    .0
+            })
+            .collect::<Vec<usize>>();
+
+        Ok(Vector::new(classes))
+    }
 }
 
 /// Base Neural Network struct
@@ -249,6 +800,58 @@ pub struct BaseNeuralNet<T: Criterion> {
     layers: Vec<Box<dyn NetLayer>>,
     weights: Vec<f64>,
     criterion: T,
+    /// The seed of the next dropout masks, updated after each gradient.
+    dropout_seed: Cell<usize>,
+    /// The costs reported by the optimization algorithm.
+    train_losses: RefCell<Vec<f64>>,
+    /// The validation state during training.
+    validation: RefCell<Option<Validation>>,
+    /// The penalty on the weights, excluding the biases.
+    regularization: Regularization<f64>,
+    /// The maximum norm of the incoming weights of each unit.
+    max_norm: Option<f64>,
+    /// The index and batch statistics of each layer with state, from the latest gradient.
+    batch_states: RefCell<Vec<(usize, Vec<f64>)>>,
+}
+
+/// The validation data and losses of a training run.
+#[derive(Debug)]
+struct Validation {
+    inputs: Matrix<f64>,
+    targets: Matrix<f64>,
+    /// The patience and minimum improvement for early stopping.
+    early_stopping: Option<(usize, f64)>,
+    /// The latest weights, from the last gradient or parameter update.
+    weights: Vec<f64>,
+    /// The validation loss at each iteration.
+    losses: Vec<f64>,
+    /// The iteration, validation loss and weights with the lowest loss.
+    best: Option<(usize, f64, Vec<f64>)>,
+}
+
+impl Validation {
+    /// Record the validation loss of the latest weights.
+    ///
+    /// Returns true if training should stop early.
+    fn record(&mut self, loss: f64) -> bool {
+        let epoch = self.losses.len();
+        self.losses.push(loss);
+
+        let min_delta = self.early_stopping.map_or(0f64, |(_, d)| d);
+        let improved = match self.best {
+            Some((_, best_loss, _)) => loss < best_loss - min_delta,
+            None => true,
+        };
+        if improved {
+            self.best = Some((epoch, loss, self.weights.clone()));
+        }
+
+        let best_epoch = self.best.as_ref().map_or(epoch, |b| b.0);
+        match self.early_stopping {
+            Some((patience, _)) => epoch - best_epoch >= patience,
+            None => false,
+        }
+    }
 }
 
 
@@ -267,7 +870,13 @@ impl<T: Criterion> BaseNeuralNet<T> {
         BaseNeuralNet {
             layers: Vec::new(),
             weights: Vec::new(),
-            criterion: criterion
+            criterion: criterion,
+            dropout_seed: Cell::new(0),
+            train_losses: RefCell::new(Vec::new()),
+            validation: RefCell::new(None),
+            regularization: Regularization::None,
+            max_norm: None,
+            batch_states: RefCell::new(Vec::new()),
         }
     } 
 
@@ -277,7 +886,13 @@ impl<T: Criterion> BaseNeuralNet<T> {
         let mut mlp = BaseNeuralNet {
             layers: Vec::with_capacity(2*(layer_sizes.len()-1)),
             weights: Vec::new(),
-            criterion: criterion
+            criterion: criterion,
+            dropout_seed: Cell::new(0),
+            train_losses: RefCell::new(Vec::new()),
+            validation: RefCell::new(None),
+            regularization: Regularization::None,
+            max_norm: None,
+            batch_states: RefCell::new(Vec::new()),
         };
         for shape in layer_sizes.windows(2) {
             mlp.add(Box::new(net_layer::Linear::new(shape[0], shape[1])));
@@ -303,6 +918,26 @@ impl<T: Criterion> BaseNeuralNet<T> {
         self
     }
 
+    /// Reinitialize the weights of every layer using the given scheme.
+    fn init_weights<R: Rng>(&mut self, init: WeightInit, rng: &mut R) {
+        let mut weights = Vec::with_capacity(self.weights.len());
+        for layer in &self.layers {
+            weights.extend_from_slice(&layer.init_params(init, rng));
+        }
+        self.weights = weights;
+    }
+
+    /// The number of outputs of the last layer with parameters.
+    ///
+    /// Returns zero if no layer has parameters.
+    fn output_size(&self) -> usize {
+        self.layers
+            .iter()
+            .rev()
+            .find(|l| l.num_params() > 0)
+            .map_or(0, |l| l.param_shape().1)
+    }
+
     /// Gets matrix of weights for the specified layer for the weights.
     fn get_layer_weights(&self, weights: &[f64], idx: usize) -> MatrixSlice<f64> {
         debug_assert!(idx < self.layers.len());
@@ -337,6 +972,10 @@ impl<T: Criterion> BaseNeuralNet<T> {
         let mut activations = Vec::with_capacity(self.layers.len());
         // params[i] is the weights for layer[i]
         let mut params = Vec::with_capacity(self.layers.len());
+        // masks[i] is the dropout mask applied to the output of layer[i]
+        let mut masks = Vec::with_capacity(self.layers.len());
+        let mut batch_states = Vec::new();
+        let mut rng = StdRng::from_seed(&[self.dropout_seed.get()]);
 
         // Forward propagation
         
@@ -351,32 +990,55 @@ impl<T: Criterion> BaseNeuralNet<T> {
                                             shape.1)
             };
 
-            let output = if i == 0 {
-                layer.forward(inputs, slice).unwrap()
+            let input = if i == 0 { inputs } else { activations.last().unwrap() };
+            if let Some(state) = layer.batch_state(input) {
+                batch_states.push((i, state));
+            }
+            let mut output = layer.forward_train(input, slice).unwrap();
+
+            let p = layer.dropout();
+            if p > 0f64 {
+                let mask = dropout_mask(p, output.rows(), output.cols(), &mut rng);
+                output = output.elemul(&mask);
+                masks.push(Some(mask));
             } else {
-                layer.forward(activations.last().unwrap(), slice).unwrap()
-            };
+                masks.push(None);
+            }
 
             activations.push(output);
             params.push(slice);
             index += layer.num_params();
         }
         let output = activations.last().unwrap();
+        self.dropout_seed.set(rng.gen());
+        *self.batch_states.borrow_mut() = batch_states;
 
         // Backward propagation
         
         // The gradient with respect to the current layer's output
         let mut out_grad = self.criterion.cost_grad(output, targets);
+        // A fused criterion gives the gradient with respect to the final layer's input
+        let fused_layer = if self.criterion.fuses_output_layer() {
+            Some(self.layers.len() - 1)
+        } else {
+            None
+        };
         // at this point index == weights.len()
         for (i, layer) in self.layers.iter().enumerate().rev() {
             let activation = if i == 0 {inputs} else {&activations[i-1]};
             let result = &activations[i];
             index -= layer.num_params();
 
+            if let Some(ref mask) = masks[i] {
+                out_grad = out_grad.elemul(mask);
+            }
+
             let grad_params = &mut gradients[index..index+layer.num_params()];
             grad_params.copy_from_slice(layer.back_params(&out_grad, activation, result, params[i]).data());
             
-            out_grad = layer.back_input(&out_grad, activation, result, params[i]);
+            if fused_layer != Some(i) {
+                out_grad = layer.back_input(&out_grad, activation, result, params[i]);
+            }
         }
 
         let mut cost = self.criterion.cost(output, targets);
@@ -389,18 +1051,74 @@ impl<T: Criterion> BaseNeuralNet<T> {
                                        |x, &y| *x = *x + y);
             cost += self.criterion.reg_cost(all_params);
         }
+        cost += self.add_weight_penalty(weights, &mut gradients) / inputs.rows() as f64;
         (cost, gradients)
     }
 
+    /// Add the gradient of the weight penalty to `gradients` and return the penalty.
+    ///
+    /// The biases are not penalized.
+    fn add_weight_penalty(&self, weights: &[f64], gradients: &mut [f64]) -> f64 {
+        let (l1, l2) = match self.regularization {
+            Regularization::L1(l1) => (l1, 0f64),
+            Regularization::L2(l2) => (0f64, l2),
+            Regularization::ElasticNet(l1, l2) => (l1, l2),
+            Regularization::None => return 0f64,
+        };
+
+        let mut penalty = 0f64;
+        let mut index = 0;
+        for layer in &self.layers {
+            let start = index + layer.num_bias_rows() * layer.param_shape().1;
+            index += layer.num_params();
+            for (w, g) in weights[start..index].iter().zip(gradients[start..index].iter_mut()) {
+                penalty += l1 * w.abs() + 0.5 * l2 * w * w;
+                *g += l1 * w.signum() + l2 * w;
+            }
+        }
+        penalty
+    }
+
+    /// Rescale the incoming weights of each unit to the maximum norm.
+    fn apply_max_norm(&self, max_norm: f64, weights: &mut [f64]) {
+        let mut index = 0;
+        for layer in &self.layers {
+            let (rows, cols) = layer.param_shape();
+            let params = &mut weights[index..index + layer.num_params()];
+            index += layer.num_params();
+
+            for j in 0..cols {
+                let norm = (layer.num_bias_rows()..rows)
+                    .map(|i| params[i * cols + j] * params[i * cols + j])
+                    .sum::<f64>()
+                    .sqrt();
+                if norm > max_norm {
+                    let scale = max_norm / norm;
+                    for i in layer.num_bias_rows()..rows {
+                        params[i * cols + j] *= scale;
+                    }
+                }
+            }
+        }
+    }
+
     /// Forward propagation of the model weights to get the outputs.
     fn forward_prop(&self, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        self.forward_prop_with(&self.weights, inputs)
+    }
+
+    /// Forward propagation of the given weights to get the outputs.
+    fn forward_prop_with(&self,
+                         weights: &[f64],
+                         inputs: &Matrix<f64>)
+                         -> LearningResult<Matrix<f64>> {
         if self.layers.is_empty() {
             return Ok(inputs.clone());
         }
 
         let mut outputs = unsafe {
             let shape = self.layers[0].param_shape();
-            let slice = MatrixSlice::from_raw_parts(self.weights.as_ptr(),
+            let slice = MatrixSlice::from_raw_parts(weights.as_ptr(),
                                                     shape.0,
                                                     shape.1,
                                                     shape.1);
@@ -412,7 +1130,7 @@ impl<T: Criterion> BaseNeuralNet<T> {
             let shape = layer.param_shape();
 
             let slice = unsafe {
-                MatrixSlice::from_raw_parts(self.weights.as_ptr().offset(index as isize),
+                MatrixSlice::from_raw_parts(weights.as_ptr().offset(index as isize),
                                             shape.0,
                                             shape.1,
                                             shape.1)
@@ -430,6 +1148,18 @@ impl<T: Criterion> BaseNeuralNet<T> {
     }
 }
 
+/// Draw an inverted dropout mask.
+///
+/// Each entry is zero with probability `p` and `1 / (1 - p)` otherwise.
+fn dropout_mask<R: Rng>(p: f64, rows: usize, cols: usize, rng: &mut R) -> Matrix<f64> {
+    let scale = if p < 1f64 { 1f64 / (1f64 - p) } else { 0f64 };
+    let mask = (0..rows * cols)
+        .map(|_| if rng.gen::<f64>() < p { 0f64 } else { scale })
+        .collect::<Vec<f64>>();
+
+    Matrix::new(rows, cols, mask)
+}
+
 /// Compute the gradient of the Neural Network using the
 /// back propagation algorithm.
 impl<T: Criterion> Optimizable for BaseNeuralNet<T> {
@@ -442,8 +1172,51 @@ impl<T: Criterion> Optimizable for BaseNeuralNet<T> {
                     inputs: &Matrix<f64>,
                     targets: &Matrix<f64>)
                     -> (f64, Vec<f64>) {
+        if let Some(ref mut validation) = *self.validation.borrow_mut() {
+            validation.weights.clear();
+            validation.weights.extend_from_slice(params);
+        }
         self.compute_grad(params, inputs, targets)
     }
+
+    /// Records the cost and the validation loss, and stops
+    /// once the validation loss stops improving.
+    fn end_iteration(&self, _iter: usize, cost: f64) -> bool {
+        self.train_losses.borrow_mut().push(cost);
+
+        if let Some(ref mut validation) = *self.validation.borrow_mut() {
+            let loss = match self.forward_prop_with(&validation.weights, &validation.inputs) {
+                Ok(outputs) => self.criterion.cost(&outputs, &validation.targets),
+                Err(_) => return true,
+            };
+            validation.record(loss)
+        } else {
+            false
+        }
+    }
+
+    /// Updates the state of the layers with the statistics of the latest
+    /// gradient and applies the max-norm constraint, if any.
+    ///
+    /// The updated weights are kept for the validation loss, as algorithms
+    /// which update several times per iteration only report the cost after
+    /// the final update.
+    fn constrain_params(&self, params: &mut [f64]) {
+        for (i, state) in self.batch_states.replace(Vec::new()) {
+            let start = self.layers[..i].iter().map(|l| l.num_params()).sum::<usize>();
+            let end = start + self.layers[i].num_params();
+            self.layers[i].update_state(&state, &mut params[start..end]);
+        }
+
+        if let Some(max_norm) = self.max_norm {
+            self.apply_max_norm(max_norm, params);
+        }
+
+        if let Some(ref mut validation) = *self.validation.borrow_mut() {
+            validation.weights.clear();
+            validation.weights.extend_from_slice(params);
+        }
+    }
 }
 
 /// Criterion for Neural Networks
@@ -503,6 +1276,25 @@ pub trait Criterion {
     fn reg_cost_grad(&self, reg_weights: MatrixSlice<f64>) -> Matrix<f64> {
         self.regularization().reg_grad(reg_weights)
     }
+
+    /// Checks if the cost gradient is with respect to the inputs of the final layer.
+    ///
+    /// The network then skips the backward pass through the final layer,
+    /// which must have no parameters.
+    ///
+    /// Will return `false` by default.
+    fn fuses_output_layer(&self) -> bool {
+        false
+    }
+
+    /// The activation of the output layer expected by the cost function.
+    ///
+    /// Networks built by a `NetBuilder` must have a matching output layer.
+    ///
+    /// Will return `None` by default, allowing any output layer.
+    fn output_activation(&self) -> Option<OutputActivation> {
+        None
+    }
 }
 
 /// The binary cross entropy criterion.
@@ -520,6 +1312,10 @@ impl Criterion for BCECriterion {
     fn regularization(&self) -> Regularization<f64> {
         self.regularization
     }
+
+    fn output_activation(&self) -> Option<OutputActivation> {
+        Some(OutputActivation::Sigmoid)
+    }
 }
 
 /// Creates an MSE Criterion without any regularization.
@@ -561,6 +1357,10 @@ impl Criterion for MSECriterion {
     fn regularization(&self) -> Regularization<f64> {
         self.regularization
     }
+
+    fn output_activation(&self) -> Option<OutputActivation> {
+        Some(OutputActivation::Linear)
+    }
 }
 
 /// Creates an MSE Criterion without any regularization.
@@ -586,3 +1386,1053 @@ impl MSECriterion {
         MSECriterion { regularization: regularization }
     }
 }
+
+
+/// The categorical cross entropy criterion.
+///
+/// Uses a final `Softmax` layer and the categorical cross entropy
+/// error. The backward pass through the softmax is fused into the
+/// cost, so the gradient of the softmax inputs is computed directly
+/// as the difference between the outputs and the one-hot targets.
+///
+/// The network must end with a `Softmax` layer, as in the networks
+/// built by `NeuralNet::softmax_mlp`. The targets are either a
+/// one-hot `Matrix<f64>` with a column per class, or class
+/// labels passed to `NeuralNet::train_classes`.
+#[derive(Clone, Copy, Debug)]
+pub struct CategoricalCECriterion {
+    regularization: Regularization<f64>,
+}
+
+impl Criterion for CategoricalCECriterion {
+    type Cost = cost_fn::CategoricalCrossEntropy;
+
+    fn regularization(&self) -> Regularization<f64> {
+        self.regularization
+    }
+
+    fn fuses_output_layer(&self) -> bool {
+        true
+    }
+
+    fn output_activation(&self) -> Option<OutputActivation> {
+        Some(OutputActivation::Softmax)
+    }
+}
+
+/// Creates a categorical cross entropy criterion without any regularization.
+impl Default for CategoricalCECriterion {
+    fn default() -> Self {
+        CategoricalCECriterion { regularization: Regularization::None }
+    }
+}
+
+impl CategoricalCECriterion {
+    /// Constructs a new CategoricalCECriterion with the given regularization.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::nnet::CategoricalCECriterion;
+    /// use rusty_machine::learning::toolkit::regularization::Regularization;
+    ///
+    /// // Create a new categorical cross entropy criterion with L2 regularization of 0.3.
+    /// let criterion = CategoricalCECriterion::new(Regularization::L2(0.3f64));
+    /// ```
+    pub fn new(regularization: Regularization<f64>) -> Self {
+        CategoricalCECriterion { regularization: regularization }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BaseNeuralNet, BCECriterion, CategoricalCECriterion, Criterion, MSECriterion,
+                NeuralNet, dropout_mask, validation_split};
+    use super::builder::NetBuilder;
+    use super::net_layer::{BatchNorm, Conv1D, Dropout, Linear, MaxPool1D, NetLayer, Softmax,
+                           WeightInit};
+    use learning::SupModel;
+    use learning::optim::fmincg::ConjugateGD;
+    use learning::optim::grad_desc::{Adam, GradientDesc, StochasticGD};
+    use learning::optim::lbfgs::LBFGS;
+    use learning::toolkit::activ_fn::{self, Elu, LeakyRelu, Relu, Sigmoid, Tanh};
+    use learning::toolkit::grad_check::{grad_check, grad_check_fn, step_size, tolerance};
+    use learning::toolkit::regularization::Regularization;
+    use linalg::{BaseMatrix, Matrix, MatrixSlice, Vector};
+    use rand::{Rng, SeedableRng, StdRng};
+    use rand::distributions::Sample;
+    use rand::distributions::normal::Normal;
+
+    /// Check the gradient of the network against central differences.
+    ///
+    /// Uses a single sample so the cost and gradient are on the same scale.
+    fn gradient_check<T: Criterion>(net: &BaseNeuralNet<T>, inputs: &Matrix<f64>,
+                                    targets: &Matrix<f64>) {
+        let mut rng = StdRng::from_seed(&[5]);
+        let weights = (0..net.weights.len())
+            .map(|_| rng.gen_range(-1.0, 1.0))
+            .collect::<Vec<f64>>();
+
+        let error = grad_check(net, &weights, inputs, targets, weights.len(), &mut rng);
+        assert!(error < tolerance(step_size(1.0)), "Gradient error {}", error);
+    }
+
+    #[test]
+    fn test_rectified_gradient_check() {
+        let mut net = BaseNeuralNet::new(MSECriterion::default());
+        net.add(Box::new(Linear::new(3, 5)))
+            .add(Box::new(Relu))
+            .add(Box::new(Linear::new(5, 4)))
+            .add(Box::new(LeakyRelu::new(0.1)))
+            .add(Box::new(Linear::new(4, 3)))
+            .add(Box::new(Elu::new(0.5)))
+            .add(Box::new(Linear::new(3, 2)));
+
+        let inputs = Matrix::new(1, 3, vec![0.3, -1.2, 0.8]);
+        let targets = Matrix::new(1, 2, vec![0.5, 1.0]);
+        gradient_check(&net, &inputs, &targets);
+    }
+
+    #[test]
+    fn test_relu_network_learns_xor() {
+        let inputs = Matrix::new(4, 2, vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+        let targets = Matrix::new(4, 1, vec![0.0, 1.0, 1.0, 0.0]);
+
+        let mut model = NeuralNet::new(BCECriterion::default(), GradientDesc::new(0.3, 1000));
+        model.add(Box::new(Linear::new(2, 32)))
+            .add(Box::new(Relu))
+            .add(Box::new(Linear::new(32, 1)))
+            .add(Box::new(Sigmoid));
+        model.train(&inputs, &targets).unwrap();
+
+        let outputs = model.predict(&inputs).unwrap();
+        for (o, t) in outputs.data().iter().zip(targets.data().iter()) {
+            assert_eq!(*o > 0.5, *t == 1.0);
+        }
+    }
+
+    #[test]
+    fn test_adam_network_learns_xor() {
+        let inputs = Matrix::new(4, 2, vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+        let targets = Matrix::new(4, 1, vec![0.0, 1.0, 1.0, 0.0]);
+
+        // The default settings need no tuning of the learning rate.
+        let mut model = NeuralNet::mlp(&[2, 8, 1],
+                                       BCECriterion::default(),
+                                       Adam::default(),
+                                       Sigmoid);
+        let mut rng = StdRng::from_seed(&[3]);
+        model.init_weights(WeightInit::Xavier, &mut rng);
+        model.train_with_rng(&inputs, &targets, &mut rng).unwrap();
+
+        let outputs = model.predict(&inputs).unwrap();
+        for (o, t) in outputs.data().iter().zip(targets.data().iter()) {
+            assert_eq!(*o > 0.5, *t == 1.0);
+        }
+    }
+
+    #[test]
+    fn test_mlp_with_parameterized_activation() {
+        let inputs = Matrix::new(3, 2, vec![-1.0, 2.0, 0.5, 0.5, 3.0, -2.0]);
+
+        let model = NeuralNet::mlp(&[2, 4, 3],
+                                   MSECriterion::default(),
+                                   GradientDesc::default(),
+                                   LeakyRelu::new(0.2));
+        let outputs = model.predict(&inputs).unwrap();
+
+        assert_eq!(outputs.rows(), 3);
+        assert_eq!(outputs.cols(), 3);
+    }
+
+    #[test]
+    fn test_softmax_rows_sum_to_one() {
+        // Large scores overflow without subtracting the row maximum.
+        let inputs = Matrix::new(3, 3, vec![1.0, 2.0, 3.0,
+                                            1000.0, 1001.0, 999.0,
+                                            -5.0, -5.0, -5.0]);
+        let mut net = BaseNeuralNet::new(CategoricalCECriterion::default());
+        net.add(Box::new(Softmax));
+        let outputs = net.forward_prop(&inputs).unwrap();
+
+        for row in outputs.row_iter() {
+            assert!(row.raw_slice().iter().all(|p| p.is_finite() && *p > 0.0));
+            assert!((row.raw_slice().iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        }
+        assert!((outputs[[2, 0]] - 1.0 / 3.0).abs() < 1e-12);
+        assert!(outputs[[1, 1]] > outputs[[1, 0]]);
+    }
+
+    #[test]
+    fn test_softmax_cross_entropy_gradient_check() {
+        let mut net = BaseNeuralNet::new(CategoricalCECriterion::default());
+        net.add(Box::new(Linear::new(3, 4)))
+            .add(Box::new(Tanh))
+            .add(Box::new(Linear::new(4, 3)))
+            .add(Box::new(Softmax));
+
+        let inputs = Matrix::new(1, 3, vec![0.3, -1.2, 0.8]);
+        let targets = Matrix::new(1, 3, vec![0.0, 1.0, 0.0]);
+        gradient_check(&net, &inputs, &targets);
+    }
+
+    #[test]
+    fn test_softmax_jacobian_gradient_check() {
+        // Without the fused criterion the full softmax Jacobian is used.
+        let mut net = BaseNeuralNet::new(MSECriterion::default());
+        net.add(Box::new(Linear::new(3, 3)))
+            .add(Box::new(Softmax));
+
+        let inputs = Matrix::new(1, 3, vec![0.3, -1.2, 0.8]);
+        let targets = Matrix::new(1, 3, vec![0.2, 0.5, 0.3]);
+        gradient_check(&net, &inputs, &targets);
+    }
+
+    #[test]
+    fn test_softmax_mlp_classes() {
+        let inputs = Matrix::new(6, 2, vec![0.0, 0.0, 0.2, 0.1,
+                                            3.0, 0.0, 3.1, 0.2,
+                                            0.0, 3.0, 0.1, 3.2]);
+        let targets = Vector::new(vec![0, 0, 1, 1, 2, 2]);
+
+        let mut model = NeuralNet::softmax_mlp(&[2, 3],
+                                               CategoricalCECriterion::default(),
+                                               GradientDesc::new(0.05, 500),
+                                               Sigmoid);
+        model.train_classes(&inputs, &targets).unwrap();
+
+        let probs = model.predict(&inputs).unwrap();
+        assert_eq!(probs.cols(), 3);
+        assert_eq!(model.predict_classes(&inputs).unwrap(), targets);
+
+        assert!(model.train_classes(&inputs, &Vector::new(vec![0, 1, 2, 3, 0, 1])).is_err());
+        assert!(model.train_classes(&inputs, &Vector::new(vec![0, 1])).is_err());
+    }
+
+    #[test]
+    fn test_full_dropout_zeros_outputs() {
+        let mask = dropout_mask(1.0, 2, 3, &mut StdRng::from_seed(&[1]));
+        assert!(mask.data().iter().all(|&m| m == 0.0));
+
+        let mask = dropout_mask(0.5, 2, 3, &mut StdRng::from_seed(&[1]));
+        assert!(mask.data().iter().all(|&m| m == 0.0 || m == 2.0));
+
+        let mut net = BaseNeuralNet::new(MSECriterion::default());
+        net.add(Box::new(Linear::new(2, 3)))
+            .add(Box::new(Dropout::new(1.0)));
+
+        let inputs = Matrix::new(2, 2, vec![1.0, 2.0, -1.0, 0.5]);
+        let targets = Matrix::new(2, 3, vec![1.0, 0.0, 2.0, 0.0, 1.0, 1.0]);
+
+        // Every training output is dropped so no gradient reaches the weights.
+        let weights = net.weights.clone();
+        let (cost, grad) = net.compute_grad(&weights, &inputs, &targets);
+        assert_eq!(cost, MSECriterion::default().cost(&Matrix::zeros(2, 3), &targets));
+        assert!(grad.iter().all(|&g| g == 0.0));
+
+        // Prediction never applies dropout.
+        let mut dropout_only = BaseNeuralNet::new(MSECriterion::default());
+        dropout_only.add(Box::new(Dropout::new(1.0)));
+        assert_eq!(dropout_only.forward_prop(&inputs).unwrap(), inputs);
+    }
+
+    #[test]
+    fn test_dropout_masks_are_reproducible() {
+        let first = dropout_mask(0.5, 3, 4, &mut StdRng::from_seed(&[1]));
+        assert_eq!(first, dropout_mask(0.5, 3, 4, &mut StdRng::from_seed(&[1])));
+
+        let inputs = Matrix::new(4, 2, vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+        let targets = Matrix::new(4, 1, vec![0.0, 1.0, 1.0, 0.0]);
+
+        let build = || {
+            let mut net = NeuralNet::new(MSECriterion::default(), GradientDesc::new(0.1, 20));
+            net.add(Box::new(Linear::new(2, 6)))
+                .add(Box::new(Dropout::new(0.5)))
+                .add(Box::new(Linear::new(6, 1)));
+            net
+        };
+
+        let mut a = build();
+        let mut b = build();
+        let mut c = build();
+        b.base.weights = a.base.weights.clone();
+        c.base.weights = a.base.weights.clone();
+
+        a.train_with_rng(&inputs, &targets, &mut StdRng::from_seed(&[4])).unwrap();
+        b.train_with_rng(&inputs, &targets, &mut StdRng::from_seed(&[4])).unwrap();
+        c.train_with_rng(&inputs, &targets, &mut StdRng::from_seed(&[5])).unwrap();
+
+        assert_eq!(a.base.weights, b.base.weights);
+        assert!(a.base.weights != c.base.weights);
+    }
+
+    /// Noisy linear targets depending on the first three of twenty features.
+    fn noisy_linear_data(n: usize, rng: &mut StdRng) -> (Matrix<f64>, Matrix<f64>) {
+        let mut normal = Normal::new(0.0, 1.0);
+        let inputs = (0..n * 20).map(|_| normal.sample(rng)).collect::<Vec<f64>>();
+        let targets = inputs.chunks(20)
+            .map(|x| x[0] - x[1] + 0.5 * x[2] + 2.0 * normal.sample(rng))
+            .collect::<Vec<f64>>();
+
+        (Matrix::new(n, 20, inputs), Matrix::new(n, 1, targets))
+    }
+
+    #[test]
+    fn test_dropout_reduces_overfitting() {
+        let mut rng = StdRng::from_seed(&[7]);
+        let (inputs, targets) = noisy_linear_data(20, &mut rng);
+        let (val_inputs, val_targets) = noisy_linear_data(200, &mut rng);
+
+        let mut plain = NeuralNet::new(MSECriterion::default(), GradientDesc::new(0.01, 3000));
+        plain.add(Box::new(Linear::new(20, 1)));
+
+        let mut dropout = NeuralNet::new(MSECriterion::default(), GradientDesc::new(0.01, 3000));
+        dropout.add(Box::new(Dropout::new(0.3)))
+            .add(Box::new(Linear::new(20, 1)));
+        dropout.base.weights = plain.base.weights.clone();
+
+        plain.train(&inputs, &targets).unwrap();
+        dropout.train_with_rng(&inputs, &targets, &mut StdRng::from_seed(&[3])).unwrap();
+
+        let criterion = MSECriterion::default();
+        let plain_err = criterion.cost(&plain.predict(&val_inputs).unwrap(), &val_targets);
+        let dropout_err = criterion.cost(&dropout.predict(&val_inputs).unwrap(), &val_targets);
+        assert!(dropout_err < plain_err);
+    }
+
+    #[test]
+    fn test_validation_split_stratified() {
+        let labels = (0..20).map(|i| if i < 6 { 0 } else if i < 10 { 1 } else { 2 });
+        let mut one_hot = Vec::new();
+        for c in labels {
+            one_hot.extend((0..3).map(|j| if j == c { 1.0 } else { 0.0 }));
+        }
+        let targets = Matrix::new(20, 3, one_hot);
+
+        let (train_idx, val_idx) = validation_split(&targets, 0.5, &mut StdRng::from_seed(&[2]));
+        assert_eq!(val_idx.iter().filter(|&&i| i < 6).count(), 3);
+        assert_eq!(val_idx.iter().filter(|&&i| i >= 6 && i < 10).count(), 2);
+        assert_eq!(val_idx.len(), 10);
+
+        let mut all = train_idx.iter().chain(val_idx.iter()).cloned().collect::<Vec<usize>>();
+        all.sort();
+        assert_eq!(all, (0..20).collect::<Vec<usize>>());
+
+        // A single column of labels is also stratified.
+        let binary = Matrix::new(8, 1, vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+        let (_, val_idx) = validation_split(&binary, 0.5, &mut StdRng::from_seed(&[2]));
+        assert_eq!(val_idx.iter().filter(|&&i| i >= 6).count(), 1);
+
+        let real = Matrix::new(5, 1, vec![0.5, 1.5, 2.5, 3.5, 4.5]);
+        let (train_idx, val_idx) = validation_split(&real, 0.4, &mut StdRng::from_seed(&[2]));
+        assert_eq!((train_idx.len(), val_idx.len()), (3, 2));
+    }
+
+    #[test]
+    fn test_early_stopping_restores_best_weights() {
+        let (inputs, targets) = noisy_linear_data(40, &mut StdRng::from_seed(&[8]));
+
+        let mut model = NeuralNet::new(MSECriterion::default(), GradientDesc::new(0.002, 3000));
+        model.add(Box::new(Linear::new(20, 1)));
+        model.set_validation_fraction(0.5);
+        model.set_early_stopping(50, 0.0);
+        model.train_with_rng(&inputs, &targets, &mut StdRng::from_seed(&[9])).unwrap();
+
+        // The training stops before the iteration limit.
+        let losses = model.validation_loss_history().to_vec();
+        assert!(losses.len() < 3000);
+        assert_eq!(model.training_loss_history().len(), losses.len());
+
+        let best = model.best_epoch().unwrap();
+        assert_eq!(losses.len(), best + 51);
+        assert!(losses[best] < *losses.last().unwrap());
+
+        // The restored weights have the best validation loss.
+        let mut rng = StdRng::from_seed(&[9]);
+        let _: usize = rng.gen();
+        let (_, val_idx) = validation_split(&targets, 0.5, &mut rng);
+        let val_inputs = inputs.select_rows(&val_idx);
+        let val_targets = targets.select_rows(&val_idx);
+
+        let outputs = model.predict(&val_inputs).unwrap();
+        assert_eq!(MSECriterion::default().cost(&outputs, &val_targets), losses[best]);
+    }
+
+    #[test]
+    fn test_validation_loss_uses_updated_weights() {
+        let (inputs, targets) = noisy_linear_data(40, &mut StdRng::from_seed(&[8]));
+
+        // Stochastic gradient descent updates the weights after each batch.
+        let mut model = NeuralNet::new(MSECriterion::default(), StochasticGD::new(0.1, 0.001, 5));
+        model.add(Box::new(Linear::new(20, 1)));
+        model.set_validation_fraction(0.5);
+        model.train_with_rng(&inputs, &targets, &mut StdRng::from_seed(&[9])).unwrap();
+
+        let mut rng = StdRng::from_seed(&[9]);
+        let _: usize = rng.gen();
+        let (_, val_idx) = validation_split(&targets, 0.5, &mut rng);
+        let val_inputs = inputs.select_rows(&val_idx);
+        let val_targets = targets.select_rows(&val_idx);
+
+        // The last validation loss is that of the trained weights.
+        let losses = model.validation_loss_history();
+        assert_eq!(losses.len(), 5);
+        let outputs = model.predict(&val_inputs).unwrap();
+        assert_eq!(MSECriterion::default().cost(&outputs, &val_targets), losses[4]);
+    }
+
+    #[test]
+    fn test_loss_histories_without_validation() {
+        let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+        let targets = Matrix::new(3, 1, vec![0.5, 1.0, 1.5]);
+
+        let mut model = NeuralNet::new(MSECriterion::default(), GradientDesc::new(0.01, 10));
+        model.add(Box::new(Linear::new(1, 1)));
+        model.train(&inputs, &targets).unwrap();
+
+        assert_eq!(model.training_loss_history().len(), 10);
+        assert!(model.validation_loss_history().is_empty());
+        assert!(model.best_epoch().is_none());
+
+        // Early stopping needs validation rows.
+        model.set_early_stopping(5, 0.0);
+        assert!(model.train(&inputs, &targets).is_err());
+        assert!(model.train(&inputs, &Matrix::new(2, 1, vec![0.5, 1.0])).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_validation_fraction() {
+        let mut model = NeuralNet::new(MSECriterion::default(), GradientDesc::default());
+        model.set_validation_fraction(1.0);
+    }
+
+    #[test]
+    fn test_init_weights_reproducible() {
+        let layers = &[3, 5, 2];
+        let new_net = || {
+            NeuralNet::mlp(layers, MSECriterion::default(), GradientDesc::default(), Relu)
+        };
+        let mut first = new_net();
+        let mut second = new_net();
+
+        first.init_weights(WeightInit::He, &mut StdRng::from_seed(&[4]));
+        second.init_weights(WeightInit::He, &mut StdRng::from_seed(&[4]));
+        assert_eq!(first.base.weights, second.base.weights);
+
+        second.init_weights(WeightInit::He, &mut StdRng::from_seed(&[5]));
+        assert!(first.base.weights != second.base.weights);
+
+        second.init_weights(WeightInit::Uniform(0.1), &mut StdRng::from_seed(&[4]));
+        assert!(second.base.weights.iter().all(|w| w.abs() <= 0.1));
+
+        // The first row of each linear layer holds the biases.
+        for &idx in &[0, 2] {
+            for net in &[&first, &second] {
+                let w = net.get_net_weights(idx);
+                let biases = w.row_iter().next().unwrap();
+                assert!(biases.raw_slice().iter().all(|&b| b == 0.0));
+                assert!(w.iter().any(|&x| x != 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_init_weights_keep_activation_variance() {
+        let mut rng = StdRng::from_seed(&[6]);
+        let mut distro = Normal::new(0.0, 1.0);
+        let data = (0..6400).map(|_| distro.sample(&mut rng)).collect::<Vec<f64>>();
+        let inputs = Matrix::new(100, 64, data);
+
+        // He initialization with rectified linear activations and Xavier
+        // initialization without activations both preserve the variance.
+        for &init in &[WeightInit::He, WeightInit::Xavier] {
+            let mut net = NeuralNet::new(MSECriterion::default(), GradientDesc::default());
+            for _ in 0..5 {
+                net.add(Box::new(Linear::new(64, 64)));
+                if init == WeightInit::He {
+                    net.add(Box::new(Relu));
+                }
+            }
+            net.init_weights(init, &mut rng);
+
+            let mut variances = Vec::new();
+            let mut outputs = inputs.clone();
+            for (i, layer) in net.base.layers.iter().enumerate() {
+                outputs = layer.forward(&outputs, net.get_net_weights(i)).unwrap();
+                if layer.num_params() > 0 {
+                    let n = outputs.data().len() as f64;
+                    let mean = outputs.sum() / n;
+                    let sq_dev = outputs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>();
+                    variances.push(sq_dev / n);
+                }
+            }
+
+            for v in &variances {
+                let ratio = v / variances[0];
+                assert!(ratio > 1.0 / 3.0 && ratio < 3.0);
+            }
+        }
+    }
+
+    fn deep_relu_net() -> NeuralNet<MSECriterion, GradientDesc> {
+        let mut net = NeuralNet::new(MSECriterion::default(), GradientDesc::new(0.002, 40));
+        net.add(Box::new(Linear::new(1, 16)));
+        for _ in 0..3 {
+            net.add(Box::new(Relu))
+               .add(Box::new(Linear::new(16, 16)));
+        }
+        net.add(Box::new(Relu))
+           .add(Box::new(Linear::new(16, 1)));
+        net
+    }
+
+    #[test]
+    fn test_he_init_trains_deep_relu_network() {
+        let data = (0..16).map(|i| -2.0 + 4.0 * i as f64 / 15.0).collect::<Vec<f64>>();
+        let inputs = Matrix::new(16, 1, data);
+        let targets = inputs.clone().apply(&|x: f64| x.abs());
+
+        // The mean loss over several networks after a few iterations
+        let mean_loss = |init: Option<WeightInit>| {
+            (0..5)
+                .map(|seed| {
+                    let mut net = deep_relu_net();
+                    if let Some(init) = init {
+                        net.init_weights(init, &mut StdRng::from_seed(&[seed]));
+                    }
+                    net.train(&inputs, &targets).unwrap();
+                    let outputs = net.predict(&inputs).unwrap();
+                    MSECriterion::default().cost(&outputs, &targets)
+                })
+                .sum::<f64>() / 5.0
+        };
+
+        // The default initialization shrinks the activations in each layer.
+        assert!(mean_loss(Some(WeightInit::He)) < 0.03);
+        assert!(mean_loss(None) > 0.03);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_init_weights_invalid_uniform_scale() {
+        let mut net = NeuralNet::mlp(&[2, 2],
+                                     MSECriterion::default(),
+                                     GradientDesc::default(),
+                                     Relu);
+        net.init_weights(WeightInit::Uniform(0.0), &mut StdRng::from_seed(&[1]));
+    }
+
+    #[test]
+    fn test_weights_round_trip() {
+        let inputs = Matrix::new(4, 2, vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+        let targets = Matrix::new(4, 1, vec![0.0, 1.0, 1.0, 0.0]);
+
+        let mut trained = NeuralNet::mlp(&[2, 3, 1], BCECriterion::default(),
+                                         GradientDesc::new(0.1, 100), Sigmoid);
+        trained.train(&inputs, &targets).unwrap();
+
+        let mut loaded = NeuralNet::mlp(&[2, 3, 1], BCECriterion::default(),
+                                        GradientDesc::default(), Sigmoid);
+        loaded.set_weights(trained.weights()).unwrap();
+
+        assert_eq!(loaded.weights(), trained.weights());
+        assert_eq!(loaded.predict(&inputs).unwrap(), trained.predict(&inputs).unwrap());
+    }
+
+    #[test]
+    fn test_set_weights_untrained() {
+        let mut net = NeuralNet::new(MSECriterion::default(), GradientDesc::default());
+        net.add(Box::new(Linear::new(2, 1)));
+
+        // The first row holds the bias.
+        net.set_weights(vec![Matrix::new(3, 1, vec![0.5, 1.0, 2.0])]).unwrap();
+
+        let outputs = net.predict(&Matrix::new(2, 2, vec![1.0, 0.0, 1.0, 1.0])).unwrap();
+        assert_eq!(outputs.into_vec(), vec![1.5, 3.5]);
+    }
+
+    #[test]
+    fn test_set_weights_shape_mismatch() {
+        let mut net = NeuralNet::default(&[2, 3, 1]);
+        let weights = net.weights();
+
+        assert!(net.set_weights(weights[..1].to_vec()).is_err());
+        assert!(net.set_weights(vec![weights[0].transpose(), weights[1].clone()]).is_err());
+        assert!(net.set_weights(vec![weights[0].clone(), weights[1].clone(),
+                                     Matrix::new(1, 1, vec![1.0])])
+            .is_err());
+
+        // The weights are unchanged after a failure.
+        assert_eq!(net.weights(), weights);
+    }
+
+    /// The norm of the weights of a layer without its bias row.
+    fn weight_norm(weights: &Matrix<f64>) -> f64 {
+        weights.iter().skip(weights.cols()).map(|w| w * w).sum::<f64>().sqrt()
+    }
+
+    #[test]
+    fn test_l2_regularization_shrinks_weights() {
+        let mut rng = StdRng::from_seed(&[11]);
+        let (inputs, targets) = noisy_linear_data(20, &mut rng);
+
+        let mut plain = NeuralNet::new(MSECriterion::default(), GradientDesc::new(0.01, 1000));
+        plain.add(Box::new(Linear::new(20, 1)));
+
+        let mut penalized = NeuralNet::new(MSECriterion::default(),
+                                           GradientDesc::new(0.01, 1000));
+        penalized.add(Box::new(Linear::new(20, 1)));
+        penalized.set_weights(plain.weights()).unwrap();
+        penalized.set_regularization(Regularization::L2(20.0));
+
+        plain.train(&inputs, &targets).unwrap();
+        penalized.train(&inputs, &targets).unwrap();
+
+        let plain_norm = weight_norm(&plain.weights()[0]);
+        let penalized_norm = weight_norm(&penalized.weights()[0]);
+        assert!(penalized_norm < 0.5 * plain_norm,
+                "Penalized norm {} is not much smaller than {}", penalized_norm, plain_norm);
+
+        // The penalized cost still decreases.
+        let losses = penalized.training_loss_history();
+        assert!(losses.windows(2).all(|w| w[1] <= w[0]));
+        assert!(losses.last().unwrap() < &losses[0]);
+    }
+
+    #[test]
+    fn test_max_norm_after_each_epoch() {
+        let mut rng = StdRng::from_seed(&[12]);
+        let (inputs, targets) = noisy_linear_data(20, &mut rng);
+
+        let initial = vec![Matrix::new(21, 3, vec![1.0; 63]), Matrix::new(4, 1, vec![2.0; 4])];
+        for iters in 1..6 {
+            let mut net = NeuralNet::new(MSECriterion::default(),
+                                         GradientDesc::new(0.01, iters));
+            net.add(Box::new(Linear::new(20, 3)))
+                .add(Box::new(Sigmoid))
+                .add(Box::new(Linear::new(3, 1)));
+            net.set_weights(initial.clone()).unwrap();
+            net.set_max_norm(0.5);
+            net.train(&inputs, &targets).unwrap();
+
+            for weights in net.weights() {
+                for j in 0..weights.cols() {
+                    let norm = (1..weights.rows())
+                        .map(|i| weights[[i, j]] * weights[[i, j]])
+                        .sum::<f64>()
+                        .sqrt();
+                    assert!(norm <= 0.5 + 1e-12, "Norm {} after {} epochs", norm, iters);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_weight_penalty_excludes_biases() {
+        let mut net = NeuralNet::default(&[2, 2]);
+        net.set_weights(vec![Matrix::new(3, 2, vec![5.0, -5.0, 1.0, 2.0, -2.0, 0.0])]).unwrap();
+        net.set_regularization(Regularization::L2(1.0));
+
+        let inputs = Matrix::new(1, 2, vec![0.0, 0.0]);
+        let targets = Matrix::new(1, 2, vec![0.0, 0.0]);
+        let weights = net.base.weights.clone();
+        let (penalized, grad) = net.base.compute_grad(&weights, &inputs, &targets);
+
+        net.set_regularization(Regularization::None);
+        let (plain, plain_grad) = net.base.compute_grad(&weights, &inputs, &targets);
+
+        assert!((penalized - plain - 4.5).abs() < 1e-12);
+        assert_eq!(&grad[..2], &plain_grad[..2]);
+        assert_eq!(grad[2] - plain_grad[2], 1.0);
+        assert_eq!(grad[5] - plain_grad[5], 0.0);
+    }
+
+    #[test]
+    fn test_weight_penalty_gradient_check() {
+        let mut net = BaseNeuralNet::new(MSECriterion::default());
+        net.add(Box::new(Linear::new(3, 4)))
+            .add(Box::new(Tanh))
+            .add(Box::new(Linear::new(4, 2)));
+        net.regularization = Regularization::ElasticNet(0.3, 0.5);
+
+        let inputs = Matrix::new(1, 3, vec![0.3, -1.2, 0.8]);
+        let targets = Matrix::new(1, 2, vec![0.5, 1.0]);
+        gradient_check(&net, &inputs, &targets);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_max_norm() {
+        let mut net = NeuralNet::default(&[2, 2]);
+        net.set_max_norm(0.0);
+    }
+
+    #[test]
+    fn test_builder_regression() {
+        let inputs = Matrix::new(11, 1, (0..11).map(|i| i as f64 / 10.0).collect::<Vec<f64>>());
+        let targets = inputs.clone().apply(&|x: f64| 2.0 * x + 1.0);
+
+        let mut net = NetBuilder::new(1)
+            .layer(16, Relu)
+            .output(1, activ_fn::Linear)
+            .build(MSECriterion::default(), GradientDesc::new(0.01, 1000))
+            .unwrap();
+        net.train(&inputs, &targets).unwrap();
+
+        let outputs = net.predict(&inputs).unwrap();
+        for (y, t) in outputs.iter().zip(targets.iter()) {
+            assert!((y - t).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn test_builder_gradient_check() {
+        let net = NetBuilder::new(3)
+            .layer(5, Relu)
+            .layer(4, Tanh)
+            .output(2, Sigmoid)
+            .build(BCECriterion::default(), GradientDesc::default())
+            .unwrap();
+
+        let inputs = Matrix::new(1, 3, vec![0.3, -1.2, 0.8]);
+        let targets = Matrix::new(1, 2, vec![1.0, 0.0]);
+        gradient_check(&net.base, &inputs, &targets);
+    }
+
+    #[test]
+    fn test_builder_output_checks() {
+        let builder = || NetBuilder::new(2).layer(4, Tanh);
+
+        assert!(builder().build(MSECriterion::default(), GradientDesc::default()).is_err());
+        assert!(builder()
+            .output(1, Sigmoid)
+            .build(MSECriterion::default(), GradientDesc::default())
+            .is_err());
+        assert!(builder()
+            .output(1, activ_fn::Linear)
+            .build(BCECriterion::default(), GradientDesc::default())
+            .is_err());
+        assert!(builder()
+            .output(3, Sigmoid)
+            .build(CategoricalCECriterion::default(), GradientDesc::default())
+            .is_err());
+        assert!(builder()
+            .output(1, Softmax)
+            .build(CategoricalCECriterion::default(), GradientDesc::default())
+            .is_err());
+
+        let net = builder()
+            .output(3, Softmax)
+            .build(CategoricalCECriterion::default(), GradientDesc::default())
+            .unwrap();
+        let outputs = net.predict(&Matrix::new(1, 2, vec![0.5, -0.5])).unwrap();
+        assert_eq!(outputs.cols(), 3);
+        assert!((outputs.sum() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_conv1d_forward() {
+        // Two positions of a sequence of length three with two channels.
+        let mut net = BaseNeuralNet::new(MSECriterion::default());
+        net.add(Box::new(Conv1D::new(3, 2, 2, 2, 1)));
+
+        // The first filter picks the first channel of the window and the
+        // second filter sums the window.
+        let weights = vec![0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0];
+        let inputs = Matrix::new(2, 6, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0,
+                                            0.0, 0.0, 0.0, 0.0, -1.0, 1.0]);
+
+        let outputs = net.forward_prop_with(&weights, &inputs).unwrap();
+        assert_eq!(outputs.rows(), 2);
+        assert_eq!(outputs.cols(), 4);
+        assert_eq!(outputs.into_vec(), vec![1.0, 11.0, 3.0, 19.0, 0.0, 1.0, 0.0, 1.0]);
+
+        assert!(net.forward_prop_with(&weights, &Matrix::new(1, 4, vec![0.0; 4])).is_err());
+    }
+
+    #[test]
+    fn test_max_pool1d_forward_backward() {
+        // The last position does not fill a window.
+        let pool = MaxPool1D::new(5, 2, 2);
+        let inputs = Matrix::new(1, 10, vec![1.0, -1.0, 3.0, -4.0, 2.0, 5.0, 0.0, 0.0, 9.0, 9.0]);
+        let empty = Matrix::zeros(1, 1);
+        let params = MatrixSlice::from_matrix(&empty, [0, 0], 0, 0);
+
+        let outputs = pool.forward(&inputs, params).unwrap();
+        assert_eq!(outputs.data(), &vec![3.0, -1.0, 2.0, 5.0]);
+
+        let out_grad = Matrix::new(1, 4, vec![1.0, 2.0, 3.0, 4.0]);
+        let in_grad = pool.back_input(&out_grad, &inputs, &outputs, params);
+        assert_eq!(in_grad.into_vec(), vec![0.0, 2.0, 1.0, 0.0, 3.0, 4.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_conv1d_gradient_check() {
+        // The windows of the convolution overlap and the pooling drops a position.
+        let mut net = BaseNeuralNet::new(MSECriterion::default());
+        net.add(Box::new(Conv1D::new(7, 2, 3, 3, 2)))
+            .add(Box::new(Tanh))
+            .add(Box::new(MaxPool1D::new(3, 3, 2)))
+            .add(Box::new(Linear::new(3, 2)));
+
+        let inputs = Matrix::new(1, 14, (0..14).map(|i| ((i * 5) % 7) as f64 / 3.0 - 1.0)
+            .collect::<Vec<f64>>());
+        let targets = Matrix::new(1, 2, vec![0.5, -1.0]);
+        gradient_check(&net, &inputs, &targets);
+    }
+
+    #[test]
+    fn test_builder_sequence_shapes() {
+        let net = NetBuilder::sequence(10, 2)
+            .conv1d(3, 3, 2, Relu)
+            .max_pool1d(2)
+            .flatten()
+            .layer(4, Tanh)
+            .output(1, Sigmoid)
+            .build(BCECriterion::default(), GradientDesc::default())
+            .unwrap();
+        let outputs = net.predict(&Matrix::zeros(5, 20)).unwrap();
+        assert_eq!(outputs.rows(), 5);
+        assert_eq!(outputs.cols(), 1);
+
+        let build = |builder: NetBuilder| {
+            builder.output(1, Sigmoid).build(BCECriterion::default(), GradientDesc::default())
+        };
+        assert!(build(NetBuilder::new(10).conv1d(3, 3, 1, Relu)).is_err());
+        assert!(build(NetBuilder::new(10).max_pool1d(2)).is_err());
+        assert!(build(NetBuilder::new(10).flatten()).is_err());
+        assert!(build(NetBuilder::sequence(10, 1).layer(4, Relu)).is_err());
+        assert!(build(NetBuilder::sequence(10, 1).conv1d(3, 3, 1, Relu)).is_err());
+        assert!(build(NetBuilder::sequence(10, 1).conv1d(3, 11, 1, Relu).flatten()).is_err());
+        assert!(build(NetBuilder::sequence(10, 1).conv1d(3, 3, 1, Relu).max_pool1d(9).flatten())
+            .is_err());
+        assert!(build(NetBuilder::sequence(10, 1).flatten().flatten()).is_err());
+        assert!(build(NetBuilder::sequence(10, 1).conv1d(3, 3, 1, Relu).max_pool1d(8).flatten())
+            .is_ok());
+    }
+
+    /// Sequences of gaussian noise, every other one containing a short
+    /// pattern at a random position.
+    fn pattern_data(n: usize, rng: &mut StdRng) -> (Matrix<f64>, Matrix<f64>) {
+        let (length, pattern) = (20, [1.0, -1.0, 1.0]);
+        let mut noise = Normal::new(0.0, 0.3);
+
+        let mut inputs = Vec::with_capacity(n * length);
+        let mut targets = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut row = (0..length).map(|_| noise.sample(rng)).collect::<Vec<f64>>();
+            if i % 2 == 1 {
+                let start = rng.gen_range(0, length - pattern.len() + 1);
+                for (x, p) in row[start..].iter_mut().zip(pattern.iter()) {
+                    *x += *p;
+                }
+            }
+            inputs.extend(row);
+            targets.push((i % 2) as f64);
+        }
+        (Matrix::new(n, length, inputs), Matrix::new(n, 1, targets))
+    }
+
+    #[test]
+    fn test_conv_net_detects_pattern() {
+        let mut rng = StdRng::from_seed(&[13]);
+        let (inputs, targets) = pattern_data(100, &mut rng);
+        let (test_inputs, test_targets) = pattern_data(200, &mut rng);
+
+        let accuracy = |net: &NeuralNet<BCECriterion, Adam>| {
+            let outputs = net.predict(&test_inputs).unwrap();
+            let correct = outputs.iter()
+                .zip(test_targets.iter())
+                .filter(|&(o, t)| (*o > 0.5) == (*t > 0.5))
+                .count();
+            correct as f64 / test_targets.rows() as f64
+        };
+        let alg = Adam::new(0.05, 0.9, 0.999, 1e-8, 300);
+
+        // The filters are shared across positions and the pooling
+        // takes the strongest response anywhere in the sequence.
+        let mut conv = NetBuilder::sequence(20, 1)
+            .conv1d(4, 3, 1, Relu)
+            .max_pool1d(18)
+            .flatten()
+            .output(1, Sigmoid)
+            .build(BCECriterion::default(), alg)
+            .unwrap();
+        conv.init_weights(WeightInit::Xavier, &mut rng);
+        conv.train_with_rng(&inputs, &targets, &mut rng).unwrap();
+
+        // A dense network with more parameters must learn each position separately.
+        let mut dense = NetBuilder::new(20)
+            .layer(4, Relu)
+            .output(1, Sigmoid)
+            .build(BCECriterion::default(), alg)
+            .unwrap();
+        dense.init_weights(WeightInit::Xavier, &mut rng);
+        dense.train_with_rng(&inputs, &targets, &mut rng).unwrap();
+
+        let (conv_accuracy, dense_accuracy) = (accuracy(&conv), accuracy(&dense));
+        assert!(conv_accuracy > 0.9, "Conv accuracy {}", conv_accuracy);
+        assert!(conv_accuracy - dense_accuracy > 0.1,
+                "Conv accuracy {}, dense accuracy {}",
+                conv_accuracy,
+                dense_accuracy);
+    }
+
+    #[test]
+    fn test_batch_norm_gradient_check() {
+        let mut net = BaseNeuralNet::new(MSECriterion::default());
+        net.add(Box::new(Linear::new(3, 4)))
+            .add(Box::new(BatchNorm::new(4)))
+            .add(Box::new(Tanh))
+            .add(Box::new(Linear::new(4, 2)));
+
+        // The batch statistics couple the samples so a single sample is not enough.
+        let inputs = Matrix::new(5, 3, vec![0.3, -1.2, 0.8,
+                                            1.5, 0.4, -0.6,
+                                            -0.9, 0.7, 0.2,
+                                            0.1, -0.3, -1.4,
+                                            2.0, 1.1, 0.5]);
+        let targets = Matrix::new(5, 2, vec![0.5, -1.0, 1.0, 0.0, -0.5, 0.5, 0.0, 1.5, 1.0, -1.0]);
+
+        let mut rng = StdRng::from_seed(&[5]);
+        let weights = (0..net.weights.len())
+            .map(|_| rng.gen_range(-1.0, 1.0))
+            .collect::<Vec<f64>>();
+
+        // The gradient is summed over the samples while the cost is averaged.
+        let cost_grad = |w: &[f64]| {
+            let (cost, grad) = net.compute_grad(w, &inputs, &targets);
+            (cost * inputs.rows() as f64, grad)
+        };
+        let error = grad_check_fn(cost_grad, &weights, weights.len(), &mut rng);
+        assert!(error < tolerance(step_size(1.0)), "Gradient error {}", error);
+    }
+
+    #[test]
+    fn test_batch_norm_running_statistics() {
+        let mut rng = StdRng::from_seed(&[3]);
+        let mut first = Normal::new(3.0, 2.0);
+        let mut second = Normal::new(-1.0, 0.5);
+
+        let data = (0..1000)
+            .flat_map(|_| vec![first.sample(&mut rng), second.sample(&mut rng)])
+            .collect::<Vec<f64>>();
+        let inputs = Matrix::new(1000, 2, data);
+        let targets = Matrix::new(1000, 1, inputs.data()
+            .chunks(2)
+            .map(|x| x[0] - 2.0 * x[1])
+            .collect::<Vec<f64>>());
+
+        let mut sgd = StochasticGD::new(0.5, 0.001, 10);
+        sgd.set_batch_size(50);
+        sgd.set_seed(1);
+
+        let mut net = NeuralNet::new(MSECriterion::default(), sgd);
+        net.add(Box::new(BatchNorm::new(2)))
+            .add(Box::new(Linear::new(2, 1)));
+        net.train_with_rng(&inputs, &targets, &mut rng).unwrap();
+
+        // The rows of the batch norm weights are the shift, scale, mean and variance.
+        let weights = net.weights();
+        let stats = weights[0].data();
+        assert!((stats[4] - 3.0).abs() < 0.25, "Running mean {}", stats[4]);
+        assert!((stats[5] + 1.0).abs() < 0.1, "Running mean {}", stats[5]);
+        assert!((stats[6] / 4.0 - 1.0).abs() < 0.2, "Running variance {}", stats[6]);
+        assert!((stats[7] / 0.25 - 1.0).abs() < 0.2, "Running variance {}", stats[7]);
+
+        // The running statistics are loaded with the weights.
+        let mut loaded = NeuralNet::new(MSECriterion::default(), StochasticGD::default());
+        loaded.add(Box::new(BatchNorm::new(2)))
+            .add(Box::new(Linear::new(2, 1)));
+        loaded.set_weights(weights).unwrap();
+        assert_eq!(loaded.predict(&inputs).unwrap(), net.predict(&inputs).unwrap());
+    }
+
+    #[test]
+    fn test_batch_norm_running_statistics_line_search() {
+        let mut rng = StdRng::from_seed(&[3]);
+        let mut first = Normal::new(3.0, 2.0);
+        let mut second = Normal::new(-1.0, 0.5);
+
+        let data = (0..200)
+            .flat_map(|_| vec![first.sample(&mut rng), second.sample(&mut rng)])
+            .collect::<Vec<f64>>();
+        let inputs = Matrix::new(200, 2, data);
+        let targets = Matrix::new(200, 1, inputs.data()
+            .chunks(2)
+            .map(|x| (x[0] - 2.0 * x[1]).tanh())
+            .collect::<Vec<f64>>());
+
+        // Each batch is the full input, so the running statistics tend to its statistics.
+        let column = |j: usize| inputs.data().chunks(2).map(|x| x[j]).collect::<Vec<f64>>();
+        let mut expected = Vec::new();
+        for j in 0..2 {
+            let col = column(j);
+            let mean = col.iter().sum::<f64>() / 200.0;
+            let var = col.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / 200.0;
+            expected.push((mean, var));
+        }
+
+        let assert_converged = |weights: Vec<Matrix<f64>>| {
+            let stats = weights[0].data();
+            for (j, &(mean, var)) in expected.iter().enumerate() {
+                let (running_mean, running_var) = (stats[4 + j], stats[6 + j]);
+                assert!((running_mean - mean).abs() < 0.05, "Running mean {}", running_mean);
+                assert!((running_var / var - 1.0).abs() < 0.02, "Running variance {}", running_var);
+            }
+        };
+
+        let mut net = NeuralNet::new(MSECriterion::default(), LBFGS::new(5, 50));
+        net.add(Box::new(BatchNorm::with_momentum(2, 0.5)))
+            .add(Box::new(Linear::new(2, 1)));
+        net.train_with_rng(&inputs, &targets, &mut rng).unwrap();
+        assert_converged(net.weights());
+
+        let mut net = NeuralNet::new(MSECriterion::default(), ConjugateGD::default());
+        net.add(Box::new(BatchNorm::with_momentum(2, 0.5)))
+            .add(Box::new(Linear::new(2, 1)));
+        net.train_with_rng(&inputs, &targets, &mut rng).unwrap();
+        assert_converged(net.weights());
+    }
+
+    #[test]
+    fn test_batch_norm_predicts_with_running_statistics() {
+        let mut net = NeuralNet::new(MSECriterion::default(), GradientDesc::default());
+        net.add(Box::new(BatchNorm::new(2)));
+        net.set_weights(vec![Matrix::new(4, 2, vec![0.5, -1.0,
+                                                     2.0, 1.0,
+                                                     1.0, 2.0,
+                                                     4.0, 0.25])])
+            .unwrap();
+
+        // The statistics of a single row would normalize it to the shift.
+        let inputs = Matrix::new(1, 2, vec![3.0, 2.5]);
+        let outputs = net.predict(&inputs).unwrap();
+        assert!((outputs[[0, 0]] - 2.5).abs() < 1e-4);
+        assert!(outputs[[0, 1]].abs() < 1e-4);
+
+        let params = net.get_net_weights(0);
+        let train_outputs = BatchNorm::new(2).forward_train(&inputs, params).unwrap();
+        assert_eq!(train_outputs.into_vec(), vec![0.5, -1.0]);
+    }
+
+    #[test]
+    fn test_builder_batch_norm() {
+        let net = NetBuilder::new(3)
+            .layer(8, Relu)
+            .batch_norm()
+            .output(1, activ_fn::Linear)
+            .build(MSECriterion::default(), GradientDesc::default())
+            .unwrap();
+        let shapes = net.weights().iter().map(|w| (w.rows(), w.cols())).collect::<Vec<_>>();
+        assert_eq!(shapes, vec![(4, 8), (4, 8), (9, 1)]);
+
+        assert!(NetBuilder::sequence(10, 1)
+            .batch_norm()
+            .flatten()
+            .output(1, activ_fn::Linear)
+            .build(MSECriterion::default(), GradientDesc::default())
+            .is_err());
+    }
+}
@@ -1,15 +1,17 @@
 //! Neural Network Layers
 
 use linalg::{Matrix, MatrixSlice, BaseMatrix};
+use rulinalg::utils;
 
 use learning::LearningResult;
 use learning::error::{Error, ErrorKind};
 use learning::toolkit::activ_fn::ActivationFunc;
 
-use rand::thread_rng;
-use rand::distributions::Sample;
+use rand::{Rng, thread_rng};
+use rand::distributions::{Range, Sample};
 use rand::distributions::normal::Normal;
 
+use std::f64;
 use std::fmt::Debug;
 
 /// Trait for neural net layers
@@ -17,6 +19,13 @@ pub trait NetLayer : Debug {
     /// The result of propogating data forward through this layer
     fn forward(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>>;
 
+    /// The result of propogating data forward through this layer during training
+    ///
+    /// Will return the result of `forward` by default.
+    fn forward_train(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        self.forward(input, params)
+    }
+
     /// The gradient of the output of this layer with respect to its input
     fn back_input(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, output: &Matrix<f64>, params: MatrixSlice<f64>) -> Matrix<f64>;
     
@@ -26,6 +35,13 @@ pub trait NetLayer : Debug {
     /// The default value of the parameters of this layer before training
     fn default_params(&self) -> Vec<f64>;
 
+    /// The value of the parameters of this layer using the given initialization scheme
+    ///
+    /// Will return the default parameters by default.
+    fn init_params(&self, _init: WeightInit, _rng: &mut dyn Rng) -> Vec<f64> {
+        self.default_params()
+    }
+
     /// The shape of the parameters used by this layer
     fn param_shape(&self) -> (usize, usize);
 
@@ -34,6 +50,55 @@ pub trait NetLayer : Debug {
         let shape = self.param_shape();
         shape.0 * shape.1
     }
+
+    /// The number of leading rows of the parameters which hold biases
+    ///
+    /// The biases are excluded from the weight penalty and the max-norm
+    /// constraint of the network. Will return `0` by default.
+    fn num_bias_rows(&self) -> usize {
+        0
+    }
+
+    /// The probability that each output of this layer is set to zero during training
+    ///
+    /// The network multiplies the outputs by a random mask while training, and the
+    /// output gradient by the same mask, so the backward pass of a layer with dropout
+    /// should not depend on its output. Will return `0` by default.
+    fn dropout(&self) -> f64 {
+        0f64
+    }
+
+    /// The statistics of a training batch used to update the state of this layer
+    ///
+    /// The network computes the statistics of the input of this layer during
+    /// training and passes them to `update_state` after the next update of the
+    /// parameters. Will return `None` by default.
+    fn batch_state(&self, _input: &Matrix<f64>) -> Option<Vec<f64>> {
+        None
+    }
+
+    /// Update the state held in the parameters of this layer with the statistics of a batch
+    ///
+    /// Does nothing by default.
+    fn update_state(&self, _batch_state: &[f64], _params: &mut [f64]) {}
+}
+
+/// Weight initialization schemes
+///
+/// The weights of a layer are drawn using its fan-in, the number of
+/// inputs excluding the bias, and its fan-out, the number of outputs.
+/// The biases are initialized to zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightInit {
+    /// Weights drawn uniformly between `-scale` and `scale`
+    Uniform(f64),
+    /// Weights drawn from a gaussian with 0 mean and variance 2/(fan_in+fan_out)
+    Xavier,
+    /// Weights drawn from a gaussian with 0 mean and variance 2/fan_in
+    ///
+    /// Keeps the variance of the activations constant across layers with
+    /// rectified linear activation functions.
+    He,
 }
 
 /// Linear network layer
@@ -97,6 +162,41 @@ fn remove_first_col(mat: Matrix<f64>) -> Matrix<f64>
     Matrix::new(rows, cols - 1, data)
 }
 
+/// Draw zero biases followed by `fan_in x fan_out` weights using the given scheme
+///
+/// # Panics
+///
+/// - The scale of `WeightInit::Uniform` is not positive and finite.
+fn draw_params(init: WeightInit,
+               bias_size: usize,
+               fan_in: usize,
+               fan_out: usize,
+               mut rng: &mut dyn Rng)
+               -> Vec<f64> {
+    let weight_size = fan_in * fan_out;
+
+    let mut params = vec![0f64; bias_size];
+    params.reserve(weight_size);
+    match init {
+        WeightInit::Uniform(scale) => {
+            assert!(scale > 0f64 && scale.is_finite(),
+                    "The uniform initialization scale must be positive and finite.");
+            let mut distro = Range::new(-scale, scale);
+            params.extend((0..weight_size).map(|_| distro.sample(&mut rng)));
+        }
+        WeightInit::Xavier | WeightInit::He => {
+            let var = if init == WeightInit::He {
+                2.0 / fan_in as f64
+            } else {
+                2.0 / (fan_in + fan_out) as f64
+            };
+            let mut distro = Normal::new(0.0, var.sqrt());
+            params.extend((0..weight_size).map(|_| distro.sample(&mut rng)));
+        }
+    }
+    params
+}
+
 impl NetLayer for Linear {
     /// Computes a matrix product
     ///
@@ -148,9 +248,614 @@ impl NetLayer for Linear {
                                              .collect()
     }
 
+    /// Initializes the weights using the given scheme, with zero biases
+    ///
+    /// # Panics
+    ///
+    /// - The scale of `WeightInit::Uniform` is not positive and finite.
+    fn init_params(&self, init: WeightInit, rng: &mut dyn Rng) -> Vec<f64> {
+        let bias_size = if self.has_bias { self.output_size } else { 0 };
+        let fan_in = if self.has_bias { self.input_size - 1 } else { self.input_size };
+        draw_params(init, bias_size, fan_in, self.output_size, rng)
+    }
+
     fn param_shape(&self) -> (usize, usize) {
         (self.input_size, self.output_size)
     }
+
+    fn num_bias_rows(&self) -> usize {
+        if self.has_bias { 1 } else { 0 }
+    }
+}
+
+/// One dimensional convolution network layer
+///
+/// Each row of the input is a sequence of `length` positions with
+/// `channels` values at each position, stored position by position so
+/// that channel `c` of position `t` is in column `t * channels + c`.
+///
+/// The layer slides `filters` kernels of `width` positions along the
+/// sequence, moving `stride` positions at a time. The output is a
+/// sequence of `output_length` positions with one channel per filter,
+/// stored in the same layout.
+///
+/// The parameters are a matrix of size `(width * channels + 1) x filters`.
+/// The first row holds the bias of each filter and the remaining rows the
+/// kernel weights, position by position. The convolution is computed as a
+/// single matrix product with the windows of the input unrolled into rows.
+#[derive(Debug, Clone, Copy)]
+pub struct Conv1D {
+    /// The number of positions of the input sequence
+    length: usize,
+    /// The number of channels at each position of the input
+    channels: usize,
+    /// The number of filters, the channels of the output
+    filters: usize,
+    /// The number of positions covered by each filter
+    width: usize,
+    /// The number of positions between the starts of consecutive windows
+    stride: usize,
+}
+
+impl Conv1D {
+    /// Construct a new Conv1D layer
+    ///
+    /// # Panics
+    ///
+    /// - Any of the sizes is zero.
+    /// - `width` is greater than `length`.
+    pub fn new(length: usize,
+               channels: usize,
+               filters: usize,
+               width: usize,
+               stride: usize)
+               -> Conv1D {
+        assert!(length > 0 && channels > 0 && filters > 0 && width > 0 && stride > 0,
+                "The sizes of the convolution must be greater than 0.");
+        assert!(width <= length, "The filter width cannot exceed the sequence length.");
+        Conv1D {
+            length: length,
+            channels: channels,
+            filters: filters,
+            width: width,
+            stride: stride,
+        }
+    }
+
+    /// The number of positions of the output sequence
+    pub fn output_length(&self) -> usize {
+        (self.length - self.width) / self.stride + 1
+    }
+
+    /// The number of input values covered by each window
+    fn window_size(&self) -> usize {
+        self.width * self.channels
+    }
+
+    /// Unroll the windows of each input row into the rows of a matrix
+    ///
+    /// Each row starts with a one for the bias.
+    fn im2col(&self, input: &Matrix<f64>) -> Matrix<f64> {
+        let window = self.window_size();
+        let out_len = self.output_length();
+
+        let mut data = Vec::with_capacity(input.rows() * out_len * (window + 1));
+        for row in input.data().chunks(self.length * self.channels) {
+            for t in 0..out_len {
+                let start = t * self.stride * self.channels;
+                data.push(1f64);
+                data.extend_from_slice(&row[start..start + window]);
+            }
+        }
+        Matrix::new(input.rows() * out_len, window + 1, data)
+    }
+
+    /// The output gradient with one row per window
+    fn window_grad(&self, out_grad: &Matrix<f64>) -> Matrix<f64> {
+        Matrix::new(out_grad.rows() * self.output_length(),
+                    self.filters,
+                    out_grad.data().clone())
+    }
+}
+
+impl NetLayer for Conv1D {
+    /// Computes the convolution of each input row
+    ///
+    /// input should have dimensions N x (length * channels)
+    fn forward(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        if input.cols() != self.length * self.channels {
+            Err(Error::new(ErrorKind::InvalidData, "The input had the wrong number of columns"))
+        } else {
+            let output = &self.im2col(input) * &params;
+            Ok(Matrix::new(input.rows(),
+                           self.output_length() * self.filters,
+                           output.into_vec()))
+        }
+    }
+
+    /// Sums the gradients of the windows covering each input value
+    fn back_input(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, params: MatrixSlice<f64>) -> Matrix<f64> {
+        let window = self.window_size();
+        let out_len = self.output_length();
+        let cols_grad = &self.window_grad(out_grad) * &params.transpose();
+
+        let row_size = self.length * self.channels;
+        let mut in_grad = vec![0f64; input.rows() * row_size];
+        for (r, g) in cols_grad.data().chunks(window + 1).enumerate() {
+            let start = (r / out_len) * row_size + (r % out_len) * self.stride * self.channels;
+            for (x, d) in in_grad[start..start + window].iter_mut().zip(&g[1..]) {
+                *x += *d;
+            }
+        }
+        Matrix::new(input.rows(), row_size, in_grad)
+    }
+
+    fn back_params(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        debug_assert_eq!(input.rows(), out_grad.rows());
+        &self.im2col(input).transpose() * &self.window_grad(out_grad)
+    }
+
+    /// Initializes weights using Xavier initialization
+    ///
+    /// weights drawn from gaussian distribution with 0 mean and variance
+    /// 2/(width*channels+1+filters)
+    fn default_params(&self) -> Vec<f64> {
+        let (rows, cols) = self.param_shape();
+        let mut distro = Normal::new(0.0, (2.0/(rows+cols) as f64).sqrt());
+        let mut rng = thread_rng();
+
+        (0..rows*cols).map(|_| distro.sample(&mut rng)).collect()
+    }
+
+    /// Initializes the weights using the given scheme, with zero biases
+    ///
+    /// The fan-in is the number of input values covered by each window.
+    ///
+    /// # Panics
+    ///
+    /// - The scale of `WeightInit::Uniform` is not positive and finite.
+    fn init_params(&self, init: WeightInit, rng: &mut dyn Rng) -> Vec<f64> {
+        draw_params(init, self.filters, self.window_size(), self.filters, rng)
+    }
+
+    fn param_shape(&self) -> (usize, usize) {
+        (self.window_size() + 1, self.filters)
+    }
+
+    fn num_bias_rows(&self) -> usize {
+        1
+    }
+}
+
+/// One dimensional max pooling network layer
+///
+/// Takes the maximum of each channel over non-overlapping windows of
+/// `size` positions of a sequence, stored in the layout of `Conv1D`.
+/// Positions at the end of the sequence which do not fill a window
+/// are dropped.
+///
+/// The gradient of each output is passed to the first input in its
+/// window holding the maximum.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxPool1D {
+    /// The number of positions of the input sequence
+    length: usize,
+    /// The number of channels at each position
+    channels: usize,
+    /// The number of positions in each window
+    size: usize,
+}
+
+impl MaxPool1D {
+    /// Construct a new MaxPool1D layer
+    ///
+    /// # Panics
+    ///
+    /// - Any of the sizes is zero.
+    /// - `size` is greater than `length`.
+    pub fn new(length: usize, channels: usize, size: usize) -> MaxPool1D {
+        assert!(length > 0 && channels > 0 && size > 0,
+                "The sizes of the pooling must be greater than 0.");
+        assert!(size <= length, "The pooling size cannot exceed the sequence length.");
+        MaxPool1D {
+            length: length,
+            channels: channels,
+            size: size,
+        }
+    }
+
+    /// The number of positions of the output sequence
+    pub fn output_length(&self) -> usize {
+        self.length / self.size
+    }
+
+    /// The column of the input holding the maximum of an output column
+    fn argmax(&self, row: &[f64], out_col: usize) -> usize {
+        let (t, c) = (out_col / self.channels, out_col % self.channels);
+        let first = t * self.size * self.channels + c;
+        (1..self.size)
+            .map(|k| first + k * self.channels)
+            .fold(first, |best, col| if row[col] > row[best] { col } else { best })
+    }
+}
+
+impl NetLayer for MaxPool1D {
+    fn forward(&self, input: &Matrix<f64>, _: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        if input.cols() != self.length * self.channels {
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  "The input had the wrong number of columns"));
+        }
+
+        let out_cols = self.output_length() * self.channels;
+        let mut output = Vec::with_capacity(input.rows() * out_cols);
+        for row in input.data().chunks(input.cols()) {
+            output.extend((0..out_cols).map(|j| row[self.argmax(row, j)]));
+        }
+        Ok(Matrix::new(input.rows(), out_cols, output))
+    }
+
+    fn back_input(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        let mut in_grad = vec![0f64; input.rows() * input.cols()];
+        let samples = input.data()
+            .chunks(input.cols())
+            .zip(out_grad.data().chunks(out_grad.cols()));
+        for (i, (row, g)) in samples.enumerate() {
+            for (j, g_j) in g.iter().enumerate() {
+                in_grad[i * input.cols() + self.argmax(row, j)] += *g_j;
+            }
+        }
+        Matrix::new(input.rows(), input.cols(), in_grad)
+    }
+
+    fn back_params(&self, _: &Matrix<f64>, _: &Matrix<f64>, _: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        Matrix::new(0, 0, Vec::new())
+    }
+
+    fn default_params(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    fn param_shape(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+/// Batch normalization network layer
+///
+/// Normalizes each column of the input to zero mean and unit variance
+/// and then applies a learned scale and shift to each column.
+///
+/// During training each batch is normalized with its own mean and
+/// variance, and running averages of these are kept for prediction.
+/// With momentum `m` each batch updates a running statistic `s` to
+/// `m * s + (1 - m) * batch`. The network updates the running averages
+/// after each step of the optimization algorithms in the `optim` module,
+/// and once at the end of training for other algorithms.
+///
+/// The parameters are a matrix of size 4 x N where N is the dimensionality
+/// of the input. The rows hold the shift, the scale, the running mean and
+/// the running variance of each column. The running statistics are part
+/// of the weights of the network so they are kept by `set_weights`, but
+/// they are not trained by back propagation.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchNorm {
+    /// The number of dimensions of the input
+    size: usize,
+    /// The weight of the previous running statistics in each update
+    momentum: f64,
+    /// Small value added to the variance to avoid divide by zero
+    epsilon: f64,
+}
+
+impl BatchNorm {
+    /// Construct a new BatchNorm layer with momentum 0.9
+    ///
+    /// # Panics
+    ///
+    /// - `size` is zero.
+    pub fn new(size: usize) -> BatchNorm {
+        BatchNorm::with_momentum(size, 0.9)
+    }
+
+    /// Construct a new BatchNorm layer with the given momentum
+    ///
+    /// # Panics
+    ///
+    /// - `size` is zero.
+    /// - `momentum` is not in `[0, 1)`.
+    pub fn with_momentum(size: usize, momentum: f64) -> BatchNorm {
+        assert!(size > 0, "The input size must be greater than 0.");
+        assert!(momentum >= 0f64 && momentum < 1f64, "The momentum must be in [0, 1).");
+        BatchNorm {
+            size: size,
+            momentum: momentum,
+            epsilon: 1e-5,
+        }
+    }
+
+    /// The weight of the previous running statistics in each update
+    pub fn momentum(&self) -> f64 {
+        self.momentum
+    }
+
+    /// The mean and biased variance of each column of the input
+    fn moments(&self, input: &Matrix<f64>) -> (Vec<f64>, Vec<f64>) {
+        let n = input.rows() as f64;
+        let mut mean = vec![0f64; self.size];
+        for row in input.data().chunks(self.size) {
+            utils::in_place_vec_bin_op(&mut mean, row, |m, &x| *m += x);
+        }
+        for m in &mut mean {
+            *m /= n;
+        }
+
+        let mut var = vec![0f64; self.size];
+        for row in input.data().chunks(self.size) {
+            for ((v, x), m) in var.iter_mut().zip(row).zip(&mean) {
+                *v += (x - m) * (x - m);
+            }
+        }
+        for v in &mut var {
+            *v /= n;
+        }
+        (mean, var)
+    }
+
+    /// Normalize each column using the given mean and variance, then scale and shift
+    fn normalize(&self,
+                 input: &Matrix<f64>,
+                 mean: &[f64],
+                 var: &[f64],
+                 params: &[f64])
+                 -> Matrix<f64> {
+        let (shift, scale) = (&params[..self.size], &params[self.size..2 * self.size]);
+        let mut output = input.data().clone();
+        for row in output.chunks_mut(self.size) {
+            for j in 0..self.size {
+                row[j] = scale[j] * (row[j] - mean[j]) / (var[j] + self.epsilon).sqrt() + shift[j];
+            }
+        }
+        Matrix::new(input.rows(), self.size, output)
+    }
+
+    /// The input normalized with the batch statistics, and the inverse standard deviations
+    fn batch_normalized(&self, input: &Matrix<f64>) -> (Vec<f64>, Vec<f64>) {
+        let (mean, var) = self.moments(input);
+        let inv_std = var.iter().map(|v| 1f64 / (v + self.epsilon).sqrt()).collect::<Vec<f64>>();
+
+        let mut normalized = input.data().clone();
+        for row in normalized.chunks_mut(self.size) {
+            for j in 0..self.size {
+                row[j] = (row[j] - mean[j]) * inv_std[j];
+            }
+        }
+        (normalized, inv_std)
+    }
+
+    /// Check that the input has one column per feature
+    fn check_input(&self, input: &Matrix<f64>) -> LearningResult<()> {
+        if input.cols() != self.size {
+            Err(Error::new(ErrorKind::InvalidData, "The input had the wrong number of columns"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl NetLayer for BatchNorm {
+    /// Normalizes the input with the running statistics
+    fn forward(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        self.check_input(input)?;
+        let params = params.iter().cloned().collect::<Vec<f64>>();
+        let (mean, var) = (&params[2 * self.size..3 * self.size], &params[3 * self.size..]);
+        Ok(self.normalize(input, mean, var, &params))
+    }
+
+    /// Normalizes the input with the statistics of the batch
+    fn forward_train(&self, input: &Matrix<f64>, params: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        self.check_input(input)?;
+        let (mean, var) = self.moments(input);
+        let params = params.iter().cloned().collect::<Vec<f64>>();
+        Ok(self.normalize(input, &mean, &var, &params))
+    }
+
+    /// Computes the gradient through the batch statistics
+    ///
+    /// For each column this is `scale / std * (g - mean(g) - x̂ * mean(g * x̂))`
+    /// where `x̂` is the normalized input.
+    fn back_input(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, params: MatrixSlice<f64>) -> Matrix<f64> {
+        let n = input.rows() as f64;
+        let (normalized, inv_std) = self.batch_normalized(input);
+
+        let mut mean_g = vec![0f64; self.size];
+        let mut mean_gx = vec![0f64; self.size];
+        for (x, g) in normalized.chunks(self.size).zip(out_grad.data().chunks(self.size)) {
+            for j in 0..self.size {
+                mean_g[j] += g[j] / n;
+                mean_gx[j] += g[j] * x[j] / n;
+            }
+        }
+
+        let scale = params.iter().skip(self.size).take(self.size).cloned().collect::<Vec<f64>>();
+        let mut in_grad = Vec::with_capacity(input.rows() * self.size);
+        for (x, g) in normalized.chunks(self.size).zip(out_grad.data().chunks(self.size)) {
+            for j in 0..self.size {
+                in_grad.push(scale[j] * inv_std[j] * (g[j] - mean_g[j] - x[j] * mean_gx[j]));
+            }
+        }
+        Matrix::new(input.rows(), self.size, in_grad)
+    }
+
+    /// Computes the gradient of the shift and scale
+    ///
+    /// The gradient of the running statistics is zero.
+    fn back_params(&self, out_grad: &Matrix<f64>, input: &Matrix<f64>, _: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        let (normalized, _) = self.batch_normalized(input);
+
+        let mut grad = vec![0f64; 4 * self.size];
+        for (x, g) in normalized.chunks(self.size).zip(out_grad.data().chunks(self.size)) {
+            for j in 0..self.size {
+                grad[j] += g[j];
+                grad[self.size + j] += g[j] * x[j];
+            }
+        }
+        Matrix::new(4, self.size, grad)
+    }
+
+    /// Initializes the shift to zero, the scale to one and the running
+    /// statistics to a standard gaussian
+    fn default_params(&self) -> Vec<f64> {
+        let mut params = vec![0f64; 4 * self.size];
+        for j in 0..self.size {
+            params[self.size + j] = 1f64;
+            params[3 * self.size + j] = 1f64;
+        }
+        params
+    }
+
+    fn param_shape(&self) -> (usize, usize) {
+        (4, self.size)
+    }
+
+    /// None of the parameters are weights, so all of the rows are
+    /// excluded from the weight penalty and the max-norm constraint.
+    fn num_bias_rows(&self) -> usize {
+        4
+    }
+
+    /// The mean and unbiased variance of each column of the input
+    fn batch_state(&self, input: &Matrix<f64>) -> Option<Vec<f64>> {
+        let (mut state, var) = self.moments(input);
+        let n = input.rows() as f64;
+        let correction = if n > 1f64 { n / (n - 1f64) } else { 1f64 };
+        state.extend(var.into_iter().map(|v| v * correction));
+        Some(state)
+    }
+
+    fn update_state(&self, batch_state: &[f64], params: &mut [f64]) {
+        let running = &mut params[2 * self.size..];
+        utils::in_place_vec_bin_op(running,
+                                   batch_state,
+                                   |s, &b| *s = self.momentum * *s + (1f64 - self.momentum) * b);
+    }
+}
+
+/// Softmax network layer
+///
+/// Maps each row of the input to a probability distribution,
+/// computing `exp(x_i) / sum_j exp(x_j)` over the row. The largest
+/// entry of each row is subtracted first to avoid overflow.
+///
+/// Paired with the `CategoricalCECriterion` as the final layer of a
+/// network, the backward pass of this layer is fused into the cost.
+#[derive(Debug, Clone, Copy)]
+pub struct Softmax;
+
+impl NetLayer for Softmax {
+    fn forward(&self, input: &Matrix<f64>, _: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        let cols = input.cols();
+        let mut output = input.data().clone();
+
+        for row in output.chunks_mut(cols) {
+            let max = row.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
+            let mut total = 0f64;
+            for x in row.iter_mut() {
+                *x = (*x - max).exp();
+                total += *x;
+            }
+            for x in row.iter_mut() {
+                *x /= total;
+            }
+        }
+
+        Ok(Matrix::new(input.rows(), cols, output))
+    }
+
+    /// Computes the product of the output gradient with the softmax Jacobian
+    ///
+    /// For each row this is `y_i * (g_i - sum_j g_j y_j)`.
+    fn back_input(&self, out_grad: &Matrix<f64>, _: &Matrix<f64>, output: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        let cols = output.cols();
+        let mut in_grad = Vec::with_capacity(output.rows() * cols);
+
+        for (y, g) in output.data().chunks(cols).zip(out_grad.data().chunks(cols)) {
+            let dot = y.iter().zip(g.iter()).map(|(a, b)| a * b).sum::<f64>();
+            for (y_i, g_i) in y.iter().zip(g.iter()) {
+                in_grad.push(y_i * (g_i - dot));
+            }
+        }
+
+        Matrix::new(output.rows(), cols, in_grad)
+    }
+
+    fn back_params(&self, _: &Matrix<f64>, _: &Matrix<f64>, _: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        Matrix::new(0, 0, Vec::new())
+    }
+
+    fn default_params(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    fn param_shape(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+/// Dropout network layer
+///
+/// During training each output is set to zero with probability `p`
+/// and the remaining outputs are scaled by `1 / (1 - p)`. This is
+/// inverted dropout, so the layer is the identity at prediction time.
+///
+/// The random masks are drawn by the network, see `NeuralNet::train_with_rng`.
+#[derive(Debug, Clone, Copy)]
+pub struct Dropout {
+    /// The probability of dropping each output
+    p: f64,
+}
+
+impl Dropout {
+    /// Construct a new Dropout layer with drop probability `p`
+    ///
+    /// # Panics
+    ///
+    /// - `p` is not between 0 and 1.
+    pub fn new(p: f64) -> Dropout {
+        assert!(p >= 0f64 && p <= 1f64, "The dropout probability must be between 0 and 1.");
+        Dropout { p: p }
+    }
+
+    /// The probability of dropping each output
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+}
+
+impl NetLayer for Dropout {
+    /// Returns the input unchanged, the mask is only applied during training
+    fn forward(&self, input: &Matrix<f64>, _: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
+        Ok(input.clone())
+    }
+
+    fn back_input(&self, out_grad: &Matrix<f64>, _: &Matrix<f64>, _: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        out_grad.clone()
+    }
+
+    fn back_params(&self, _: &Matrix<f64>, _: &Matrix<f64>, _: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
+        Matrix::new(0, 0, Vec::new())
+    }
+
+    fn default_params(&self) -> Vec<f64> {
+        Vec::new()
+    }
+
+    fn param_shape(&self) -> (usize, usize) {
+        (0, 0)
+    }
+
+    fn dropout(&self) -> f64 {
+        self.p
+    }
 }
 
 impl<T: ActivationFunc> NetLayer for T {
@@ -158,7 +863,7 @@ impl<T: ActivationFunc> NetLayer for T {
     fn forward(&self, input: &Matrix<f64>, _: MatrixSlice<f64>) -> LearningResult<Matrix<f64>> {
         let mut output = Vec::with_capacity(input.rows()*input.cols());
         for val in input.data() {
-            output.push(T::func(*val));
+            output.push(self.activate(*val));
         }
         Ok(Matrix::new(input.rows(), input.cols(), output))
     }
@@ -166,7 +871,7 @@ impl<T: ActivationFunc> NetLayer for T {
     fn back_input(&self, out_grad: &Matrix<f64>, _: &Matrix<f64>, output: &Matrix<f64>, _: MatrixSlice<f64>) -> Matrix<f64> {
         let mut in_grad = Vec::with_capacity(output.rows()*output.cols());
         for (y, g) in output.data().iter().zip(out_grad.data()) {
-            in_grad.push(T::func_grad_from_output(*y) * g);
+            in_grad.push(self.activate_grad_from_output(*y) * g);
         }
         Matrix::new(output.rows(), output.cols(), in_grad)
     }
@@ -228,6 +228,15 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
             }
 
             if success {
+                // The constrained parameters need a new cost and gradient.
+                let unconstrained = x.clone();
+                model.constrain_params(x.mut_data());
+                if x != unconstrained {
+                    let cost_grad = model.compute_grad(x.data(), inputs, targets);
+                    f2 = cost_grad.0;
+                    df2 = Vector::new(cost_grad.1);
+                }
+
                 f1 = f2;
                 s = s * (&df2 - &df1).dot(&df2) / df1.dot(&df1) - &df2;
 
@@ -249,6 +258,10 @@ impl<M: Optimizable> OptimAlgorithm<M> for ConjugateGD {
 
                 d1 = d2;
                 ls_failed = false;
+
+                if model.end_iteration(i, f1) {
+                    break;
+                }
             } else {
                 x = x0;
                 f1 = f0;
@@ -12,18 +12,39 @@ use learning::optim::{Optimizable, OptimAlgorithm};
 use linalg::Vector;
 use linalg::{Matrix, BaseMatrix};
 use rulinalg::utils;
+use rand::{Rng, StdRng, SeedableRng, thread_rng};
 
 use learning::toolkit::rand_utils;
 
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
 const LEARNING_EPS: f64 = 1e-20;
 
+/// A callback invoked with the iteration and current cost.
+type Callback = Arc<Mutex<Box<dyn FnMut(usize, f64) + Send>>>;
+
 /// Batch Gradient Descent algorithm
-#[derive(Clone, Copy, Debug)]
+///
+/// Cloning the algorithm shares its callback.
+#[derive(Clone)]
 pub struct GradientDesc {
     /// The step-size for the gradient descent steps.
     alpha: f64,
     /// The number of iterations to run.
     iters: usize,
+    /// Called with the iteration and cost on each iteration.
+    callback: Option<Callback>,
+}
+
+impl fmt::Debug for GradientDesc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GradientDesc")
+            .field("alpha", &self.alpha)
+            .field("iters", &self.iters)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
 }
 
 /// The default gradient descent algorithm.
@@ -37,6 +58,7 @@ impl Default for GradientDesc {
         GradientDesc {
             alpha: 0.3,
             iters: 100,
+            callback: None,
         }
     }
 }
@@ -61,8 +83,30 @@ impl GradientDesc {
         GradientDesc {
             alpha: alpha,
             iters: iters,
+            callback: None,
         }
     }
+
+    /// Set a function to call on each iteration with the iteration
+    /// number and the cost at the start of that iteration.
+    ///
+    /// This can be used to log progress or record the loss curve.
+    /// There is no callback by default.
+    ///
+    /// Clones of the algorithm share the callback, so a model trained
+    /// with a clone also calls it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::GradientDesc;
+    ///
+    /// let mut gd = GradientDesc::default();
+    /// gd.set_callback(|iter, cost| println!("Iteration {}: cost {}", iter, cost));
+    /// ```
+    pub fn set_callback<F: FnMut(usize, f64) + Send + 'static>(&mut self, f: F) {
+        self.callback = Some(Arc::new(Mutex::new(Box::new(f))));
+    }
 }
 
 impl<M: Optimizable> OptimAlgorithm<M> for GradientDesc {
@@ -78,16 +122,25 @@ impl<M: Optimizable> OptimAlgorithm<M> for GradientDesc {
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // Compute the cost and gradient for the current parameters
             let (cost, grad) = model.compute_grad(optimizing_val.data(), inputs, targets);
 
+            if let Some(ref callback) = self.callback {
+                (*callback.lock().unwrap())(iter, cost);
+            }
+
+            if model.end_iteration(iter, cost) {
+                break;
+            }
+
             // Early stopping
             if (start_iter_cost - cost).abs() < LEARNING_EPS {
                 break;
             } else {
                 // Update the optimal parameters using gradient descent
                 optimizing_val = &optimizing_val - Vector::new(grad) * self.alpha;
+                model.constrain_params(optimizing_val.mut_data());
                 // Update the latest cost
                 start_iter_cost = cost;
             }
@@ -99,6 +152,10 @@ impl<M: Optimizable> OptimAlgorithm<M> for GradientDesc {
 /// Stochastic Gradient Descent algorithm.
 ///
 /// Uses basic momentum to control the learning rate.
+///
+/// Each pass through the data visits the rows in a random order, split
+/// into mini-batches of `batch_size` rows. The gradient is computed on
+/// each mini-batch alone and the parameters are updated after each one.
 #[derive(Clone, Copy, Debug)]
 pub struct StochasticGD {
     /// Controls the momentum of the descent
@@ -107,6 +164,10 @@ pub struct StochasticGD {
     mu: f64,
     /// The number of passes through the data.
     iters: usize,
+    /// The number of rows in each mini-batch.
+    batch_size: usize,
+    /// The seed used to shuffle the data.
+    seed: Option<usize>,
 }
 
 /// The default Stochastic GD algorithm.
@@ -116,12 +177,16 @@ pub struct StochasticGD {
 /// - alpha = 0.1
 /// - mu = 0.1
 /// - iters = 20
+/// - batch_size = 1
+/// - unseeded shuffling
 impl Default for StochasticGD {
     fn default() -> StochasticGD {
         StochasticGD {
             alpha: 0.1,
             mu: 0.1,
             iters: 20,
+            batch_size: 1,
+            seed: None,
         }
     }
 }
@@ -149,8 +214,45 @@ impl StochasticGD {
             alpha: alpha,
             mu: mu,
             iters: iters,
+            batch_size: 1,
+            seed: None,
         }
     }
+
+    /// Set the number of rows in each mini-batch.
+    ///
+    /// The last mini-batch of each pass is smaller if the number
+    /// of rows is not a multiple of the batch size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::StochasticGD;
+    ///
+    /// let mut sgd = StochasticGD::new(0.1, 0.3, 5);
+    /// sgd.set_batch_size(32);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `batch_size` is zero.
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        assert!(batch_size > 0, "The batch size must be greater than 0.");
+        self.batch_size = batch_size;
+    }
+
+    /// Get the number of rows in each mini-batch.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Set the seed used to shuffle the data.
+    ///
+    /// With a seed every call to `optimize` visits the mini-batches
+    /// in the same order.
+    pub fn set_seed(&mut self, seed: usize) {
+        self.seed = Some(seed);
+    }
 }
 
 impl<M> OptimAlgorithm<M> for StochasticGD
@@ -173,16 +275,22 @@ impl<M> OptimAlgorithm<M> for StochasticGD
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        let seed = match self.seed {
+            Some(s) => s,
+            None => thread_rng().gen(),
+        };
+        let mut rng = StdRng::from_seed(&[seed]);
+
+        for iter in 0..self.iters {
             // The cost at the end of each stochastic gd pass
             let mut end_cost = 0f64;
             // Permute the indices
-            rand_utils::in_place_fisher_yates(&mut permutation);
-            for i in &permutation {
-                // Compute the cost and gradient for this data pair
+            rand_utils::in_place_fisher_yates_with_rng(&mut permutation, &mut rng);
+            for batch in permutation.chunks(self.batch_size) {
+                // Compute the cost and gradient for this mini-batch
                 let (cost, vec_data) = model.compute_grad(optimizing_val.data(),
-                                                          &inputs.select_rows(&[*i]),
-                                                          &targets.select_rows(&[*i]));
+                                                          &inputs.select_rows(batch),
+                                                          &targets.select_rows(batch));
 
                 // Backup previous velocity
                 let prev_w = delta_w.clone();
@@ -191,12 +299,17 @@ impl<M> OptimAlgorithm<M> for StochasticGD
                 // Update the parameters
                 optimizing_val = &optimizing_val -
                     (&prev_w * (-self.alpha) + &delta_w * (1. + self.alpha));
+                model.constrain_params(optimizing_val.mut_data());
                 // Set the end cost (this is only used after the last iteration)
-                end_cost += cost;
+                end_cost += cost * batch.len() as f64;
             }
 
             end_cost /= inputs.rows() as f64;
 
+            if model.end_iteration(iter, end_cost) {
+                break;
+            }
+
             // Early stopping
             if (start_iter_cost - end_cost).abs() < LEARNING_EPS {
                 break;
@@ -272,7 +385,7 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
         // The cost at the start of each iteration
         let mut start_iter_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at the end of each stochastic gd pass
             let mut end_cost = 0f64;
             // Permute the indices
@@ -291,11 +404,16 @@ impl<M: Optimizable<Inputs = Matrix<f64>, Targets = Matrix<f64>>> OptimAlgorithm
                 });
                 // Update the parameters
                 optimizing_val = &optimizing_val - Vector::new(vec_data);
+                model.constrain_params(optimizing_val.mut_data());
                 // Set the end cost (this is only used after the last iteration)
                 end_cost += cost;
             }
             end_cost /= inputs.rows() as f64;
 
+            if model.end_iteration(iter, end_cost) {
+                break;
+            }
+
             // Early stopping
             if (start_iter_cost - end_cost).abs() < LEARNING_EPS {
                 break;
@@ -386,7 +504,7 @@ impl<M> OptimAlgorithm<M> for RMSProp
         // The cost from the previous iteration
         let mut prev_cost = 0f64;
 
-        for _ in 0..self.iters {
+        for iter in 0..self.iters {
             // The cost at end of each pass
             let mut end_cost = 0f64;
             // Permute the vertices
@@ -405,11 +523,16 @@ impl<M> OptimAlgorithm<M> for RMSProp
                     *x = *x * self.learning_rate / (y + self.epsilon).sqrt();
                 });
                 params = &params - &grad;
+                model.constrain_params(params.mut_data());
 
                 end_cost += cost;
             }
             end_cost /= inputs.rows() as f64;
 
+            if model.end_iteration(iter, end_cost) {
+                break;
+            }
+
             // Early stopping
             if (prev_cost - end_cost).abs() < LEARNING_EPS {
                 break;
@@ -421,10 +544,134 @@ impl<M> OptimAlgorithm<M> for RMSProp
     }
 }
 
+/// Adam
+///
+/// The Adam algorithm (Kingma and Ba 2014) with bias corrected
+/// estimates of the first and second moments of the gradient.
+///
+/// Each step uses the gradient over all of the data, so Adam can
+/// train any model implementing `Optimizable`.
+#[derive(Debug, Clone, Copy)]
+pub struct Adam {
+    /// The step size
+    alpha: f64,
+    /// Decay rate of the first moment estimate
+    beta1: f64,
+    /// Decay rate of the second moment estimate
+    beta2: f64,
+    /// Small value used to avoid divide by zero
+    epsilon: f64,
+    /// The number of iterations to run
+    iters: usize,
+}
+
+/// The default Adam configuration
+///
+/// The defaults are:
+///
+/// - alpha = 0.01
+/// - beta1 = 0.9
+/// - beta2 = 0.999
+/// - epsilon = 1.0e-8
+/// - iters = 1000
+impl Default for Adam {
+    fn default() -> Adam {
+        Adam {
+            alpha: 0.01,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1.0e-8,
+            iters: 1000,
+        }
+    }
+}
+
+impl Adam {
+    /// Construct an Adam algorithm.
+    ///
+    /// Requires the step size, the moment decay rates, epsilon, and iteration count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::grad_desc::Adam;
+    /// use rusty_machine::learning::logistic_reg::LogisticRegressor;
+    ///
+    /// let adam = Adam::new(0.001, 0.9, 0.999, 1e-8, 500);
+    /// let model = LogisticRegressor::new(adam);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `alpha` or `epsilon` is not positive.
+    /// - `beta1` or `beta2` is not in `[0, 1)`.
+    pub fn new(alpha: f64, beta1: f64, beta2: f64, epsilon: f64, iters: usize) -> Adam {
+        assert!(0f64 < alpha, "The step size (alpha) must be positive");
+        assert!(0f64 <= beta1 && beta1 < 1f64, "The decay rate beta1 must be in [0, 1)");
+        assert!(0f64 <= beta2 && beta2 < 1f64, "The decay rate beta2 must be in [0, 1)");
+        assert!(0f64 < epsilon, "Epsilon must be positive");
+
+        Adam {
+            alpha: alpha,
+            beta1: beta1,
+            beta2: beta2,
+            epsilon: epsilon,
+            iters: iters,
+        }
+    }
+}
+
+impl<M: Optimizable> OptimAlgorithm<M> for Adam {
+    fn optimize(&self,
+                model: &M,
+                start: &[f64],
+                inputs: &M::Inputs,
+                targets: &M::Targets)
+                -> Vec<f64> {
+        // Initial parameters
+        let mut params = start.to_vec();
+        // The first and second moment estimates
+        let mut m = vec![0f64; start.len()];
+        let mut v = vec![0f64; start.len()];
+        // The cost from the previous iteration
+        let mut prev_cost = 0f64;
+
+        for iter in 0..self.iters {
+            let (cost, grad) = model.compute_grad(&params, inputs, targets);
+
+            if model.end_iteration(iter, cost) {
+                break;
+            }
+
+            // Early stopping
+            if (prev_cost - cost).abs() < LEARNING_EPS {
+                break;
+            }
+            prev_cost = cost;
+
+            // The bias corrections of the moment estimates
+            let t = (iter + 1) as i32;
+            let m_correction = 1f64 - self.beta1.powi(t);
+            let v_correction = 1f64 - self.beta2.powi(t);
+
+            for (i, g) in grad.into_iter().enumerate() {
+                m[i] = self.beta1 * m[i] + (1f64 - self.beta1) * g;
+                v[i] = self.beta2 * v[i] + (1f64 - self.beta2) * g * g;
+
+                let m_hat = m[i] / m_correction;
+                let v_hat = v[i] / v_correction;
+                params[i] -= self.alpha * m_hat / (v_hat.sqrt() + self.epsilon);
+            }
+            model.constrain_params(&mut params);
+        }
+        params
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::{GradientDesc, StochasticGD, AdaGrad, RMSProp};
+    use super::{GradientDesc, StochasticGD, AdaGrad, RMSProp, Adam};
 
     #[test]
     #[should_panic]
@@ -444,6 +691,13 @@ mod tests {
         let _ = StochasticGD::new(0.5, -1f64, 0);
     }
 
+    #[test]
+    #[should_panic]
+    fn stochastic_gd_zero_batch_size() {
+        let mut sgd = StochasticGD::default();
+        sgd.set_batch_size(0);
+    }
+
     #[test]
     #[should_panic]
     fn adagrad_neg_stepsize() {
@@ -473,4 +727,28 @@ mod tests {
     fn rmsprop_neg_learning_rate() {
         let _ = RMSProp::new(0.5, -0.005, 1.0e-5, 0);
     }
+
+    #[test]
+    #[should_panic]
+    fn adam_neg_stepsize() {
+        let _ = Adam::new(-0.01, 0.9, 0.999, 1.0e-8, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn adam_beta1_out_of_range() {
+        let _ = Adam::new(0.01, 1.0, 0.999, 1.0e-8, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn adam_neg_beta2() {
+        let _ = Adam::new(0.01, 0.9, -0.5, 1.0e-8, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn adam_zero_epsilon() {
+        let _ = Adam::new(0.01, 0.9, 0.999, 0.0, 0);
+    }
 }
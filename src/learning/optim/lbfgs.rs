@@ -0,0 +1,222 @@
+//! Module for the L-BFGS optimization algorithm.
+//!
+//! The limited memory BFGS algorithm approximates the inverse Hessian
+//! of the objective using the last `m` parameter and gradient differences.
+//! The approximation is applied with the two-loop recursion and each step
+//! is chosen by a backtracking line search satisfying the Armijo condition.
+//!
+//! L-BFGS typically needs far fewer iterations than first order methods
+//! for smooth objectives.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::optim::lbfgs::LBFGS;
+//!
+//! let lbfgs = LBFGS::new(5, 50);
+//! ```
+
+use learning::optim::{Optimizable, OptimAlgorithm};
+use linalg::Vector;
+
+use std::collections::VecDeque;
+
+/// The sufficient decrease constant in the Armijo condition.
+const ARMIJO_C: f64 = 1e-4;
+
+/// The factor by which the step is shrunk in the line search.
+const BACKTRACK_FACTOR: f64 = 0.5;
+
+/// The maximum number of step reductions in each line search.
+const MAX_BACKTRACKS: usize = 40;
+
+/// The smallest curvature `sᵀy` accepted into the history.
+const CURVATURE_EPS: f64 = 1e-10;
+
+/// Limited memory BFGS algorithm
+#[derive(Clone, Copy, Debug)]
+pub struct LBFGS {
+    /// The number of parameter and gradient differences to keep.
+    m: usize,
+    /// The maximum number of iterations.
+    iters: usize,
+    /// Stop when the gradient norm falls below this value.
+    grad_tol: f64,
+}
+
+/// The default L-BFGS algorithm.
+///
+/// The defaults are:
+///
+/// - m = 10
+/// - iters = 100
+/// - grad_tol = 1e-8
+impl Default for LBFGS {
+    fn default() -> LBFGS {
+        LBFGS {
+            m: 10,
+            iters: 100,
+            grad_tol: 1e-8,
+        }
+    }
+}
+
+impl LBFGS {
+    /// Construct an L-BFGS algorithm.
+    ///
+    /// Requires the history size and iteration count to be specified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::optim::lbfgs::LBFGS;
+    ///
+    /// let lbfgs = LBFGS::new(5, 50);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `m` is zero.
+    pub fn new(m: usize, iters: usize) -> LBFGS {
+        assert!(m > 0, "The history size (m) must be greater than 0.");
+
+        LBFGS {
+            m: m,
+            iters: iters,
+            grad_tol: 1e-8,
+        }
+    }
+
+    /// Set the gradient norm below which the optimization stops.
+    ///
+    /// # Panics
+    ///
+    /// - `grad_tol` is negative.
+    pub fn set_grad_tol(&mut self, grad_tol: f64) {
+        assert!(grad_tol >= 0f64, "The gradient tolerance must be non-negative.");
+        self.grad_tol = grad_tol;
+    }
+}
+
+/// Compute the search direction `-H g` using the two-loop recursion.
+fn search_direction(grad: &Vector<f64>, history: &VecDeque<(Vector<f64>, Vector<f64>)>) -> Vector<f64> {
+    let mut q = grad.clone();
+    let mut alphas = Vec::with_capacity(history.len());
+
+    for &(ref s, ref y) in history.iter().rev() {
+        let alpha = s.dot(&q) / y.dot(s);
+        q = q - y * alpha;
+        alphas.push(alpha);
+    }
+
+    // Scale by the most recent curvature estimate.
+    if let Some(&(ref s, ref y)) = history.back() {
+        q = q * (s.dot(y) / y.dot(y));
+    }
+
+    for (&(ref s, ref y), alpha) in history.iter().zip(alphas.into_iter().rev()) {
+        let beta = y.dot(&q) / y.dot(s);
+        q = q + s * (alpha - beta);
+    }
+
+    -q
+}
+
+impl<M: Optimizable> OptimAlgorithm<M> for LBFGS {
+    fn optimize(&self,
+                model: &M,
+                start: &[f64],
+                inputs: &M::Inputs,
+                targets: &M::Targets)
+                -> Vec<f64> {
+        let mut params = Vector::new(start.to_vec());
+        let (mut cost, grad) = model.compute_grad(params.data(), inputs, targets);
+        let mut grad = Vector::new(grad);
+
+        let mut history = VecDeque::with_capacity(self.m);
+
+        for iter in 0..self.iters {
+            let grad_norm = grad.dot(&grad).sqrt();
+            if grad_norm < self.grad_tol {
+                break;
+            }
+
+            let mut direction = search_direction(&grad, &history);
+            let mut slope = grad.dot(&direction);
+
+            // Fall back to steepest descent if the direction does not descend.
+            if slope >= 0f64 {
+                history.clear();
+                direction = -grad.clone();
+                slope = -grad_norm * grad_norm;
+            }
+
+            // Without curvature information take a unit length first step.
+            let mut step = if history.is_empty() { 1f64 / grad_norm } else { 1f64 };
+
+            let mut accepted = None;
+            for _ in 0..MAX_BACKTRACKS {
+                let new_params = &params + &direction * step;
+                let (new_cost, new_grad) = model.compute_grad(new_params.data(), inputs, targets);
+
+                if new_cost <= cost + ARMIJO_C * step * slope {
+                    accepted = Some((new_params, new_cost, Vector::new(new_grad)));
+                    break;
+                }
+                step *= BACKTRACK_FACTOR;
+            }
+
+            let (new_params, new_cost, new_grad) = match accepted {
+                Some(point) => point,
+                // The line search failed to make progress.
+                None => break,
+            };
+
+            let s = &new_params - &params;
+            let y = &new_grad - &grad;
+            if s.dot(&y) > CURVATURE_EPS {
+                if history.len() == self.m {
+                    history.pop_front();
+                }
+                history.push_back((s, y));
+            }
+
+            params = new_params;
+            cost = new_cost;
+            grad = new_grad;
+
+            // The constrained parameters need a new cost and gradient.
+            let unconstrained = params.clone();
+            model.constrain_params(params.mut_data());
+            if params != unconstrained {
+                let (new_cost, new_grad) = model.compute_grad(params.data(), inputs, targets);
+                cost = new_cost;
+                grad = Vector::new(new_grad);
+            }
+
+            if model.end_iteration(iter, cost) {
+                break;
+            }
+        }
+
+        params.into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LBFGS;
+
+    #[test]
+    #[should_panic]
+    fn lbfgs_zero_history() {
+        let _ = LBFGS::new(0, 10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn lbfgs_neg_grad_tol() {
+        let mut lbfgs = LBFGS::default();
+        lbfgs.set_grad_tol(-1f64);
+    }
+}
@@ -101,6 +101,26 @@ impl<K: Kernel> SVM<K> {
 }
 
 impl<K: Kernel> SVM<K> {
+    /// Compute the signed distances of the inputs from the separating hyperplane.
+    ///
+    /// The `predict` function returns the signs of these margins.
+    ///
+    /// The model must be trained.
+    pub fn decision_function(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
+        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
+        let full_inputs = ones.hcat(inputs);
+
+        if let (&Some(ref alpha), &Some(ref train_inputs), &Some(ref train_targets)) =
+               (&self.alpha, &self.train_inputs, &self.train_targets) {
+            let ker_mat = self.ker_mat(&full_inputs, train_inputs)?;
+            let weight_vec = alpha.elemul(train_targets) / self.lambda;
+
+            Ok(ker_mat * weight_vec)
+        } else {
+            Err(Error::new_untrained())
+        }
+    }
+
     /// Construct a kernel matrix
     fn ker_mat(&self, m1: &Matrix<f64>, m2: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
         if m1.cols() != m2.cols() {
@@ -125,20 +145,7 @@ impl<K: Kernel> SVM<K> {
 /// predict the model output from new data.
 impl<K: Kernel> SupModel<Matrix<f64>, Vector<f64>> for SVM<K> {
     fn predict(&self, inputs: &Matrix<f64>) -> LearningResult<Vector<f64>> {
-        let ones = Matrix::<f64>::ones(inputs.rows(), 1);
-        let full_inputs = ones.hcat(inputs);
-
-        if let (&Some(ref alpha), &Some(ref train_inputs), &Some(ref train_targets)) =
-               (&self.alpha, &self.train_inputs, &self.train_targets) {
-            let ker_mat = self.ker_mat(&full_inputs, train_inputs)?;
-            let weight_vec = alpha.elemul(train_targets) / self.lambda;
-
-            let plane_dist = ker_mat * weight_vec;
-
-            Ok(plane_dist.apply(&|d| d.signum()))
-        } else {
-            Err(Error::new_untrained())
-        }
+        Ok(self.decision_function(inputs)?.apply(&|d| d.signum()))
     }
 
     fn train(&mut self, inputs: &Matrix<f64>, targets: &Vector<f64>) -> LearningResult<()> {
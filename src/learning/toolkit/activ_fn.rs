@@ -7,9 +7,20 @@
 //!
 //! You can also create your own custom activation Functions for use in your models.
 //! Just create a unit struct implementing the `ActivationFunc` trait.
+//!
+//! Activation functions with parameters, such as `LeakyRelu` and `Elu`,
+//! override the `activate` and `activate_grad_from_output` methods to
+//! use their configured parameters. Neural network layers call these
+//! methods, while the associated functions use the default parameters.
 
 use std::fmt::Debug;
 
+/// The default negative slope of the `LeakyRelu` activation function.
+const LEAKY_RELU_SLOPE: f64 = 0.01;
+
+/// The default scale of the negative part of the `Elu` activation function.
+const ELU_ALPHA: f64 = 1.0;
+
 /// Trait for activation functions in models.
 pub trait ActivationFunc: Clone + Debug {
     /// The activation function.
@@ -24,6 +35,21 @@ pub trait ActivationFunc: Clone + Debug {
 
     /// The inverse of the activation function.
     fn func_inv(x: f64) -> f64;
+
+    /// The activation function using the parameters of this instance.
+    ///
+    /// Defaults to `func`.
+    fn activate(&self, x: f64) -> f64 {
+        Self::func(x)
+    }
+
+    /// The gradient of the activation function calculated using the
+    /// output of the function and the parameters of this instance.
+    ///
+    /// Defaults to `func_grad_from_output`.
+    fn activate_grad_from_output(&self, y: f64) -> f64 {
+        Self::func_grad_from_output(y)
+    }
 }
 
 /// Sigmoid activation function.
@@ -119,4 +145,256 @@ impl ActivationFunc for Tanh {
     fn func_inv(x: f64) -> f64 {
         0.5*((1.0+x)/(1.0-x)).ln()
     }
-}
\ No newline at end of file
+}
+
+/// Rectified linear unit activation function.
+///
+/// Computes `max(0, x)`. The function is not differentiable at zero,
+/// where the subgradient `0` is used.
+#[derive(Clone, Copy, Debug)]
+pub struct Relu;
+
+impl ActivationFunc for Relu {
+    fn func(x: f64) -> f64 {
+        if x > 0f64 { x } else { 0f64 }
+    }
+
+    fn func_grad(x: f64) -> f64 {
+        if x > 0f64 { 1f64 } else { 0f64 }
+    }
+
+    fn func_grad_from_output(y: f64) -> f64 {
+        Self::func_grad(y)
+    }
+
+    /// The inverse for positive outputs.
+    ///
+    /// All non-positive inputs map to zero, so zero is returned
+    /// for non-positive outputs.
+    fn func_inv(x: f64) -> f64 {
+        Self::func(x)
+    }
+}
+
+/// Leaky rectified linear unit activation function.
+///
+/// Computes `x` for positive `x` and `slope * x` otherwise. The function
+/// is not differentiable at zero, where the subgradient `slope` is used.
+///
+/// The associated functions use the default slope of `0.01`.
+#[derive(Clone, Copy, Debug)]
+pub struct LeakyRelu {
+    slope: f64,
+}
+
+impl LeakyRelu {
+    /// Constructs a leaky ReLU with the given negative slope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::activ_fn::{ActivationFunc, LeakyRelu};
+    ///
+    /// let leaky = LeakyRelu::new(0.1);
+    /// assert_eq!(leaky.activate(-2.0), -0.2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `slope` is negative.
+    pub fn new(slope: f64) -> LeakyRelu {
+        assert!(slope >= 0f64, "The negative slope cannot be negative.");
+        LeakyRelu { slope: slope }
+    }
+
+    /// Get the negative slope.
+    pub fn slope(&self) -> f64 {
+        self.slope
+    }
+}
+
+/// Constructs a leaky ReLU with a negative slope of `0.01`.
+impl Default for LeakyRelu {
+    fn default() -> LeakyRelu {
+        LeakyRelu::new(LEAKY_RELU_SLOPE)
+    }
+}
+
+impl ActivationFunc for LeakyRelu {
+    fn func(x: f64) -> f64 {
+        LeakyRelu::default().activate(x)
+    }
+
+    fn func_grad(x: f64) -> f64 {
+        if x > 0f64 { 1f64 } else { LEAKY_RELU_SLOPE }
+    }
+
+    fn func_grad_from_output(y: f64) -> f64 {
+        LeakyRelu::default().activate_grad_from_output(y)
+    }
+
+    fn func_inv(x: f64) -> f64 {
+        if x > 0f64 { x } else { x / LEAKY_RELU_SLOPE }
+    }
+
+    fn activate(&self, x: f64) -> f64 {
+        if x > 0f64 { x } else { self.slope * x }
+    }
+
+    fn activate_grad_from_output(&self, y: f64) -> f64 {
+        if y > 0f64 { 1f64 } else { self.slope }
+    }
+}
+
+/// Exponential linear unit activation function.
+///
+/// Computes `x` for positive `x` and `alpha * (e^x - 1)` otherwise. For
+/// `alpha` other than one the function is not differentiable at zero,
+/// where the subgradient `alpha` is used.
+///
+/// The associated functions use the default alpha of `1`.
+#[derive(Clone, Copy, Debug)]
+pub struct Elu {
+    alpha: f64,
+}
+
+impl Elu {
+    /// Constructs an ELU with the given scale of the negative part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::activ_fn::{ActivationFunc, Elu};
+    ///
+    /// let elu = Elu::new(0.5);
+    /// assert_eq!(elu.activate(1.0), 1.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `alpha` is not positive.
+    pub fn new(alpha: f64) -> Elu {
+        assert!(alpha > 0f64, "Alpha must be greater than 0.");
+        Elu { alpha: alpha }
+    }
+
+    /// Get the scale of the negative part.
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+}
+
+/// Constructs an ELU with an alpha of `1`.
+impl Default for Elu {
+    fn default() -> Elu {
+        Elu::new(ELU_ALPHA)
+    }
+}
+
+impl ActivationFunc for Elu {
+    fn func(x: f64) -> f64 {
+        Elu::default().activate(x)
+    }
+
+    fn func_grad(x: f64) -> f64 {
+        if x > 0f64 { 1f64 } else { ELU_ALPHA * x.exp() }
+    }
+
+    fn func_grad_from_output(y: f64) -> f64 {
+        Elu::default().activate_grad_from_output(y)
+    }
+
+    fn func_inv(x: f64) -> f64 {
+        if x > 0f64 { x } else { (x / ELU_ALPHA).ln_1p() }
+    }
+
+    fn activate(&self, x: f64) -> f64 {
+        if x > 0f64 { x } else { self.alpha * x.exp_m1() }
+    }
+
+    /// The gradient `alpha * e^x` of the negative part is `y + alpha`.
+    fn activate_grad_from_output(&self, y: f64) -> f64 {
+        if y > 0f64 { 1f64 } else { y + self.alpha }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActivationFunc, Relu, LeakyRelu, Elu};
+
+    #[test]
+    fn test_relu() {
+        assert_eq!(Relu::func(-2.0), 0.0);
+        assert_eq!(Relu::func(0.0), 0.0);
+        assert_eq!(Relu::func(3.5), 3.5);
+
+        assert_eq!(Relu::func_grad(-2.0), 0.0);
+        assert_eq!(Relu::func_grad(0.0), 0.0);
+        assert_eq!(Relu::func_grad(3.5), 1.0);
+
+        assert_eq!(Relu::func_grad_from_output(0.0), 0.0);
+        assert_eq!(Relu::func_grad_from_output(3.5), 1.0);
+        assert_eq!(Relu::func_inv(3.5), 3.5);
+    }
+
+    #[test]
+    fn test_leaky_relu() {
+        assert_eq!(LeakyRelu::func(-2.0), -0.02);
+        assert_eq!(LeakyRelu::func_grad(-2.0), 0.01);
+        assert_eq!(LeakyRelu::func_grad(0.0), 0.01);
+        assert_eq!(LeakyRelu::func_grad(2.0), 1.0);
+
+        let leaky = LeakyRelu::new(0.2);
+        assert_eq!(leaky.slope(), 0.2);
+        assert_eq!(leaky.activate(-2.0), -0.4);
+        assert_eq!(leaky.activate(2.0), 2.0);
+        assert_eq!(leaky.activate_grad_from_output(-0.4), 0.2);
+        assert_eq!(leaky.activate_grad_from_output(2.0), 1.0);
+        assert!((LeakyRelu::func_inv(LeakyRelu::func(-3.0)) + 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_elu() {
+        assert_eq!(Elu::func(2.0), 2.0);
+        assert!((Elu::func(-1.0) - (-1f64).exp_m1()).abs() < 1e-15);
+        assert_eq!(Elu::func_grad(0.0), 1.0);
+        assert!((Elu::func_grad(-1.0) - (-1f64).exp()).abs() < 1e-15);
+
+        let elu = Elu::new(0.5);
+        let y = elu.activate(-1.0);
+        assert!((y - 0.5 * (-1f64).exp_m1()).abs() < 1e-15);
+        assert!((elu.activate_grad_from_output(y) - 0.5 * (-1f64).exp()).abs() < 1e-15);
+        assert_eq!(elu.activate_grad_from_output(2.0), 1.0);
+        assert!((Elu::func_inv(Elu::func(-0.7)) + 0.7).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_grad_from_output_matches_grad() {
+        for &x in &[-3.0, -0.5, 0.7, 2.0] {
+            let leaky = LeakyRelu::new(0.3);
+            let elu = Elu::new(2.0);
+
+            assert_eq!(Relu::func_grad_from_output(Relu::func(x)), Relu::func_grad(x));
+            assert_eq!(LeakyRelu::func_grad_from_output(LeakyRelu::func(x)),
+                       LeakyRelu::func_grad(x));
+            assert!((Elu::func_grad_from_output(Elu::func(x)) - Elu::func_grad(x)).abs() < 1e-12);
+
+            let leaky_grad = if x > 0.0 { 1.0 } else { 0.3 };
+            assert_eq!(leaky.activate_grad_from_output(leaky.activate(x)), leaky_grad);
+            let elu_grad = if x > 0.0 { 1.0 } else { 2.0 * x.exp() };
+            assert!((elu.activate_grad_from_output(elu.activate(x)) - elu_grad).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_leaky_slope() {
+        let _ = LeakyRelu::new(-0.1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_non_positive_elu_alpha() {
+        let _ = Elu::new(0.0);
+    }
+}
@@ -11,6 +11,8 @@
 use linalg::{Matrix, BaseMatrix, BaseMatrixMut};
 use linalg::Vector;
 
+use std::f64;
+
 /// Trait for cost functions in models.
 pub trait CostFunc<T> {
     /// The cost function.
@@ -96,6 +98,34 @@ impl CostFunc<Vector<f64>> for CrossEntropyError {
     }
 }
 
+/// The categorical cross entropy error cost function.
+///
+/// The outputs are the class probabilities computed by a softmax and
+/// the targets are one-hot encoded with a column per class.
+///
+/// The gradient is taken with respect to the inputs of the softmax
+/// rather than its outputs, which simplifies to `outputs - targets`.
+/// It must be paired with a softmax producing the outputs.
+#[derive(Clone, Copy, Debug)]
+pub struct CategoricalCrossEntropy;
+
+impl CostFunc<Matrix<f64>> for CategoricalCrossEntropy {
+    fn cost(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> f64 {
+        // Clamp the probabilities so a zero output gives a finite cost.
+        let total = outputs.data()
+            .iter()
+            .zip(targets.data().iter())
+            .map(|(y, t)| t * y.max(f64::MIN_POSITIVE).ln())
+            .sum::<f64>();
+
+        -total / (outputs.rows() as f64)
+    }
+
+    fn grad_cost(outputs: &Matrix<f64>, targets: &Matrix<f64>) -> Matrix<f64> {
+        outputs - targets
+    }
+}
+
 /// Logarithm for applying within cost function.
 fn ln(x: f64) -> f64 {
     x.ln()
@@ -0,0 +1,90 @@
+//! Covariance Module
+//!
+//! This module contains the `covariance_matrix` function which computes
+//! the sample covariance of the columns of a data matrix. It is used by
+//! the models which need the covariance of their training inputs.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::toolkit::covariance::covariance_matrix;
+//! use rusty_machine::linalg::Matrix;
+//!
+//! let data = Matrix::new(3, 2, vec![1.0, 2.0,
+//!                                   2.0, 4.0,
+//!                                   3.0, 6.0]);
+//!
+//! let cov = covariance_matrix(&data);
+//! assert_eq!(cov.into_vec(), vec![1.0, 2.0, 2.0, 4.0]);
+//! ```
+
+use linalg::{Matrix, BaseMatrix, BaseMatrixMut, Axes};
+use rulinalg::utils;
+
+/// Compute the sample covariance matrix of the columns of `data`.
+///
+/// Each row of `data` is a sample and each column a feature. Returns
+/// a square matrix with one row and column per feature, normalized
+/// by the number of samples minus one.
+///
+/// The covariance is computed as `XᵀX / (n - 1)` for the centered data `X`.
+///
+/// # Panics
+///
+/// - `data` has fewer than two rows.
+pub fn covariance_matrix(data: &Matrix<f64>) -> Matrix<f64> {
+    assert!(data.rows() > 1, "Data must have at least two rows to compute a covariance.");
+
+    let means = data.mean(Axes::Row);
+    let mut centered = data.clone();
+    for mut row in centered.row_iter_mut() {
+        utils::in_place_vec_bin_op(row.raw_slice_mut(), means.data(), |x, &m| *x -= m);
+    }
+
+    (centered.transpose() * &centered) / (data.rows() - 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::covariance_matrix;
+    use linalg::{Matrix, BaseMatrix};
+
+    #[test]
+    fn test_matches_elementwise_covariance() {
+        let data = Matrix::new(5, 3, vec![1.0, -2.0, 0.5,
+                                          3.0, 0.0, 1.5,
+                                          -1.0, 4.0, 2.0,
+                                          2.0, 1.0, -0.5,
+                                          0.0, 2.0, 1.0]);
+        let cov = covariance_matrix(&data);
+
+        let n = data.rows() as f64;
+        for j in 0..3 {
+            for k in 0..3 {
+                let mean_j = (0..data.rows()).map(|i| data[[i, j]]).sum::<f64>() / n;
+                let mean_k = (0..data.rows()).map(|i| data[[i, k]]).sum::<f64>() / n;
+                let expected = (0..data.rows())
+                    .map(|i| (data[[i, j]] - mean_j) * (data[[i, k]] - mean_k))
+                    .sum::<f64>() / (n - 1.0);
+
+                assert!((cov[[j, k]] - expected).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_covariance_is_symmetric() {
+        let data = Matrix::new(4, 2, vec![1.0, 5.0, 2.0, 3.0, 7.0, -1.0, 4.0, 0.0]);
+        let cov = covariance_matrix(&data);
+
+        assert_eq!(cov.rows(), 2);
+        assert_eq!(cov.cols(), 2);
+        assert_eq!(cov[[0, 1]], cov[[1, 0]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_single_row() {
+        let _ = covariance_matrix(&Matrix::new(1, 2, vec![1.0, 2.0]));
+    }
+}
@@ -0,0 +1,185 @@
+//! Distance Module
+//!
+//! This module contains the distance metrics which can be
+//! used by the clustering algorithms.
+//!
+//! The module contains a `DistanceMetric` enum which provides access to
+//! `Euclidean`, `Manhattan` and `Cosine` distances and the `KullbackLeibler`
+//! divergence, and a `pairwise_distances` function which computes the
+//! distances between the rows of two matrices.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::toolkit::distance::DistanceMetric;
+//!
+//! let metric = DistanceMetric::Manhattan;
+//! assert_eq!(metric.dist(&[1.0, 2.0], &[3.0, 0.0]), 4.0);
+//! ```
+
+use linalg::{Matrix, BaseMatrix};
+
+/// Distance metric between two points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceMetric {
+    /// The Euclidean (L2) distance.
+    Euclidean,
+    /// The Manhattan (L1) distance.
+    Manhattan,
+    /// The cosine distance, one minus the cosine similarity.
+    ///
+    /// The distance between a zero vector and any other vector is one.
+    Cosine,
+    /// The Kullback-Leibler divergence `Σ p ln(p / q)` of the second
+    /// point `q` from the first point `p`.
+    ///
+    /// The points should be probability distributions. Terms with `p`
+    /// zero are zero, and the divergence is infinite if `q` is zero
+    /// where `p` is not. The divergence is not symmetric.
+    KullbackLeibler,
+}
+
+impl DistanceMetric {
+    /// Compute the distance between two points.
+    ///
+    /// # Panics
+    ///
+    /// - The points have different lengths.
+    pub fn dist(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len(), "Points must have the same length.");
+
+        match *self {
+            DistanceMetric::Euclidean => {
+                x1.iter()
+                    .zip(x2.iter())
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<f64>()
+                    .sqrt()
+            }
+            DistanceMetric::Manhattan => {
+                x1.iter()
+                    .zip(x2.iter())
+                    .map(|(a, b)| (a - b).abs())
+                    .sum::<f64>()
+            }
+            DistanceMetric::Cosine => {
+                let dot = x1.iter().zip(x2.iter()).map(|(a, b)| a * b).sum::<f64>();
+                let norms = x1.iter().map(|a| a * a).sum::<f64>().sqrt() *
+                            x2.iter().map(|b| b * b).sum::<f64>().sqrt();
+
+                if norms == 0f64 {
+                    1f64
+                } else {
+                    1f64 - dot / norms
+                }
+            }
+            DistanceMetric::KullbackLeibler => {
+                x1.iter()
+                    .zip(x2.iter())
+                    .map(|(p, q)| if *p == 0f64 { 0f64 } else { p * (p / q).ln() })
+                    .sum::<f64>()
+            }
+        }
+    }
+}
+
+/// Compute the distances between each row of `a` and each row of `b`.
+///
+/// Returns a matrix with `a.rows()` rows and `b.rows()` columns where
+/// the entry at `[i, j]` is the distance between row `i` of `a` and
+/// row `j` of `b`.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::linalg::Matrix;
+/// use rusty_machine::learning::toolkit::distance::{pairwise_distances, DistanceMetric};
+///
+/// let a = Matrix::new(2, 2, vec![0.0, 0.0, 1.0, 1.0]);
+/// let b = Matrix::new(1, 2, vec![3.0, 4.0]);
+///
+/// let dists = pairwise_distances(&a, &b, DistanceMetric::Euclidean);
+/// assert_eq!(dists.rows(), 2);
+/// assert_eq!(dists[[0, 0]], 5.0);
+/// ```
+///
+/// # Panics
+///
+/// - The matrices have a different number of columns.
+pub fn pairwise_distances(a: &Matrix<f64>, b: &Matrix<f64>, metric: DistanceMetric) -> Matrix<f64> {
+    assert_eq!(a.cols(), b.cols(), "Matrices must have the same number of columns.");
+
+    let mut data = Vec::with_capacity(a.rows() * b.rows());
+    for x in a.row_iter() {
+        for y in b.row_iter() {
+            data.push(metric.dist(x.raw_slice(), y.raw_slice()));
+        }
+    }
+
+    Matrix::new(a.rows(), b.rows(), data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pairwise_distances, DistanceMetric};
+    use linalg::{Matrix, BaseMatrix};
+
+    #[test]
+    fn test_euclidean() {
+        assert_eq!(DistanceMetric::Euclidean.dist(&[0.0, 0.0], &[3.0, 4.0]), 5.0);
+    }
+
+    #[test]
+    fn test_manhattan() {
+        assert_eq!(DistanceMetric::Manhattan.dist(&[1.0, -1.0], &[3.0, 4.0]), 7.0);
+    }
+
+    #[test]
+    fn test_cosine() {
+        assert!(DistanceMetric::Cosine.dist(&[1.0, 0.0], &[2.0, 0.0]).abs() < 1e-12);
+        assert!((DistanceMetric::Cosine.dist(&[1.0, 0.0], &[0.0, 3.0]) - 1.0).abs() < 1e-12);
+        assert!((DistanceMetric::Cosine.dist(&[1.0, 0.0], &[-1.0, 0.0]) - 2.0).abs() < 1e-12);
+        assert_eq!(DistanceMetric::Cosine.dist(&[0.0, 0.0], &[1.0, 1.0]), 1.0);
+    }
+
+    #[test]
+    fn test_kullback_leibler() {
+        let metric = DistanceMetric::KullbackLeibler;
+        assert_eq!(metric.dist(&[0.25, 0.75], &[0.25, 0.75]), 0.0);
+        assert!((metric.dist(&[1.0, 0.0], &[0.5, 0.5]) - 2f64.ln()).abs() < 1e-12);
+        assert_eq!(metric.dist(&[0.5, 0.5], &[1.0, 0.0]), ::std::f64::INFINITY);
+
+        let (p, q) = ([0.2, 0.3, 0.5], [0.4, 0.4, 0.2]);
+        assert!(metric.dist(&p, &q) > 0.0);
+        assert!((metric.dist(&p, &q) - metric.dist(&q, &p)).abs() > 1e-3);
+    }
+
+    #[test]
+    fn test_pairwise_distances() {
+        let a = Matrix::new(2, 2, vec![0.0, 0.0, 1.0, 2.0]);
+        let b = Matrix::new(3, 2, vec![0.0, 0.0, 3.0, 4.0, 1.0, 0.0]);
+
+        let dists = pairwise_distances(&a, &b, DistanceMetric::Manhattan);
+        assert_eq!(dists.rows(), 2);
+        assert_eq!(dists.cols(), 3);
+        assert_eq!(dists.into_vec(), vec![0.0, 7.0, 1.0, 3.0, 4.0, 2.0]);
+
+        let self_dists = pairwise_distances(&a, &a, DistanceMetric::Euclidean);
+        assert_eq!(self_dists[[0, 0]], 0.0);
+        assert_eq!(self_dists[[0, 1]], self_dists[[1, 0]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pairwise_mismatched_cols() {
+        let a = Matrix::new(1, 2, vec![0.0, 0.0]);
+        let b = Matrix::new(1, 3, vec![0.0, 0.0, 0.0]);
+        pairwise_distances(&a, &b, DistanceMetric::Euclidean);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mismatched_lengths() {
+        DistanceMetric::Euclidean.dist(&[1.0], &[1.0, 2.0]);
+    }
+}
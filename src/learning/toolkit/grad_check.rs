@@ -0,0 +1,190 @@
+//! Gradient Checking Module
+//!
+//! This module contains utilities which compare the analytic gradient of
+//! a model against central finite differences of its cost. They are
+//! intended for tests of models implementing the `Optimizable` trait,
+//! and of any other cost function through `grad_check_fn`.
+//!
+//! The checks return the maximum relative error over a random subset of
+//! the parameters. A correct gradient has an error below `tolerance`,
+//! while a wrong gradient usually has an error many orders of magnitude
+//! larger.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate rand;
+//! # extern crate rusty_machine;
+//! use rusty_machine::learning::toolkit::grad_check::{grad_check_fn, step_size, tolerance};
+//! use rand::{SeedableRng, StdRng};
+//!
+//! # fn main() {
+//! // The cost x² + 3xy with its gradient.
+//! let cost = |p: &[f64]| {
+//!     (p[0] * p[0] + 3.0 * p[0] * p[1], vec![2.0 * p[0] + 3.0 * p[1], 3.0 * p[0]])
+//! };
+//!
+//! let error = grad_check_fn(cost, &[1.5, -2.0], 2, &mut StdRng::from_seed(&[1]));
+//! assert!(error < tolerance(step_size(1.0)));
+//! # }
+//! ```
+
+use learning::optim::Optimizable;
+use learning::toolkit::rand_utils::in_place_fisher_yates_with_rng;
+
+use rand::Rng;
+
+use std::f64;
+
+/// The step size of the central difference for a parameter with value `x`.
+///
+/// The step is the cube root of the machine epsilon, which balances the
+/// truncation and rounding errors, scaled by the magnitude of `x` when
+/// it is larger than one.
+pub fn step_size(x: f64) -> f64 {
+    f64::EPSILON.cbrt() * x.abs().max(1f64)
+}
+
+/// A tolerance for the relative error of central differences with step size `step`.
+///
+/// The truncation error of the central differences is of order `step²`
+/// and the rounding error of order `EPSILON / step`. The tolerance
+/// allows a generous constant on both.
+pub fn tolerance(step: f64) -> f64 {
+    1e4 * (step * step + f64::EPSILON / step)
+}
+
+/// The relative error between an analytic and a numeric derivative.
+///
+/// The difference is scaled by the larger magnitude of the two, or
+/// by one if both are smaller than one so that derivatives close to
+/// zero are compared absolutely.
+pub fn relative_error(analytic: f64, numeric: f64) -> f64 {
+    (analytic - numeric).abs() / analytic.abs().max(numeric.abs()).max(1f64)
+}
+
+/// Check the gradient of a model at the given parameters.
+///
+/// Compares the gradient from `compute_grad` with central differences
+/// of the cost for `num_checks` parameters chosen at random, or for
+/// every parameter if there are fewer. Returns the maximum relative error.
+///
+/// # Panics
+///
+/// - The gradient does not have one entry per parameter.
+pub fn grad_check<M, R>(model: &M,
+                        params: &[f64],
+                        inputs: &M::Inputs,
+                        targets: &M::Targets,
+                        num_checks: usize,
+                        rng: &mut R)
+                        -> f64
+    where M: Optimizable,
+          R: Rng
+{
+    grad_check_fn(|p: &[f64]| model.compute_grad(p, inputs, targets),
+                  params,
+                  num_checks,
+                  rng)
+}
+
+/// Check the gradient returned by `cost_grad` at the given parameters.
+///
+/// The function returns the cost and its gradient at the parameters it is
+/// given. Otherwise this is the same as `grad_check`.
+///
+/// # Panics
+///
+/// - The gradient does not have one entry per parameter.
+pub fn grad_check_fn<F, R>(cost_grad: F, params: &[f64], num_checks: usize, rng: &mut R) -> f64
+    where F: Fn(&[f64]) -> (f64, Vec<f64>),
+          R: Rng
+{
+    let (_, grad) = cost_grad(params);
+    assert_eq!(grad.len(), params.len(), "The gradient must have one entry per parameter.");
+
+    let mut indices = (0..params.len()).collect::<Vec<usize>>();
+    in_place_fisher_yates_with_rng(&mut indices, rng);
+    indices.truncate(num_checks);
+
+    let mut shifted = params.to_vec();
+    let mut max_error = 0f64;
+    for i in indices {
+        let step = step_size(params[i]);
+        // The steps actually taken after rounding.
+        let (plus, minus) = (params[i] + step, params[i] - step);
+
+        shifted[i] = plus;
+        let (cost_plus, _) = cost_grad(&shifted);
+        shifted[i] = minus;
+        let (cost_minus, _) = cost_grad(&shifted);
+        shifted[i] = params[i];
+
+        let numeric = (cost_plus - cost_minus) / (plus - minus);
+        max_error = max_error.max(relative_error(grad[i], numeric));
+    }
+
+    max_error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{grad_check_fn, relative_error, step_size, tolerance};
+    use rand::{SeedableRng, StdRng};
+
+    /// The cost Σ exp(pᵢ) pᵢ₊₁ with its gradient.
+    fn chained_cost(p: &[f64]) -> (f64, Vec<f64>) {
+        let n = p.len();
+        let cost = (0..n).map(|i| p[i].exp() * p[(i + 1) % n]).sum();
+        let grad = (0..n)
+            .map(|i| p[i].exp() * p[(i + 1) % n] + p[(i + n - 1) % n].exp())
+            .collect();
+        (cost, grad)
+    }
+
+    #[test]
+    fn test_correct_gradient() {
+        let params = (0..20).map(|i| (i as f64 - 10.0) / 7.0).collect::<Vec<f64>>();
+        let error = grad_check_fn(chained_cost, &params, 20, &mut StdRng::from_seed(&[1]));
+        assert!(error < tolerance(step_size(1.0)), "Error {}", error);
+    }
+
+    #[test]
+    fn test_broken_gradient() {
+        // The gradient is missing the term from the previous parameter.
+        let broken = |p: &[f64]| {
+            let (cost, mut grad) = chained_cost(p);
+            grad[3] -= p[2].exp();
+            (cost, grad)
+        };
+
+        let params = (0..20).map(|i| (i as f64 - 10.0) / 7.0).collect::<Vec<f64>>();
+        let error = grad_check_fn(&broken, &params, 20, &mut StdRng::from_seed(&[1]));
+        assert!(error > 1e-2, "Error {}", error);
+
+        // Only the checked parameters can be caught.
+        let error = grad_check_fn(&broken, &params, 0, &mut StdRng::from_seed(&[1]));
+        assert_eq!(error, 0.0);
+    }
+
+    #[test]
+    fn test_large_parameters() {
+        let cost = |p: &[f64]| (p[0] * p[0] * p[1], vec![2.0 * p[0] * p[1], p[0] * p[0]]);
+        let error = grad_check_fn(cost, &[1e4, -3e3], 2, &mut StdRng::from_seed(&[2]));
+        assert!(error < tolerance(step_size(1.0)), "Error {}", error);
+    }
+
+    #[test]
+    fn test_relative_error() {
+        assert_eq!(relative_error(2.0, 2.0), 0.0);
+        assert!((relative_error(200.0, 198.0) - 0.01).abs() < 1e-12);
+        assert!((relative_error(1e-3, 0.0) - 1e-3).abs() < 1e-15);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_wrong_gradient_length() {
+        let cost = |p: &[f64]| (p[0], vec![1.0, 0.0]);
+        let _ = grad_check_fn(cost, &[1.0], 1, &mut StdRng::from_seed(&[1]));
+    }
+}
@@ -1,10 +1,32 @@
 //! Module for kernels
 //!
-//! Currently used within Gaussian Processes and SVMs.
+//! Currently used within Gaussian Processes and SVMs. Each kernel
+//! evaluates pairs of points with `Kernel::kernel` and can compute the
+//! Gram matrix of a dataset with `Kernel::gram`, so the kernels can be
+//! reused by other kernel methods.
+//!
+//! Kernels can be combined by wrapping them in `KernelArith` and using
+//! the `+` and `*` operators. When the kernel is only known at runtime
+//! the kernels can instead be boxed as `Box<dyn Kernel>`, which also
+//! supports `+` and `*`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::learning::toolkit::kernel::{Kernel, SquaredExp, Periodic, WhiteNoise};
+//!
+//! let se: Box<dyn Kernel> = Box::new(SquaredExp::default());
+//! let periodic: Box<dyn Kernel> = Box::new(Periodic::default());
+//! let white: Box<dyn Kernel> = Box::new(WhiteNoise::new(0.1));
+//!
+//! let ker = se * periodic + white;
+//! println!("{0}", ker.kernel(&[1., 2.], &[3., 4.]));
+//! ```
 
 use std::ops::{Add, Mul};
+use std::f64::consts::PI;
 
-use linalg::Vector;
+use linalg::{Matrix, BaseMatrix, Vector};
 use linalg::norm::{Euclidean, VectorNorm, VectorMetric};
 use rulinalg::utils;
 
@@ -16,6 +38,50 @@ pub trait Kernel {
     ///
     /// Takes two equal length slices and returns a scalar.
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64;
+
+    /// Get the parameters of the kernel as a flat vector.
+    ///
+    /// This is used to optimize the kernel parameters, for example
+    /// within `GaussianProcess::optimize_hyperparameters`.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics. All kernels in this
+    /// module override it.
+    fn get_params(&self) -> Vec<f64> {
+        panic!("This kernel does not expose its parameters.")
+    }
+
+    /// Set the parameters of the kernel from a flat vector.
+    ///
+    /// The parameters are in the same order as returned by `get_params`.
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics. All kernels in this
+    /// module override it, and panic if the number of parameters is wrong.
+    fn set_params(&mut self, _params: &[f64]) {
+        panic!("This kernel does not expose its parameters.")
+    }
+
+    /// Compute the Gram matrix of the kernel between the rows of `data`.
+    ///
+    /// The entry at `[i, j]` is the kernel between rows `i` and `j`.
+    /// Kernels are symmetric, so each pair of rows is evaluated once.
+    fn gram(&self, data: &Matrix<f64>) -> Matrix<f64> {
+        let n = data.rows();
+        let mut gram = Matrix::zeros(n, n);
+
+        for (i, x1) in data.row_iter().enumerate() {
+            for (j, x2) in data.row_iter().enumerate().skip(i) {
+                let k = self.kernel(x1.raw_slice(), x2.raw_slice());
+                gram[[i, j]] = k;
+                gram[[j, i]] = k;
+            }
+        }
+
+        gram
+    }
 }
 
 /// The sum of two kernels
@@ -40,7 +106,7 @@ pub trait Kernel {
 /// println!("{0}", poly_plus_hypert_ker.kernel(&[1f64,2f64,3f64],
 ///                                             &[3f64,1f64,2f64]));
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct KernelSum<T, U>
     where T: Kernel,
           U: Kernel
@@ -57,6 +123,18 @@ impl<T, U> Kernel for KernelSum<T, U>
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
         self.k1.kernel(x1, x2) + self.k2.kernel(x1, x2)
     }
+
+    fn get_params(&self) -> Vec<f64> {
+        let mut params = self.k1.get_params();
+        params.extend(self.k2.get_params());
+        params
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        let n = self.k1.get_params().len();
+        self.k1.set_params(&params[..n]);
+        self.k2.set_params(&params[n..]);
+    }
 }
 
 /// The pointwise product of two kernels
@@ -81,7 +159,7 @@ impl<T, U> Kernel for KernelSum<T, U>
 /// println!("{0}", poly_plus_hypert_ker.kernel(&[1f64,2f64,3f64],
 ///                                             &[3f64,1f64,2f64]));
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct KernelProd<T, U>
     where T: Kernel,
           U: Kernel
@@ -98,10 +176,22 @@ impl<T, U> Kernel for KernelProd<T, U>
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
         self.k1.kernel(x1, x2) * self.k2.kernel(x1, x2)
     }
+
+    fn get_params(&self) -> Vec<f64> {
+        let mut params = self.k1.get_params();
+        params.extend(self.k2.get_params());
+        params
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        let n = self.k1.get_params().len();
+        self.k1.set_params(&params[..n]);
+        self.k2.set_params(&params[n..]);
+    }
 }
 
 /// A wrapper tuple struct used for kernel arithmetic
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct KernelArith<K: Kernel>(pub K);
 
 impl<T: Kernel, U: Kernel> Add<KernelArith<T>> for KernelArith<U> {
@@ -126,6 +216,160 @@ impl<T: Kernel, U: Kernel> Mul<KernelArith<T>> for KernelArith<U> {
     }
 }
 
+impl<K: Kernel + ?Sized> Kernel for Box<K> {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        (**self).kernel(x1, x2)
+    }
+
+    fn get_params(&self) -> Vec<f64> {
+        (**self).get_params()
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        (**self).set_params(params)
+    }
+
+    fn gram(&self, data: &Matrix<f64>) -> Matrix<f64> {
+        (**self).gram(data)
+    }
+}
+
+impl Add for Box<dyn Kernel> {
+    type Output = Box<dyn Kernel>;
+
+    fn add(self, ker: Box<dyn Kernel>) -> Box<dyn Kernel> {
+        Box::new(KernelSum {
+            k1: self,
+            k2: ker,
+        })
+    }
+}
+
+impl Mul for Box<dyn Kernel> {
+    type Output = Box<dyn Kernel>;
+
+    fn mul(self, ker: Box<dyn Kernel>) -> Box<dyn Kernel> {
+        Box::new(KernelProd {
+            k1: self,
+            k2: ker,
+        })
+    }
+}
+
+/// The Constant Kernel
+///
+/// k(x,y) = c
+///
+/// Multiplying by this kernel scales another kernel.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantKernel {
+    /// The constant value of the kernel.
+    pub c: f64,
+}
+
+impl ConstantKernel {
+    /// Constructs a new Constant Kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// let ker = kernel::ConstantKernel::new(2.0);
+    ///
+    /// assert_eq!(ker.kernel(&[1.,2.,3.], &[3.,4.,5.]), 2.0);
+    /// ```
+    pub fn new(c: f64) -> ConstantKernel {
+        ConstantKernel { c: c }
+    }
+}
+
+/// Constructs a default Constant Kernel.
+///
+/// The defaults are:
+///
+/// - c = 1
+impl Default for ConstantKernel {
+    fn default() -> ConstantKernel {
+        ConstantKernel { c: 1f64 }
+    }
+}
+
+impl Kernel for ConstantKernel {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+        self.c
+    }
+
+    /// The parameters are `[c]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.c]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 1, "Expected 1 kernel parameter.");
+        self.c = params[0];
+    }
+}
+
+/// The White Noise Kernel
+///
+/// k(x,y) = σ<sup>2</sup> if x = y and 0 otherwise.
+#[derive(Clone, Copy, Debug)]
+pub struct WhiteNoise {
+    /// The noise variance.
+    pub noise: f64,
+}
+
+impl WhiteNoise {
+    /// Constructs a new White Noise Kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// let ker = kernel::WhiteNoise::new(0.5);
+    ///
+    /// assert_eq!(ker.kernel(&[1.,2.], &[1.,2.]), 0.5);
+    /// assert_eq!(ker.kernel(&[1.,2.], &[1.,3.]), 0.0);
+    /// ```
+    pub fn new(noise: f64) -> WhiteNoise {
+        WhiteNoise { noise: noise }
+    }
+}
+
+/// Constructs a default White Noise Kernel.
+///
+/// The defaults are:
+///
+/// - noise = 1
+impl Default for WhiteNoise {
+    fn default() -> WhiteNoise {
+        WhiteNoise { noise: 1f64 }
+    }
+}
+
+impl Kernel for WhiteNoise {
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+
+        if x1 == x2 { self.noise } else { 0f64 }
+    }
+
+    /// The parameters are `[noise]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.noise]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 1, "Expected 1 kernel parameter.");
+        self.noise = params[0];
+    }
+}
+
 /// The Linear Kernel
 ///
 /// k(x,y) = x<sup>T</sup>y + c
@@ -168,6 +412,16 @@ impl Kernel for Linear {
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
         utils::dot(x1, x2) + self.c
     }
+
+    /// The parameters are `[c]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.c]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 1, "Expected 1 kernel parameter.");
+        self.c = params[0];
+    }
 }
 
 /// The Polynomial Kernel
@@ -227,6 +481,18 @@ impl Kernel for Polynomial {
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
         (self.alpha * utils::dot(x1, x2) + self.c).powf(self.d)
     }
+
+    /// The parameters are `[alpha, c, d]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.alpha, self.c, self.d]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 3, "Expected 3 kernel parameters.");
+        self.alpha = params[0];
+        self.c = params[1];
+        self.d = params[2];
+    }
 }
 
 /// Squared exponential kernel
@@ -244,6 +510,9 @@ pub struct SquaredExp {
     pub ampl: f64,
 }
 
+/// The radial basis function kernel, another name for the squared exponential kernel.
+pub type RBF = SquaredExp;
+
 impl SquaredExp {
     /// Construct a new squared exponential kernel.
     ///
@@ -291,6 +560,95 @@ impl Kernel for SquaredExp {
         let x = -diff.dot(&diff) / (2f64 * self.ls * self.ls);
         (self.ampl * x.exp())
     }
+
+    /// The parameters are `[ls, ampl]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.ls, self.ampl]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 2, "Expected 2 kernel parameters.");
+        self.ls = params[0];
+        self.ampl = params[1];
+    }
+}
+
+/// Squared exponential kernel with automatic relevance determination.
+///
+/// k(x,y) = A _exp_(-Σ<sub>i</sub> (x<sub>i</sub>-y<sub>i</sub>)<sup>2</sup> / 2l<sub>i</sub><sup>2</sup>)
+///
+/// Where A is the amplitude and l<sub>i</sub> the length scale of dimension i.
+/// A large length scale means the kernel is insensitive to that dimension.
+#[derive(Clone, Debug)]
+pub struct SquaredExpARD {
+    /// The length scale of each dimension.
+    pub ls: Vec<f64>,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl SquaredExpARD {
+    /// Construct a new ARD squared exponential kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel for three dimensional inputs with amplitude 1.
+    /// let ker = kernel::SquaredExpARD::new(vec![1f64, 2f64, 10f64], 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Any of the length scales is not strictly positive.
+    pub fn new(ls: Vec<f64>, ampl: f64) -> SquaredExpARD {
+        assert!(ls.iter().all(|&l| l > 0f64), "The length scales must be positive.");
+
+        SquaredExpARD {
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+}
+
+impl Kernel for SquaredExpARD {
+    /// The ARD squared exponential kernel function.
+    ///
+    /// # Panics
+    ///
+    /// - The inputs do not have one entry per length scale.
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+        assert_eq!(x1.len(), self.ls.len(),
+                   "The inputs must have the same dimension as the length scales.");
+
+        let x = x1.iter()
+            .zip(x2.iter())
+            .zip(self.ls.iter())
+            .map(|((a, b), l)| (a - b) * (a - b) / (l * l))
+            .sum::<f64>();
+
+        self.ampl * (-x / 2f64).exp()
+    }
+
+    /// The parameters are the length scales followed by `ampl`.
+    fn get_params(&self) -> Vec<f64> {
+        let mut params = self.ls.clone();
+        params.push(self.ampl);
+        params
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), self.ls.len() + 1,
+                   "Expected one parameter per length scale and the amplitude.");
+        let n = self.ls.len();
+        self.ls = params[..n].to_vec();
+        self.ampl = params[n];
+    }
 }
 
 /// The Exponential Kernel
@@ -353,6 +711,257 @@ impl Kernel for Exponential {
         let x = -Euclidean.norm(&diff) / (2f64 * self.ls * self.ls);
         (self.ampl * x.exp())
     }
+
+    /// The parameters are `[ls, ampl]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.ls, self.ampl]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 2, "Expected 2 kernel parameters.");
+        self.ls = params[0];
+        self.ampl = params[1];
+    }
+}
+
+/// The Matérn kernel with ν = 3/2.
+///
+/// k(x,y) = A (1 + √3 r / l) _exp_(-√3 r / l)
+///
+/// Where r = ||x-y||, A is the amplitude and l is the length scale.
+///
+/// Functions drawn from a GP with this kernel are once differentiable,
+/// making it a good choice for data which is rougher than the squared
+/// exponential kernel allows.
+#[derive(Clone, Copy, Debug)]
+pub struct Matern32 {
+    /// The length scale of the kernel.
+    pub ls: f64,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl Matern32 {
+    /// Construct a new Matérn 3/2 kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel with lengthscale 2 and amplitude 1.
+    /// let ker = kernel::Matern32::new(2f64, 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    pub fn new(ls: f64, ampl: f64) -> Matern32 {
+        Matern32 {
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+}
+
+/// Constructs the default Matérn 3/2 kernel.
+///
+/// The defaults are:
+///
+/// - ls = 1
+/// - ampl = 1
+impl Default for Matern32 {
+    fn default() -> Matern32 {
+        Matern32 {
+            ls: 1f64,
+            ampl: 1f64,
+        }
+    }
+}
+
+impl Kernel for Matern32 {
+    /// The Matérn 3/2 kernel function.
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+
+        let r = Euclidean.metric(&(x1.into()), &(x2.into()));
+        let x = 3f64.sqrt() * r / self.ls;
+        self.ampl * (1f64 + x) * (-x).exp()
+    }
+
+    /// The parameters are `[ls, ampl]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.ls, self.ampl]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 2, "Expected 2 kernel parameters.");
+        self.ls = params[0];
+        self.ampl = params[1];
+    }
+}
+
+/// The Matérn kernel with ν = 5/2.
+///
+/// k(x,y) = A (1 + √5 r / l + 5r<sup>2</sup> / 3l<sup>2</sup>) _exp_(-√5 r / l)
+///
+/// Where r = ||x-y||, A is the amplitude and l is the length scale.
+///
+/// Functions drawn from a GP with this kernel are twice differentiable.
+#[derive(Clone, Copy, Debug)]
+pub struct Matern52 {
+    /// The length scale of the kernel.
+    pub ls: f64,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl Matern52 {
+    /// Construct a new Matérn 5/2 kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel with lengthscale 2 and amplitude 1.
+    /// let ker = kernel::Matern52::new(2f64, 1f64);
+    ///
+    /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
+    /// ```
+    pub fn new(ls: f64, ampl: f64) -> Matern52 {
+        Matern52 {
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+}
+
+/// Constructs the default Matérn 5/2 kernel.
+///
+/// The defaults are:
+///
+/// - ls = 1
+/// - ampl = 1
+impl Default for Matern52 {
+    fn default() -> Matern52 {
+        Matern52 {
+            ls: 1f64,
+            ampl: 1f64,
+        }
+    }
+}
+
+impl Kernel for Matern52 {
+    /// The Matérn 5/2 kernel function.
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+
+        let r = Euclidean.metric(&(x1.into()), &(x2.into()));
+        let x = 5f64.sqrt() * r / self.ls;
+        self.ampl * (1f64 + x + x * x / 3f64) * (-x).exp()
+    }
+
+    /// The parameters are `[ls, ampl]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.ls, self.ampl]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 2, "Expected 2 kernel parameters.");
+        self.ls = params[0];
+        self.ampl = params[1];
+    }
+}
+
+/// The Periodic Kernel
+///
+/// Also known as the exp-sine-squared kernel.
+///
+/// k(x,y) = A _exp_(-2 _sin_<sup>2</sup>(π||x-y|| / p) / l<sup>2</sup>)
+///
+/// Where A is the amplitude, p is the period and l is the length scale.
+///
+/// This kernel is useful for seasonal data, and can be combined with
+/// other kernels to model functions which are only locally periodic.
+#[derive(Clone, Copy, Debug)]
+pub struct Periodic {
+    /// The period of the kernel.
+    pub period: f64,
+    /// The length scale of the kernel.
+    pub ls: f64,
+    /// The amplitude of the kernel.
+    pub ampl: f64,
+}
+
+impl Periodic {
+    /// Construct a new periodic kernel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rusty_machine::learning::toolkit::kernel;
+    /// use rusty_machine::learning::toolkit::kernel::Kernel;
+    ///
+    /// // Construct a kernel with period 12, lengthscale 1 and amplitude 1.
+    /// let ker = kernel::Periodic::new(12f64, 1f64, 1f64);
+    ///
+    /// assert!((ker.kernel(&[1.], &[13.]) - 1.0).abs() < 1e-12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `period` or `ls` is not strictly positive.
+    pub fn new(period: f64, ls: f64, ampl: f64) -> Periodic {
+        assert!(period > 0f64, "The period must be positive.");
+        assert!(ls > 0f64, "The length scale must be positive.");
+
+        Periodic {
+            period: period,
+            ls: ls,
+            ampl: ampl,
+        }
+    }
+}
+
+/// Constructs the default Periodic kernel.
+///
+/// The defaults are:
+///
+/// - period = 1
+/// - ls = 1
+/// - ampl = 1
+impl Default for Periodic {
+    fn default() -> Periodic {
+        Periodic {
+            period: 1f64,
+            ls: 1f64,
+            ampl: 1f64,
+        }
+    }
+}
+
+impl Kernel for Periodic {
+    /// The periodic kernel function.
+    fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        assert_eq!(x1.len(), x2.len());
+
+        let r = Euclidean.metric(&(x1.into()), &(x2.into()));
+        let sin = (PI * r / self.period).sin();
+        self.ampl * (-2f64 * sin * sin / (self.ls * self.ls)).exp()
+    }
+
+    /// The parameters are `[period, ls, ampl]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.period, self.ls, self.ampl]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 3, "Expected 3 kernel parameters.");
+        self.period = params[0];
+        self.ls = params[1];
+        self.ampl = params[2];
+    }
 }
 
 /// The Hyperbolic Tangent Kernel.
@@ -407,6 +1016,17 @@ impl Kernel for HyperTan {
     fn kernel(&self, x1: &[f64], x2: &[f64]) -> f64 {
         (self.alpha * utils::dot(x1, x2) + self.c).tanh()
     }
+
+    /// The parameters are `[alpha, c]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.alpha, self.c]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 2, "Expected 2 kernel parameters.");
+        self.alpha = params[0];
+        self.c = params[1];
+    }
 }
 
 /// The Multiquadric Kernel.
@@ -454,11 +1074,25 @@ impl Kernel for Multiquadric {
 
         Euclidean.metric(&(x1.into()), &(x2.into())).hypot(self.c)
     }
+
+    /// The parameters are `[c]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.c]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 1, "Expected 1 kernel parameter.");
+        self.c = params[0];
+    }
 }
 
 /// The Rational Quadratic Kernel.
 ///
 /// k(x,y) = (1 + ||x-y||<sup>2</sup> / (2αl<sup>2</sup>))<sup>-α</sup>
+///
+/// This is a scale mixture of squared exponential kernels with differing
+/// length scales. As α grows it approaches the squared exponential kernel
+/// with length scale l.
 #[derive(Clone, Copy, Debug)]
 pub struct RationalQuadratic {
     /// Controls inverse power and difference scale.
@@ -481,7 +1115,14 @@ impl RationalQuadratic {
     ///
     /// println!("{0}", ker.kernel(&[1.,2.,3.], &[3.,4.,5.]));
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// - `alpha` or `ls` is not strictly positive.
     pub fn new(alpha: f64, ls: f64) -> RationalQuadratic {
+        assert!(alpha > 0f64, "The alpha parameter must be positive.");
+        assert!(ls > 0f64, "The length scale must be positive.");
+
         RationalQuadratic {
             alpha: alpha,
             ls: ls,
@@ -510,4 +1151,203 @@ impl Kernel for RationalQuadratic {
 
         (1f64 + diff.dot(&diff) / (2f64 * self.alpha * self.ls * self.ls)).powf(-self.alpha)
     }
+
+    /// The parameters are `[alpha, ls]`.
+    fn get_params(&self) -> Vec<f64> {
+        vec![self.alpha, self.ls]
+    }
+
+    fn set_params(&mut self, params: &[f64]) {
+        assert_eq!(params.len(), 2, "Expected 2 kernel parameters.");
+        self.alpha = params[0];
+        self.ls = params[1];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kernel, KernelArith, Matern32, Matern52, Periodic, RationalQuadratic, SquaredExp,
+                SquaredExpARD, ConstantKernel, WhiteNoise, Linear, Polynomial, RBF};
+    use linalg::{Matrix, BaseMatrix};
+
+    #[test]
+    fn test_matern_zero_distance() {
+        let x = [1.0, 2.0];
+        assert_eq!(Matern32::new(1.5, 2.0).kernel(&x, &x), 2.0);
+        assert_eq!(Matern52::new(1.5, 2.0).kernel(&x, &x), 2.0);
+    }
+
+    #[test]
+    fn test_matern_decay() {
+        let m32 = Matern32::default();
+        let m52 = Matern52::default();
+
+        let mut prev_32 = m32.kernel(&[0.0], &[0.0]);
+        let mut prev_52 = m52.kernel(&[0.0], &[0.0]);
+        for i in 1..20 {
+            let r = i as f64 * 0.25;
+            let k_32 = m32.kernel(&[0.0], &[r]);
+            let k_52 = m52.kernel(&[0.0], &[r]);
+            assert!(k_32 < prev_32 && k_32 > 0.0);
+            assert!(k_52 < prev_52 && k_52 > 0.0);
+            prev_32 = k_32;
+            prev_52 = k_52;
+        }
+    }
+
+    #[test]
+    fn test_matern52_smoother() {
+        // Matern52 falls off more slowly than Matern32 close to zero.
+        for &r in &[1e-3, 1e-2, 0.1] {
+            let k_32 = Matern32::default().kernel(&[0.0], &[r]);
+            let k_52 = Matern52::default().kernel(&[0.0], &[r]);
+            assert!(k_52 > k_32);
+        }
+    }
+
+    #[test]
+    fn test_matern_arithmetic() {
+        let ker = KernelArith(Matern52::default()) + KernelArith(SquaredExp::default());
+        let k = ker.kernel(&[0.0], &[1.0]);
+        let expected = Matern52::default().kernel(&[0.0], &[1.0]) +
+                       SquaredExp::default().kernel(&[0.0], &[1.0]);
+        assert!((k - expected).abs() < 1e-12);
+
+        let ker = KernelArith(Matern32::default()) * KernelArith(Matern52::default());
+        assert_eq!(ker.kernel(&[0.0], &[0.0]), 1.0);
+    }
+
+    #[test]
+    fn test_periodic() {
+        let ker = Periodic::new(2.5, 0.7, 1.5);
+
+        for &x in &[0.0, 0.3, 1.7, -4.2] {
+            let k_same = ker.kernel(&[x], &[x]);
+            assert_eq!(k_same, 1.5);
+            assert!((ker.kernel(&[x], &[x + 2.5]) - k_same).abs() < 1e-12);
+            assert!((ker.kernel(&[x], &[x + 5.0]) - k_same).abs() < 1e-12);
+            assert!(ker.kernel(&[x], &[x + 1.25]) < k_same);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_periodic_invalid_period() {
+        let _ = Periodic::new(0.0, 1.0, 1.0);
+    }
+
+    #[test]
+    fn test_rational_quadratic_limit() {
+        let se = SquaredExp::new(1.3, 1.0);
+        let rq = RationalQuadratic::new(1e8, 1.3);
+
+        for &r in &[0.0, 0.5, 1.0, 2.0] {
+            assert!((rq.kernel(&[0.0], &[r]) - se.kernel(&[0.0], &[r])).abs() < 1e-6);
+        }
+
+        // Small alpha gives heavier tails
+        let rq = RationalQuadratic::new(0.5, 1.3);
+        assert!(rq.kernel(&[0.0], &[4.0]) > se.kernel(&[0.0], &[4.0]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rational_quadratic_invalid_alpha() {
+        let _ = RationalQuadratic::new(-1.0, 1.0);
+    }
+
+    #[test]
+    fn test_ard_matches_isotropic() {
+        let ard = SquaredExpARD::new(vec![1.5, 1.5, 1.5], 2.0);
+        let se = SquaredExp::new(1.5, 2.0);
+
+        let (x1, x2) = ([1.0, -2.0, 0.5], [0.3, 1.0, 2.0]);
+        assert!((ard.kernel(&x1, &x2) - se.kernel(&x1, &x2)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ard_irrelevant_dimension() {
+        let ard = SquaredExpARD::new(vec![1.0, 1e10], 1.0);
+        let se = SquaredExp::new(1.0, 1.0);
+
+        // The second feature has no effect on the kernel value.
+        let k = ard.kernel(&[0.0, 0.0], &[1.0, 100.0]);
+        assert!((k - se.kernel(&[0.0], &[1.0])).abs() < 1e-12);
+
+        let ker = KernelArith(ard) + KernelArith(se);
+        assert_eq!(ker.kernel(&[0.0, 0.0], &[0.0, 0.0]), 2.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ard_dimension_mismatch() {
+        let ard = SquaredExpARD::new(vec![1.0, 1.0], 1.0);
+        ard.kernel(&[0.0, 0.0, 0.0], &[1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_params_roundtrip() {
+        let mut ker = KernelArith(SquaredExp::new(2.0, 3.0)) * KernelArith(Periodic::default());
+        assert_eq!(ker.get_params(), vec![2.0, 3.0, 1.0, 1.0, 1.0]);
+
+        ker.set_params(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(ker.get_params(), vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        let mut ard = SquaredExpARD::new(vec![1.0, 2.0], 3.0);
+        ard.set_params(&[4.0, 5.0, 6.0]);
+        assert_eq!(ard.ls, vec![4.0, 5.0]);
+        assert_eq!(ard.ampl, 6.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_params_wrong_length() {
+        let mut ker = Matern32::default();
+        ker.set_params(&[1.0]);
+    }
+
+    #[test]
+    fn test_boxed_arithmetic() {
+        let (x1, x2) = ([0.5, 1.0], [1.5, -1.0]);
+
+        let boxed = (Box::new(SquaredExp::default()) as Box<dyn Kernel>) *
+                    (Box::new(ConstantKernel::new(3.0)) as Box<dyn Kernel>) +
+                    (Box::new(WhiteNoise::new(0.1)) as Box<dyn Kernel>);
+
+        let expected = 3.0 * SquaredExp::default().kernel(&x1, &x2);
+        assert!((boxed.kernel(&x1, &x2) - expected).abs() < 1e-12);
+        assert!((boxed.kernel(&x1, &x1) - 3.1).abs() < 1e-12);
+        assert_eq!(boxed.get_params(), vec![1.0, 1.0, 3.0, 0.1]);
+    }
+
+    #[test]
+    fn test_gram() {
+        let data = Matrix::new(3, 2, vec![0.0, 1.0, 2.0, -1.0, 0.5, 0.5]);
+        let ker = Polynomial::new(1.0, 2.0, 1.0);
+
+        let gram = ker.gram(&data);
+        assert_eq!(gram.rows(), 3);
+        assert_eq!(gram.cols(), 3);
+
+        for (i, x1) in data.row_iter().enumerate() {
+            for (j, x2) in data.row_iter().enumerate() {
+                assert_eq!(gram[[i, j]], ker.kernel(x1.raw_slice(), x2.raw_slice()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_boxed_gram() {
+        let data = Matrix::new(2, 1, vec![1.0, 3.0]);
+        let boxed: Box<dyn Kernel> = Box::new(Linear::new(1.0));
+
+        assert_eq!(boxed.gram(&data).into_vec(), vec![2.0, 4.0, 4.0, 10.0]);
+    }
+
+    #[test]
+    fn test_rbf_is_squared_exp() {
+        let rbf = RBF::new(2.0, 1.5);
+        assert_eq!(rbf.kernel(&[0.0, 1.0], &[2.0, 0.0]),
+                   SquaredExp::new(2.0, 1.5).kernel(&[0.0, 1.0], &[2.0, 0.0]));
+    }
 }
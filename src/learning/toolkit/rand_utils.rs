@@ -3,7 +3,11 @@
 //! This module provides sampling and shuffling which are used
 //! within the learning modules.
 
-use rand::{Rng, thread_rng};
+use rand::{Rng, StdRng, SeedableRng, thread_rng};
+
+use linalg::Vector;
+
+use std::collections::BTreeMap;
 
 /// ```
 /// use rusty_machine::learning::toolkit::rand_utils;
@@ -97,8 +101,31 @@ pub fn fisher_yates<T: Copy>(arr: &[T]) -> Vec<T> {
 /// rand_utils::in_place_fisher_yates(&mut a);
 /// ```
 pub fn in_place_fisher_yates<T>(arr: &mut [T]) {
+    in_place_fisher_yates_with_rng(arr, &mut thread_rng());
+}
+
+/// The in place Fisher-Yates shuffle using the given random number generator.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate rand;
+/// # extern crate rusty_machine;
+/// use rusty_machine::learning::toolkit::rand_utils;
+/// use rand::{StdRng, SeedableRng};
+///
+/// # fn main() {
+/// let mut a = (0..5).collect::<Vec<_>>();
+/// let mut b = a.clone();
+///
+/// // The same seed gives the same permutation.
+/// rand_utils::in_place_fisher_yates_with_rng(&mut a, &mut StdRng::from_seed(&[1]));
+/// rand_utils::in_place_fisher_yates_with_rng(&mut b, &mut StdRng::from_seed(&[1]));
+/// assert_eq!(a, b);
+/// # }
+/// ```
+pub fn in_place_fisher_yates_with_rng<T, R: Rng>(arr: &mut [T], rng: &mut R) {
     let n = arr.len();
-    let mut rng = thread_rng();
 
     for i in 0..n {
         // Swap i with a random point after it
@@ -107,9 +134,63 @@ pub fn in_place_fisher_yates<T>(arr: &mut [T]) {
     }
 }
 
+/// Sample row indices with a fixed number of samples from each class.
+///
+/// Classes with at least `per_class` members are sampled without
+/// replacement, smaller classes are sampled with replacement. The
+/// indices are grouped by class in ascending class order.
+///
+/// The returned indices can be passed to `select_rows` to balance
+/// a dataset.
+///
+/// # Examples
+///
+/// ```
+/// use rusty_machine::learning::toolkit::rand_utils;
+/// use rusty_machine::linalg::Vector;
+///
+/// let targets = Vector::new(vec![0, 0, 0, 0, 1, 2, 2]);
+///
+/// // Two samples from each class.
+/// let idx = rand_utils::stratified_sample(&targets, 2, Some(42));
+/// assert_eq!(idx.len(), 6);
+/// ```
+pub fn stratified_sample(targets: &Vector<usize>, per_class: usize, seed: Option<usize>) -> Vec<usize> {
+    let mut classes: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, t) in targets.iter().enumerate() {
+        classes.entry(*t).or_insert_with(Vec::new).push(i);
+    }
+
+    let seed = match seed {
+        Some(s) => s,
+        None => thread_rng().gen(),
+    };
+    let mut rng = StdRng::from_seed(&[seed]);
+
+    let mut sample = Vec::with_capacity(classes.len() * per_class);
+    for (_, mut members) in classes {
+        let n = members.len();
+        if n >= per_class {
+            // Partial Fisher-Yates shuffle
+            for i in 0..per_class {
+                let j = rng.gen_range(0, n - i);
+                members.swap(i, i + j);
+            }
+            sample.extend_from_slice(&members[..per_class]);
+        } else {
+            for _ in 0..per_class {
+                sample.push(members[rng.gen_range(0, n)]);
+            }
+        }
+    }
+
+    sample
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use linalg::Vector;
 
     #[test]
     fn test_reservoir_sample() {
@@ -141,4 +222,24 @@ mod tests {
             assert!(a.contains(&val));
         }
     }
+
+    #[test]
+    fn test_stratified_sample() {
+        let targets = Vector::new(vec![0, 0, 0, 0, 1, 2, 2]);
+
+        let idx = stratified_sample(&targets, 3, Some(1));
+        assert_eq!(idx.len(), 9);
+
+        let classes = idx.iter().map(|&i| targets[i]).collect::<Vec<_>>();
+        assert_eq!(classes, vec![0, 0, 0, 1, 1, 1, 2, 2, 2]);
+
+        // The large class is sampled without replacement
+        let mut large = idx[..3].to_vec();
+        large.sort();
+        large.dedup();
+        assert_eq!(large.len(), 3);
+
+        // The sample is reproducible
+        assert_eq!(idx, stratified_sample(&targets, 3, Some(1)));
+    }
 }
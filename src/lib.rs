@@ -19,12 +19,16 @@
 //! - Logistic Regression
 //! - Generalized Linear Models
 //! - K-Means Clustering
+//! - Bisecting K-Means Clustering
+//! - Agglomerative Hierarchical Clustering
 //! - Neural Networks
 //! - Gaussian Process Regression
+//! - Kernel Ridge Regression
 //! - Support Vector Machines
 //! - Gaussian Mixture Models
 //! - Naive Bayes Classifiers
 //! - DBSCAN
+//! - Isolation Forests
 //! - k-Nearest Neighbor Classifiers
 //! - Principal Component Analysis
 //!
@@ -111,6 +115,8 @@
 extern crate rulinalg;
 extern crate num as libnum;
 extern crate rand;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
 pub mod prelude;
 
@@ -122,6 +128,8 @@ pub mod linalg {
     pub use rulinalg::vector::Vector;
     pub use rulinalg::norm;
     pub use rulinalg::matrix::decomposition::*;
+
+    pub mod sparse;
 }
 
 /// Module for data handling
@@ -131,14 +139,18 @@ pub mod data {
 
 /// Module for machine learning.
 pub mod learning {
+    pub mod agglomerative;
+    pub mod bisecting_k_means;
     pub mod dbscan;
     pub mod glm;
     pub mod gmm;
+    pub mod isolation_forest;
     pub mod lin_reg;
     pub mod logistic_reg;
     pub mod k_means;
     pub mod nnet;
     pub mod gp;
+    pub mod kernel_ridge;
     pub mod svm;
     pub mod naive_bayes;
     pub mod knn;
@@ -165,6 +177,28 @@ pub mod learning {
 
         /// Train the model using inputs.
         fn train(&mut self, inputs: &T) -> LearningResult<()>;
+
+        /// Train the model using inputs and predict the outputs of the same inputs.
+        ///
+        /// Calls `train` followed by `predict` by default. Models which know
+        /// the outputs of the training inputs once trained may return them
+        /// directly.
+        fn fit_predict(&mut self, inputs: &T) -> LearningResult<U> {
+            self.train(inputs)?;
+            self.predict(inputs)
+        }
+    }
+
+    /// Trait for models with a fixed number of free parameters.
+    ///
+    /// This allows information criteria such as the BIC to be computed
+    /// in the same way for different model types.
+    pub trait Parametric {
+        /// The number of free parameters in the model.
+        ///
+        /// Models whose parameter count depends on the training data
+        /// report zero until they are trained.
+        fn n_parameters(&self) -> usize;
     }
 
     /// Module for optimization in machine learning setting.
@@ -183,6 +217,23 @@ pub mod learning {
                             inputs: &Self::Inputs,
                             targets: &Self::Targets)
                             -> (f64, Vec<f64>);
+
+            /// Called by the optimization algorithms with the cost
+            /// at the end of each iteration.
+            ///
+            /// Returns `true` to stop the optimization early. The default
+            /// implementation never stops. Models can override this to record
+            /// the cost history or implement their own convergence criterion.
+            fn end_iteration(&self, _iter: usize, _cost: f64) -> bool {
+                false
+            }
+
+            /// Called by the optimization algorithms after each
+            /// accepted update of the parameters.
+            ///
+            /// Models can override this to project the parameters back
+            /// onto a constraint set. The default implementation does nothing.
+            fn constrain_params(&self, _params: &mut [f64]) {}
         }
 
         /// Trait for optimization algorithms.
@@ -200,12 +251,16 @@ pub mod learning {
 
         pub mod grad_desc;
         pub mod fmincg;
+        pub mod lbfgs;
     }
 
     /// Module for learning tools.
     pub mod toolkit {
         pub mod activ_fn;
         pub mod cost_fn;
+        pub mod covariance;
+        pub mod distance;
+        pub mod grad_check;
         pub mod kernel;
         pub mod rand_utils;
         pub mod regularization;
@@ -222,6 +277,7 @@ pub mod stats {
 
 /// Module for evaluating models.
 pub mod analysis {
+    pub mod calibration;
     pub mod confusion_matrix;
     pub mod cross_validation;
     pub mod score;
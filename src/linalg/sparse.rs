@@ -0,0 +1,214 @@
+//! Sparse matrices
+//!
+//! This module contains the `CsrMatrix`, a sparse matrix stored in the
+//! compressed sparse row format. Only the nonzero entries are stored so
+//! very large matrices with few nonzeros per row fit in memory.
+//!
+//! The matrix supports the matrix-vector products needed to train linear
+//! models. It is not a replacement for the dense `Matrix`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rusty_machine::linalg::Vector;
+//! use rusty_machine::linalg::sparse::CsrMatrix;
+//!
+//! // The matrix [[1, 0, 2], [0, 0, 3]]
+//! let mat = CsrMatrix::from_triplets(2, 3, &[(0, 0, 1.0), (0, 2, 2.0), (1, 2, 3.0)]).unwrap();
+//! assert_eq!(mat.nnz(), 3);
+//!
+//! let y = mat.mul_vec(&Vector::new(vec![1.0, 1.0, 1.0]));
+//! assert_eq!(y.into_vec(), vec![3.0, 3.0]);
+//! ```
+
+use learning::LearningResult;
+use learning::error::{Error, ErrorKind};
+use linalg::Vector;
+
+/// A sparse matrix in compressed sparse row format.
+///
+/// The nonzero entries of row `i` are stored at the positions
+/// `row_ptr[i]..row_ptr[i + 1]` of the column indices and values,
+/// in increasing column order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix {
+    rows: usize,
+    cols: usize,
+    row_ptr: Vec<usize>,
+    col_indices: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl CsrMatrix {
+    /// Construct a sparse matrix from `(row, col, value)` triplets.
+    ///
+    /// The triplets may be in any order. Values of repeated
+    /// entries are summed.
+    ///
+    /// # Failures
+    ///
+    /// - A row or column index is out of bounds.
+    /// - A value is not finite.
+    pub fn from_triplets(rows: usize,
+                         cols: usize,
+                         triplets: &[(usize, usize, f64)])
+                         -> LearningResult<CsrMatrix> {
+        for &(r, c, v) in triplets {
+            if r >= rows || c >= cols {
+                return Err(Error::new(ErrorKind::InvalidData,
+                                      "Triplet index is outside of the matrix dimensions."));
+            }
+            if !v.is_finite() {
+                return Err(Error::new(ErrorKind::InvalidData, "Triplet value is not finite."));
+            }
+        }
+
+        let mut sorted = triplets.to_vec();
+        sorted.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+        let mut row_ptr = vec![0; rows + 1];
+        let mut col_indices: Vec<usize> = Vec::with_capacity(sorted.len());
+        let mut values: Vec<f64> = Vec::with_capacity(sorted.len());
+        let mut last = None;
+
+        for (r, c, v) in sorted {
+            if last == Some((r, c)) {
+                // The repeated entry is the last one stored.
+                *values.last_mut().unwrap() += v;
+            } else {
+                row_ptr[r + 1] += 1;
+                col_indices.push(c);
+                values.push(v);
+                last = Some((r, c));
+            }
+        }
+
+        for i in 0..rows {
+            row_ptr[i + 1] += row_ptr[i];
+        }
+
+        Ok(CsrMatrix {
+            rows: rows,
+            cols: cols,
+            row_ptr: row_ptr,
+            col_indices: col_indices,
+            values: values,
+        })
+    }
+
+    /// The number of rows of the matrix.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The number of columns of the matrix.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The number of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The column indices and values of the stored entries of a row.
+    ///
+    /// # Panics
+    ///
+    /// - `row` is out of bounds.
+    pub fn row(&self, row: usize) -> (&[usize], &[f64]) {
+        assert!(row < self.rows, "Row index is out of bounds.");
+        let range = self.row_ptr[row]..self.row_ptr[row + 1];
+        (&self.col_indices[range.clone()], &self.values[range])
+    }
+
+    /// Compute the product `Ax` of the matrix with a vector.
+    ///
+    /// # Panics
+    ///
+    /// - The vector length is not the number of columns.
+    pub fn mul_vec(&self, x: &Vector<f64>) -> Vector<f64> {
+        assert_eq!(x.size(), self.cols, "Vector length must match the number of columns.");
+
+        let x = x.data();
+        let out = (0..self.rows)
+            .map(|i| {
+                let (cols, values) = self.row(i);
+                cols.iter().zip(values.iter()).map(|(&c, v)| v * x[c]).sum::<f64>()
+            })
+            .collect::<Vec<f64>>();
+
+        Vector::new(out)
+    }
+
+    /// Compute the product `Aᵀx` of the transposed matrix with a vector.
+    ///
+    /// # Panics
+    ///
+    /// - The vector length is not the number of rows.
+    pub fn transpose_mul_vec(&self, x: &Vector<f64>) -> Vector<f64> {
+        assert_eq!(x.size(), self.rows, "Vector length must match the number of rows.");
+
+        let mut out = vec![0f64; self.cols];
+        for (i, xi) in x.iter().enumerate() {
+            let (cols, values) = self.row(i);
+            for (&c, v) in cols.iter().zip(values.iter()) {
+                out[c] += v * xi;
+            }
+        }
+
+        Vector::new(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CsrMatrix;
+    use linalg::{Matrix, BaseMatrix, Vector};
+
+    #[test]
+    fn test_from_triplets() {
+        let mat = CsrMatrix::from_triplets(3, 2, &[(2, 1, 4.0), (0, 1, 1.0), (2, 0, 3.0),
+                                                   (0, 1, 1.5)])
+            .unwrap();
+
+        assert_eq!(mat.rows(), 3);
+        assert_eq!(mat.cols(), 2);
+        assert_eq!(mat.nnz(), 3);
+        assert_eq!(mat.row(0).0, &[1]);
+        assert_eq!(mat.row(0).1, &[2.5]);
+        assert!(mat.row(1).0.is_empty());
+        assert_eq!(mat.row(2).0, &[0, 1]);
+        assert_eq!(mat.row(2).1, &[3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_invalid_triplets() {
+        assert!(CsrMatrix::from_triplets(2, 2, &[(2, 0, 1.0)]).is_err());
+        assert!(CsrMatrix::from_triplets(2, 2, &[(0, 2, 1.0)]).is_err());
+        assert!(CsrMatrix::from_triplets(2, 2, &[(0, 0, ::std::f64::NAN)]).is_err());
+    }
+
+    #[test]
+    fn test_products_match_dense() {
+        let dense = Matrix::new(3, 4, vec![1.0, 0.0, 0.0, 2.0,
+                                           0.0, 0.0, 0.0, 0.0,
+                                           0.0, -3.0, 0.5, 0.0]);
+        let sparse = CsrMatrix::from_triplets(3, 4, &[(0, 0, 1.0), (0, 3, 2.0),
+                                                      (2, 1, -3.0), (2, 2, 0.5)])
+            .unwrap();
+
+        let x = Vector::new(vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(sparse.mul_vec(&x), &dense * &x);
+
+        let y = Vector::new(vec![1.0, -1.0, 2.0]);
+        assert_eq!(sparse.transpose_mul_vec(&y), dense.transpose() * &y);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mul_vec_wrong_length() {
+        let mat = CsrMatrix::from_triplets(2, 2, &[(0, 0, 1.0)]).unwrap();
+        let _ = mat.mul_vec(&Vector::new(vec![1.0]));
+    }
+}
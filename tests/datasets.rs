@@ -4,6 +4,9 @@ extern crate rusty_machine as rm;
 pub mod test {
 
     use rm::datasets;
+    use rm::learning::nnet::{NeuralNet, CategoricalCECriterion};
+    use rm::learning::optim::grad_desc::GradientDesc;
+    use rm::learning::toolkit::activ_fn::Sigmoid;
     use rm::linalg::BaseMatrix;
 
     #[test]
@@ -23,4 +26,19 @@ pub mod test {
 
         assert_eq!(dt.target().size(), 31);
     }
+
+    #[test]
+    fn test_iris_softmax_net() {
+        let dt = datasets::iris::load();
+
+        let mut model = NeuralNet::softmax_mlp(&[4, 8, 3],
+                                               CategoricalCECriterion::default(),
+                                               GradientDesc::new(0.005, 1500),
+                                               Sigmoid);
+        model.train_classes(dt.data(), dt.target()).unwrap();
+
+        let classes = model.predict_classes(dt.data()).unwrap();
+        let correct = classes.iter().zip(dt.target().iter()).filter(|&(c, t)| c == t).count();
+        assert!(correct as f64 / 150.0 > 0.9);
+    }
 }
@@ -0,0 +1,93 @@
+use rm::linalg::{Matrix, BaseMatrix};
+use rm::learning::UnSupModel;
+use rm::learning::bisecting_k_means::BisectingKMeans;
+use rm::learning::k_means::KMeansClassifier;
+
+use learning::fixtures::{clustered_points, OFFSETS};
+
+/// Four well separated clusters of five points each.
+fn blobs() -> Matrix<f64> {
+    clustered_points(&[(0.0, 0.0), (10.0, 10.0), (-10.0, 10.0), (10.0, -10.0)],
+                     &OFFSETS[..5])
+}
+
+fn inertia(inputs: &Matrix<f64>, centroids: &Matrix<f64>) -> f64 {
+    inputs.row_iter()
+        .map(|x| {
+            centroids.row_iter()
+                .map(|c| {
+                    c.raw_slice()
+                        .iter()
+                        .zip(x.raw_slice().iter())
+                        .map(|(a, b)| (a - b) * (a - b))
+                        .sum::<f64>()
+                })
+                .fold(::std::f64::INFINITY, f64::min)
+        })
+        .sum()
+}
+
+#[test]
+fn test_inertia_comparable_to_k_means() {
+    let inputs = blobs();
+
+    let mut model = BisectingKMeans::new(4);
+    model.set_seed(7);
+    model.train(&inputs).unwrap();
+    let bisect_inertia = inertia(&inputs, model.centroids().as_ref().unwrap());
+
+    let mut k_means = KMeansClassifier::new(4);
+    k_means.train(&inputs).unwrap();
+    let k_means_inertia = inertia(&inputs, k_means.centroids().as_ref().unwrap());
+
+    // The blobs are well separated so both should find them.
+    assert!(bisect_inertia <= k_means_inertia * 1.1 + 1e-8);
+    assert!(bisect_inertia < 5.0);
+}
+
+#[test]
+fn test_seed_reproduction() {
+    let inputs = blobs();
+
+    let mut model_a = BisectingKMeans::new(3);
+    model_a.set_seed(11);
+    model_a.train(&inputs).unwrap();
+
+    let mut model_b = BisectingKMeans::new(3);
+    model_b.set_seed(11);
+    model_b.train(&inputs).unwrap();
+
+    assert_eq!(model_a.centroids().as_ref().unwrap().data(),
+               model_b.centroids().as_ref().unwrap().data());
+    assert_eq!(model_a.predict(&inputs).unwrap(),
+               model_b.predict(&inputs).unwrap());
+}
+
+#[test]
+fn test_split_tree() {
+    let inputs = blobs();
+
+    let mut model = BisectingKMeans::new(4);
+    model.set_seed(3);
+    model.train(&inputs).unwrap();
+
+    let tree = model.split_tree().unwrap();
+    let splits = tree.iter().filter(|n| n.children().is_some()).count();
+    let leaves = tree.iter().filter(|n| n.children().is_none()).count();
+
+    assert_eq!(splits, 3);
+    assert_eq!(leaves, 4);
+    assert_eq!(tree[0].size(), 20);
+
+    for node in tree {
+        if let Some((left, right)) = node.children() {
+            assert_eq!(tree[left].size() + tree[right].size(), node.size());
+        }
+    }
+
+    for n in 1..5 {
+        assert_eq!(model.cut(n).unwrap().rows(), n);
+    }
+    assert!(model.cut(0).is_err());
+    assert!(model.cut(5).is_err());
+}
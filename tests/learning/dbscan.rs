@@ -41,3 +41,23 @@ fn test_basic_prediction() {
     assert!(classes[0] == Some(0));
     assert!(classes[1] == None);
 }
+
+#[test]
+fn test_large_clusters() {
+    // A 400 x 250 grid with unit spacing forms a single cluster.
+    let mut data = Vec::with_capacity(200000);
+    for i in 0..400 {
+        for j in 0..250 {
+            data.push(i as f64);
+            data.push(j as f64);
+        }
+    }
+    let inputs = Matrix::new(100000, 2, data);
+
+    // check stack doesn't overflow and the search is fast
+    let mut model = DBSCAN::new(1.5, 4);
+    model.train(&inputs).unwrap();
+
+    let clustering = model.clusters().unwrap();
+    assert!(clustering.data().iter().all(|x| *x == Some(0)));
+}
@@ -0,0 +1,23 @@
+use rm::linalg::Matrix;
+
+/// Small offsets from a cluster center, giving tight clusters.
+pub const OFFSETS: [(f64, f64); 6] = [(0.3, -0.2), (-0.4, 0.1), (0.1, 0.5),
+                                      (-0.2, -0.3), (0.0, 0.2), (0.5, 0.4)];
+
+/// A point at each offset from each center, grouped by center.
+pub fn clustered_points(centers: &[(f64, f64)], offsets: &[(f64, f64)]) -> Matrix<f64> {
+    let mut data = Vec::with_capacity(2 * centers.len() * offsets.len());
+    for &(cx, cy) in centers {
+        for &(ox, oy) in offsets {
+            data.push(cx + ox);
+            data.push(cy + oy);
+        }
+    }
+
+    Matrix::new(centers.len() * offsets.len(), 2, data)
+}
+
+/// Three well separated clusters of six points each.
+pub fn three_clusters() -> Matrix<f64> {
+    clustered_points(&[(0.0, 0.0), (10.0, 10.0), (-10.0, 10.0)], &OFFSETS)
+}
@@ -1,7 +1,36 @@
 use rm::linalg::Matrix;
 use rm::linalg::Vector;
+use rm::linalg::{BaseMatrix, Cholesky, Decomposition};
 use rm::learning::SupModel;
-use rm::learning::gp::GaussianProcess;
+use rm::learning::gp::{GaussianProcess, GaussianProcessClassifier, SparseGaussianProcess, ConstMean,
+                       LinearMean};
+use rm::learning::error::ErrorKind;
+use rm::learning::toolkit::kernel::{Kernel, KernelArith, ConstantKernel, Linear, Matern52,
+                                    Periodic, SquaredExp, SquaredExpARD, WhiteNoise};
+
+use rand::{Rng, StdRng, SeedableRng};
+use rand::distributions::IndependentSample;
+use rand::distributions::normal::Normal;
+
+use std::f64::consts::PI;
+
+fn held_out_mse<K: Kernel>(ker: K) -> f64 {
+	let train_x = (0..21).map(|i| i as f64 * 0.5).collect::<Vec<f64>>();
+	let test_x = (0..20).map(|i| 0.25 + i as f64 * 0.5).collect::<Vec<f64>>();
+
+	// A rough function with kinks at multiples of pi.
+	let f = |x: f64| x.sin().abs();
+
+	let mut gp = GaussianProcess::new(ker, ConstMean::default(), 1e-3f64);
+	let targets = Vector::new(train_x.iter().map(|&x| f(x)).collect::<Vec<f64>>());
+	gp.train(&Matrix::new(21, 1, train_x), &targets).unwrap();
+
+	let outputs = gp.predict(&Matrix::new(20, 1, test_x.clone())).unwrap();
+	outputs.iter()
+		.zip(test_x.iter())
+		.map(|(o, &x)| (o - f(x)) * (o - f(x)))
+		.sum::<f64>() / 20f64
+}
 
 #[test]
 fn test_default_gp() {
@@ -17,3 +46,630 @@ fn test_default_gp() {
 
 	let _outputs = gp.predict(&test_inputs).unwrap();
 }
+
+#[test]
+fn test_gp_variance_at_training_inputs() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-4f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,2.,4.]);
+	let targets = Vector::new(vec![0.,1.,0.]);
+
+	gp.train(&inputs, &targets).unwrap();
+
+	let (_, var) = gp.predict_with_variance(&inputs).unwrap();
+	for v in var.into_vec() {
+		assert!(v >= 0f64);
+		assert!(v < 2e-4f64);
+	}
+}
+
+#[test]
+fn test_gp_variance_grows_with_distance() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-2f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.,0.]);
+
+	gp.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(4,1,vec![2.,3.,4.,6.]);
+	let (mean, var) = gp.predict_with_variance(&test_inputs).unwrap();
+
+	for i in 1..4 {
+		assert!(var[i] > var[i - 1]);
+	}
+
+	// The mean matches the point predictions.
+	let outputs = gp.predict(&test_inputs).unwrap();
+	for (a, b) in mean.iter().zip(outputs.iter()) {
+		assert!((a - b).abs() < 1e-10);
+	}
+}
+
+#[test]
+fn test_gp_covariance_matches_variance() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-2f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.,0.]);
+
+	gp.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(3,1,vec![0.5,1.5,5.]);
+	let (_, var) = gp.predict_with_variance(&test_inputs).unwrap();
+	let (_, cov) = gp.predict_with_covariance(&test_inputs).unwrap();
+
+	for i in 0..3 {
+		assert!((cov[[i, i]] - var[i]).abs() < 1e-10);
+	}
+	assert!((cov[[0, 1]] - cov[[1, 0]]).abs() < 1e-10);
+}
+
+#[test]
+fn test_gp_variance_untrained() {
+	let gp = GaussianProcess::default();
+	let test_inputs = Matrix::new(1,1,vec![0.]);
+
+	assert!(gp.predict_with_variance(&test_inputs).is_err());
+	assert!(gp.predict_with_covariance(&test_inputs).is_err());
+}
+
+#[test]
+fn test_matern_gp_fits_rough_function() {
+	let matern_mse = held_out_mse(Matern52::default());
+	let se_mse = held_out_mse(SquaredExp::default());
+
+	assert!(matern_mse < se_mse);
+}
+
+#[test]
+fn test_periodic_gp_extrapolates_seasonal() {
+	let train_x = (0..41).map(|i| i as f64 * 0.25).collect::<Vec<f64>>();
+	let test_x = (0..16).map(|i| 10.1 + i as f64 * 0.25).collect::<Vec<f64>>();
+
+	// A seasonal series with period 3.
+	let f = |x: f64| (2f64 * PI * x / 3f64).sin();
+	let targets = Vector::new(train_x.iter().map(|&x| f(x)).collect::<Vec<f64>>());
+	let train_x = Matrix::new(41, 1, train_x);
+
+	let seasonal = KernelArith(SquaredExp::new(10f64, 1f64)) * KernelArith(Periodic::new(3f64, 1f64, 1f64));
+	let mut seasonal_gp = GaussianProcess::new(seasonal, ConstMean::default(), 1e-2f64);
+	seasonal_gp.train(&train_x, &targets).unwrap();
+
+	let mut se_gp = GaussianProcess::new(SquaredExp::default(), ConstMean::default(), 1e-2f64);
+	se_gp.train(&train_x, &targets).unwrap();
+
+	let mse = |outputs: Vector<f64>| {
+		outputs.iter()
+			.zip(test_x.iter())
+			.map(|(o, &x)| (o - f(x)) * (o - f(x)))
+			.sum::<f64>() / 16f64
+	};
+
+	let test_inputs = Matrix::new(16, 1, test_x.clone());
+	let seasonal_mse = mse(seasonal_gp.predict(&test_inputs).unwrap());
+	let se_mse = mse(se_gp.predict(&test_inputs).unwrap());
+
+	assert!(seasonal_mse < 0.05);
+	assert!(seasonal_mse < se_mse);
+}
+
+#[test]
+fn test_ard_gp() {
+	let ker = SquaredExpARD::new(vec![1f64, 100f64], 1f64);
+	let mut gp = GaussianProcess::new(ker, ConstMean::default(), 1e-2f64);
+
+	let inputs = Matrix::new(4,2,vec![0.,0.,1.,50.,2.,100.,3.,150.]);
+	let targets = Vector::new(vec![0.,1.,2.,3.]);
+
+	gp.train(&inputs, &targets).unwrap();
+	let _outputs = gp.predict(&Matrix::new(1,2,vec![1.5,75.])).unwrap();
+}
+
+/// Draw targets from a GP prior with a squared exponential kernel.
+fn sample_gp_targets(inputs: &Matrix<f64>, ls: f64, noise_std: f64) -> Vector<f64> {
+	let ker = SquaredExp::new(ls, 1f64);
+	let n = inputs.rows();
+
+	let mut cov_data = Vec::with_capacity(n * n);
+	for i in 0..n {
+		for j in 0..n {
+			let jitter = if i == j { 1e-8 } else { 0f64 };
+			cov_data.push(ker.kernel(&[inputs[[i, 0]]], &[inputs[[j, 0]]]) + jitter);
+		}
+	}
+	let chol = Cholesky::decompose(Matrix::new(n, n, cov_data)).unwrap().unpack();
+
+	let mut rng = StdRng::from_seed(&[3, 14, 15]);
+	let normal = Normal::new(0f64, 1f64);
+	let z = Vector::new((0..n).map(|_| normal.ind_sample(&mut rng)).collect::<Vec<f64>>());
+	let eps = Vector::new((0..n).map(|_| noise_std * normal.ind_sample(&mut rng)).collect::<Vec<f64>>());
+
+	chol * z + eps
+}
+
+#[test]
+fn test_optimize_hyperparameters() {
+	let inputs = Matrix::new(60, 1, (0..60).map(|i| i as f64 * 0.5).collect::<Vec<f64>>());
+	let targets = sample_gp_targets(&inputs, 2f64, 0.05);
+
+	let mut gp = GaussianProcess::new(SquaredExp::new(0.7, 1f64), ConstMean::default(), 0.1);
+	let initial = gp.log_marginal_likelihood(&inputs, &targets).unwrap();
+
+	let mut rng = StdRng::from_seed(&[2]);
+	let optimized = gp.optimize_hyperparameters(&inputs, &targets, 3, &mut rng).unwrap();
+	assert!(optimized > initial);
+	assert!((gp.log_marginal_likelihood(&inputs, &targets).unwrap() - optimized).abs() < 1e-6);
+
+	// The true length scale is recovered.
+	let ls = gp.kernel().ls;
+	assert!(ls > 2f64 / 1.5 && ls < 2f64 * 1.5);
+
+	// The model is trained with the new parameters.
+	assert!(gp.predict(&inputs).is_ok());
+}
+
+#[test]
+fn test_optimize_hyperparameters_invalid() {
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.,0.]);
+
+	// A zero kernel parameter cannot be optimized in log space.
+	let mut gp = GaussianProcess::new(Linear::default(), ConstMean::default(), 1e-2f64);
+	assert!(gp.optimize_hyperparameters(&inputs, &targets, 0, &mut StdRng::from_seed(&[1])).is_err());
+}
+
+#[test]
+fn test_optimize_hyperparameters_zero_noise() {
+	let inputs = Matrix::new(5,1,vec![0.,1.,2.,3.,4.]);
+	let targets = Vector::new(vec![0.,0.8,0.9,0.1,-0.8]);
+
+	let mut gp = GaussianProcess::default();
+	gp.optimize_hyperparameters(&inputs, &targets, 0, &mut StdRng::from_seed(&[1])).unwrap();
+	assert!(gp.noise > 0f64);
+}
+
+#[test]
+fn test_gp_zero_noise_duplicate_inputs() {
+	// The training covariance is singular without jitter.
+	let mut gp = GaussianProcess::default();
+	let inputs = Matrix::new(3,1,vec![0.,0.,1.]);
+	let targets = Vector::new(vec![1.,1.,0.]);
+
+	gp.train(&inputs, &targets).unwrap();
+	let outputs = gp.predict(&inputs).unwrap();
+	assert!((outputs[0] - 1f64).abs() < 1e-3);
+}
+
+#[test]
+fn test_gp_set_noise() {
+	let inputs = Matrix::new(6,1,vec![0.,1.,2.,3.,4.,5.]);
+	let targets = Vector::new(vec![0.5,-0.5,0.5,-0.5,0.5,-0.5]);
+
+	let residual = |noise: f64| {
+		let mut gp = GaussianProcess::default();
+		gp.set_noise(noise);
+		gp.train(&inputs, &targets).unwrap();
+
+		let outputs = gp.predict(&inputs).unwrap();
+		outputs.iter().zip(targets.iter()).map(|(o, t)| (o - t) * (o - t)).sum::<f64>()
+	};
+
+	// More noise smooths the posterior mean through the noisy targets.
+	assert!(residual(1e-3) < residual(0.1));
+	assert!(residual(0.1) < residual(1.0));
+
+	let mut gp = GaussianProcess::default();
+	gp.set_noise(0.25);
+	gp.train(&inputs, &targets).unwrap();
+
+	let (_, latent) = gp.predict_with_variance(&inputs).unwrap();
+	let (_, noisy) = gp.predict_with_noisy_variance(&inputs).unwrap();
+	for (l, n) in latent.iter().zip(noisy.iter()) {
+		assert!((n - l - 0.25).abs() < 1e-12);
+	}
+}
+
+#[test]
+#[should_panic]
+fn test_gp_negative_noise() {
+	let mut gp = GaussianProcess::default();
+	gp.set_noise(-1f64);
+}
+
+#[test]
+fn test_sample_posterior() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-6f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.,0.]);
+	gp.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(3,1,vec![1.,2.5,4.]);
+	let (mean, _) = gp.predict_with_variance(&test_inputs).unwrap();
+
+	let mut rng = StdRng::from_seed(&[7, 8, 9]);
+	let samples = gp.sample_posterior(&test_inputs, 4000, &mut rng).unwrap();
+	assert_eq!(samples.rows(), 4000);
+	assert_eq!(samples.cols(), 3);
+
+	// The sample mean converges to the posterior mean.
+	for j in 0..3 {
+		let sample_mean = samples.col(j).iter().sum::<f64>() / 4000f64;
+		assert!((sample_mean - mean[j]).abs() < 0.05);
+	}
+
+	// Samples at a training point have very little spread.
+	assert!(samples.col(0).iter().all(|&x| (x - targets[1]).abs() < 1e-2));
+}
+
+#[test]
+fn test_sample_posterior_duplicate_inputs() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-2f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.,0.]);
+	gp.train(&inputs, &targets).unwrap();
+
+	// The posterior covariance of duplicated points is singular.
+	let test_inputs = Matrix::new(2,1,vec![5.,5.]);
+	let mut rng = StdRng::from_seed(&[1]);
+	let samples = gp.sample_posterior(&test_inputs, 10, &mut rng).unwrap();
+
+	for row in samples.row_iter() {
+		let row = row.raw_slice();
+		assert!((row[0] - row[1]).abs() < 1e-3);
+	}
+}
+
+#[test]
+fn test_gp_predict_reuses_factorization() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-2f64;
+
+	let inputs = Matrix::new(50, 1, (0..50).map(|i| i as f64 * 0.2).collect::<Vec<f64>>());
+	let targets = Vector::new((0..50).map(|i| (i as f64 * 0.2).sin()).collect::<Vec<f64>>());
+	gp.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(2,1,vec![1.1,3.3]);
+	let first = gp.predict(&test_inputs).unwrap();
+
+	// Repeated predictions only use the stored factorization.
+	for _ in 0..1000 {
+		assert_eq!(gp.predict(&test_inputs).unwrap(), first);
+	}
+
+	let (mean, _) = gp.get_posterior(&test_inputs).unwrap();
+	for (a, b) in mean.iter().zip(first.iter()) {
+		assert!((a - b).abs() < 1e-10);
+	}
+}
+
+#[test]
+fn test_gp_train_mismatched_targets() {
+	let mut gp = GaussianProcess::default();
+	gp.noise = 1e-2f64;
+
+	let inputs = Matrix::new(3,1,vec![0.,1.,2.]);
+	let targets = Vector::new(vec![0.,1.]);
+
+	assert!(gp.train(&inputs, &targets).is_err());
+}
+
+/// Build a kernel from a description like "se*periodic+white".
+///
+/// Products bind more tightly than sums.
+fn parse_kernel(desc: &str) -> Box<dyn Kernel> {
+	let parse_term = |term: &str| {
+		term.split('*')
+			.map(|name| -> Box<dyn Kernel> {
+				match name.trim() {
+					"se" => Box::new(SquaredExp::new(2f64, 1f64)),
+					"periodic" => Box::new(Periodic::new(3f64, 1f64, 1f64)),
+					"white" => Box::new(WhiteNoise::new(0.1)),
+					"const" => Box::new(ConstantKernel::new(2f64)),
+					other => panic!("Unknown kernel: {}", other),
+				}
+			})
+			.fold(None, |acc: Option<Box<dyn Kernel>>, k| Some(match acc {
+				Some(acc) => acc * k,
+				None => k,
+			}))
+			.unwrap()
+	};
+
+	desc.split('+')
+		.map(parse_term)
+		.fold(None, |acc: Option<Box<dyn Kernel>>, k| Some(match acc {
+			Some(acc) => acc + k,
+			None => k,
+		}))
+		.unwrap()
+}
+
+#[test]
+fn test_gp_runtime_kernel() {
+	let inputs = Matrix::new(8,1,vec![0.,1.,2.,3.,4.,5.,6.,7.]);
+	let targets = Vector::new(vec![0.,1.,0.,-1.,0.,1.,0.,-1.]);
+	let test_inputs = Matrix::new(3,1,vec![0.5,3.5,9.]);
+
+	let mut boxed_gp = GaussianProcess::new(parse_kernel("se*periodic+white"), ConstMean::default(), 1e-3f64);
+	boxed_gp.train(&inputs, &targets).unwrap();
+	let boxed_outputs = boxed_gp.predict(&test_inputs).unwrap();
+
+	let static_ker = KernelArith(KernelArith(SquaredExp::new(2f64, 1f64)) * KernelArith(Periodic::new(3f64, 1f64, 1f64))) +
+		KernelArith(WhiteNoise::new(0.1));
+	let mut static_gp = GaussianProcess::new(static_ker, ConstMean::default(), 1e-3f64);
+	static_gp.train(&inputs, &targets).unwrap();
+	let static_outputs = static_gp.predict(&test_inputs).unwrap();
+
+	for (a, b) in boxed_outputs.iter().zip(static_outputs.iter()) {
+		assert!((a - b).abs() < 1e-12);
+	}
+}
+
+/// Noisy samples of a sine wave at random points in [0, 10].
+fn noisy_sine(n: usize, seed: usize) -> (Matrix<f64>, Vector<f64>) {
+	let mut rng = StdRng::from_seed(&[seed]);
+	let normal = Normal::new(0f64, 0.1);
+
+	let x = (0..n).map(|_| rng.gen_range(0f64, 10f64)).collect::<Vec<f64>>();
+	let y = x.iter().map(|x| x.sin() + normal.ind_sample(&mut rng)).collect::<Vec<f64>>();
+
+	(Matrix::new(n, 1, x), Vector::new(y))
+}
+
+#[test]
+fn test_sparse_gp_matches_exact_gp() {
+	let (inputs, targets) = noisy_sine(20000, 1);
+
+	let mut sparse = SparseGaussianProcess::new(SquaredExp::new(1f64, 1f64), ConstMean::default(), 0.01, 30);
+	sparse.train(&inputs, &targets).unwrap();
+	assert_eq!(sparse.inducing_points().as_ref().unwrap().rows(), 30);
+
+	let sub_inputs = inputs.select_rows(&(0..2000).collect::<Vec<usize>>());
+	let sub_targets = Vector::new(&targets.data()[..2000]);
+	let mut exact = GaussianProcess::new(SquaredExp::new(1f64, 1f64), ConstMean::default(), 0.01);
+	exact.train(&sub_inputs, &sub_targets).unwrap();
+
+	let test_inputs = Matrix::new(17, 1, (0..17).map(|i| 1f64 + i as f64 * 0.5).collect::<Vec<f64>>());
+	let sparse_out = sparse.predict(&test_inputs).unwrap();
+	let exact_out = exact.predict(&test_inputs).unwrap();
+
+	for (s, e) in sparse_out.iter().zip(exact_out.iter()) {
+		assert!((s - e).abs() < 0.05);
+	}
+}
+
+#[test]
+fn test_sparse_gp_variance_grows_with_distance() {
+	let (inputs, targets) = noisy_sine(500, 2);
+
+	let mut gp = SparseGaussianProcess::new(SquaredExp::new(1f64, 1f64), ConstMean::default(), 0.01, 20);
+	gp.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(3, 1, vec![5.0, 12.0, 20.0]);
+	let (_, var) = gp.predict_with_variance(&test_inputs).unwrap();
+
+	assert!(var[0] < var[1]);
+	assert!(var[1] < var[2]);
+	// Far from the data the variance returns to the prior variance.
+	assert!((var[2] - 1f64).abs() < 1e-6);
+}
+
+#[test]
+fn test_sparse_gp_all_inducing_is_exact() {
+	let (inputs, targets) = noisy_sine(30, 3);
+
+	// With at least as many inducing points as inputs the inputs are used directly.
+	let mut sparse = SparseGaussianProcess::new(SquaredExp::new(1f64, 1f64), ConstMean::default(), 0.01, 50);
+	sparse.train(&inputs, &targets).unwrap();
+
+	let mut exact = GaussianProcess::new(SquaredExp::new(1f64, 1f64), ConstMean::default(), 0.01);
+	exact.train(&inputs, &targets).unwrap();
+
+	let test_inputs = Matrix::new(5, 1, vec![0.5, 2.5, 4.5, 6.5, 8.5]);
+	let (sparse_mean, sparse_var) = sparse.predict_with_variance(&test_inputs).unwrap();
+	let (exact_mean, exact_var) = exact.predict_with_variance(&test_inputs).unwrap();
+
+	// The sparse solve is less well conditioned, so compare relative to the scale.
+	let close = |a: f64, b: f64| (a - b).abs() <= 1e-4 * (1f64 + b.abs());
+
+	for i in 0..5 {
+		assert!(close(sparse_mean[i], exact_mean[i]));
+		assert!(close(sparse_var[i], exact_var[i]));
+	}
+
+	let bound = sparse.log_marginal_bound(&inputs, &targets).unwrap();
+	let exact_lml = exact.log_marginal_likelihood(&inputs, &targets).unwrap();
+	assert!(close(bound, exact_lml));
+}
+
+#[test]
+fn test_sparse_gp_optimize_hyperparameters() {
+	let (inputs, targets) = noisy_sine(200, 4);
+
+	let mut gp = SparseGaussianProcess::new(SquaredExp::new(0.3, 1f64), ConstMean::default(), 0.5, 8);
+	let initial = gp.log_marginal_bound(&inputs, &targets).unwrap();
+
+	let optimized = gp.optimize_hyperparameters(&inputs, &targets, true).unwrap();
+	assert!(optimized > initial);
+	assert!(gp.noise() < 0.5);
+
+	// The optimized inducing points are kept when retraining.
+	let points = gp.inducing_points().clone().unwrap();
+	assert_eq!(points.rows(), 8);
+	gp.train(&inputs, &targets).unwrap();
+	assert_eq!(gp.inducing_points().as_ref().unwrap().data(), points.data());
+}
+
+#[test]
+fn test_sparse_gp_set_inducing_points() {
+	let (inputs, targets) = noisy_sine(100, 5);
+
+	let mut gp = SparseGaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0.01, 10);
+	gp.set_inducing_points(Matrix::new(3, 1, vec![2.0, 5.0, 8.0]));
+	assert_eq!(gp.n_inducing(), 3);
+
+	gp.train(&inputs, &targets).unwrap();
+	assert_eq!(gp.inducing_points().as_ref().unwrap().data(), &vec![2.0, 5.0, 8.0]);
+}
+
+#[test]
+fn test_sparse_gp_untrained() {
+	let gp = SparseGaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0.01, 10);
+	let test_inputs = Matrix::new(1, 1, vec![0.0]);
+
+	assert!(gp.predict(&test_inputs).is_err());
+	assert!(gp.predict_with_variance(&test_inputs).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_sparse_gp_zero_noise() {
+	let _ = SparseGaussianProcess::new(SquaredExp::default(), ConstMean::default(), 0f64, 10);
+}
+
+/// A grid of points in [-2, 2]² labelled by the sign of x + y.
+///
+/// Points on the boundary are left out so the data is symmetric.
+fn separable_grid() -> (Matrix<f64>, Vector<usize>) {
+	let mut data = Vec::new();
+	let mut labels = Vec::new();
+	for i in 0..9 {
+		for j in 0..9 {
+			let (x, y) = (-2f64 + i as f64 * 0.5, -2f64 + j as f64 * 0.5);
+			if x + y != 0f64 {
+				data.push(x);
+				data.push(y);
+				labels.push(if x + y > 0f64 { 1 } else { 0 });
+			}
+		}
+	}
+
+	(Matrix::new(labels.len(), 2, data), Vector::new(labels))
+}
+
+#[test]
+fn test_gp_classifier_separable() {
+	let (inputs, targets) = separable_grid();
+
+	let mut gpc = GaussianProcessClassifier::new(SquaredExp::new(1f64, 4f64));
+	gpc.train(&inputs, &targets).unwrap();
+
+	let mut rng = StdRng::from_seed(&[7]);
+	let mut test_data = Vec::new();
+	let mut test_labels = Vec::new();
+	while test_labels.len() < 200 {
+		let (x, y) = (rng.gen_range(-2f64, 2f64), rng.gen_range(-2f64, 2f64));
+		if (x + y).abs() > 0.2 {
+			test_data.push(x);
+			test_data.push(y);
+			test_labels.push(if x + y > 0f64 { 1 } else { 0 });
+		}
+	}
+
+	let outputs = gpc.predict(&Matrix::new(200, 2, test_data)).unwrap();
+	let correct = outputs.iter().zip(test_labels.iter()).filter(|&(o, t)| o == t).count();
+	assert!(correct as f64 / 200f64 > 0.95);
+}
+
+#[test]
+fn test_gp_classifier_boundary_probabilities() {
+	let (inputs, targets) = separable_grid();
+
+	let mut gpc = GaussianProcessClassifier::new(SquaredExp::new(1f64, 4f64));
+	gpc.train(&inputs, &targets).unwrap();
+
+	// Points on the decision boundary are equally likely to be in either class.
+	let boundary = Matrix::new(3, 2, vec![-1.0, 1.0, 0.0, 0.0, 0.75, -0.75]);
+	for p in gpc.predict_proba(&boundary).unwrap().iter() {
+		assert!((p - 0.5).abs() < 0.05);
+	}
+
+	// Points far from the boundary are confidently classified.
+	let probs = gpc.predict_proba(&Matrix::new(2, 2, vec![1.5, 1.5, -1.5, -1.5])).unwrap();
+	assert!(probs[0] > 0.85);
+	assert!(probs[1] < 0.15);
+}
+
+#[test]
+fn test_gp_classifier_non_binary_labels() {
+	let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 2.0]);
+	let targets = Vector::new(vec![0, 1, 2]);
+
+	let mut gpc = GaussianProcessClassifier::default();
+	match *gpc.train(&inputs, &targets).unwrap_err().kind() {
+		ErrorKind::InvalidData => {}
+		_ => panic!("Expected an InvalidData error."),
+	}
+}
+
+#[test]
+fn test_gp_classifier_untrained() {
+	let gpc = GaussianProcessClassifier::default();
+	assert!(gpc.predict_proba(&Matrix::new(1, 1, vec![0.0])).is_err());
+}
+
+/// A strong linear trend with small wiggles on [0, 10].
+fn trend_data() -> (Matrix<f64>, Vector<f64>) {
+	let x = (0..41).map(|i| i as f64 * 0.25).collect::<Vec<f64>>();
+	let y = x.iter().map(|x| 2f64 * x + 1f64 + 0.3 * (3f64 * x).sin()).collect::<Vec<f64>>();
+	(Matrix::new(41, 1, x), Vector::new(y))
+}
+
+#[test]
+fn test_linear_mean_gp_extrapolates_trend() {
+	let (inputs, targets) = trend_data();
+	let test_inputs = Matrix::new(2, 1, vec![20.0, 30.0]);
+
+	let mean = LinearMean::new(Vector::zeros(1), 0f64);
+	let mut linear_gp = GaussianProcess::new(SquaredExp::new(1f64, 1f64), mean, 1e-2);
+	linear_gp.train_with_mean(&inputs, &targets).unwrap();
+
+	let outputs = linear_gp.predict(&test_inputs).unwrap();
+	assert!((outputs[0] - 41f64).abs() < 1f64);
+	assert!((outputs[1] - 61f64).abs() < 1f64);
+
+	let mut zero_gp = GaussianProcess::new(SquaredExp::new(1f64, 1f64), ConstMean::default(), 1e-2);
+	zero_gp.train(&inputs, &targets).unwrap();
+
+	let outputs = zero_gp.predict(&test_inputs).unwrap();
+	assert!(outputs[0].abs() < 1f64);
+	assert!(outputs[1].abs() < 1f64);
+}
+
+#[test]
+fn test_const_mean_fit() {
+	let (inputs, targets) = trend_data();
+
+	let mut gp = GaussianProcess::new(SquaredExp::new(1f64, 1f64), ConstMean::new(5f64), 1e-2);
+	assert_eq!(gp.mean().value(), 5f64);
+
+	gp.train_with_mean(&inputs, &targets).unwrap();
+	assert!((gp.mean().value() - targets.sum() / 41f64).abs() < 1e-12);
+
+	// Far from the data the predictions revert to the fitted constant.
+	let outputs = gp.predict(&Matrix::new(1, 1, vec![100.0])).unwrap();
+	assert!((outputs[0] - gp.mean().value()).abs() < 1e-6);
+}
+
+#[test]
+fn test_linear_mean_fit_recovers_coefficients() {
+	let inputs = Matrix::new(4, 2, vec![0.0, 1.0, 1.0, 0.0, 2.0, 3.0, 3.0, 1.0]);
+	let targets = Vector::new(vec![2.0, 4.0, 10.0, 11.0]);
+
+	let mut gp = GaussianProcess::new(SquaredExp::default(), LinearMean::new(Vector::zeros(2), 0f64), 1e-2);
+	gp.train_with_mean(&inputs, &targets).unwrap();
+
+	// The targets are exactly 3x + y + 1.
+	let mean = gp.mean();
+	assert!((mean.weights()[0] - 3f64).abs() < 1e-8);
+	assert!((mean.weights()[1] - 1f64).abs() < 1e-8);
+	assert!((mean.bias() - 1f64).abs() < 1e-8);
+}
@@ -1,7 +1,15 @@
-use rm::linalg::Matrix;
+use rm::linalg::{Matrix, BaseMatrix, Vector};
+use rm::learning::LearningResult;
 use rm::learning::UnSupModel;
 use rm::learning::k_means::KMeansClassifier;
-use rm::learning::k_means::{Forgy, RandomPartition, KPlusPlus};
+use rm::learning::k_means::{Forgy, RandomPartition, KPlusPlus, Initializer};
+use rm::learning::k_means::{gap_optimal_k, gap_statistic, gap_statistic_with_std_err,
+                             kmeans_sweep, GapStatistic, KMeansConfig};
+use rm::learning::toolkit::distance::DistanceMetric;
+
+use learning::fixtures::{clustered_points, three_clusters, OFFSETS};
+
+use std::f64;
 
 #[test]
 fn test_model_default() {
@@ -99,3 +107,384 @@ fn test_two_centroids() {
     assert!(classes.data().iter().take(3).all(|x| *x == class_a));
     assert!(classes.data().iter().skip(3).all(|x| *x == class_b));
 }
+
+#[test]
+fn test_sweep_elbow() {
+    let inputs = three_clusters();
+
+    let mut config = KMeansConfig::default();
+    config.n_init = 5;
+    config.seed = Some(42);
+    config.silhouette = true;
+
+    let runs = kmeans_sweep(&inputs, 1..7, &config).unwrap();
+    assert_eq!(runs.len(), 6);
+
+    let inertia = runs.iter().map(|r| r.inertia).collect::<Vec<_>>();
+
+    // The largest relative drop in inertia is at k = 3.
+    let drop_2_3 = inertia[1] - inertia[2];
+    let drop_3_4 = inertia[2] - inertia[3];
+    assert!(drop_2_3 > 10.0 * drop_3_4);
+    assert!(inertia[2] < 0.05 * inertia[1]);
+
+    assert!(runs[0].silhouette.is_none());
+    assert!(runs[2].silhouette.unwrap() > 0.8);
+
+    // The sweep is reproducible
+    let runs_again = kmeans_sweep(&inputs, 1..7, &config).unwrap();
+    for (a, b) in runs.iter().zip(runs_again.iter()) {
+        assert_eq!(a.inertia, b.inertia);
+    }
+}
+
+#[test]
+fn test_sweep_skips_large_k() {
+    let inputs = Matrix::new(3, 2, vec![1.0, 2.0, 1.0, 3.0, 1.0, 4.0]);
+
+    let runs = kmeans_sweep(&inputs, 2..6, &KMeansConfig::default()).unwrap();
+
+    assert_eq!(runs.len(), 4);
+    assert!(!runs[0].is_skipped());
+    assert!(!runs[1].is_skipped());
+    assert!(runs[2].is_skipped());
+    assert!(runs[3].is_skipped());
+}
+
+#[derive(Debug)]
+struct FixedInit(Vec<f64>);
+
+impl Initializer for FixedInit {
+    fn init_centroids(&self, k: usize, inputs: &Matrix<f64>) -> LearningResult<Matrix<f64>> {
+        Ok(Matrix::new(k, inputs.cols(), self.0.clone()))
+    }
+}
+
+#[test]
+fn test_cosine_metric() {
+    // Two directions with very different magnitudes.
+    let inputs = Matrix::new(6, 2, vec![1.0, 0.0,
+                                        10.0, 0.5,
+                                        5.0, 0.1,
+                                        0.0, 1.0,
+                                        0.2, 8.0,
+                                        0.1, 3.0]);
+
+    let init = FixedInit(vec![1.0, 1.0, 1.0, 1.1]);
+    let mut model = KMeansClassifier::new_specified(2, 100, init);
+    model.set_metric(DistanceMetric::Cosine);
+    model.train(&inputs).unwrap();
+
+    let classes = model.predict(&inputs).unwrap().into_vec();
+    assert!(classes[0] == classes[1] && classes[1] == classes[2]);
+    assert!(classes[3] == classes[4] && classes[4] == classes[5]);
+    assert!(classes[0] != classes[3]);
+
+    // The centroids are unit length.
+    for row in model.centroids().as_ref().unwrap().row_iter() {
+        let norm = row.raw_slice().iter().map(|x| x * x).sum::<f64>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn test_manhattan_metric() {
+    let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 100.0]);
+
+    let mut model = KMeansClassifier::new(1);
+    model.set_metric(DistanceMetric::Manhattan);
+    model.train(&inputs).unwrap();
+
+    // The centroid is the median rather than the mean.
+    assert_eq!(model.centroids().as_ref().unwrap().data(), &vec![1.0]);
+    assert_eq!(model.inertia(), Some(100.0));
+}
+
+#[test]
+fn test_kullback_leibler_metric() {
+    // Two groups of distributions which differ only in a small probability.
+    let data = [0.05, 0.0005]
+        .iter()
+        .flat_map(|&small| {
+            (0..10).flat_map(move |i| {
+                let e = -0.15 + 0.3 * i as f64 / 9.0;
+                vec![0.5 + e, 0.5 - e - small, small]
+            })
+        })
+        .collect::<Vec<f64>>();
+    let inputs = Matrix::new(20, 3, data);
+
+    let separates = |metric: DistanceMetric| {
+        let mut model = KMeansClassifier::new_specified(2, 100, Forgy);
+        model.set_metric(metric);
+        model.set_n_init(10);
+        model.set_seed(1);
+        let classes = model.fit_predict(&inputs).unwrap().into_vec();
+        classes[..10].iter().all(|&c| c == classes[0]) &&
+        classes[10..].iter().all(|&c| c == classes[10]) && classes[0] != classes[10]
+    };
+
+    // The Euclidean distance is dominated by the large probabilities.
+    assert!(separates(DistanceMetric::KullbackLeibler));
+    assert!(!separates(DistanceMetric::Euclidean));
+
+    let mut model = KMeansClassifier::new_specified(2, 100, Forgy);
+    model.set_metric(DistanceMetric::KullbackLeibler);
+    model.set_n_init(10);
+    model.set_seed(1);
+    let classes = model.fit_predict(&inputs).unwrap();
+
+    // The centroids are the means of the groups, so are distributions.
+    let centroids = model.centroids().as_ref().unwrap();
+    let first = centroids.row(classes[0]).raw_slice().to_vec();
+    let expected = [0.5, 0.45, 0.05];
+    for (c, e) in first.iter().zip(expected.iter()) {
+        assert!((c - e).abs() < 1e-12);
+    }
+    for row in centroids.row_iter() {
+        assert!((row.raw_slice().iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_kullback_leibler_negative_inputs() {
+    let inputs = Matrix::new(3, 2, vec![0.5, 0.5, 1.2, -0.2, 0.0, 1.0]);
+
+    let mut model = KMeansClassifier::new(2);
+    model.set_metric(DistanceMetric::KullbackLeibler);
+    assert!(model.train(&inputs).is_err());
+    assert!(model.partial_fit(&inputs).is_err());
+
+    // The weighted mean is the centroid too.
+    let inputs = Matrix::new(3, 2, vec![0.5, 0.5, 0.2, 0.8, 0.9, 0.1]);
+    model.train_with_weights(&inputs, &Vector::new(vec![1.0; 3])).unwrap();
+}
+
+#[test]
+fn test_cluster_sizes_and_distances() {
+    let inputs = Matrix::new(5, 1, vec![0.0, 0.5, 1.0, 10.0, 11.0]);
+
+    let init = FixedInit(vec![0.0, 10.0]);
+    let mut model = KMeansClassifier::new_specified(2, 100, init);
+    assert!(model.cluster_sizes().is_none());
+    assert!(model.predict_with_distances(&inputs).is_err());
+
+    model.train(&inputs).unwrap();
+    assert_eq!(model.cluster_sizes(), Some(vec![3, 2]));
+
+    let test_inputs = Matrix::new(2, 1, vec![0.5, 20.5]);
+    let (classes, distances) = model.predict_with_distances(&test_inputs).unwrap();
+    assert_eq!(classes.into_vec(), vec![0, 1]);
+    assert_eq!(distances.into_vec(), vec![0.0, 10.0]);
+}
+
+#[test]
+fn test_partial_fit_running_mean() {
+    let chunk_1 = Matrix::new(3, 1, vec![0.0, 1.0, 10.0]);
+    let chunk_2 = Matrix::new(3, 1, vec![2.0, 12.0, 11.0]);
+
+    let init = FixedInit(vec![0.0, 10.0]);
+    let mut model = KMeansClassifier::new_specified(2, 100, init);
+
+    model.partial_fit(&chunk_1).unwrap();
+    assert_eq!(model.centroids().as_ref().unwrap().data(), &vec![0.5, 10.0]);
+    assert_eq!(model.cluster_sizes(), Some(vec![2, 1]));
+    assert!(model.inertia().is_none());
+
+    // The centroids are the means of all points seen so far.
+    model.partial_fit(&chunk_2).unwrap();
+    assert_eq!(model.centroids().as_ref().unwrap().data(), &vec![1.0, 11.0]);
+    assert_eq!(model.cluster_sizes(), Some(vec![3, 3]));
+
+    assert!(model.partial_fit(&Matrix::new(1, 2, vec![0.0, 1.0])).is_err());
+}
+
+#[test]
+fn test_partial_fit_non_euclidean() {
+    let mut model = KMeansClassifier::new(1);
+    model.set_metric(DistanceMetric::Manhattan);
+    assert!(model.partial_fit(&Matrix::new(2, 1, vec![0.0, 1.0])).is_err());
+}
+
+#[test]
+fn test_weighted_matches_repeated_rows() {
+    let inputs = Matrix::new(5, 1, vec![0.0, 1.0, 4.0, 10.0, 13.0]);
+    let weights = Vector::new(vec![1.0, 3.0, 0.0, 2.0, 1.0]);
+    let repeated = Matrix::new(7, 1, vec![0.0, 1.0, 1.0, 1.0, 10.0, 10.0, 13.0]);
+
+    let mut weighted = KMeansClassifier::new_specified(2, 100, FixedInit(vec![0.0, 10.0]));
+    weighted.train_with_weights(&inputs, &weights).unwrap();
+
+    let mut model = KMeansClassifier::new_specified(2, 100, FixedInit(vec![0.0, 10.0]));
+    model.train(&repeated).unwrap();
+
+    assert_eq!(weighted.centroids(), model.centroids());
+    assert_eq!(weighted.centroids().as_ref().unwrap().data(), &vec![0.75, 11.0]);
+    assert_eq!(weighted.inertia(), model.inertia());
+
+    // The sizes count rows rather than weights.
+    assert_eq!(weighted.cluster_sizes(), Some(vec![3, 2]));
+}
+
+#[test]
+fn test_weighted_zero_weight_cluster() {
+    let inputs = Matrix::new(3, 1, vec![0.0, 2.0, 10.0]);
+    let weights = Vector::new(vec![1.0, 1.0, 0.0]);
+
+    let mut model = KMeansClassifier::new_specified(2, 100, FixedInit(vec![0.0, 10.0]));
+    model.train_with_weights(&inputs, &weights).unwrap();
+
+    // The second centroid has no weight so stays at its initial value.
+    assert_eq!(model.centroids().as_ref().unwrap().data(), &vec![1.0, 10.0]);
+    assert_eq!(model.inertia(), Some(2.0));
+}
+
+#[test]
+fn test_weighted_invalid() {
+    let inputs = Matrix::new(3, 1, vec![0.0, 2.0, 10.0]);
+    let mut model = KMeansClassifier::new(2);
+
+    assert!(model.train_with_weights(&inputs, &Vector::new(vec![1.0, 1.0])).is_err());
+    assert!(model.train_with_weights(&inputs, &Vector::new(vec![1.0, -1.0, 1.0])).is_err());
+    assert!(model.train_with_weights(&inputs, &Vector::new(vec![1.0, f64::NAN, 1.0])).is_err());
+
+    model.set_metric(DistanceMetric::Cosine);
+    assert!(model.train_with_weights(&inputs, &Vector::new(vec![1.0; 3])).is_err());
+}
+
+#[test]
+fn test_seed_reproduction() {
+    let inputs = three_clusters();
+
+    let mut model_a = KMeansClassifier::new_specified(3, 100, Forgy);
+    model_a.set_seed(5);
+    model_a.set_n_init(3);
+    model_a.train(&inputs).unwrap();
+
+    let mut model_b = KMeansClassifier::new_specified(3, 100, Forgy);
+    model_b.set_seed(5);
+    model_b.set_n_init(3);
+    model_b.train(&inputs).unwrap();
+
+    assert_eq!(model_a.seed(), Some(5));
+    assert_eq!(model_a.n_init(), 3);
+    assert_eq!(model_a.centroids(), model_b.centroids());
+    assert_eq!(model_a.inertia(), model_b.inertia());
+    assert_eq!(model_a.cluster_sizes(), model_b.cluster_sizes());
+}
+
+#[test]
+fn test_n_init_keeps_lowest_inertia() {
+    let inputs = three_clusters();
+
+    for seed in 0..5 {
+        // The first restart uses the same initialization as a single run.
+        let mut single = KMeansClassifier::new_specified(3, 100, Forgy);
+        single.set_seed(seed);
+        single.train(&inputs).unwrap();
+
+        let mut restarted = KMeansClassifier::new_specified(3, 100, Forgy);
+        restarted.set_seed(seed);
+        restarted.set_n_init(40);
+        restarted.train(&inputs).unwrap();
+
+        assert!(restarted.inertia().unwrap() <= single.inertia().unwrap());
+
+        // With enough restarts one initialization finds every cluster.
+        assert!(restarted.inertia().unwrap() < 10.0);
+        assert_eq!(restarted.cluster_sizes(), Some(vec![6, 6, 6]));
+    }
+}
+
+#[test]
+fn test_fit_predict() {
+    let inputs = three_clusters();
+
+    let mut model = KMeansClassifier::new_specified(3, 100, Forgy);
+    model.set_seed(3);
+    model.set_n_init(5);
+    let classes = model.fit_predict(&inputs).unwrap();
+
+    assert_eq!(classes, model.predict(&inputs).unwrap());
+
+    let mut sizes = vec![0; 3];
+    for &c in classes.data() {
+        sizes[c] += 1;
+    }
+    assert_eq!(Some(sizes), model.cluster_sizes());
+}
+
+#[test]
+#[should_panic]
+fn test_zero_n_init() {
+    let mut model = KMeansClassifier::new(2);
+    model.set_n_init(0);
+}
+
+#[test]
+fn test_gap_statistic() {
+    let inputs = three_clusters();
+
+    let gaps = gap_statistic(&inputs, 0..4, 10, Some(3));
+    assert_eq!(gaps.iter().map(|g| g.0).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    assert!(gaps[0].1.is_nan());
+
+    // The gap jumps at the true number of clusters.
+    assert!(gaps[3].1 > gaps[1].1 + 2.0);
+    assert!(gaps[3].1 > gaps[2].1 + 2.0);
+
+    // The gaps are reproducible
+    let gaps_again = gap_statistic(&inputs, 0..4, 10, Some(3));
+    for (a, b) in gaps.iter().skip(1).zip(gaps_again.iter().skip(1)) {
+        assert_eq!(a, b);
+    }
+
+    assert!(gap_statistic(&inputs, 19..20, 10, None)[0].1.is_nan());
+}
+
+#[test]
+fn test_gap_statistic_with_std_err() {
+    let inputs = three_clusters();
+
+    let gaps = gap_statistic_with_std_err(&inputs, 0..4, 10, Some(3));
+    assert!(gaps[0].gap.is_nan());
+    assert!(gaps[0].std_err.is_nan());
+    assert!(gaps[1..].iter().all(|g| g.std_err > 0.0 && g.std_err.is_finite()));
+
+    // The gaps match those without the standard errors.
+    let plain = gap_statistic(&inputs, 0..4, 10, Some(3));
+    for (g, p) in gaps.iter().zip(plain.iter()).skip(1) {
+        assert_eq!((g.k, g.gap), *p);
+    }
+}
+
+#[test]
+fn test_gap_optimal_k() {
+    let inputs = clustered_points(&[(0.0, 0.0), (10.0, 10.0)], &OFFSETS);
+
+    let gaps = gap_statistic_with_std_err(&inputs, 1..6, 10, Some(3));
+    assert_eq!(gap_optimal_k(&gaps), Some(2));
+}
+
+#[test]
+fn test_gap_optimal_k_rule() {
+    let gap = |k, gap, std_err| {
+        GapStatistic {
+            k: k,
+            gap: gap,
+            std_err: std_err,
+        }
+    };
+
+    // Gap(2) is within one standard error of Gap(3).
+    let gaps = vec![gap(1, 0.0, 0.1), gap(2, 1.0, 0.1), gap(3, 1.05, 0.1), gap(4, 2.0, 0.1)];
+    assert_eq!(gap_optimal_k(&gaps), Some(2));
+
+    // The gaps increase too fast for any choice in the range.
+    let gaps = vec![gap(1, 0.0, 0.1), gap(2, 1.0, 0.1), gap(3, 2.0, 0.1)];
+    assert_eq!(gap_optimal_k(&gaps), None);
+
+    // Skipped values of k are never chosen.
+    let gaps = vec![gap(0, f64::NAN, f64::NAN), gap(1, 0.0, 0.1), gap(2, -1.0, 0.1)];
+    assert_eq!(gap_optimal_k(&gaps), Some(1));
+}
@@ -1,6 +1,6 @@
 use rm::linalg::{Matrix, Vector};
 use rm::learning::SupModel;
-use rm::learning::knn::KNNClassifier;
+use rm::learning::knn::{KNNClassifier, KNNWeights};
 
 #[test]
 fn test_knn() {
@@ -18,6 +18,25 @@ fn test_knn() {
     assert_eq!(res, exp);
 }
 
+#[test]
+fn test_knn_distance_weighted() {
+    let data = matrix![0., 0.;
+                       3., 0.;
+                       3.5, 0.];
+    let target = Vector::new(vec![0, 1, 1]);
+
+    // majority vote picks the two far points
+    let mut knn = KNNClassifier::new(3);
+    let _ = knn.train(&data, &target).unwrap();
+    let res = knn.predict(&matrix![0.5, 0.]).unwrap();
+    assert_eq!(res, Vector::new(vec![1]));
+
+    // distance weighting picks the close point
+    knn.set_weights(KNNWeights::Distance);
+    let res = knn.predict(&matrix![0.5, 0.]).unwrap();
+    assert_eq!(res, Vector::new(vec![0]));
+}
+
 #[test]
 fn test_knn_long() {
     let vals = (0..200000).map(|x: usize| x as f64).collect::<Vec<f64>>();
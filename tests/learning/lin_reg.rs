@@ -1,7 +1,8 @@
-use rm::linalg::Matrix;
+use rm::linalg::{Matrix, BaseMatrix};
 use rm::linalg::Vector;
-use rm::learning::SupModel;
-use rm::learning::lin_reg::LinRegressor;
+use rm::learning::{Parametric, SupModel};
+use rm::learning::lin_reg::{HuberRegressor, LinRegressor, MultiLinRegressor, RidgeRegressor,
+                            Solver};
 use libnum::abs;
 
 #[test]
@@ -32,6 +33,19 @@ fn test_regression() {
     assert!(err_2 < 1e-8);
 }
 
+#[test]
+fn test_n_parameters() {
+    let mut lin_mod = LinRegressor::default();
+    assert_eq!(lin_mod.n_parameters(), 0);
+
+    let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 2.0, 1.0, 3.0, 5.0, 4.0, 3.0]);
+    let targets = Vector::new(vec![5.0, 4.0, 11.0, 9.0]);
+    lin_mod.train(&inputs, &targets).unwrap();
+
+    // One coefficient per column plus the intercept.
+    assert_eq!(lin_mod.n_parameters(), 3);
+}
+
 #[test]
 #[should_panic]
 fn test_no_train_params() {
@@ -55,7 +69,8 @@ fn test_regression_datasets_trees() {
     use rm::datasets::trees;
     let trees = trees::load();
 
-    let mut lin_mod = LinRegressor::default();
+    // The expected values are exact for the normal equations.
+    let mut lin_mod = LinRegressor::new(Solver::NormalEquations);
     lin_mod.train(&trees.data(), &trees.target()).unwrap();
     let params = lin_mod.parameters().unwrap();
     assert_eq!(params, &Vector::new(vec![-57.98765891838409, 4.708160503017506, 0.3392512342447438]));
@@ -83,3 +98,490 @@ fn test_train_no_data() {
 
     assert!(res.is_err());
 }
+
+#[test]
+fn test_ridge_collinear_columns() {
+    // The two columns are identical.
+    let inputs = Matrix::new(5, 2, vec![1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, 5.0, 5.0]);
+    let targets = Vector::new(vec![3.1, 4.9, 7.2, 8.8, 11.0]);
+
+    let mut ridge = RidgeRegressor::new(1e-3);
+    ridge.train(&inputs, &targets).unwrap();
+
+    let params = ridge.parameters().unwrap();
+    assert!(params.iter().all(|p| p.is_finite()));
+    // The weight is shared equally between the identical columns.
+    assert!((params[1] - params[2]).abs() < 1e-8);
+    assert!((params[1] + params[2] - 1.97).abs() < 1e-2);
+
+    // Ordinary least squares cannot find a stable solution.
+    let mut lin_mod = LinRegressor::default();
+    let ols_stable = lin_mod.train(&inputs, &targets).is_ok() &&
+                     lin_mod.parameters().unwrap().iter().all(|p| p.is_finite() && p.abs() < 1e6);
+    assert!(!ols_stable);
+}
+
+#[test]
+fn test_ridge_zero_alpha_is_ols() {
+    let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 2.0, 1.0, 3.0, 5.0, 4.0, 3.0]);
+    let targets = Vector::new(vec![5.0, 4.0, 11.0, 9.0]);
+
+    let mut ridge = RidgeRegressor::new(0f64);
+    ridge.train(&inputs, &targets).unwrap();
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.train(&inputs, &targets).unwrap();
+
+    for (r, l) in ridge.parameters().unwrap().iter().zip(lin_mod.parameters().unwrap().iter()) {
+        assert!(abs(r - l) < 1e-8);
+    }
+}
+
+#[test]
+fn test_ridge_large_alpha_shrinks() {
+    let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 2.0, 1.0, 3.0, 5.0, 4.0, 3.0]);
+    let targets = Vector::new(vec![5.0, 4.0, 11.0, 9.0]);
+
+    let mut ridge = RidgeRegressor::new(1e10);
+    ridge.train(&inputs, &targets).unwrap();
+
+    let params = ridge.parameters().unwrap();
+    assert!(abs(params[1]) < 1e-6);
+    assert!(abs(params[2]) < 1e-6);
+    // The unpenalized intercept tends to the mean of the targets.
+    assert!(abs(params[0] - 7.25) < 1e-4);
+}
+
+#[test]
+fn test_ridge_mismatched_targets() {
+    let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+    let targets = Vector::new(vec![1.0, 2.0]);
+
+    let mut ridge = RidgeRegressor::new(0.1);
+    assert!(ridge.train(&inputs, &targets).is_err());
+    assert!(ridge.parameters().is_none());
+}
+
+#[test]
+#[should_panic]
+fn test_ridge_negative_alpha() {
+    let _ = RidgeRegressor::new(-1f64);
+}
+
+/// A polynomial design matrix with columns x, x², ..., x¹⁰ on [0, 1],
+/// and targets from coefficients which are all one.
+fn ill_conditioned_data() -> (Matrix<f64>, Vector<f64>) {
+    let xs = (0..30).map(|i| i as f64 / 29f64).collect::<Vec<f64>>();
+
+    let mut data = Vec::with_capacity(300);
+    for x in &xs {
+        data.extend((1..11).map(|k| x.powi(k)));
+    }
+    let targets = xs.iter().map(|x| (0..11).map(|k| x.powi(k)).sum::<f64>()).collect::<Vec<f64>>();
+
+    (Matrix::new(30, 10, data), Vector::new(targets))
+}
+
+#[test]
+fn test_qr_ill_conditioned() {
+    let (inputs, targets) = ill_conditioned_data();
+
+    let mut qr = LinRegressor::default();
+    assert_eq!(qr.solver(), Solver::Qr);
+    qr.train(&inputs, &targets).unwrap();
+    assert!(qr.parameters().unwrap().iter().all(|p| abs(p - 1f64) < 1e-8));
+
+    // The normal equations square the condition number and lose accuracy.
+    let mut normal = LinRegressor::new(Solver::NormalEquations);
+    normal.train(&inputs, &targets).unwrap();
+    assert!(normal.parameters().unwrap().iter().any(|p| abs(p - 1f64) > 1e-4));
+}
+
+#[test]
+fn test_svd_ill_conditioned() {
+    let (inputs, targets) = ill_conditioned_data();
+
+    let mut svd = LinRegressor::new(Solver::Svd);
+    svd.train(&inputs, &targets).unwrap();
+
+    assert_eq!(svd.rank(), Some(11));
+    assert!(svd.parameters().unwrap().iter().all(|p| abs(p - 1f64) < 1e-6));
+}
+
+#[test]
+fn test_rank_deficient_inputs() {
+    // The two columns are identical.
+    let inputs = Matrix::new(4, 2, vec![1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0]);
+    let targets = Vector::new(vec![3.0, 5.0, 7.0, 9.0]);
+
+    let mut qr = LinRegressor::new(Solver::Qr);
+    assert!(qr.train(&inputs, &targets).is_err());
+    assert_eq!(qr.rank(), None);
+
+    // The SVD gives the minimum norm solution, sharing the weight equally.
+    let mut svd = LinRegressor::new(Solver::Svd);
+    svd.train(&inputs, &targets).unwrap();
+    assert_eq!(svd.rank(), Some(2));
+
+    let params = svd.parameters().unwrap();
+    assert!(abs(params[0] - 1f64) < 1e-8);
+    assert!(abs(params[1] - 1f64) < 1e-8);
+    assert!(abs(params[2] - 1f64) < 1e-8);
+}
+
+#[test]
+fn test_predict_wrong_columns() {
+    let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 2.0, 1.0, 3.0, 5.0, 4.0, 3.0]);
+    let targets = Vector::new(vec![5.0, 4.0, 11.0, 9.0]);
+    let wrong = Matrix::new(1, 3, vec![1.0, 2.0, 3.0]);
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.train(&inputs, &targets).unwrap();
+    assert!(lin_mod.predict(&wrong).is_err());
+
+    let mut ridge = RidgeRegressor::default();
+    ridge.train(&inputs, &targets).unwrap();
+    assert!(ridge.predict(&wrong).is_err());
+}
+
+#[test]
+fn test_weighted_contradictory_points() {
+    // The last two points contradict each other.
+    let inputs = Matrix::new(3, 1, vec![0.0, 1.0, 1.0]);
+    let targets = Vector::new(vec![0.0, 1.0, 3.0]);
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.train_weighted(&inputs, &targets, &Vector::new(vec![1.0, 1000.0, 1.0])).unwrap();
+
+    // The heavily weighted point pulls the fit towards it.
+    let slope = lin_mod.parameters().unwrap()[1];
+    assert!(abs(slope - 1f64) < 0.01);
+
+    lin_mod.train(&inputs, &targets).unwrap();
+    assert!(abs(lin_mod.parameters().unwrap()[1] - 2f64) < 1e-8);
+}
+
+#[test]
+fn test_weighted_uniform_is_ols() {
+    let inputs = Matrix::new(4, 2, vec![1.0, 2.0, 2.0, 1.0, 3.0, 5.0, 4.0, 3.0]);
+    let targets = Vector::new(vec![5.0, 4.0, 11.0, 9.0]);
+
+    let mut ols = LinRegressor::default();
+    ols.train(&inputs, &targets).unwrap();
+
+    let mut wls = LinRegressor::default();
+    wls.train_weighted(&inputs, &targets, &Vector::ones(4)).unwrap();
+    assert_eq!(wls.parameters().unwrap().data(), ols.parameters().unwrap().data());
+
+    wls.train_weighted(&inputs, &targets, &Vector::new(vec![4.0; 4])).unwrap();
+    for (w, o) in wls.parameters().unwrap().iter().zip(ols.parameters().unwrap().iter()) {
+        assert!(abs(w - o) < 1e-10);
+    }
+}
+
+#[test]
+fn test_weighted_zero_weight_ignored() {
+    let inputs = Matrix::new(5, 1, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+    let targets = Vector::new(vec![3.0, 5.0, 7.0, 9.0, 100.0]);
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.train_weighted(&inputs, &targets, &Vector::new(vec![1.0, 1.0, 1.0, 1.0, 0.0])).unwrap();
+
+    let params = lin_mod.parameters().unwrap();
+    assert!(abs(params[0] - 1f64) < 1e-10);
+    assert!(abs(params[1] - 2f64) < 1e-10);
+}
+
+#[test]
+fn test_weighted_invalid_weights() {
+    let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+    let targets = Vector::new(vec![1.0, 2.0, 3.0]);
+
+    let mut lin_mod = LinRegressor::default();
+    assert!(lin_mod.train_weighted(&inputs, &targets, &Vector::new(vec![1.0, -1.0, 1.0])).is_err());
+    assert!(lin_mod.train_weighted(&inputs, &targets, &Vector::new(vec![1.0, 1.0])).is_err());
+}
+
+#[test]
+fn test_multi_output_matches_single_regressions() {
+    let inputs = Matrix::new(6, 2, vec![1.0, 0.5,
+                                        2.0, -1.0,
+                                        3.0, 2.5,
+                                        4.0, 0.0,
+                                        5.0, 1.5,
+                                        6.0, -2.0]);
+    let data = (0..18).map(|i| ((i * 7) % 11) as f64 - 4.0).collect::<Vec<f64>>();
+    let targets = Matrix::new(6, 3, data);
+
+    let mut multi = MultiLinRegressor::default();
+    multi.train(&inputs, &targets).unwrap();
+
+    let params = multi.parameters().unwrap();
+    assert_eq!(params.rows(), 3);
+    assert_eq!(params.cols(), 3);
+    assert_eq!(multi.n_parameters(), 9);
+
+    let outputs = multi.predict(&inputs).unwrap();
+    assert_eq!(outputs.rows(), 6);
+    assert_eq!(outputs.cols(), 3);
+
+    for j in 0..3 {
+        let column = Vector::new(targets.row_iter().map(|r| r.raw_slice()[j]).collect::<Vec<f64>>());
+
+        let mut single = LinRegressor::default();
+        single.train(&inputs, &column).unwrap();
+
+        let single_params = single.parameters().unwrap();
+        for i in 0..3 {
+            assert_eq!(params[[i, j]], single_params[i]);
+        }
+
+        let single_outputs = single.predict(&inputs).unwrap();
+        for (i, o) in single_outputs.iter().enumerate() {
+            assert!(abs(outputs[[i, j]] - o) < 1e-10);
+        }
+    }
+}
+
+#[test]
+fn test_multi_output_invalid_data() {
+    let inputs = Matrix::new(3, 1, vec![1.0, 2.0, 3.0]);
+
+    let mut multi = MultiLinRegressor::default();
+    assert!(multi.predict(&inputs).is_err());
+    assert!(multi.train(&inputs, &Matrix::new(2, 1, vec![1.0, 2.0])).is_err());
+
+    multi.train(&inputs, &Matrix::new(3, 2, vec![1.0, 0.0, 2.0, 1.0, 3.0, 0.0])).unwrap();
+    assert!(multi.predict(&Matrix::new(1, 2, vec![1.0, 2.0])).is_err());
+}
+
+/// A noisy line `y = 2 + 3x`, with every tenth target shifted by
+/// `outlier` when it is non-zero.
+fn huber_data(outlier: f64) -> (Matrix<f64>, Vector<f64>) {
+    let x = (0..50).map(|i| i as f64 / 10.0).collect::<Vec<f64>>();
+    let y = x.iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let shift = if i % 10 == 0 { outlier } else { 0.0 };
+            2.0 + 3.0 * x + 0.1 * (i as f64 * 1.7).sin() + shift
+        })
+        .collect::<Vec<f64>>();
+
+    (Matrix::new(50, 1, x), Vector::new(y))
+}
+
+#[test]
+fn test_huber_clean_data_matches_ols() {
+    let (inputs, targets) = huber_data(0.0);
+
+    let mut ols = LinRegressor::default();
+    ols.train(&inputs, &targets).unwrap();
+
+    let mut huber = HuberRegressor::default();
+    huber.train(&inputs, &targets).unwrap();
+
+    for (h, o) in huber.parameters().unwrap().iter().zip(ols.parameters().unwrap().iter()) {
+        assert!(abs(h - o) < 1e-3);
+    }
+}
+
+#[test]
+fn test_huber_gross_outliers() {
+    let (inputs, targets) = huber_data(50.0);
+
+    let mut ols = LinRegressor::default();
+    ols.train(&inputs, &targets).unwrap();
+    let ols_params = ols.parameters().unwrap();
+    assert!(abs(ols_params[0] - 2.0) > 5.0);
+
+    let mut huber = HuberRegressor::default();
+    huber.train(&inputs, &targets).unwrap();
+    let params = huber.parameters().unwrap();
+    assert!(abs(params[0] - 2.0) < 0.1);
+    assert!(abs(params[1] - 3.0) < 0.1);
+
+    let weights = huber.weights().unwrap();
+    for (i, w) in weights.iter().enumerate() {
+        if i % 10 == 0 {
+            assert!(*w < 0.1);
+        }
+    }
+}
+
+#[test]
+fn test_huber_unscaled_delta() {
+    let (inputs, targets) = huber_data(50.0);
+
+    let mut huber = HuberRegressor::new(0.5);
+    huber.set_scale_delta(false);
+    huber.train(&inputs, &targets).unwrap();
+
+    let params = huber.parameters().unwrap();
+    assert!(abs(params[1] - 3.0) < 0.1);
+    assert!(huber.predict(&Matrix::new(1, 2, vec![1.0, 2.0])).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_huber_non_positive_delta() {
+    let _ = HuberRegressor::new(0.0);
+}
+
+/// A large dataset from the plane `y = 1 + 2a - 3b` with small noise.
+fn streaming_data() -> (Matrix<f64>, Vector<f64>) {
+    let mut data = Vec::with_capacity(4000);
+    let mut targets = Vec::with_capacity(2000);
+    for i in 0..2000 {
+        let a = (i % 50) as f64 / 50.0;
+        let b = ((i * 7) % 40) as f64 / 40.0;
+        data.push(a);
+        data.push(b);
+        targets.push(1.0 + 2.0 * a - 3.0 * b + 0.05 * (i as f64).sin());
+    }
+
+    (Matrix::new(2000, 2, data), Vector::new(targets))
+}
+
+fn assert_within_one_percent(params: &Vector<f64>, expected: &Vector<f64>) {
+    for (p, e) in params.iter().zip(expected.iter()) {
+        assert!(abs(p - e) < 0.01 * abs(*e));
+    }
+}
+
+#[test]
+fn test_partial_train_streaming() {
+    let (inputs, targets) = streaming_data();
+
+    let mut ols = LinRegressor::default();
+    ols.train(&inputs, &targets).unwrap();
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.set_learning_rate(0.5);
+
+    for _ in 0..20 {
+        for start in (0..20).map(|c| c * 100) {
+            let rows = (start..start + 100).collect::<Vec<usize>>();
+            let batch_targets = rows.iter().map(|&i| targets[i]).collect::<Vec<f64>>();
+            lin_mod.partial_train(&inputs.select_rows(&rows), &Vector::new(batch_targets))
+                .unwrap();
+        }
+    }
+
+    assert_within_one_percent(lin_mod.parameters().unwrap(), ols.parameters().unwrap());
+}
+
+#[test]
+fn test_train_epochs() {
+    let (inputs, targets) = streaming_data();
+
+    let mut ols = LinRegressor::default();
+    ols.train(&inputs, &targets).unwrap();
+
+    let mut lin_mod = LinRegressor::default();
+    lin_mod.set_learning_rate(0.5);
+    lin_mod.train_epochs(&inputs, &targets, 100, 20).unwrap();
+
+    assert_within_one_percent(lin_mod.parameters().unwrap(), ols.parameters().unwrap());
+    assert!(lin_mod.train_epochs(&inputs, &targets, 0, 1).is_err());
+}
+
+#[test]
+fn test_partial_train_dimension_mismatch() {
+    let mut lin_mod = LinRegressor::default();
+
+    // The first batch fixes the number of columns.
+    lin_mod.partial_train(&Matrix::new(2, 2, vec![1.0, 2.0, 3.0, 4.0]),
+                       &Vector::new(vec![1.0, 2.0]))
+        .unwrap();
+    let params = lin_mod.parameters().unwrap().clone();
+
+    assert!(lin_mod.partial_train(&Matrix::new(1, 3, vec![1.0, 2.0, 3.0]), &Vector::new(vec![1.0]))
+        .is_err());
+    assert!(lin_mod.partial_train(&Matrix::new(1, 2, vec![1.0, 2.0]), &Vector::new(vec![]))
+        .is_err());
+    assert_eq!(lin_mod.parameters().unwrap(), &params);
+}
+
+#[test]
+fn test_normalize_predictions_unchanged() {
+    let inputs = Matrix::new(6, 2, vec![1.0, 10.0,
+                                        2.0, 30.0,
+                                        3.0, 20.0,
+                                        4.0, 50.0,
+                                        5.0, 40.0,
+                                        6.0, 70.0]);
+    let targets = Vector::new(vec![3.0, 7.5, 6.0, 12.0, 10.5, 16.0]);
+    let test_inputs = Matrix::new(2, 2, vec![2.5, 25.0, 10.0, -5.0]);
+
+    let mut plain = LinRegressor::default();
+    plain.train(&inputs, &targets).unwrap();
+
+    let mut normalized = LinRegressor::default();
+    normalized.set_normalize(true);
+    normalized.train(&inputs, &targets).unwrap();
+
+    let plain_params = plain.parameters().unwrap();
+    for (a, b) in plain_params.iter().zip(normalized.parameters().unwrap().iter()) {
+        assert!(abs(a - b) < 1e-10);
+    }
+
+    let plain_outputs = plain.predict(&test_inputs).unwrap();
+    let normalized_outputs = normalized.predict(&test_inputs).unwrap();
+    for (a, b) in plain_outputs.iter().zip(normalized_outputs.iter()) {
+        assert!(abs(a - b) < 1e-10);
+    }
+}
+
+#[test]
+fn test_normalize_gradient_descent_badly_scaled() {
+    // The first column is a thousand times smaller than the second.
+    let a = (0..100).map(|i| (i % 10) as f64 / 1000.0).collect::<Vec<f64>>();
+    let b = (0..100).map(|i| ((i * 3) % 7) as f64 / 7.0).collect::<Vec<f64>>();
+    let targets = a.iter().zip(b.iter()).map(|(a, b)| 1.0 + 100.0 * a + 2.0 * b).collect();
+    let targets = Vector::new(targets);
+
+    let mut data = Vec::with_capacity(200);
+    for (a, b) in a.iter().zip(b.iter()) {
+        data.push(*a);
+        data.push(*b);
+    }
+    let inputs = Matrix::new(100, 2, data);
+
+    let mut plain = LinRegressor::default();
+    plain.train_with_optimization(&inputs, &targets);
+    assert!(abs(plain.parameters().unwrap()[1] - 100.0) > 50.0);
+
+    let mut normalized = LinRegressor::default();
+    normalized.set_normalize(true);
+    normalized.train_with_optimization(&inputs, &targets);
+
+    let params = normalized.parameters().unwrap();
+    assert!(abs(params[0] - 1.0) < 1e-6);
+    assert!(abs(params[1] - 100.0) < 1e-4);
+    assert!(abs(params[2] - 2.0) < 1e-6);
+}
+
+#[test]
+fn test_without_intercept_centered_data() {
+    // Both the inputs and targets have zero mean.
+    let inputs = Matrix::new(4, 1, vec![-3.0, -1.0, 1.0, 3.0]);
+    let targets = Vector::new(vec![-6.2, -1.9, 2.1, 6.0]);
+
+    let mut with_intercept = LinRegressor::default();
+    with_intercept.train(&inputs, &targets).unwrap();
+
+    for normalize in vec![false, true] {
+        let mut lin_mod = LinRegressor::default();
+        lin_mod.set_fit_intercept(false);
+        lin_mod.set_normalize(normalize);
+        lin_mod.train(&inputs, &targets).unwrap();
+
+        let params = lin_mod.parameters().unwrap();
+        assert_eq!(params[0], 0.0);
+        assert!(abs(params[1] - with_intercept.parameters().unwrap()[1]) < 1e-10);
+        assert_eq!(lin_mod.n_parameters(), 1);
+
+        let outputs = lin_mod.predict(&Matrix::new(1, 1, vec![0.0])).unwrap();
+        assert_eq!(outputs[0], 0.0);
+    }
+}
@@ -1,9 +1,13 @@
 use rm::learning::optim::Optimizable;
 use rm::learning::optim::fmincg::ConjugateGD;
-use rm::learning::optim::grad_desc::{GradientDesc, StochasticGD, AdaGrad, RMSProp};
+use rm::learning::optim::lbfgs::LBFGS;
+use rm::learning::optim::grad_desc::{GradientDesc, StochasticGD, AdaGrad, RMSProp, Adam};
 use rm::learning::optim::OptimAlgorithm;
 
-use rm::linalg::Matrix;
+use rm::linalg::{Matrix, BaseMatrix};
+
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
 
 /// A model which uses the cost function
 /// y = (x - c)^2
@@ -15,7 +19,7 @@ struct XSqModel {
 
 impl Optimizable for XSqModel {
     type Inputs = Matrix<f64>;
-	type Targets = Matrix<f64>;
+    type Targets = Matrix<f64>;
 
     fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
 
@@ -86,15 +90,221 @@ fn convex_adagrad_training() {
 
 #[test]
 fn convex_rmsprop_training() {
-  let x_sq = XSqModel { c: 20f64 };
+    let x_sq = XSqModel { c: 20f64 };
 
-  let rms = RMSProp::new(0.05, 0.9, 1e-5, 50);
-  let test_data = vec![100f64];
-  let params = rms.optimize(&x_sq,
+    let rms = RMSProp::new(0.05, 0.9, 1e-5, 50);
+    let test_data = vec![100f64];
+    let params = rms.optimize(&x_sq,
                               &test_data[..],
                               &Matrix::zeros(100, 1),
                               &Matrix::zeros(100, 1));
 
-  assert!(params[0] - 20f64 < 1e-10);
-  assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
-}
\ No newline at end of file
+    assert!(params[0] - 20f64 < 1e-10);
+    assert!(x_sq.compute_grad(&params, &Matrix::zeros(1, 1), &Matrix::zeros(1, 1)).0 < 1e-10);
+}
+
+/// A model which learns the mean of its inputs, with cost
+/// the mean of (x - p)^2 over the rows of the batch.
+///
+/// Records the number of rows in each batch it is given.
+struct MeanModel {
+    batch_sizes: RefCell<Vec<usize>>,
+}
+
+impl Optimizable for MeanModel {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self, params: &[f64], inputs: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+        self.batch_sizes.borrow_mut().push(inputs.rows());
+
+        let n = inputs.rows() as f64;
+        let cost = inputs.iter().map(|x| (params[0] - x) * (params[0] - x)).sum::<f64>() / n;
+        let grad = inputs.iter().map(|x| 2f64 * (params[0] - x)).sum::<f64>() / n;
+        (cost, vec![grad])
+    }
+}
+
+#[test]
+fn stochastic_gd_mini_batches() {
+    let model = MeanModel { batch_sizes: RefCell::new(Vec::new()) };
+    let inputs = Matrix::new(45, 1, (0..45).map(|x| x as f64).collect::<Vec<f64>>());
+
+    let mut sgd = StochasticGD::new(0.5, 0.05, 2);
+    sgd.set_batch_size(10);
+    assert_eq!(sgd.batch_size(), 10);
+    sgd.optimize(&model, &[0f64], &inputs, &Matrix::zeros(45, 1));
+
+    assert_eq!(*model.batch_sizes.borrow(), vec![10, 10, 10, 10, 5, 10, 10, 10, 10, 5]);
+}
+
+#[test]
+fn stochastic_gd_seeded_mini_batches() {
+    let model = MeanModel { batch_sizes: RefCell::new(Vec::new()) };
+    let inputs = Matrix::new(100, 1, (0..100).map(|x| x as f64 / 10f64).collect::<Vec<f64>>());
+    let targets = Matrix::zeros(100, 1);
+
+    let mut sgd = StochasticGD::new(0.5, 0.05, 50);
+    sgd.set_batch_size(16);
+    sgd.set_seed(7);
+
+    let params = sgd.optimize(&model, &[0f64], &inputs, &targets);
+    assert!((params[0] - 4.95).abs() < 0.5);
+
+    // The same seed visits the batches in the same order.
+    assert_eq!(sgd.optimize(&model, &[0f64], &inputs, &targets), params);
+}
+
+/// The Rosenbrock function, with a long curved valley around the minimum at (1, 1).
+///
+/// Counts the number of cost evaluations.
+struct Rosenbrock {
+    evals: RefCell<usize>,
+}
+
+impl Optimizable for Rosenbrock {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self, params: &[f64], _: &Matrix<f64>, _: &Matrix<f64>) -> (f64, Vec<f64>) {
+        *self.evals.borrow_mut() += 1;
+
+        let (x, y) = (params[0], params[1]);
+        let cost = (1f64 - x) * (1f64 - x) + 100f64 * (y - x * x) * (y - x * x);
+        let grad = vec![-2f64 * (1f64 - x) - 400f64 * x * (y - x * x), 200f64 * (y - x * x)];
+        (cost, grad)
+    }
+}
+
+#[test]
+fn convex_lbfgs_training() {
+    let x_sq = XSqModel { c: 20f64 };
+
+    let lbfgs = LBFGS::default();
+    let test_data = vec![500f64];
+    let params = lbfgs.optimize(&x_sq,
+                                &test_data[..],
+                                &Matrix::zeros(1, 1),
+                                &Matrix::zeros(1, 1));
+
+    assert!((params[0] - 20f64).abs() < 1e-8);
+}
+
+#[test]
+fn rosenbrock_lbfgs_training() {
+    let model = Rosenbrock { evals: RefCell::new(0) };
+
+    let lbfgs = LBFGS::new(5, 200);
+    let params = lbfgs.optimize(&model, &[-1.2, 1.0], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1));
+
+    assert!((params[0] - 1f64).abs() < 1e-5);
+    assert!((params[1] - 1f64).abs() < 1e-5);
+
+    // Gradient descent makes little progress along the valley in as many evaluations.
+    let evals = *model.evals.borrow();
+    let gd_params = GradientDesc::new(1e-3, evals).optimize(&Rosenbrock { evals: RefCell::new(0) },
+                                                          &[-1.2, 1.0],
+                                                          &Matrix::zeros(1, 1),
+                                                          &Matrix::zeros(1, 1));
+    assert!((gd_params[0] - 1f64).abs() > 1e-2);
+}
+
+#[test]
+fn convex_adam_training() {
+    let x_sq = XSqModel { c: 20f64 };
+
+    let adam = Adam::new(0.5, 0.9, 0.999, 1e-8, 1000);
+    let params = adam.optimize(&x_sq, &[100f64], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1));
+
+    assert!((params[0] - 20f64).abs() < 1e-6);
+}
+
+#[test]
+fn rosenbrock_adam_training() {
+    let model = Rosenbrock { evals: RefCell::new(0) };
+
+    let adam = Adam::new(0.02, 0.9, 0.999, 1e-8, 5000);
+    let params = adam.optimize(&model, &[-1.2, 1.0], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1));
+
+    assert!((params[0] - 1f64).abs() < 1e-5);
+    assert!((params[1] - 1f64).abs() < 1e-5);
+}
+
+#[test]
+fn gd_callback_records_costs() {
+    let x_sq = XSqModel { c: 20f64 };
+    let costs = Arc::new(Mutex::new(Vec::new()));
+
+    let mut gd = GradientDesc::new(0.1, 10);
+    let recorded = costs.clone();
+    gd.set_callback(move |iter, cost| recorded.lock().unwrap().push((iter, cost)));
+
+    gd.optimize(&x_sq, &[30f64], &Matrix::zeros(1, 1), &Matrix::zeros(1, 1));
+
+    let costs = costs.lock().unwrap();
+    assert_eq!(costs.len(), 10);
+    assert_eq!(costs[0], (0, 100f64));
+    for w in costs.windows(2) {
+        assert_eq!(w[1].0, w[0].0 + 1);
+        assert!(w[1].1 < w[0].1);
+    }
+}
+
+#[test]
+fn gd_with_callback_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let mut gd = GradientDesc::default();
+    gd.set_callback(|_, _| {});
+    assert_send_sync(&gd);
+}
+
+/// The `XSqModel` which asks the optimizer to stop after a fixed
+/// number of iterations.
+struct StoppingModel {
+    model: XSqModel,
+    max_iters: usize,
+    iters: RefCell<usize>,
+}
+
+impl Optimizable for StoppingModel {
+    type Inputs = Matrix<f64>;
+    type Targets = Matrix<f64>;
+
+    fn compute_grad(&self, params: &[f64], i: &Matrix<f64>, t: &Matrix<f64>) -> (f64, Vec<f64>) {
+        self.model.compute_grad(params, i, t)
+    }
+
+    fn end_iteration(&self, _: usize, _: f64) -> bool {
+        *self.iters.borrow_mut() += 1;
+        *self.iters.borrow() >= self.max_iters
+    }
+}
+
+#[test]
+fn models_stop_optimization_early() {
+    let stopping_model = || {
+        StoppingModel {
+            model: XSqModel { c: 20f64 },
+            max_iters: 3,
+            iters: RefCell::new(0),
+        }
+    };
+    let data = Matrix::zeros(10, 1);
+
+    let model = stopping_model();
+    GradientDesc::new(0.01, 1000).optimize(&model, &[30f64], &data, &data);
+    assert_eq!(*model.iters.borrow(), 3);
+
+    let model = stopping_model();
+    StochasticGD::new(0.1, 0.01, 1000).optimize(&model, &[30f64], &data, &data);
+    assert_eq!(*model.iters.borrow(), 3);
+
+    let model = stopping_model();
+    Adam::default().optimize(&model, &[30f64], &data, &data);
+    assert_eq!(*model.iters.borrow(), 3);
+
+    let model = stopping_model();
+    LBFGS::new(5, 1000).optimize(&model, &[30f64], &data, &data);
+    assert!(*model.iters.borrow() <= 3);
+}
@@ -28,6 +28,21 @@ fn test_default() {
     assert_matrix_eq!(outputs, exp, comp=abs, tol=1e-8);
 }
 
+#[test]
+fn test_fit_predict() {
+    let inputs = Matrix::new(4, 2, vec![1., 2.,
+                                        2., 3.5,
+                                        3., 6.,
+                                        5., 9.]);
+
+    let mut model = PCA::default();
+    let outputs = model.fit_predict(&inputs).unwrap();
+
+    let mut trained = PCA::default();
+    trained.train(&inputs).unwrap();
+    assert_matrix_eq!(outputs, trained.predict(&inputs).unwrap(), comp=abs, tol=1e-12);
+}
+
 #[test]
 fn test_not_centering() {
     let mut model = PCA::new(3, false);
@@ -2,6 +2,7 @@
 extern crate rulinalg;
 extern crate rusty_machine as rm;
 extern crate num as libnum;
+extern crate rand;
 
 pub mod learning {
     mod dbscan;
@@ -10,6 +11,9 @@ pub mod learning {
     mod gp;
     mod knn;
     mod pca;
+    mod bisecting_k_means;
+
+    mod fixtures;
 
     pub mod optim {
     	mod grad_desc;